@@ -45,9 +45,10 @@ my @inode = get_rows($dbh->prepare("SELECT
     (SELECT `can_read` FROM `permissions` WHERE `id` = (SELECT `other_perm` FROM `inode` WHERE `id` = '$inode_id')) AS `other_can_read`,
     (SELECT `can_write` FROM `permissions` WHERE `id` = (SELECT `other_perm` FROM `inode` WHERE `id` = '$inode_id')) AS `other_can_write`,
     (SELECT `can_execute` FROM `permissions` WHERE `id` = (SELECT `other_perm` FROM `inode` WHERE `id` = '$inode_id')) AS `other_can_execute`,
-    `created_at` AS `ctime`,
-    `modified_at` AS `mtime`,
-    `accessed_at` AS `atime`
+    `ctime`,
+    `mtime`,
+    `atime`,
+    `crtime`
 FROM `inode` WHERE `id` = '$inode_id'"));
 is(scalar @inode, 1);
 
@@ -64,9 +65,10 @@ is($inode[0]->{"group_can_execute"}, 1);
 is($inode[0]->{"other_can_read"}, 1);
 is($inode[0]->{"other_can_write"}, 0);
 is($inode[0]->{"other_can_execute"}, 1);
-is($inode[0]->{"ctime"}, "2024-10-24 17:52:52");
-is($inode[0]->{"mtime"}, "2024-10-24 17:53:10");
-is($inode[0]->{"atime"}, "2024-10-24 17:52:52");
+is($inode[0]->{"ctime"}, 1729792372);
+is($inode[0]->{"crtime"}, 1729792372);
+is($inode[0]->{"mtime"}, 1729792390);
+is($inode[0]->{"atime"}, 1729792372);
 
 
 done_testing();
@@ -0,0 +1,192 @@
+-- dbfs schema for Microsoft SQL Server
+--
+-- Safe to apply to an existing database: tables and static data are only created if missing.
+-- The script is a single batch (no `GO`), so it can be run by `sqlcmd` as well as by dbfs itself.
+SET XACT_ABORT ON;
+BEGIN TRANSACTION;
+
+IF OBJECT_ID(N'dbo.file_types', N'U') IS NULL
+CREATE TABLE [file_types] (
+  [id] char(1) NOT NULL PRIMARY KEY,
+  [description] varchar(50) NOT NULL
+);
+INSERT INTO [file_types] ([id], [description])
+SELECT [v].[id], [v].[description] FROM (VALUES
+('-', 'Regular file'),
+('b', 'Block device'),
+('c', 'Character device'),
+('d', 'Directory'),
+('l', 'Symbolic link'),
+('p', 'Named pipe'),
+('s', 'Socket')
+) AS [v] ([id], [description])
+WHERE NOT EXISTS (SELECT 1 FROM [file_types] WHERE [file_types].[id] = [v].[id]);
+
+IF OBJECT_ID(N'dbo.permissions', N'U') IS NULL
+CREATE TABLE [permissions] (
+  [id] tinyint NOT NULL PRIMARY KEY,
+  [can_read] tinyint NOT NULL,
+  [can_write] tinyint NOT NULL,
+  [can_execute] tinyint NOT NULL
+);
+INSERT INTO [permissions] ([id], [can_read], [can_write], [can_execute])
+SELECT [v].[id], [v].[can_read], [v].[can_write], [v].[can_execute] FROM (VALUES
+(0, 0, 0, 0),
+(1, 0, 0, 1),
+(2, 0, 1, 0),
+(3, 0, 1, 1),
+(4, 1, 0, 0),
+(5, 1, 0, 1),
+(6, 1, 1, 0),
+(7, 1, 1, 1)
+) AS [v] ([id], [can_read], [can_write], [can_execute])
+WHERE NOT EXISTS (SELECT 1 FROM [permissions] WHERE [permissions].[id] = [v].[id]);
+
+IF OBJECT_ID(N'dbo.special_bits', N'U') IS NULL
+CREATE TABLE [special_bits] (
+  [id] tinyint NOT NULL PRIMARY KEY,
+  [setuid] tinyint NOT NULL,
+  [setgid] tinyint NOT NULL,
+  [sticky] tinyint NOT NULL,
+  [description] varchar(100) NOT NULL
+);
+INSERT INTO [special_bits] ([id], [setuid], [setgid], [sticky], [description])
+SELECT [v].[id], [v].[setuid], [v].[setgid], [v].[sticky], [v].[description] FROM (VALUES
+(0, 0, 0, 0, 'No special bits'),
+(1, 0, 0, 1, 'Sticky bit'),
+(2, 0, 1, 0, 'Set group ID'),
+(3, 0, 1, 1, 'Set group ID and Sticky bit'),
+(4, 1, 0, 0, 'Set user ID'),
+(5, 1, 0, 1, 'Set user ID and Sticky bit'),
+(6, 1, 1, 0, 'Set user ID and Set group ID'),
+(7, 1, 1, 1, 'Set user ID, Set group ID, and Sticky bit')
+) AS [v] ([id], [setuid], [setgid], [sticky], [description])
+WHERE NOT EXISTS (SELECT 1 FROM [special_bits] WHERE [special_bits].[id] = [v].[id]);
+
+-- Ids are `bigint` as SQL Server has no unsigned 32-bit integer
+IF OBJECT_ID(N'dbo.user', N'U') IS NULL
+CREATE TABLE [user] (
+  [id] bigint NOT NULL PRIMARY KEY,
+  [name] nvarchar(255) DEFAULT NULL
+);
+INSERT INTO [user] ([id], [name])
+SELECT [v].[id], [v].[name] FROM (VALUES
+(0, 'root'),
+(1, 'user')
+) AS [v] ([id], [name])
+WHERE NOT EXISTS (SELECT 1 FROM [user] WHERE [user].[id] = [v].[id]);
+
+IF OBJECT_ID(N'dbo.group', N'U') IS NULL
+CREATE TABLE [group] (
+  [id] bigint NOT NULL PRIMARY KEY,
+  [name] nvarchar(255) DEFAULT NULL
+);
+INSERT INTO [group] ([id], [name])
+SELECT [v].[id], [v].[name] FROM (VALUES
+(0, 'root'),
+(1, 'user')
+) AS [v] ([id], [name])
+WHERE NOT EXISTS (SELECT 1 FROM [group] WHERE [group].[id] = [v].[id]);
+
+IF OBJECT_ID(N'dbo.volume', N'U') IS NULL
+CREATE TABLE [volume] (
+  [id] bigint IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+  [name] nvarchar(255) COLLATE Latin1_General_100_BIN2 NOT NULL UNIQUE,
+  [root_inode_id] bigint DEFAULT NULL,
+  [block_size] bigint NOT NULL,
+  [uuid] char(36) NOT NULL,
+  [label] nvarchar(255) NOT NULL,
+  [created_at] datetime2 NOT NULL DEFAULT SYSUTCDATETIME(),
+  [features] bigint NOT NULL DEFAULT 0
+);
+IF NOT EXISTS (SELECT 1 FROM [volume] WHERE [id] = 1)
+BEGIN
+  SET IDENTITY_INSERT [volume] ON;
+  INSERT INTO [volume] ([id], [name], [root_inode_id], [block_size], [uuid], [label], [features]) VALUES
+  (1, 'default', 1, 65536, LOWER(CONVERT(char(36), NEWID())), '', 0);
+  SET IDENTITY_INSERT [volume] OFF;
+END;
+
+IF OBJECT_ID(N'dbo.inode', N'U') IS NULL
+BEGIN
+  CREATE TABLE [inode] (
+    [id] bigint IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+    [volume_id] bigint NOT NULL REFERENCES [volume] ([id]),
+    [owner] bigint NOT NULL REFERENCES [user] ([id]),
+    [group] bigint NOT NULL REFERENCES [group] ([id]),
+    [file_type] char(1) NOT NULL REFERENCES [file_types] ([id]),
+    [special_bits] tinyint NOT NULL DEFAULT 0 REFERENCES [special_bits] ([id]),
+    [user_perm] tinyint NOT NULL DEFAULT 0 REFERENCES [permissions] ([id]),
+    [group_perm] tinyint NOT NULL DEFAULT 0 REFERENCES [permissions] ([id]),
+    [other_perm] tinyint NOT NULL DEFAULT 0 REFERENCES [permissions] ([id]),
+    [rdev] bigint NOT NULL DEFAULT 0,
+    [atime] bigint NOT NULL DEFAULT 0,
+    [atime_nsec] bigint NOT NULL DEFAULT 0,
+    [mtime] bigint NOT NULL DEFAULT 0,
+    [mtime_nsec] bigint NOT NULL DEFAULT 0,
+    [ctime] bigint NOT NULL DEFAULT 0,
+    [ctime_nsec] bigint NOT NULL DEFAULT 0,
+    [crtime] bigint NOT NULL DEFAULT 0,
+    [crtime_nsec] bigint NOT NULL DEFAULT 0
+  );
+  CREATE INDEX [inode_file_type] ON [inode] ([file_type]);
+  CREATE INDEX [inode_owner] ON [inode] ([owner]);
+  CREATE INDEX [inode_group] ON [inode] ([group]);
+  CREATE INDEX [inode_volume] ON [inode] ([volume_id]);
+END;
+IF NOT EXISTS (SELECT 1 FROM [inode] WHERE [id] = 1)
+BEGIN
+  SET IDENTITY_INSERT [inode] ON;
+  INSERT INTO [inode] ([id], [volume_id], [owner], [group], [file_type], [special_bits], [user_perm], [group_perm], [other_perm], [atime], [atime_nsec], [mtime], [mtime_nsec], [ctime], [ctime_nsec], [crtime], [crtime_nsec]) VALUES
+  (1, 1, 0, 0, 'd', 0, 7, 5, 5, 1729792372, 0, 1729792390, 0, 1729792372, 0, 1729792372, 0);
+  SET IDENTITY_INSERT [inode] OFF;
+END;
+
+-- Binary collation, like `utf8mb4_bin` in the MySQL schema
+IF OBJECT_ID(N'dbo.file', N'U') IS NULL
+BEGIN
+  CREATE TABLE [file] (
+    [parent_inode_id] bigint NOT NULL REFERENCES [inode] ([id]),
+    [name] nvarchar(255) COLLATE Latin1_General_100_BIN2 NOT NULL,
+    [inode_id] bigint NOT NULL REFERENCES [inode] ([id]),
+    PRIMARY KEY ([parent_inode_id], [name])
+  );
+  CREATE INDEX [file_inode_id] ON [file] ([inode_id]);
+END;
+IF NOT EXISTS (SELECT 1 FROM [file] WHERE [parent_inode_id] = 1 AND [name] = '/')
+INSERT INTO [file] ([parent_inode_id], [name], [inode_id]) VALUES
+(1, '/', 1);
+
+IF OBJECT_ID(N'dbo.block', N'U') IS NULL
+CREATE TABLE [block] (
+  [inode_id] bigint NOT NULL REFERENCES [inode] ([id]) ON DELETE CASCADE ON UPDATE CASCADE,
+  [block_id] bigint NOT NULL,
+  [data] varbinary(max) NOT NULL,
+  PRIMARY KEY ([inode_id], [block_id])
+);
+
+IF OBJECT_ID(N'dbo.extended_attributes', N'U') IS NULL
+CREATE TABLE [extended_attributes] (
+  [inode_id] bigint NOT NULL REFERENCES [inode] ([id]) ON DELETE CASCADE ON UPDATE CASCADE,
+  [key] nvarchar(255) NOT NULL,
+  [value] varbinary(max) NOT NULL,
+  PRIMARY KEY ([inode_id], [key])
+);
+
+IF OBJECT_ID(N'dbo.orphan', N'U') IS NULL
+CREATE TABLE [orphan] (
+  [inode_id] bigint NOT NULL PRIMARY KEY REFERENCES [inode] ([id]) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+IF OBJECT_ID(N'dbo.metadata', N'U') IS NULL
+CREATE TABLE [metadata] (
+  [key] varchar(64) NOT NULL PRIMARY KEY,
+  [value] nvarchar(255) NOT NULL
+);
+INSERT INTO [metadata] ([key], [value])
+SELECT [v].[key], [v].[value] FROM (VALUES
+('schema_version', '6')
+) AS [v] ([key], [value])
+WHERE NOT EXISTS (SELECT 1 FROM [metadata] WHERE [metadata].[key] = [v].[key]);
+
+COMMIT TRANSACTION;
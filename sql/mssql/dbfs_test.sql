@@ -0,0 +1,173 @@
+-- Integration testing data for Microsoft SQL Server (equivalent to `sql/mysql/dbfs_test.sql`)
+SET XACT_ABORT ON;
+BEGIN TRANSACTION;
+
+DROP TABLE IF EXISTS [superblock], [metadata], [orphan], [extended_attributes], [block], [file], [inode], [volume], [user], [group], [special_bits], [permissions], [file_types];
+
+CREATE TABLE [file_types] (
+  [id] char(1) NOT NULL PRIMARY KEY,
+  [description] varchar(50) NOT NULL
+);
+INSERT INTO [file_types] ([id], [description]) VALUES
+('-', 'Regular file'),
+('b', 'Block device'),
+('c', 'Character device'),
+('d', 'Directory'),
+('l', 'Symbolic link'),
+('p', 'Named pipe'),
+('s', 'Socket');
+
+CREATE TABLE [permissions] (
+  [id] tinyint NOT NULL PRIMARY KEY,
+  [can_read] tinyint NOT NULL,
+  [can_write] tinyint NOT NULL,
+  [can_execute] tinyint NOT NULL
+);
+INSERT INTO [permissions] ([id], [can_read], [can_write], [can_execute]) VALUES
+(0, 0, 0, 0),
+(1, 0, 0, 1),
+(2, 0, 1, 0),
+(3, 0, 1, 1),
+(4, 1, 0, 0),
+(5, 1, 0, 1),
+(6, 1, 1, 0),
+(7, 1, 1, 1);
+
+CREATE TABLE [special_bits] (
+  [id] tinyint NOT NULL PRIMARY KEY,
+  [setuid] tinyint NOT NULL,
+  [setgid] tinyint NOT NULL,
+  [sticky] tinyint NOT NULL,
+  [description] varchar(100) NOT NULL
+);
+INSERT INTO [special_bits] ([id], [setuid], [setgid], [sticky], [description]) VALUES
+(0, 0, 0, 0, 'No special bits'),
+(1, 0, 0, 1, 'Sticky bit'),
+(2, 0, 1, 0, 'Set group ID'),
+(3, 0, 1, 1, 'Set group ID and Sticky bit'),
+(4, 1, 0, 0, 'Set user ID'),
+(5, 1, 0, 1, 'Set user ID and Sticky bit'),
+(6, 1, 1, 0, 'Set user ID and Set group ID'),
+(7, 1, 1, 1, 'Set user ID, Set group ID, and Sticky bit');
+
+CREATE TABLE [user] (
+  [id] bigint NOT NULL PRIMARY KEY,
+  [name] nvarchar(255) DEFAULT NULL
+);
+INSERT INTO [user] ([id], [name]) VALUES
+(1, 'root'),
+(2, 'user');
+
+CREATE TABLE [group] (
+  [id] bigint NOT NULL PRIMARY KEY,
+  [name] nvarchar(255) DEFAULT NULL
+);
+INSERT INTO [group] ([id], [name]) VALUES
+(1, 'root'),
+(2, 'user');
+
+CREATE TABLE [volume] (
+  [id] bigint IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+  [name] nvarchar(255) COLLATE Latin1_General_100_BIN2 NOT NULL UNIQUE,
+  [root_inode_id] bigint DEFAULT NULL,
+  [block_size] bigint NOT NULL,
+  [uuid] char(36) NOT NULL,
+  [label] nvarchar(255) NOT NULL,
+  [created_at] datetime2 NOT NULL DEFAULT SYSUTCDATETIME(),
+  [features] bigint NOT NULL DEFAULT 0
+);
+SET IDENTITY_INSERT [volume] ON;
+INSERT INTO [volume] ([id], [name], [root_inode_id], [block_size], [uuid], [label], [features]) VALUES
+(1, 'default', 1, 4096, '00000000-0000-4000-8000-000000000001', '', 0);
+SET IDENTITY_INSERT [volume] OFF;
+
+CREATE TABLE [inode] (
+  [id] bigint IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+  [volume_id] bigint NOT NULL REFERENCES [volume] ([id]),
+  [owner] bigint NOT NULL REFERENCES [user] ([id]),
+  [group] bigint NOT NULL REFERENCES [group] ([id]),
+  [file_type] char(1) NOT NULL REFERENCES [file_types] ([id]),
+  [special_bits] tinyint NOT NULL DEFAULT 0 REFERENCES [special_bits] ([id]),
+  [user_perm] tinyint NOT NULL DEFAULT 0 REFERENCES [permissions] ([id]),
+  [group_perm] tinyint NOT NULL DEFAULT 0 REFERENCES [permissions] ([id]),
+  [other_perm] tinyint NOT NULL DEFAULT 0 REFERENCES [permissions] ([id]),
+  [rdev] bigint NOT NULL DEFAULT 0,
+  [atime] bigint NOT NULL DEFAULT 0,
+  [atime_nsec] bigint NOT NULL DEFAULT 0,
+  [mtime] bigint NOT NULL DEFAULT 0,
+  [mtime_nsec] bigint NOT NULL DEFAULT 0,
+  [ctime] bigint NOT NULL DEFAULT 0,
+  [ctime_nsec] bigint NOT NULL DEFAULT 0,
+  [crtime] bigint NOT NULL DEFAULT 0,
+  [crtime_nsec] bigint NOT NULL DEFAULT 0
+);
+CREATE INDEX [inode_file_type] ON [inode] ([file_type]);
+CREATE INDEX [inode_owner] ON [inode] ([owner]);
+CREATE INDEX [inode_group] ON [inode] ([group]);
+CREATE INDEX [inode_volume] ON [inode] ([volume_id]);
+SET IDENTITY_INSERT [inode] ON;
+INSERT INTO [inode] ([id], [volume_id], [owner], [group], [file_type], [special_bits], [user_perm], [group_perm], [other_perm], [atime], [atime_nsec], [mtime], [mtime_nsec], [ctime], [ctime_nsec], [crtime], [crtime_nsec]) VALUES
+(1, 1, 1, 1, 'd', 0, 7, 5, 5, 1729792372, 0, 1729792390, 0, 1729792372, 0, 1729792372, 0),
+(2, 1, 2, 2, '-', 0, 6, 4, 4, 1729792440, 0, 1729792440, 0, 1729792440, 0, 1729792440, 0),
+(3, 1, 2, 2, '-', 0, 6, 4, 4, 1729792594, 0, 1729792634, 0, 1729792594, 0, 1729792594, 0),
+(4, 1, 2, 2, 'd', 0, 7, 5, 5, 1729961970, 0, 1729961970, 0, 1729961970, 0, 1729961970, 0),
+(5, 1, 2, 2, '-', 0, 6, 4, 0, 1729962019, 0, 1729962019, 0, 1729962019, 0, 1729962019, 0),
+(6, 1, 1, 1, '-', 0, 6, 0, 0, 1729962047, 0, 1729962047, 0, 1729962047, 0, 1729962047, 0),
+(7, 1, 2, 2, '-', 0, 6, 4, 4, 1729966232, 0, 1729966232, 0, 1729966232, 0, 1729966232, 0),
+(8, 1, 2, 2, 'l', 0, 6, 4, 4, 1730017626, 0, 1730017626, 0, 1730017626, 0, 1730017626, 0);
+SET IDENTITY_INSERT [inode] OFF;
+
+-- Binary collation, like `utf8mb4_bin` in the MySQL schema
+CREATE TABLE [file] (
+  [parent_inode_id] bigint NOT NULL REFERENCES [inode] ([id]),
+  [name] nvarchar(255) COLLATE Latin1_General_100_BIN2 NOT NULL,
+  [inode_id] bigint NOT NULL REFERENCES [inode] ([id]),
+  PRIMARY KEY ([parent_inode_id], [name])
+);
+CREATE INDEX [file_inode_id] ON [file] ([inode_id]);
+INSERT INTO [file] ([parent_inode_id], [name], [inode_id]) VALUES
+(1, '/', 1),
+(1, 'test.txt', 2),
+(1, 'hardlink_to_test.bin', 3),
+(1, 'test.bin', 3),
+(1, 'more_testing', 4),
+(4, 'partially_private_file.txt', 5),
+(4, 'very_private_file.txt', 6),
+(4, 'empty_file.bin', 7),
+(1, 'symlink_to_test.txt', 8);
+
+CREATE TABLE [block] (
+  [inode_id] bigint NOT NULL REFERENCES [inode] ([id]) ON DELETE CASCADE ON UPDATE CASCADE,
+  [block_id] bigint NOT NULL,
+  [data] varbinary(max) NOT NULL,
+  PRIMARY KEY ([inode_id], [block_id])
+);
+INSERT INTO [block] ([inode_id], [block_id], [data]) VALUES
+(2, 1, 0x48656c6c6f2c20776f726c64210a),
+(3, 1, CONVERT(varbinary(max), REPLICATE(CONVERT(varchar(max), '00'), 4096), 2)),
+(3, 2, CONVERT(varbinary(max), REPLICATE(CONVERT(varchar(max), '00'), 4096), 2)),
+(3, 3, CONVERT(varbinary(max), REPLICATE(CONVERT(varchar(max), '00'), 4096), 2)),
+(3, 4, 0x616161610a),
+(5, 1, 0x77686174207765726520796f7520657870656374696e670a),
+(6, 1, 0x68747470733a2f2f7777772e796f75747562652e636f6d2f77617463683f763d64517734773957675863510a),
+(8, 1, 0x746573742e747874);
+
+CREATE TABLE [extended_attributes] (
+  [inode_id] bigint NOT NULL REFERENCES [inode] ([id]) ON DELETE CASCADE ON UPDATE CASCADE,
+  [key] nvarchar(255) NOT NULL,
+  [value] varbinary(max) NOT NULL,
+  PRIMARY KEY ([inode_id], [key])
+);
+
+CREATE TABLE [orphan] (
+  [inode_id] bigint NOT NULL PRIMARY KEY REFERENCES [inode] ([id]) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE TABLE [metadata] (
+  [key] varchar(64) NOT NULL PRIMARY KEY,
+  [value] nvarchar(255) NOT NULL
+);
+INSERT INTO [metadata] ([key], [value]) VALUES
+('schema_version', '6');
+
+COMMIT TRANSACTION;
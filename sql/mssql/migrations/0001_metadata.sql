@@ -0,0 +1,13 @@
+-- Version 1: record the schema version in a [metadata] table
+SET XACT_ABORT ON;
+BEGIN TRANSACTION;
+IF OBJECT_ID(N'dbo.metadata', N'U') IS NULL
+CREATE TABLE [metadata] (
+  [key] varchar(64) NOT NULL PRIMARY KEY,
+  [value] nvarchar(255) NOT NULL
+);
+MERGE [metadata] AS [target] USING (VALUES ('schema_version', '1')) AS [source] ([key], [value])
+ON [target].[key] = [source].[key]
+WHEN MATCHED THEN UPDATE SET [value] = [source].[value]
+WHEN NOT MATCHED THEN INSERT ([key], [value]) VALUES ([source].[key], [source].[value]);
+COMMIT TRANSACTION;
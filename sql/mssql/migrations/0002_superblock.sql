@@ -0,0 +1,20 @@
+-- Version 2: per-volume block size, UUID, label and feature flags in a [superblock] table
+--
+-- Volumes created before the superblock existed were written with the default block size of 65536
+-- (volumes written by an `integration_testing` build used 4096 and have to be fixed by hand).
+SET XACT_ABORT ON;
+BEGIN TRANSACTION;
+IF OBJECT_ID(N'dbo.superblock', N'U') IS NULL
+CREATE TABLE [superblock] (
+  [id] tinyint NOT NULL PRIMARY KEY,
+  [block_size] bigint NOT NULL,
+  [uuid] char(36) NOT NULL,
+  [label] nvarchar(255) NOT NULL,
+  [created_at] datetime2 NOT NULL DEFAULT SYSUTCDATETIME(),
+  [features] bigint NOT NULL DEFAULT 0
+);
+IF NOT EXISTS (SELECT 1 FROM [superblock])
+INSERT INTO [superblock] ([id], [block_size], [uuid], [label], [created_at], [features])
+SELECT 1, 65536, LOWER(CONVERT(char(36), NEWID())), '', [created_at], 0 FROM [inode] WHERE [id] = 1;
+UPDATE [metadata] SET [value] = '2' WHERE [key] = 'schema_version';
+COMMIT TRANSACTION;
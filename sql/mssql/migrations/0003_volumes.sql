@@ -0,0 +1,34 @@
+-- Version 3: multiple volumes in one database
+--
+-- The superblock becomes a row of the [volume] table and all existing inodes belong to the volume
+-- "default" (its root directory is the former root inode 1).
+SET XACT_ABORT ON;
+BEGIN TRANSACTION;
+IF OBJECT_ID(N'dbo.volume', N'U') IS NULL
+CREATE TABLE [volume] (
+  [id] bigint IDENTITY(1, 1) NOT NULL PRIMARY KEY,
+  [name] nvarchar(255) COLLATE Latin1_General_100_BIN2 NOT NULL UNIQUE,
+  [root_inode_id] bigint DEFAULT NULL,
+  [block_size] bigint NOT NULL,
+  [uuid] char(36) NOT NULL,
+  [label] nvarchar(255) NOT NULL,
+  [created_at] datetime2 NOT NULL DEFAULT SYSUTCDATETIME(),
+  [features] bigint NOT NULL DEFAULT 0
+);
+IF NOT EXISTS (SELECT 1 FROM [volume] WHERE [id] = 1)
+BEGIN
+  SET IDENTITY_INSERT [volume] ON;
+  INSERT INTO [volume] ([id], [name], [root_inode_id], [block_size], [uuid], [label], [created_at], [features])
+  SELECT 1, 'default', 1, [block_size], [uuid], [label], [created_at], [features] FROM [superblock] WHERE [id] = 1;
+  SET IDENTITY_INSERT [volume] OFF;
+END;
+-- The new column is only referenced through `EXEC`, as the batch is compiled before it exists
+IF COL_LENGTH(N'dbo.inode', N'volume_id') IS NULL
+BEGIN
+  ALTER TABLE [inode] ADD [volume_id] bigint NOT NULL CONSTRAINT [inode_volume_default] DEFAULT 1 REFERENCES [volume] ([id]);
+  ALTER TABLE [inode] DROP CONSTRAINT [inode_volume_default];
+  EXEC(N'CREATE INDEX [inode_volume] ON [inode] ([volume_id])');
+END;
+DROP TABLE IF EXISTS [superblock];
+UPDATE [metadata] SET [value] = '3' WHERE [key] = 'schema_version';
+COMMIT TRANSACTION;
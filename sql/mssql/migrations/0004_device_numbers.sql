@@ -0,0 +1,7 @@
+-- Version 4: device numbers of block and character devices
+SET XACT_ABORT ON;
+BEGIN TRANSACTION;
+IF COL_LENGTH(N'dbo.inode', N'rdev') IS NULL
+ALTER TABLE [inode] ADD [rdev] bigint NOT NULL DEFAULT 0;
+UPDATE [metadata] SET [value] = '4' WHERE [key] = 'schema_version';
+COMMIT TRANSACTION;
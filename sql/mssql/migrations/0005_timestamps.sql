@@ -0,0 +1,38 @@
+-- Version 5: timestamps as seconds (64-bit) and nanoseconds since the Unix epoch, a status change
+-- time separate from the birth time
+--
+-- The former [created_at] was used as both, it becomes the [ctime] and the [crtime].
+SET XACT_ABORT ON;
+BEGIN TRANSACTION;
+-- The new columns are only referenced through `EXEC`, as the batch is compiled before they exist
+IF COL_LENGTH(N'dbo.inode', N'atime') IS NULL
+BEGIN
+  ALTER TABLE [inode] ADD
+    [atime] bigint NOT NULL DEFAULT 0,
+    [atime_nsec] bigint NOT NULL DEFAULT 0,
+    [mtime] bigint NOT NULL DEFAULT 0,
+    [mtime_nsec] bigint NOT NULL DEFAULT 0,
+    [ctime] bigint NOT NULL DEFAULT 0,
+    [ctime_nsec] bigint NOT NULL DEFAULT 0,
+    [crtime] bigint NOT NULL DEFAULT 0,
+    [crtime_nsec] bigint NOT NULL DEFAULT 0;
+  EXEC(N'UPDATE [inode] SET
+    [atime] = DATEDIFF_BIG(SECOND, ''1970-01-01'', [accessed_at]),
+    [atime_nsec] = DATEPART(NANOSECOND, [accessed_at]),
+    [mtime] = DATEDIFF_BIG(SECOND, ''1970-01-01'', [modified_at]),
+    [mtime_nsec] = DATEPART(NANOSECOND, [modified_at]),
+    [ctime] = DATEDIFF_BIG(SECOND, ''1970-01-01'', [created_at]),
+    [ctime_nsec] = DATEPART(NANOSECOND, [created_at]),
+    [crtime] = DATEDIFF_BIG(SECOND, ''1970-01-01'', [created_at]),
+    [crtime_nsec] = DATEPART(NANOSECOND, [created_at])');
+END;
+-- The old columns have unnamed default constraints, which have to be dropped first
+DECLARE @drop nvarchar(max) = N'';
+SELECT @drop = @drop + N'ALTER TABLE [inode] DROP CONSTRAINT ' + QUOTENAME([d].[name]) + N';'
+FROM sys.default_constraints AS [d]
+JOIN sys.columns AS [c] ON [c].[object_id] = [d].[parent_object_id] AND [c].[column_id] = [d].[parent_column_id]
+WHERE [d].[parent_object_id] = OBJECT_ID(N'dbo.inode') AND [c].[name] IN (N'created_at', N'modified_at', N'accessed_at');
+EXEC(@drop);
+ALTER TABLE [inode] DROP COLUMN IF EXISTS [created_at], [modified_at], [accessed_at];
+UPDATE [metadata] SET [value] = '5' WHERE [key] = 'schema_version';
+COMMIT TRANSACTION;
@@ -0,0 +1,9 @@
+-- Version 6: inodes which lost their last name while they were still open
+SET XACT_ABORT ON;
+BEGIN TRANSACTION;
+IF OBJECT_ID(N'dbo.orphan', N'U') IS NULL
+CREATE TABLE [orphan] (
+  [inode_id] bigint NOT NULL PRIMARY KEY REFERENCES [inode] ([id]) ON DELETE CASCADE ON UPDATE CASCADE
+);
+UPDATE [metadata] SET [value] = '6' WHERE [key] = 'schema_version';
+COMMIT TRANSACTION;
@@ -37,6 +37,7 @@ INSERT INTO `group` (`id`, `name`) VALUES
 
 CREATE TABLE `inode` (
   `id` int(10) UNSIGNED NOT NULL,
+  `volume_id` int(10) UNSIGNED NOT NULL,
   `owner` int(10) UNSIGNED NOT NULL,
   `group` int(10) UNSIGNED NOT NULL,
   `file_type` char(1) NOT NULL,
@@ -44,11 +45,37 @@ CREATE TABLE `inode` (
   `user_perm` tinyint(4) UNSIGNED NOT NULL DEFAULT 0,
   `group_perm` tinyint(4) UNSIGNED NOT NULL DEFAULT 0,
   `other_perm` tinyint(4) UNSIGNED NOT NULL DEFAULT 0,
-  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
-  `modified_at` timestamp NOT NULL DEFAULT current_timestamp() ON UPDATE current_timestamp(),
-  `accessed_at` timestamp NOT NULL DEFAULT current_timestamp()
+  `rdev` int(10) UNSIGNED NOT NULL DEFAULT 0,
+  `atime` bigint(20) NOT NULL DEFAULT 0,
+  `atime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0,
+  `mtime` bigint(20) NOT NULL DEFAULT 0,
+  `mtime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0,
+  `ctime` bigint(20) NOT NULL DEFAULT 0,
+  `ctime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0,
+  `crtime` bigint(20) NOT NULL DEFAULT 0,
+  `crtime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+CREATE TABLE `metadata` (
+  `key` varchar(64) NOT NULL,
+  `value` varchar(255) NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+INSERT INTO `metadata` (`key`, `value`) VALUES
+('schema_version', '6');
+
+CREATE TABLE `volume` (
+  `id` int(10) UNSIGNED NOT NULL,
+  `name` varchar(255) NOT NULL,
+  `root_inode_id` int(10) UNSIGNED DEFAULT NULL,
+  `block_size` int(10) UNSIGNED NOT NULL,
+  `uuid` char(36) NOT NULL,
+  `label` varchar(255) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  `features` bigint(20) UNSIGNED NOT NULL DEFAULT 0
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+INSERT INTO `volume` (`id`, `name`, `root_inode_id`, `block_size`, `uuid`, `label`, `features`) VALUES
+(1, 'default', 1, 65536, UUID(), '', 0);
+
 CREATE TABLE `permissions` (
   `id` tinyint(4) UNSIGNED NOT NULL,
   `can_read` tinyint(1) UNSIGNED NOT NULL,
@@ -96,6 +123,10 @@ CREATE TABLE `extended_attributes` (
   `value` longblob NOT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
 
+CREATE TABLE `orphan` (
+  `inode_id` int(10) UNSIGNED NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+
 ALTER TABLE `block`
   ADD PRIMARY KEY (`inode_id`,`block_id`);
 
@@ -111,6 +142,9 @@ ALTER TABLE `file_types`
 ALTER TABLE `extended_attributes`
   ADD PRIMARY KEY (`inode_id`, `key`);
 
+ALTER TABLE `orphan`
+  ADD PRIMARY KEY (`inode_id`);
+
 ALTER TABLE `group`
   ADD PRIMARY KEY (`id`);
 
@@ -122,9 +156,17 @@ ALTER TABLE `inode`
   ADD KEY `inode_other_perm` (`other_perm`),
   ADD KEY `inode_owner` (`owner`),
   ADD KEY `inode_special_bits` (`special_bits`),
-  ADD KEY `inode_user_perm` (`user_perm`);
-INSERT INTO `inode` (`id`, `owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`, `created_at`, `modified_at`, `accessed_at`) VALUES
-(1, 0, 0, 'd', 0, 7, 5, 5, '2024-10-24 17:52:52', '2024-10-24 17:53:10', '2024-10-24 17:52:52');
+  ADD KEY `inode_user_perm` (`user_perm`),
+  ADD KEY `inode_volume` (`volume_id`);
+INSERT INTO `inode` (`id`, `volume_id`, `owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`, `atime`, `atime_nsec`, `mtime`, `mtime_nsec`, `ctime`, `ctime_nsec`, `crtime`, `crtime_nsec`) VALUES
+(1, 1, 0, 0, 'd', 0, 7, 5, 5, 1729792372, 0, 1729792390, 0, 1729792372, 0, 1729792372, 0);
+
+ALTER TABLE `metadata`
+  ADD PRIMARY KEY (`key`);
+
+ALTER TABLE `volume`
+  ADD PRIMARY KEY (`id`),
+  ADD UNIQUE KEY `volume_name` (`name`);
 
 ALTER TABLE `permissions`
   ADD PRIMARY KEY (`id`);
@@ -145,6 +187,9 @@ ALTER TABLE `inode`
 ALTER TABLE `user`
   MODIFY `id` int(10) UNSIGNED NOT NULL AUTO_INCREMENT;
 
+ALTER TABLE `volume`
+  MODIFY `id` int(10) UNSIGNED NOT NULL AUTO_INCREMENT;
+
 
 ALTER TABLE `block`
   ADD CONSTRAINT `block_inode` FOREIGN KEY (`inode_id`) REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE;
@@ -157,6 +202,9 @@ ALTER TABLE `file`
 ALTER TABLE `extended_attributes`
   ADD CONSTRAINT `xattr_inode` FOREIGN KEY (`inode_id`) REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE;
 
+ALTER TABLE `orphan`
+  ADD CONSTRAINT `orphan_inode` FOREIGN KEY (`inode_id`) REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
 
 ALTER TABLE `inode`
   ADD CONSTRAINT `inode_file_type` FOREIGN KEY (`file_type`) REFERENCES `file_types` (`id`),
@@ -165,5 +213,6 @@ ALTER TABLE `inode`
   ADD CONSTRAINT `inode_other_perm` FOREIGN KEY (`other_perm`) REFERENCES `permissions` (`id`),
   ADD CONSTRAINT `inode_owner` FOREIGN KEY (`owner`) REFERENCES `user` (`id`),
   ADD CONSTRAINT `inode_special_bits` FOREIGN KEY (`special_bits`) REFERENCES `special_bits` (`id`),
-  ADD CONSTRAINT `inode_user_perm` FOREIGN KEY (`user_perm`) REFERENCES `permissions` (`id`);
+  ADD CONSTRAINT `inode_user_perm` FOREIGN KEY (`user_perm`) REFERENCES `permissions` (`id`),
+  ADD CONSTRAINT `inode_volume` FOREIGN KEY (`volume_id`) REFERENCES `volume` (`id`);
 COMMIT;
@@ -60,6 +60,7 @@ INSERT INTO `group` (`id`, `name`) VALUES
 
 CREATE TABLE `inode` (
   `id` int(10) UNSIGNED NOT NULL,
+  `volume_id` int(10) UNSIGNED NOT NULL,
   `owner` int(10) UNSIGNED NOT NULL,
   `group` int(10) UNSIGNED NOT NULL,
   `file_type` char(1) NOT NULL,
@@ -67,20 +68,50 @@ CREATE TABLE `inode` (
   `user_perm` tinyint(4) UNSIGNED NOT NULL DEFAULT 0,
   `group_perm` tinyint(4) UNSIGNED NOT NULL DEFAULT 0,
   `other_perm` tinyint(4) UNSIGNED NOT NULL DEFAULT 0,
-  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
-  `modified_at` timestamp NOT NULL DEFAULT current_timestamp() ON UPDATE current_timestamp(),
-  `accessed_at` timestamp NOT NULL DEFAULT current_timestamp()
+  `rdev` int(10) UNSIGNED NOT NULL DEFAULT 0,
+  `atime` bigint(20) NOT NULL DEFAULT 0,
+  `atime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0,
+  `mtime` bigint(20) NOT NULL DEFAULT 0,
+  `mtime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0,
+  `ctime` bigint(20) NOT NULL DEFAULT 0,
+  `ctime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0,
+  `crtime` bigint(20) NOT NULL DEFAULT 0,
+  `crtime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
-INSERT INTO `inode` (`id`, `owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`, `created_at`, `modified_at`, `accessed_at`) VALUES
-(1, 1, 1, 'd', 0, 7, 5, 5, '2024-10-24 17:52:52', '2024-10-24 17:53:10', '2024-10-24 17:52:52'),
-(2, 2, 2, '-', 0, 6, 4, 4, '2024-10-24 17:54:00', '2024-10-24 17:54:00', '2024-10-24 17:54:00'),
-(3, 2, 2, '-', 0, 6, 4, 4, '2024-10-24 17:56:34', '2024-10-24 17:57:14', '2024-10-24 17:56:34'),
-(4, 2, 2, 'd', 0, 7, 5, 5, '2024-10-26 16:59:30', '2024-10-26 16:59:30', '2024-10-26 16:59:30'),
-(5, 2, 2, '-', 0, 6, 4, 0, '2024-10-26 17:00:19', '2024-10-26 17:00:19', '2024-10-26 17:00:19'),
-(6, 1, 1, '-', 0, 6, 0, 0, '2024-10-26 17:00:47', '2024-10-26 17:00:47', '2024-10-26 17:00:47'),
-(7, 2, 2, '-', 0, 6, 4, 4, '2024-10-26 18:10:32', '2024-10-26 18:10:32', '2024-10-26 18:10:32'),
-(8, 2, 2, 'l', 0, 6, 4, 4, '2024-10-27 08:27:06', '2024-10-27 08:27:06', '2024-10-27 08:27:06');
+INSERT INTO `inode` (`id`, `volume_id`, `owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`, `atime`, `atime_nsec`, `mtime`, `mtime_nsec`, `ctime`, `ctime_nsec`, `crtime`, `crtime_nsec`) VALUES
+(1, 1, 1, 1, 'd', 0, 7, 5, 5, 1729792372, 0, 1729792390, 0, 1729792372, 0, 1729792372, 0),
+(2, 1, 2, 2, '-', 0, 6, 4, 4, 1729792440, 0, 1729792440, 0, 1729792440, 0, 1729792440, 0),
+(3, 1, 2, 2, '-', 0, 6, 4, 4, 1729792594, 0, 1729792634, 0, 1729792594, 0, 1729792594, 0),
+(4, 1, 2, 2, 'd', 0, 7, 5, 5, 1729961970, 0, 1729961970, 0, 1729961970, 0, 1729961970, 0),
+(5, 1, 2, 2, '-', 0, 6, 4, 0, 1729962019, 0, 1729962019, 0, 1729962019, 0, 1729962019, 0),
+(6, 1, 1, 1, '-', 0, 6, 0, 0, 1729962047, 0, 1729962047, 0, 1729962047, 0, 1729962047, 0),
+(7, 1, 2, 2, '-', 0, 6, 4, 4, 1729966232, 0, 1729966232, 0, 1729966232, 0, 1729966232, 0),
+(8, 1, 2, 2, 'l', 0, 6, 4, 4, 1730017626, 0, 1730017626, 0, 1730017626, 0, 1730017626, 0);
+
+CREATE TABLE `orphan` (
+  `inode_id` int(10) UNSIGNED NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+
+CREATE TABLE `metadata` (
+  `key` varchar(64) NOT NULL,
+  `value` varchar(255) NOT NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+INSERT INTO `metadata` (`key`, `value`) VALUES
+('schema_version', '6');
+
+CREATE TABLE `volume` (
+  `id` int(10) UNSIGNED NOT NULL,
+  `name` varchar(255) NOT NULL,
+  `root_inode_id` int(10) UNSIGNED DEFAULT NULL,
+  `block_size` int(10) UNSIGNED NOT NULL,
+  `uuid` char(36) NOT NULL,
+  `label` varchar(255) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  `features` bigint(20) UNSIGNED NOT NULL DEFAULT 0
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+INSERT INTO `volume` (`id`, `name`, `root_inode_id`, `block_size`, `uuid`, `label`, `features`) VALUES
+(1, 'default', 1, 4096, '00000000-0000-4000-8000-000000000001', '', 0);
 
 CREATE TABLE `permissions` (
   `id` tinyint(4) UNSIGNED NOT NULL,
@@ -148,7 +179,15 @@ ALTER TABLE `inode`
   ADD KEY `inode_other_perm` (`other_perm`),
   ADD KEY `inode_owner` (`owner`),
   ADD KEY `inode_special_bits` (`special_bits`),
-  ADD KEY `inode_user_perm` (`user_perm`);
+  ADD KEY `inode_user_perm` (`user_perm`),
+  ADD KEY `inode_volume` (`volume_id`);
+
+ALTER TABLE `metadata`
+  ADD PRIMARY KEY (`key`);
+
+ALTER TABLE `volume`
+  ADD PRIMARY KEY (`id`),
+  ADD UNIQUE KEY `volume_name` (`name`);
 
 ALTER TABLE `permissions`
   ADD PRIMARY KEY (`id`);
@@ -169,10 +208,17 @@ ALTER TABLE `inode`
 ALTER TABLE `user`
   MODIFY `id` int(10) UNSIGNED NOT NULL AUTO_INCREMENT, AUTO_INCREMENT=3;
 
+ALTER TABLE `volume`
+  MODIFY `id` int(10) UNSIGNED NOT NULL AUTO_INCREMENT, AUTO_INCREMENT=2;
+
 
 ALTER TABLE `block`
   ADD CONSTRAINT `block_inode` FOREIGN KEY (`inode_id`) REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE;
 
+ALTER TABLE `orphan`
+  ADD PRIMARY KEY (`inode_id`),
+  ADD CONSTRAINT `orphan_inode` FOREIGN KEY (`inode_id`) REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE;
+
 ALTER TABLE `file`
   ADD CONSTRAINT `file_inode` FOREIGN KEY (`inode_id`) REFERENCES `inode` (`id`),
   ADD CONSTRAINT `file_parent_inode` FOREIGN KEY (`parent_inode_id`) REFERENCES `inode` (`id`);
@@ -184,5 +230,6 @@ ALTER TABLE `inode`
   ADD CONSTRAINT `inode_other_perm` FOREIGN KEY (`other_perm`) REFERENCES `permissions` (`id`),
   ADD CONSTRAINT `inode_owner` FOREIGN KEY (`owner`) REFERENCES `user` (`id`),
   ADD CONSTRAINT `inode_special_bits` FOREIGN KEY (`special_bits`) REFERENCES `special_bits` (`id`),
-  ADD CONSTRAINT `inode_user_perm` FOREIGN KEY (`user_perm`) REFERENCES `permissions` (`id`);
+  ADD CONSTRAINT `inode_user_perm` FOREIGN KEY (`user_perm`) REFERENCES `permissions` (`id`),
+  ADD CONSTRAINT `inode_volume` FOREIGN KEY (`volume_id`) REFERENCES `volume` (`id`);
 COMMIT;
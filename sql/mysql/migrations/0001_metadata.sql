@@ -0,0 +1,9 @@
+-- Version 1: record the schema version in a `metadata` table
+CREATE TABLE IF NOT EXISTS `metadata` (
+  `key` varchar(64) NOT NULL,
+  `value` varchar(255) NOT NULL,
+  PRIMARY KEY (`key`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+INSERT INTO `metadata` (`key`, `value`) VALUES
+('schema_version', '1')
+ON DUPLICATE KEY UPDATE `value` = VALUES(`value`);
@@ -0,0 +1,16 @@
+-- Version 2: per-volume block size, UUID, label and feature flags in a `superblock` table
+--
+-- Volumes created before the superblock existed were written with the default block size of 65536
+-- (volumes written by an `integration_testing` build used 4096 and have to be fixed by hand).
+CREATE TABLE IF NOT EXISTS `superblock` (
+  `id` tinyint(3) UNSIGNED NOT NULL,
+  `block_size` int(10) UNSIGNED NOT NULL,
+  `uuid` char(36) NOT NULL,
+  `label` varchar(255) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  `features` bigint(20) UNSIGNED NOT NULL DEFAULT 0,
+  PRIMARY KEY (`id`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+INSERT IGNORE INTO `superblock` (`id`, `block_size`, `uuid`, `label`, `created_at`, `features`)
+SELECT 1, 65536, UUID(), '', `created_at`, 0 FROM `inode` WHERE `id` = 1;
+UPDATE `metadata` SET `value` = '2' WHERE `key` = 'schema_version';
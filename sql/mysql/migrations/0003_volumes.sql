@@ -0,0 +1,25 @@
+-- Version 3: multiple volumes in one database
+--
+-- The superblock becomes a row of the `volume` table and all existing inodes belong to the volume
+-- "default" (its root directory is the former root inode 1).
+CREATE TABLE IF NOT EXISTS `volume` (
+  `id` int(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+  `name` varchar(255) NOT NULL,
+  `root_inode_id` int(10) UNSIGNED DEFAULT NULL,
+  `block_size` int(10) UNSIGNED NOT NULL,
+  `uuid` char(36) NOT NULL,
+  `label` varchar(255) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT current_timestamp(),
+  `features` bigint(20) UNSIGNED NOT NULL DEFAULT 0,
+  PRIMARY KEY (`id`),
+  UNIQUE KEY `volume_name` (`name`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+INSERT IGNORE INTO `volume` (`id`, `name`, `root_inode_id`, `block_size`, `uuid`, `label`, `created_at`, `features`)
+SELECT 1, 'default', 1, `block_size`, `uuid`, `label`, `created_at`, `features` FROM `superblock` WHERE `id` = 1;
+ALTER TABLE `inode`
+  ADD COLUMN IF NOT EXISTS `volume_id` int(10) UNSIGNED NOT NULL DEFAULT 1 AFTER `id`,
+  ADD KEY IF NOT EXISTS `inode_volume` (`volume_id`),
+  ADD CONSTRAINT `inode_volume` FOREIGN KEY IF NOT EXISTS (`volume_id`) REFERENCES `volume` (`id`);
+ALTER TABLE `inode` ALTER COLUMN `volume_id` DROP DEFAULT;
+DROP TABLE IF EXISTS `superblock`;
+UPDATE `metadata` SET `value` = '3' WHERE `key` = 'schema_version';
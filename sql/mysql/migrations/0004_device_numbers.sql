@@ -0,0 +1,4 @@
+-- Version 4: device numbers of block and character devices
+ALTER TABLE `inode`
+  ADD COLUMN IF NOT EXISTS `rdev` int(10) UNSIGNED NOT NULL DEFAULT 0 AFTER `other_perm`;
+UPDATE `metadata` SET `value` = '4' WHERE `key` = 'schema_version';
@@ -0,0 +1,23 @@
+-- Version 5: timestamps as seconds (64-bit) and nanoseconds since the Unix epoch, a status change
+-- time separate from the birth time
+--
+-- The former `created_at` was used as both, it becomes the `ctime` and the `crtime`.
+ALTER TABLE `inode`
+  ADD COLUMN IF NOT EXISTS `atime` bigint(20) NOT NULL DEFAULT 0 AFTER `rdev`,
+  ADD COLUMN IF NOT EXISTS `atime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0 AFTER `atime`,
+  ADD COLUMN IF NOT EXISTS `mtime` bigint(20) NOT NULL DEFAULT 0 AFTER `atime_nsec`,
+  ADD COLUMN IF NOT EXISTS `mtime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0 AFTER `mtime`,
+  ADD COLUMN IF NOT EXISTS `ctime` bigint(20) NOT NULL DEFAULT 0 AFTER `mtime_nsec`,
+  ADD COLUMN IF NOT EXISTS `ctime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0 AFTER `ctime`,
+  ADD COLUMN IF NOT EXISTS `crtime` bigint(20) NOT NULL DEFAULT 0 AFTER `ctime_nsec`,
+  ADD COLUMN IF NOT EXISTS `crtime_nsec` int(10) UNSIGNED NOT NULL DEFAULT 0 AFTER `crtime`;
+UPDATE `inode` SET
+  `atime` = UNIX_TIMESTAMP(`accessed_at`),
+  `mtime` = UNIX_TIMESTAMP(`modified_at`),
+  `ctime` = UNIX_TIMESTAMP(`created_at`),
+  `crtime` = UNIX_TIMESTAMP(`created_at`);
+ALTER TABLE `inode`
+  DROP COLUMN IF EXISTS `created_at`,
+  DROP COLUMN IF EXISTS `modified_at`,
+  DROP COLUMN IF EXISTS `accessed_at`;
+UPDATE `metadata` SET `value` = '5' WHERE `key` = 'schema_version';
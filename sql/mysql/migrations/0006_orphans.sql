@@ -0,0 +1,7 @@
+-- Version 6: inodes which lost their last name while they were still open
+CREATE TABLE IF NOT EXISTS `orphan` (
+  `inode_id` int(10) UNSIGNED NOT NULL,
+  PRIMARY KEY (`inode_id`),
+  CONSTRAINT `orphan_inode` FOREIGN KEY (`inode_id`) REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_bin;
+UPDATE `metadata` SET `value` = '6' WHERE `key` = 'schema_version';
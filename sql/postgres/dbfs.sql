@@ -0,0 +1,157 @@
+-- dbfs schema for PostgreSQL
+--
+-- Safe to apply to an existing database: tables and static data are only created if missing.
+SET TIME ZONE 'UTC';
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS "file_types" (
+  "id" char(1) NOT NULL PRIMARY KEY,
+  "description" varchar(50) NOT NULL
+);
+INSERT INTO "file_types" ("id", "description") VALUES
+('-', 'Regular file'),
+('b', 'Block device'),
+('c', 'Character device'),
+('d', 'Directory'),
+('l', 'Symbolic link'),
+('p', 'Named pipe'),
+('s', 'Socket')
+ON CONFLICT DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS "permissions" (
+  "id" smallint NOT NULL PRIMARY KEY,
+  "can_read" smallint NOT NULL,
+  "can_write" smallint NOT NULL,
+  "can_execute" smallint NOT NULL
+);
+INSERT INTO "permissions" ("id", "can_read", "can_write", "can_execute") VALUES
+(0, 0, 0, 0),
+(1, 0, 0, 1),
+(2, 0, 1, 0),
+(3, 0, 1, 1),
+(4, 1, 0, 0),
+(5, 1, 0, 1),
+(6, 1, 1, 0),
+(7, 1, 1, 1)
+ON CONFLICT DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS "special_bits" (
+  "id" smallint NOT NULL PRIMARY KEY,
+  "setuid" smallint NOT NULL,
+  "setgid" smallint NOT NULL,
+  "sticky" smallint NOT NULL,
+  "description" varchar(100) NOT NULL
+);
+INSERT INTO "special_bits" ("id", "setuid", "setgid", "sticky", "description") VALUES
+(0, 0, 0, 0, 'No special bits'),
+(1, 0, 0, 1, 'Sticky bit'),
+(2, 0, 1, 0, 'Set group ID'),
+(3, 0, 1, 1, 'Set group ID and Sticky bit'),
+(4, 1, 0, 0, 'Set user ID'),
+(5, 1, 0, 1, 'Set user ID and Sticky bit'),
+(6, 1, 1, 0, 'Set user ID and Set group ID'),
+(7, 1, 1, 1, 'Set user ID, Set group ID, and Sticky bit')
+ON CONFLICT DO NOTHING;
+
+-- Ids are `bigint` as PostgreSQL has no unsigned 32-bit integer
+CREATE TABLE IF NOT EXISTS "user" (
+  "id" bigint NOT NULL PRIMARY KEY,
+  "name" varchar(255) DEFAULT NULL
+);
+INSERT INTO "user" ("id", "name") VALUES
+(0, 'root'),
+(1, 'user')
+ON CONFLICT DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS "group" (
+  "id" bigint NOT NULL PRIMARY KEY,
+  "name" varchar(255) DEFAULT NULL
+);
+INSERT INTO "group" ("id", "name") VALUES
+(0, 'root'),
+(1, 'user')
+ON CONFLICT DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS "volume" (
+  "id" bigint GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+  "name" varchar(255) COLLATE "C" NOT NULL UNIQUE,
+  "root_inode_id" bigint DEFAULT NULL,
+  "block_size" bigint NOT NULL,
+  "uuid" char(36) NOT NULL,
+  "label" varchar(255) NOT NULL,
+  "created_at" timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "features" bigint NOT NULL DEFAULT 0
+);
+INSERT INTO "volume" ("id", "name", "root_inode_id", "block_size", "uuid", "label", "features") VALUES
+(1, 'default', 1, 65536, CAST(gen_random_uuid() AS char(36)), '', 0)
+ON CONFLICT DO NOTHING;
+SELECT setval(pg_get_serial_sequence('"volume"', 'id'), (SELECT MAX("id") FROM "volume"));
+
+CREATE TABLE IF NOT EXISTS "inode" (
+  "id" bigint GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+  "volume_id" bigint NOT NULL REFERENCES "volume" ("id"),
+  "owner" bigint NOT NULL REFERENCES "user" ("id"),
+  "group" bigint NOT NULL REFERENCES "group" ("id"),
+  "file_type" char(1) NOT NULL REFERENCES "file_types" ("id"),
+  "special_bits" smallint NOT NULL DEFAULT 0 REFERENCES "special_bits" ("id"),
+  "user_perm" smallint NOT NULL DEFAULT 0 REFERENCES "permissions" ("id"),
+  "group_perm" smallint NOT NULL DEFAULT 0 REFERENCES "permissions" ("id"),
+  "other_perm" smallint NOT NULL DEFAULT 0 REFERENCES "permissions" ("id"),
+  "rdev" bigint NOT NULL DEFAULT 0,
+  "atime" bigint NOT NULL DEFAULT 0,
+  "atime_nsec" bigint NOT NULL DEFAULT 0,
+  "mtime" bigint NOT NULL DEFAULT 0,
+  "mtime_nsec" bigint NOT NULL DEFAULT 0,
+  "ctime" bigint NOT NULL DEFAULT 0,
+  "ctime_nsec" bigint NOT NULL DEFAULT 0,
+  "crtime" bigint NOT NULL DEFAULT 0,
+  "crtime_nsec" bigint NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS "inode_file_type" ON "inode" ("file_type");
+CREATE INDEX IF NOT EXISTS "inode_owner" ON "inode" ("owner");
+CREATE INDEX IF NOT EXISTS "inode_group" ON "inode" ("group");
+CREATE INDEX IF NOT EXISTS "inode_volume" ON "inode" ("volume_id");
+INSERT INTO "inode" ("id", "volume_id", "owner", "group", "file_type", "special_bits", "user_perm", "group_perm", "other_perm", "atime", "atime_nsec", "mtime", "mtime_nsec", "ctime", "ctime_nsec", "crtime", "crtime_nsec") VALUES
+(1, 1, 0, 0, 'd', 0, 7, 5, 5, 1729792372, 0, 1729792390, 0, 1729792372, 0, 1729792372, 0)
+ON CONFLICT DO NOTHING;
+SELECT setval(pg_get_serial_sequence('"inode"', 'id'), (SELECT MAX("id") FROM "inode"));
+
+-- Binary collation, like `utf8mb4_bin` in the MySQL schema
+CREATE TABLE IF NOT EXISTS "file" (
+  "parent_inode_id" bigint NOT NULL REFERENCES "inode" ("id"),
+  "name" varchar(255) COLLATE "C" NOT NULL,
+  "inode_id" bigint NOT NULL REFERENCES "inode" ("id"),
+  PRIMARY KEY ("parent_inode_id", "name")
+);
+CREATE INDEX IF NOT EXISTS "file_inode_id" ON "file" ("inode_id");
+INSERT INTO "file" ("parent_inode_id", "name", "inode_id") VALUES
+(1, '/', 1)
+ON CONFLICT DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS "block" (
+  "inode_id" bigint NOT NULL REFERENCES "inode" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+  "block_id" bigint NOT NULL,
+  "data" bytea NOT NULL,
+  PRIMARY KEY ("inode_id", "block_id")
+);
+
+CREATE TABLE IF NOT EXISTS "extended_attributes" (
+  "inode_id" bigint NOT NULL REFERENCES "inode" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+  "key" varchar(255) NOT NULL,
+  "value" bytea NOT NULL,
+  PRIMARY KEY ("inode_id", "key")
+);
+
+CREATE TABLE IF NOT EXISTS "orphan" (
+  "inode_id" bigint NOT NULL PRIMARY KEY REFERENCES "inode" ("id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS "metadata" (
+  "key" varchar(64) NOT NULL PRIMARY KEY,
+  "value" varchar(255) NOT NULL
+);
+INSERT INTO "metadata" ("key", "value") VALUES
+('schema_version', '6')
+ON CONFLICT DO NOTHING;
+
+COMMIT;
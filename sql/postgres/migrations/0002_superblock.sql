@@ -0,0 +1,18 @@
+-- Version 2: per-volume block size, UUID, label and feature flags in a "superblock" table
+--
+-- Volumes created before the superblock existed were written with the default block size of 65536
+-- (volumes written by an `integration_testing` build used 4096 and have to be fixed by hand).
+BEGIN;
+CREATE TABLE IF NOT EXISTS "superblock" (
+  "id" smallint NOT NULL PRIMARY KEY,
+  "block_size" bigint NOT NULL,
+  "uuid" char(36) NOT NULL,
+  "label" varchar(255) NOT NULL,
+  "created_at" timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "features" bigint NOT NULL DEFAULT 0
+);
+INSERT INTO "superblock" ("id", "block_size", "uuid", "label", "created_at", "features")
+SELECT 1, 65536, CAST(gen_random_uuid() AS char(36)), '', "created_at", 0 FROM "inode" WHERE "id" = 1
+ON CONFLICT DO NOTHING;
+UPDATE "metadata" SET "value" = '2' WHERE "key" = 'schema_version';
+COMMIT;
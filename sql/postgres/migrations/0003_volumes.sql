@@ -0,0 +1,25 @@
+-- Version 3: multiple volumes in one database
+--
+-- The superblock becomes a row of the "volume" table and all existing inodes belong to the volume
+-- "default" (its root directory is the former root inode 1).
+BEGIN;
+CREATE TABLE IF NOT EXISTS "volume" (
+  "id" bigint GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+  "name" varchar(255) COLLATE "C" NOT NULL UNIQUE,
+  "root_inode_id" bigint DEFAULT NULL,
+  "block_size" bigint NOT NULL,
+  "uuid" char(36) NOT NULL,
+  "label" varchar(255) NOT NULL,
+  "created_at" timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "features" bigint NOT NULL DEFAULT 0
+);
+INSERT INTO "volume" ("id", "name", "root_inode_id", "block_size", "uuid", "label", "created_at", "features")
+SELECT 1, 'default', 1, "block_size", "uuid", "label", "created_at", "features" FROM "superblock" WHERE "id" = 1
+ON CONFLICT DO NOTHING;
+SELECT setval(pg_get_serial_sequence('"volume"', 'id'), (SELECT MAX("id") FROM "volume"));
+ALTER TABLE "inode" ADD COLUMN IF NOT EXISTS "volume_id" bigint NOT NULL DEFAULT 1 REFERENCES "volume" ("id");
+ALTER TABLE "inode" ALTER COLUMN "volume_id" DROP DEFAULT;
+CREATE INDEX IF NOT EXISTS "inode_volume" ON "inode" ("volume_id");
+DROP TABLE IF EXISTS "superblock";
+UPDATE "metadata" SET "value" = '3' WHERE "key" = 'schema_version';
+COMMIT;
@@ -0,0 +1,5 @@
+-- Version 4: device numbers of block and character devices
+BEGIN;
+ALTER TABLE "inode" ADD COLUMN IF NOT EXISTS "rdev" bigint NOT NULL DEFAULT 0;
+UPDATE "metadata" SET "value" = '4' WHERE "key" = 'schema_version';
+COMMIT;
@@ -0,0 +1,29 @@
+-- Version 5: timestamps as seconds (64-bit) and nanoseconds since the Unix epoch, a status change
+-- time separate from the birth time
+--
+-- The former "created_at" was used as both, it becomes the "ctime" and the "crtime".
+BEGIN;
+ALTER TABLE "inode"
+  ADD COLUMN IF NOT EXISTS "atime" bigint NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS "atime_nsec" bigint NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS "mtime" bigint NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS "mtime_nsec" bigint NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS "ctime" bigint NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS "ctime_nsec" bigint NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS "crtime" bigint NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS "crtime_nsec" bigint NOT NULL DEFAULT 0;
+UPDATE "inode" SET
+  "atime" = FLOOR(EXTRACT(EPOCH FROM "accessed_at")),
+  "atime_nsec" = FLOOR(EXTRACT(MICROSECONDS FROM "accessed_at"))::bigint % 1000000 * 1000,
+  "mtime" = FLOOR(EXTRACT(EPOCH FROM "modified_at")),
+  "mtime_nsec" = FLOOR(EXTRACT(MICROSECONDS FROM "modified_at"))::bigint % 1000000 * 1000,
+  "ctime" = FLOOR(EXTRACT(EPOCH FROM "created_at")),
+  "ctime_nsec" = FLOOR(EXTRACT(MICROSECONDS FROM "created_at"))::bigint % 1000000 * 1000,
+  "crtime" = FLOOR(EXTRACT(EPOCH FROM "created_at")),
+  "crtime_nsec" = FLOOR(EXTRACT(MICROSECONDS FROM "created_at"))::bigint % 1000000 * 1000;
+ALTER TABLE "inode"
+  DROP COLUMN "created_at",
+  DROP COLUMN "modified_at",
+  DROP COLUMN "accessed_at";
+UPDATE "metadata" SET "value" = '5' WHERE "key" = 'schema_version';
+COMMIT;
@@ -0,0 +1,141 @@
+PRAGMA foreign_keys = ON;
+BEGIN TRANSACTION;
+
+CREATE TABLE `file_types` (
+  `id` char(1) NOT NULL PRIMARY KEY,
+  `description` varchar(50) NOT NULL
+);
+INSERT INTO `file_types` (`id`, `description`) VALUES
+('-', 'Regular file'),
+('b', 'Block device'),
+('c', 'Character device'),
+('d', 'Directory'),
+('l', 'Symbolic link'),
+('p', 'Named pipe'),
+('s', 'Socket');
+
+CREATE TABLE `permissions` (
+  `id` tinyint NOT NULL PRIMARY KEY,
+  `can_read` tinyint NOT NULL,
+  `can_write` tinyint NOT NULL,
+  `can_execute` tinyint NOT NULL
+);
+INSERT INTO `permissions` (`id`, `can_read`, `can_write`, `can_execute`) VALUES
+(0, 0, 0, 0),
+(1, 0, 0, 1),
+(2, 0, 1, 0),
+(3, 0, 1, 1),
+(4, 1, 0, 0),
+(5, 1, 0, 1),
+(6, 1, 1, 0),
+(7, 1, 1, 1);
+
+CREATE TABLE `special_bits` (
+  `id` tinyint NOT NULL PRIMARY KEY,
+  `setuid` tinyint NOT NULL,
+  `setgid` tinyint NOT NULL,
+  `sticky` tinyint NOT NULL,
+  `description` varchar(100) NOT NULL
+);
+INSERT INTO `special_bits` (`id`, `setuid`, `setgid`, `sticky`, `description`) VALUES
+(0, 0, 0, 0, 'No special bits'),
+(1, 0, 0, 1, 'Sticky bit'),
+(2, 0, 1, 0, 'Set group ID'),
+(3, 0, 1, 1, 'Set group ID and Sticky bit'),
+(4, 1, 0, 0, 'Set user ID'),
+(5, 1, 0, 1, 'Set user ID and Sticky bit'),
+(6, 1, 1, 0, 'Set user ID and Set group ID'),
+(7, 1, 1, 1, 'Set user ID, Set group ID, and Sticky bit');
+
+CREATE TABLE `user` (
+  `id` integer NOT NULL PRIMARY KEY,
+  `name` varchar(255) DEFAULT NULL
+);
+INSERT INTO `user` (`id`, `name`) VALUES
+(0, 'root'),
+(1, 'user');
+
+CREATE TABLE `group` (
+  `id` integer NOT NULL PRIMARY KEY,
+  `name` varchar(255) DEFAULT NULL
+);
+INSERT INTO `group` (`id`, `name`) VALUES
+(0, 'root'),
+(1, 'user');
+
+CREATE TABLE `volume` (
+  `id` integer NOT NULL PRIMARY KEY AUTOINCREMENT,
+  `name` varchar(255) NOT NULL UNIQUE,
+  `root_inode_id` integer DEFAULT NULL,
+  `block_size` integer NOT NULL,
+  `uuid` char(36) NOT NULL,
+  `label` varchar(255) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `features` integer NOT NULL DEFAULT 0
+);
+INSERT INTO `volume` (`id`, `name`, `root_inode_id`, `block_size`, `uuid`, `label`, `features`) VALUES
+(1, 'default', 1, 65536, lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' || substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) || substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6))), '', 0);
+
+CREATE TABLE `inode` (
+  `id` integer NOT NULL PRIMARY KEY AUTOINCREMENT,
+  `volume_id` integer NOT NULL REFERENCES `volume` (`id`),
+  `owner` integer NOT NULL REFERENCES `user` (`id`),
+  `group` integer NOT NULL REFERENCES `group` (`id`),
+  `file_type` char(1) NOT NULL REFERENCES `file_types` (`id`),
+  `special_bits` tinyint NOT NULL DEFAULT 0 REFERENCES `special_bits` (`id`),
+  `user_perm` tinyint NOT NULL DEFAULT 0 REFERENCES `permissions` (`id`),
+  `group_perm` tinyint NOT NULL DEFAULT 0 REFERENCES `permissions` (`id`),
+  `other_perm` tinyint NOT NULL DEFAULT 0 REFERENCES `permissions` (`id`),
+  `rdev` integer NOT NULL DEFAULT 0,
+  `atime` integer NOT NULL DEFAULT 0,
+  `atime_nsec` integer NOT NULL DEFAULT 0,
+  `mtime` integer NOT NULL DEFAULT 0,
+  `mtime_nsec` integer NOT NULL DEFAULT 0,
+  `ctime` integer NOT NULL DEFAULT 0,
+  `ctime_nsec` integer NOT NULL DEFAULT 0,
+  `crtime` integer NOT NULL DEFAULT 0,
+  `crtime_nsec` integer NOT NULL DEFAULT 0
+);
+CREATE INDEX `inode_file_type` ON `inode` (`file_type`);
+CREATE INDEX `inode_owner` ON `inode` (`owner`);
+CREATE INDEX `inode_group` ON `inode` (`group`);
+CREATE INDEX `inode_volume` ON `inode` (`volume_id`);
+INSERT INTO `inode` (`id`, `volume_id`, `owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`, `atime`, `atime_nsec`, `mtime`, `mtime_nsec`, `ctime`, `ctime_nsec`, `crtime`, `crtime_nsec`) VALUES
+(1, 1, 0, 0, 'd', 0, 7, 5, 5, 1729792372, 0, 1729792390, 0, 1729792372, 0, 1729792372, 0);
+
+CREATE TABLE `file` (
+  `parent_inode_id` integer NOT NULL REFERENCES `inode` (`id`),
+  `name` varchar(255) NOT NULL,
+  `inode_id` integer NOT NULL REFERENCES `inode` (`id`),
+  PRIMARY KEY (`parent_inode_id`, `name`)
+);
+CREATE INDEX `file_inode_id` ON `file` (`inode_id`);
+INSERT INTO `file` (`parent_inode_id`, `name`, `inode_id`) VALUES
+(1, '/', 1);
+
+CREATE TABLE `block` (
+  `inode_id` integer NOT NULL REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  `block_id` integer NOT NULL,
+  `data` blob NOT NULL,
+  PRIMARY KEY (`inode_id`, `block_id`)
+);
+
+CREATE TABLE `extended_attributes` (
+  `inode_id` integer NOT NULL REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+  `key` varchar(255) NOT NULL,
+  `value` blob NOT NULL,
+  PRIMARY KEY (`inode_id`, `key`)
+);
+
+CREATE TABLE `orphan` (
+  `inode_id` integer NOT NULL PRIMARY KEY REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE TABLE `metadata` (
+  `key` varchar(64) NOT NULL PRIMARY KEY,
+  `value` varchar(255) NOT NULL
+);
+INSERT INTO `metadata` (`key`, `value`) VALUES
+('schema_version', '6');
+
+COMMIT;
@@ -0,0 +1,10 @@
+-- Version 1: record the schema version in a `metadata` table
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS `metadata` (
+  `key` varchar(64) NOT NULL PRIMARY KEY,
+  `value` varchar(255) NOT NULL
+);
+INSERT INTO `metadata` (`key`, `value`) VALUES
+('schema_version', '1')
+ON CONFLICT (`key`) DO UPDATE SET `value` = excluded.`value`;
+COMMIT;
@@ -0,0 +1,17 @@
+-- Version 2: per-volume block size, UUID, label and feature flags in a `superblock` table
+--
+-- Volumes created before the superblock existed were written with the default block size of 65536
+-- (volumes written by an `integration_testing` build used 4096 and have to be fixed by hand).
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS `superblock` (
+  `id` tinyint NOT NULL PRIMARY KEY,
+  `block_size` integer NOT NULL,
+  `uuid` char(36) NOT NULL,
+  `label` varchar(255) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `features` integer NOT NULL DEFAULT 0
+);
+INSERT OR IGNORE INTO `superblock` (`id`, `block_size`, `uuid`, `label`, `created_at`, `features`)
+SELECT 1, 65536, lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' || substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) || substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6))), '', `created_at`, 0 FROM `inode` WHERE `id` = 1;
+UPDATE `metadata` SET `value` = '2' WHERE `key` = 'schema_version';
+COMMIT;
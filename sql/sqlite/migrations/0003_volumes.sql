@@ -0,0 +1,46 @@
+-- Version 3: multiple volumes in one database
+--
+-- The superblock becomes a row of the `volume` table and all existing inodes belong to the volume
+-- "default" (its root directory is the former root inode 1).
+-- SQLite can not add a column with a foreign key to an existing table, so the `inode` table is
+-- rebuilt (foreign keys are switched off meanwhile, otherwise dropping it would delete all blocks).
+PRAGMA foreign_keys = OFF;
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS `volume` (
+  `id` integer NOT NULL PRIMARY KEY AUTOINCREMENT,
+  `name` varchar(255) NOT NULL UNIQUE,
+  `root_inode_id` integer DEFAULT NULL,
+  `block_size` integer NOT NULL,
+  `uuid` char(36) NOT NULL,
+  `label` varchar(255) NOT NULL,
+  `created_at` timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `features` integer NOT NULL DEFAULT 0
+);
+INSERT OR IGNORE INTO `volume` (`id`, `name`, `root_inode_id`, `block_size`, `uuid`, `label`, `created_at`, `features`)
+SELECT 1, 'default', 1, `block_size`, `uuid`, `label`, `created_at`, `features` FROM `superblock` WHERE `id` = 1;
+CREATE TABLE `inode_migration` (
+  `id` integer NOT NULL PRIMARY KEY AUTOINCREMENT,
+  `volume_id` integer NOT NULL REFERENCES `volume` (`id`),
+  `owner` integer NOT NULL REFERENCES `user` (`id`),
+  `group` integer NOT NULL REFERENCES `group` (`id`),
+  `file_type` char(1) NOT NULL REFERENCES `file_types` (`id`),
+  `special_bits` tinyint NOT NULL DEFAULT 0 REFERENCES `special_bits` (`id`),
+  `user_perm` tinyint NOT NULL DEFAULT 0 REFERENCES `permissions` (`id`),
+  `group_perm` tinyint NOT NULL DEFAULT 0 REFERENCES `permissions` (`id`),
+  `other_perm` tinyint NOT NULL DEFAULT 0 REFERENCES `permissions` (`id`),
+  `created_at` timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `modified_at` timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `accessed_at` timestamp NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+INSERT INTO `inode_migration` (`id`, `volume_id`, `owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`, `created_at`, `modified_at`, `accessed_at`)
+SELECT `id`, 1, `owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`, `created_at`, `modified_at`, `accessed_at` FROM `inode`;
+DROP TABLE `inode`;
+ALTER TABLE `inode_migration` RENAME TO `inode`;
+CREATE INDEX `inode_file_type` ON `inode` (`file_type`);
+CREATE INDEX `inode_owner` ON `inode` (`owner`);
+CREATE INDEX `inode_group` ON `inode` (`group`);
+CREATE INDEX `inode_volume` ON `inode` (`volume_id`);
+DROP TABLE IF EXISTS `superblock`;
+UPDATE `metadata` SET `value` = '3' WHERE `key` = 'schema_version';
+COMMIT;
+PRAGMA foreign_keys = ON;
@@ -0,0 +1,5 @@
+-- Version 4: device numbers of block and character devices
+BEGIN TRANSACTION;
+ALTER TABLE `inode` ADD COLUMN `rdev` integer NOT NULL DEFAULT 0;
+UPDATE `metadata` SET `value` = '4' WHERE `key` = 'schema_version';
+COMMIT;
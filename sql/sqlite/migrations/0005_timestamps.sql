@@ -0,0 +1,28 @@
+-- Version 5: timestamps as seconds (64-bit) and nanoseconds since the Unix epoch, a status change
+-- time separate from the birth time
+--
+-- The former `created_at` was used as both, it becomes the `ctime` and the `crtime`. The old
+-- columns are text, `strftime('%f')` gives their seconds with milliseconds.
+BEGIN TRANSACTION;
+ALTER TABLE `inode` ADD COLUMN `atime` integer NOT NULL DEFAULT 0;
+ALTER TABLE `inode` ADD COLUMN `atime_nsec` integer NOT NULL DEFAULT 0;
+ALTER TABLE `inode` ADD COLUMN `mtime` integer NOT NULL DEFAULT 0;
+ALTER TABLE `inode` ADD COLUMN `mtime_nsec` integer NOT NULL DEFAULT 0;
+ALTER TABLE `inode` ADD COLUMN `ctime` integer NOT NULL DEFAULT 0;
+ALTER TABLE `inode` ADD COLUMN `ctime_nsec` integer NOT NULL DEFAULT 0;
+ALTER TABLE `inode` ADD COLUMN `crtime` integer NOT NULL DEFAULT 0;
+ALTER TABLE `inode` ADD COLUMN `crtime_nsec` integer NOT NULL DEFAULT 0;
+UPDATE `inode` SET
+  `atime` = CAST(strftime('%s', `accessed_at`) AS integer),
+  `atime_nsec` = CAST(ROUND(strftime('%f', `accessed_at`) * 1000) AS integer) % 1000 * 1000000,
+  `mtime` = CAST(strftime('%s', `modified_at`) AS integer),
+  `mtime_nsec` = CAST(ROUND(strftime('%f', `modified_at`) * 1000) AS integer) % 1000 * 1000000,
+  `ctime` = CAST(strftime('%s', `created_at`) AS integer),
+  `ctime_nsec` = CAST(ROUND(strftime('%f', `created_at`) * 1000) AS integer) % 1000 * 1000000,
+  `crtime` = CAST(strftime('%s', `created_at`) AS integer),
+  `crtime_nsec` = CAST(ROUND(strftime('%f', `created_at`) * 1000) AS integer) % 1000 * 1000000;
+ALTER TABLE `inode` DROP COLUMN `created_at`;
+ALTER TABLE `inode` DROP COLUMN `modified_at`;
+ALTER TABLE `inode` DROP COLUMN `accessed_at`;
+UPDATE `metadata` SET `value` = '5' WHERE `key` = 'schema_version';
+COMMIT;
@@ -0,0 +1,7 @@
+-- Version 6: inodes which lost their last name while they were still open
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS `orphan` (
+  `inode_id` integer NOT NULL PRIMARY KEY REFERENCES `inode` (`id`) ON DELETE CASCADE ON UPDATE CASCADE
+);
+UPDATE `metadata` SET `value` = '6' WHERE `key` = 'schema_version';
+COMMIT;
@@ -12,7 +12,10 @@ pub struct CmdArgs {
 pub enum ArgCommand {
 	Mount(ArgMount),
 	Format(ArgFormat),
-	Import(ArgImport)
+	Import(ArgImport),
+	Init(ArgInit),
+	Migrate(ArgMigrate),
+	Volumes(ArgVolumes)
 }
 
 #[derive(argp::FromArgs)]
@@ -27,6 +30,54 @@ pub struct ArgMount {
 	#[argp(description = "Allows other non-root user access.")]
 	pub allow_other: bool,
 
+	#[argp(option)]
+	#[argp(description = "Number of threads serving filesystem requests (defaults to 8).")]
+	pub threads: Option<usize>,
+
+	#[argp(switch)]
+	#[argp(description = "Retry operations until the database is reachable again (hard mount).")]
+	pub hard: bool,
+
+	#[argp(option)]
+	#[argp(description = "Seconds to retry operations while the database is unreachable before failing with EIO (defaults to 30, ignored with --hard).")]
+	pub timeout: Option<u64>,
+
+	#[argp(option)]
+	#[argp(description = "When reads update the access time: strictatime, relatime or noatime (defaults to relatime).")]
+	pub atime: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Size of the write cache in MiB (defaults to 32).")]
+	pub write_cache: Option<usize>,
+
+	#[argp(option)]
+	#[argp(description = "Milliseconds written data may stay in the write cache (defaults to 1000).")]
+	pub flush_interval: Option<u64>,
+
+	#[argp(option)]
+	#[argp(description = "Size of the block read cache in MiB, 0 disables it (defaults to 64).")]
+	pub read_cache: Option<usize>,
+
+	#[argp(option)]
+	#[argp(description = "Name of the volume (defaults to \"default\").")]
+	pub volume: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Path to a configuration file.")]
+	pub config: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Database connection URL (overrides the configuration).")]
+	pub db_url: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Read the database password from a file.")]
+	pub password_file: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Use a SQLite database file as the volume (created if missing).")]
+	pub sqlite: Option<String>,
+
 	#[argp(positional)]
     #[argp(description = "Path to the mountpoint.")]
 	pub mountpoint: String
@@ -35,19 +86,232 @@ pub struct ArgMount {
 #[derive(argp::FromArgs)]
 #[argp(description = "Formats the filesystem.")]
 #[argp(subcommand, name = "format")]
-pub struct ArgFormat {}
+pub struct ArgFormat {
+	#[argp(option)]
+	#[argp(description = "Block size of the new filesystem in bytes (a power of two, 512 - 1048576).")]
+	pub block_size: Option<u32>,
+
+	#[argp(option)]
+	#[argp(description = "Label of the new filesystem.")]
+	pub label: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Name of the volume (defaults to \"default\").")]
+	pub volume: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Path to a configuration file.")]
+	pub config: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Database connection URL (overrides the configuration).")]
+	pub db_url: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Read the database password from a file.")]
+	pub password_file: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Use a SQLite database file as the volume (created if missing).")]
+	pub sqlite: Option<String>,
+}
 
 #[derive(argp::FromArgs)]
 #[argp(description = "Imports another filesystem (or just a directory) into dbfs.")]
 #[argp(subcommand, name = "import")]
 pub struct ArgImport {
+	#[argp(option)]
+	#[argp(description = "Name of the volume (defaults to \"default\").")]
+	pub volume: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Path to a configuration file.")]
+	pub config: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Database connection URL (overrides the configuration).")]
+	pub db_url: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Read the database password from a file.")]
+	pub password_file: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Use a SQLite database file as the volume (created if missing).")]
+	pub sqlite: Option<String>,
+
 	#[argp(positional)]
     #[argp(description = "Path to the source filesystem.")]
 	pub source: String
 }
 
+#[derive(argp::FromArgs)]
+#[argp(description = "Creates the database schema of the filesystem (if it does not exist yet).")]
+#[argp(subcommand, name = "init")]
+pub struct ArgInit {
+	#[argp(switch)]
+	#[argp(description = "Drops all existing tables first (erases the whole filesystem).")]
+	pub force: bool,
+
+	#[argp(option)]
+	#[argp(description = "Path to a configuration file.")]
+	pub config: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Database connection URL (overrides the configuration).")]
+	pub db_url: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Read the database password from a file.")]
+	pub password_file: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Use a SQLite database file as the volume (created if missing).")]
+	pub sqlite: Option<String>,
+}
+
+#[derive(argp::FromArgs)]
+#[argp(description = "Upgrades the database schema of the filesystem to the current version.")]
+#[argp(subcommand, name = "migrate")]
+pub struct ArgMigrate {
+	#[argp(option)]
+	#[argp(description = "Path to a configuration file.")]
+	pub config: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Database connection URL (overrides the configuration).")]
+	pub db_url: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Read the database password from a file.")]
+	pub password_file: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Use a SQLite database file as the volume (created if missing).")]
+	pub sqlite: Option<String>,
+}
+
+#[derive(argp::FromArgs)]
+#[argp(description = "Lists, creates or deletes the volumes stored in the database.")]
+#[argp(subcommand, name = "volumes")]
+pub struct ArgVolumes {
+	#[argp(subcommand)]
+	pub command: ArgVolumesCommand
+}
+
+#[derive(argp::FromArgs)]
+#[argp(subcommand)]
+pub enum ArgVolumesCommand {
+	List(ArgVolumesList),
+	Create(ArgVolumesCreate),
+	Delete(ArgVolumesDelete)
+}
+
+#[derive(argp::FromArgs)]
+#[argp(description = "Lists all volumes.")]
+#[argp(subcommand, name = "list")]
+pub struct ArgVolumesList {
+	#[argp(option)]
+	#[argp(description = "Path to a configuration file.")]
+	pub config: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Database connection URL (overrides the configuration).")]
+	pub db_url: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Read the database password from a file.")]
+	pub password_file: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Use a SQLite database file as the volume (created if missing).")]
+	pub sqlite: Option<String>,
+}
+
+#[derive(argp::FromArgs)]
+#[argp(description = "Creates and formats a new volume.")]
+#[argp(subcommand, name = "create")]
+pub struct ArgVolumesCreate {
+	#[argp(option)]
+	#[argp(description = "Block size of the new filesystem in bytes (a power of two, 512 - 1048576).")]
+	pub block_size: Option<u32>,
+
+	#[argp(option)]
+	#[argp(description = "Label of the new filesystem.")]
+	pub label: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Path to a configuration file.")]
+	pub config: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Database connection URL (overrides the configuration).")]
+	pub db_url: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Read the database password from a file.")]
+	pub password_file: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Use a SQLite database file as the volume (created if missing).")]
+	pub sqlite: Option<String>,
+
+	#[argp(positional)]
+	#[argp(description = "Name of the new volume.")]
+	pub name: String
+}
+
+#[derive(argp::FromArgs)]
+#[argp(description = "Deletes a volume together with all of its files.")]
+#[argp(subcommand, name = "delete")]
+pub struct ArgVolumesDelete {
+	#[argp(option)]
+	#[argp(description = "Path to a configuration file.")]
+	pub config: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Database connection URL (overrides the configuration).")]
+	pub db_url: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Read the database password from a file.")]
+	pub password_file: Option<String>,
+
+	#[argp(option)]
+	#[argp(description = "Use a SQLite database file as the volume (created if missing).")]
+	pub sqlite: Option<String>,
+
+	#[argp(positional)]
+	#[argp(description = "Name of the volume.")]
+	pub name: String
+}
+
+/// Connection options shared by all subcommands
+#[derive(Debug, Default)]
+pub struct ArgConnection {
+	pub config: Option<String>,
+	pub db_url: Option<String>,
+	pub password_file: Option<String>,
+	pub sqlite: Option<String>,
+}
+
+macro_rules! impl_connection_args {
+	($($arg: ty),+) => {
+		$(impl $arg {
+			pub fn connection(&self) -> ArgConnection {
+				ArgConnection {
+					config: self.config.clone(),
+					db_url: self.db_url.clone(),
+					password_file: self.password_file.clone(),
+					sqlite: self.sqlite.clone(),
+				}
+			}
+		})+
+	}
+}
+impl_connection_args!(ArgMount, ArgFormat, ArgImport, ArgInit, ArgMigrate, ArgVolumesList, ArgVolumesCreate, ArgVolumesDelete);
+
 pub fn parse() -> CmdArgs {
 	let args: CmdArgs = argp::parse_args_or_exit(argp::DEFAULT);
 	args
 }
-
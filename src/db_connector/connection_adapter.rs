@@ -1,8 +1,11 @@
-use crate::settings;
-use crate::db_connector::chrono;
-use const_format::formatcp;
+use crate::db_connector::{chrono, DbConnectorError};
 use futures::TryStreamExt;
-use sqlx::{FromRow, MySql, MySqlPool, Pool};
+use sqlx::{Database, Executor, FromRow, IntoArguments, Mssql, MySql, Pool, Postgres, Sqlite, Transaction};
+use sqlx::database::HasArguments;
+use sqlx::error::DatabaseError;
+use sqlx::query::Query;
+use std::sync::Arc;
+use tokio::sync::Mutex;
 
 
 #[derive(Debug)]
@@ -17,9 +20,10 @@ use sqlx::{FromRow, MySql, MySqlPool, Pool};
 /// ```
 ///
 /// # Supported datatypes
-/// Automatic conversion can be done from the following datatypes: `i32`, `&str`, `String`, `Vec<u8>`
+/// Automatic conversion can be done from the following datatypes: `i32`, `i64`, `&str`, `String`, `Vec<u8>`
 pub enum DbInputType {
     SignedInteger(i32),
+    SignedBigInteger(i64),
     Integer(u32),
     BigInteger(u64),
     TinyInteger(u8),
@@ -28,6 +32,7 @@ pub enum DbInputType {
     Timestamp(chrono::DateTime<chrono::Utc>)
 }
 impl Into<DbInputType> for i32 { fn into(self) -> DbInputType { DbInputType::SignedInteger(self) } }
+impl Into<DbInputType> for i64 { fn into(self) -> DbInputType { DbInputType::SignedBigInteger(self) } }
 impl Into<DbInputType> for u32 { fn into(self) -> DbInputType { DbInputType::Integer(self) } }
 impl Into<DbInputType> for u64 { fn into(self) -> DbInputType { DbInputType::BigInteger(self) } }
 impl Into<DbInputType> for String { fn into(self) -> DbInputType { DbInputType::Char(self) } }
@@ -44,44 +49,324 @@ pub struct CommandStatus {
 }
 
 
-#[derive(Debug)]
+/// Prepared statement with its arguments for the database `DB`
+pub type DbQuery<'q, DB> = Query<'q, DB, <DB as HasArguments<'q>>::Arguments>;
+
+
+/// Differences between the supported SQL databases on the adapter level.
+///
+/// Every database supported by the [`Adapter`] implements this trait.
+pub trait Dialect: Database {
+    /// Create a lazily-connected pool for the given URL
+    fn connect(url: &str) -> Result<Pool<Self>, String>;
+
+    /// Bind one argument to a prepared statement (converting it if the database lacks the type)
+    fn bind<'q>(query: DbQuery<'q, Self>, arg: &'q DbInputType) -> DbQuery<'q, Self>;
+
+    /// Extract the command status from a query result
+    fn command_status(result: &Self::QueryResult) -> CommandStatus;
+
+    /// Classify an error reported by the server (see [`DbConnectorError`])
+    fn error(err: &dyn DatabaseError) -> DbConnectorError;
+}
+
+
+/// Convert an error of the driver, keeping apart the errors which go away on their own
+fn error<DB: Dialect>(err: sqlx::Error) -> DbConnectorError {
+    match &err {
+        sqlx::Error::Database(val) => DB::error(val.as_ref()),
+        sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::Protocol(_)
+            | sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed => DbConnectorError::ConnectionError(format!("{}", err)),
+        _ => DbConnectorError::AdapterError(format!("{}", err)),
+    }
+}
+
+
+impl Dialect for MySql {
+    fn connect(url: &str) -> Result<Pool<Self>, String> {
+        Pool::connect_lazy(url).map_err(|err| format!("{}", err))
+    }
+
+    fn bind<'q>(query: DbQuery<'q, Self>, arg: &'q DbInputType) -> DbQuery<'q, Self> {
+        match arg {
+            DbInputType::SignedInteger(val) => query.bind(val),
+            DbInputType::SignedBigInteger(val) => query.bind(val),
+            DbInputType::Integer(val) => query.bind(val),
+            DbInputType::BigInteger(val) => query.bind(val),
+            DbInputType::TinyInteger(val) => query.bind(val),
+            DbInputType::Char(val) => query.bind(val),
+            DbInputType::Blob(val) => query.bind(val),
+            DbInputType::Timestamp(val) => query.bind(val)
+        }
+    }
+
+    fn command_status(result: &Self::QueryResult) -> CommandStatus {
+        CommandStatus {
+            rows_affected: result.rows_affected(),
+            last_insert_id: result.last_insert_id()
+        }
+    }
+
+    fn error(err: &dyn DatabaseError) -> DbConnectorError {
+        use sqlx::mysql::MySqlDatabaseError;
+
+        match err.try_downcast_ref::<MySqlDatabaseError>().map(|err| err.number()) {
+            // ER_LOCK_WAIT_TIMEOUT, ER_LOCK_DEADLOCK
+            Some(1205) | Some(1213) => DbConnectorError::ConflictError(err.to_string()),
+            // ER_SERVER_SHUTDOWN, ER_CONNECTION_KILLED
+            Some(1053) | Some(1927) => DbConnectorError::ConnectionError(err.to_string()),
+            // ER_DUP_ENTRY
+            Some(1062) => DbConnectorError::DuplicateError(err.to_string()),
+            // ER_DISK_FULL, ER_RECORD_FILE_FULL
+            Some(1021) | Some(1114) => DbConnectorError::NoSpaceError(err.to_string()),
+            // ER_OPTION_PREVENTS_STATEMENT (--read-only), ER_CANT_EXECUTE_IN_READ_ONLY_TRANSACTION, ER_READ_ONLY_MODE
+            Some(1290) | Some(1792) | Some(1836) => DbConnectorError::ReadOnlyError(err.to_string()),
+            // ER_DBACCESS_DENIED_ERROR, ER_TABLEACCESS_DENIED_ERROR, ER_SPECIFIC_ACCESS_DENIED_ERROR
+            Some(1044) | Some(1142) | Some(1227) => DbConnectorError::PermissionError(err.to_string()),
+            _ => DbConnectorError::AdapterError(err.to_string()),
+        }
+    }
+}
+
+
+impl Dialect for Sqlite {
+    fn connect(url: &str) -> Result<Pool<Self>, String> {
+        use std::str::FromStr;
+        use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+        let options = SqliteConnectOptions::from_str(url).map_err(|err| format!("{}", err))?
+            .create_if_missing(true)
+            .foreign_keys(true);
+        // Every connection to an in-memory database would open a separate database, and closing
+        // the only connection would drop it
+        let pool = match url.contains(":memory:") {
+            true => SqlitePoolOptions::new().max_connections(1).idle_timeout(None).max_lifetime(None),
+            false => SqlitePoolOptions::new().max_connections(4),
+        };
+        Ok(pool.connect_lazy_with(options))
+    }
+
+    fn bind<'q>(query: DbQuery<'q, Self>, arg: &'q DbInputType) -> DbQuery<'q, Self> {
+        match arg {
+            DbInputType::SignedInteger(val) => query.bind(val),
+            DbInputType::SignedBigInteger(val) => query.bind(val),
+            DbInputType::Integer(val) => query.bind(val),
+            // SQLite integers are signed 64-bit
+            DbInputType::BigInteger(val) => query.bind(*val as i64),
+            DbInputType::TinyInteger(val) => query.bind(val),
+            DbInputType::Char(val) => query.bind(val),
+            DbInputType::Blob(val) => query.bind(val),
+            DbInputType::Timestamp(val) => query.bind(val)
+        }
+    }
+
+    fn command_status(result: &Self::QueryResult) -> CommandStatus {
+        CommandStatus {
+            rows_affected: result.rows_affected(),
+            last_insert_id: result.last_insert_rowid() as u64
+        }
+    }
+
+    fn error(err: &dyn DatabaseError) -> DbConnectorError {
+        // The code is the extended result code, its low byte is the primary one
+        let code = err.code().and_then(|code| code.parse::<u32>().ok());
+        match code {
+            // SQLITE_CONSTRAINT_PRIMARYKEY, SQLITE_CONSTRAINT_UNIQUE
+            Some(1555) | Some(2067) => DbConnectorError::DuplicateError(err.to_string()),
+            _ => match code.map(|code| code & 0xff) {
+                // SQLITE_BUSY, SQLITE_LOCKED
+                Some(5) | Some(6) => DbConnectorError::ConflictError(err.to_string()),
+                // SQLITE_FULL
+                Some(13) => DbConnectorError::NoSpaceError(err.to_string()),
+                // SQLITE_READONLY
+                Some(8) => DbConnectorError::ReadOnlyError(err.to_string()),
+                // SQLITE_PERM, SQLITE_AUTH
+                Some(3) | Some(23) => DbConnectorError::PermissionError(err.to_string()),
+                _ => DbConnectorError::AdapterError(err.to_string()),
+            },
+        }
+    }
+}
+
+
+impl Dialect for Postgres {
+    fn connect(url: &str) -> Result<Pool<Self>, String> {
+        Pool::connect_lazy(url).map_err(|err| format!("{}", err))
+    }
+
+    fn bind<'q>(query: DbQuery<'q, Self>, arg: &'q DbInputType) -> DbQuery<'q, Self> {
+        // PostgreSQL has no unsigned types - every value is widened to the next signed type
+        match arg {
+            DbInputType::SignedInteger(val) => query.bind(val),
+            DbInputType::SignedBigInteger(val) => query.bind(val),
+            DbInputType::Integer(val) => query.bind(*val as i64),
+            DbInputType::BigInteger(val) => query.bind(*val as i64),
+            DbInputType::TinyInteger(val) => query.bind(*val as i16),
+            DbInputType::Char(val) => query.bind(val),
+            DbInputType::Blob(val) => query.bind(val),
+            DbInputType::Timestamp(val) => query.bind(val)
+        }
+    }
+
+    fn command_status(result: &Self::QueryResult) -> CommandStatus {
+        // There is no last insert id - use `RETURNING` instead
+        CommandStatus {
+            rows_affected: result.rows_affected(),
+            last_insert_id: 0
+        }
+    }
+
+    fn error(err: &dyn DatabaseError) -> DbConnectorError {
+        let code = err.code().unwrap_or_default();
+        match code.as_ref() {
+            // deadlock_detected, serialization_failure, lock_not_available
+            "40P01" | "40001" | "55P03" => DbConnectorError::ConflictError(err.to_string()),
+            // admin_shutdown, crash_shutdown, cannot_connect_now, connection_exception (class 08)
+            "57P01" | "57P02" | "57P03" => DbConnectorError::ConnectionError(err.to_string()),
+            code if code.starts_with("08") => DbConnectorError::ConnectionError(err.to_string()),
+            // unique_violation
+            "23505" => DbConnectorError::DuplicateError(err.to_string()),
+            // disk_full
+            "53100" => DbConnectorError::NoSpaceError(err.to_string()),
+            // read_only_sql_transaction
+            "25006" => DbConnectorError::ReadOnlyError(err.to_string()),
+            // insufficient_privilege
+            "42501" => DbConnectorError::PermissionError(err.to_string()),
+            _ => DbConnectorError::AdapterError(err.to_string()),
+        }
+    }
+}
+
+
+/// Encode binary data as an uppercase hex string (without the `0x` prefix)
+pub fn hex_encode(data: &[u8]) -> String {
+    let mut hex = String::with_capacity(data.len() * 2);
+    for byte in data {
+        hex.push_str(&format!("{:02X}", byte));
+    }
+    hex
+}
+
+
+/// Format of timestamps bound as strings (ISO 8601, accepted by T-SQL `CONVERT` style 126)
+pub const TIMESTAMP_FORMAT: &'static str = "%Y-%m-%dT%H:%M:%S%.6f";
+
+
+impl Dialect for Mssql {
+    fn connect(url: &str) -> Result<Pool<Self>, String> {
+        Pool::connect_lazy(url).map_err(|err| format!("{}", err))
+    }
+
+    fn bind<'q>(query: DbQuery<'q, Self>, arg: &'q DbInputType) -> DbQuery<'q, Self> {
+        // The MSSQL driver has no unsigned, binary or date types. Blobs are bound as hex strings
+        // (`CONVERT(varbinary(max), @p1, 2)`) and timestamps as ISO 8601 strings
+        // (`CONVERT(datetime2, @p1, 126)`).
+        match arg {
+            DbInputType::SignedInteger(val) => query.bind(val),
+            DbInputType::SignedBigInteger(val) => query.bind(val),
+            DbInputType::Integer(val) => query.bind(*val as i64),
+            DbInputType::BigInteger(val) => query.bind(*val as i64),
+            DbInputType::TinyInteger(val) => query.bind(val),
+            DbInputType::Char(val) => query.bind(val),
+            DbInputType::Blob(val) => query.bind(hex_encode(val)),
+            DbInputType::Timestamp(val) => query.bind(val.format(TIMESTAMP_FORMAT).to_string())
+        }
+    }
+
+    fn command_status(result: &Self::QueryResult) -> CommandStatus {
+        // There is no last insert id - use `OUTPUT INSERTED` instead
+        CommandStatus {
+            rows_affected: result.rows_affected(),
+            last_insert_id: 0
+        }
+    }
+
+    fn error(err: &dyn DatabaseError) -> DbConnectorError {
+        // The driver does not expose the error number, only the message
+        let message = err.message();
+        if message.contains("deadlocked") || message.contains("Lock request time out") {
+            DbConnectorError::ConflictError(err.to_string())
+        } else if message.contains("duplicate key") {
+            DbConnectorError::DuplicateError(err.to_string())
+        } else if message.contains("filegroup is full") || message.contains("transaction log for database") {
+            DbConnectorError::NoSpaceError(err.to_string())
+        } else if message.contains("database is read-only") {
+            DbConnectorError::ReadOnlyError(err.to_string())
+        } else if message.contains("permission was denied") {
+            DbConnectorError::PermissionError(err.to_string())
+        } else {
+            DbConnectorError::AdapterError(err.to_string())
+        }
+    }
+}
+
+
+/// Transaction shared by an [`Adapter`] and its clones (`None` once it was committed or rolled back)
+///
+/// The lock is held while a statement runs, so it is an async mutex.
+type SharedTransaction<DB> = Arc<Mutex<Option<Transaction<'static, DB>>>>;
+
+
 /// SQL connection adapter.
-pub struct Adapter(Pool<MySql>);
+///
+/// Cloning the adapter is cheap and all clones share the same connection pool.
+///
+/// # Transactions
+/// An adapter returned by [`Adapter::begin`] runs all statements on the connection of its
+/// transaction. Beginning a transaction on such an adapter joins the running transaction - only the
+/// adapter which started it can commit or roll it back, for the others these are no-ops.
+pub struct Adapter<DB: Dialect> {
+    pool: Pool<DB>,
+    transaction: Option<SharedTransaction<DB>>,
+    outermost: bool,
+}
+impl<DB: Dialect> Clone for Adapter<DB> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            transaction: self.transaction.clone(),
+            outermost: self.outermost,
+        }
+    }
+}
+impl<DB: Dialect> std::fmt::Debug for Adapter<DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Adapter")
+            .field("pool", &self.pool)
+            .field("transaction", &self.transaction.is_some())
+            .finish()
+    }
+}
+
+
+const TRANSACTION_ENDED: &'static str = "the transaction has already been committed or rolled back";
 
 
 macro_rules! prepared_stmt_bind_args {
     ($args: ident, $query: ident) => {
         if let Some($args) = $args {
             for arg in $args.iter() {
-                $query = match arg {
-                    DbInputType::SignedInteger(val) => $query.bind(val),
-                    DbInputType::Integer(val) => $query.bind(val),
-                    DbInputType::BigInteger(val) => $query.bind(val),
-                    DbInputType::TinyInteger(val) => $query.bind(val),
-                    DbInputType::Char(val) => $query.bind(val),
-                    DbInputType::Blob(val) => $query.bind(val),
-                    DbInputType::Timestamp(val) => $query.bind(val)
-                };
+                $query = DB::bind($query, arg);
             }
         };
     }
 }
 
 
-impl Adapter {
+impl<DB: Dialect> Adapter<DB>
+where
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> <DB as HasArguments<'q>>::Arguments: IntoArguments<'q, DB>,
+{
 
 
-    pub async fn default() -> Result<Self, String> {
-        Ok(Self (
-            MySqlPool::connect_lazy(formatcp!(
-                "mysql://{}:{}@{}/{}?{}",
-                settings::SQL_USER,
-                settings::SQL_PASSWD,
-                settings::SQL_HOST,
-                settings::SQL_DB,
-                settings::SQL_PARAMS,
-            )).map_err(|err| format!("{}", err))?
-        ))
+    /// Create a lazily-connected pool for the database at `url`.
+    pub async fn new(url: &str) -> Result<Self, DbConnectorError> {
+        Ok(Self {
+            pool: DB::connect(url).map_err(DbConnectorError::AdapterError)?,
+            transaction: None,
+            outermost: false,
+        })
     }
 
 
@@ -92,23 +377,35 @@ impl Adapter {
     /// `YourStruct`s. Each row is deserialized into one `YourStruct`. The returned set contains all
     /// collected rows.
     ///
-    /// Will return `Err(String)` if there was an error while processing the query. The inner
-    /// value is either returned directly from the server or contains connection fail info.
+    /// Will return `Err(DbConnectorError)` if there was an error while processing the query. The
+    /// inner value is either returned directly from the server or contains connection fail info.
     ///
     /// # Example usage
     /// ```rust
     /// let rows: Vec<MyStruct> = adpt.run_query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![1.into()])).await.unwrap();
     /// ```
-    pub async fn run_query<'a, T>(&mut self, query: &'a str, args: Option<&Vec<DbInputType>>) -> Result<Vec<T>, String>
+    pub async fn run_query<'a, T>(&self, query: &'a str, args: Option<&Vec<DbInputType>>) -> Result<Vec<T>, DbConnectorError>
     where 
-        T: for<'r> FromRow<'r, sqlx::mysql::MySqlRow>
+        T: for<'r> FromRow<'r, DB::Row>
     {
-        let mut query = sqlx::query(query);
+        let mut query = sqlx::query::<DB>(query);
         prepared_stmt_bind_args!(args, query);
-        let mut query_result = query.fetch(&self.0);
         let mut result = Vec::new();
-        while let Some(row) = query_result.try_next().await.map_err(|err| format!("{}", err))? {
-            result.push(T::from_row(&row).map_err(|err| format!("{}", err))?);
+        match &self.transaction {
+            None => {
+                let mut query_result = query.fetch(&self.pool);
+                while let Some(row) = query_result.try_next().await.map_err(error::<DB>)? {
+                    result.push(T::from_row(&row).map_err(error::<DB>)?);
+                }
+            },
+            Some(transaction) => {
+                let mut transaction = transaction.lock().await;
+                let connection = transaction.as_mut().ok_or(DbConnectorError::AdapterError(TRANSACTION_ENDED.to_string()))?;
+                let mut query_result = query.fetch(&mut **connection);
+                while let Some(row) = query_result.try_next().await.map_err(error::<DB>)? {
+                    result.push(T::from_row(&row).map_err(error::<DB>)?);
+                }
+            },
         }
         Ok(result)
     }
@@ -118,22 +415,89 @@ impl Adapter {
     ///
     /// Will return `Ok(CommandStatus)` if the command was executed successfully.
     ///
-    /// Will return `Err(String)` if there was an error while processing the command. The inner
-    /// value is either returned directly from the server or contains connection fail info.
+    /// Will return `Err(DbConnectorError)` if there was an error while processing the command. The
+    /// inner value is either returned directly from the server or contains connection fail info.
     ///
     /// # Example usage
     /// ```rust
     /// adpt.run_command("INSERT INTO `test` (`id`) VALUES (?)", Some(&vec![42.into()])).await.unwrap();
     /// ```
-    pub async fn run_command<'a>(&mut self, command: &'a str, args: Option<&Vec<DbInputType>>) -> Result<CommandStatus, String> {
-        let mut query = sqlx::query(command);
+    pub async fn run_command<'a>(&self, command: &'a str, args: Option<&Vec<DbInputType>>) -> Result<CommandStatus, DbConnectorError> {
+        let mut query = sqlx::query::<DB>(command);
         prepared_stmt_bind_args!(args, query);
-        let execution = query.execute(&self.0).await.map_err(|err| format!("{}", err))?;
-        Ok(CommandStatus {
-            rows_affected: execution.rows_affected(),
-            last_insert_id: execution.last_insert_id()
+        let execution = match &self.transaction {
+            None => query.execute(&self.pool).await,
+            Some(transaction) => {
+                let mut transaction = transaction.lock().await;
+                let connection = transaction.as_mut().ok_or(DbConnectorError::AdapterError(TRANSACTION_ENDED.to_string()))?;
+                query.execute(&mut **connection).await
+            },
+        }.map_err(error::<DB>)?;
+        Ok(DB::command_status(&execution))
+    }
+
+
+    /// Script is a sequence of commands separated by `;` **without arguments**.
+    ///
+    /// Unlike `run_command` the script is not prepared, so it can contain multiple statements.
+    ///
+    /// # Example usage
+    /// ```rust
+    /// adpt.run_script("CREATE TABLE `a` (`id` int); CREATE TABLE `b` (`id` int);").await.unwrap();
+    /// ```
+    pub async fn run_script<'a>(&self, script: &'a str) -> Result<(), DbConnectorError> {
+        match &self.transaction {
+            None => self.pool.execute(script).await,
+            Some(transaction) => {
+                let mut transaction = transaction.lock().await;
+                let connection = transaction.as_mut().ok_or(DbConnectorError::AdapterError(TRANSACTION_ENDED.to_string()))?;
+                connection.execute(script).await
+            },
+        }.map_err(error::<DB>)?;
+        Ok(())
+    }
+
+
+    /// Start a transaction (or join the running one, see [`Adapter`]) and return an adapter bound
+    /// to it.
+    ///
+    /// A transaction which is neither committed nor rolled back is rolled back once the last
+    /// adapter bound to it is dropped.
+    pub async fn begin(&self) -> Result<Self, DbConnectorError> {
+        if self.transaction.is_some() {
+            return Ok(Self { outermost: false, ..self.clone() });
+        }
+        let transaction = self.pool.begin().await.map_err(error::<DB>)?;
+        Ok(Self {
+            pool: self.pool.clone(),
+            transaction: Some(Arc::new(Mutex::new(Some(transaction)))),
+            outermost: true,
         })
     }
+
+
+    /// Commit the transaction started by [`Adapter::begin`]
+    pub async fn commit(&self) -> Result<(), DbConnectorError> {
+        let Some(transaction) = self.end().await? else { return Ok(()) };
+        transaction.commit().await.map_err(error::<DB>)
+    }
+
+
+    /// Roll back the transaction started by [`Adapter::begin`]
+    pub async fn rollback(&self) -> Result<(), DbConnectorError> {
+        let Some(transaction) = self.end().await? else { return Ok(()) };
+        transaction.rollback().await.map_err(error::<DB>)
+    }
+
+
+    /// Take the transaction out of an adapter which started it (`None` if it joined one)
+    async fn end(&self) -> Result<Option<Transaction<'static, DB>>, DbConnectorError> {
+        match (&self.transaction, self.outermost) {
+            (Some(transaction), true) => Ok(Some(transaction.lock().await.take().ok_or(DbConnectorError::AdapterError(TRANSACTION_ENDED.to_string()))?)),
+            (Some(_), false) => Ok(None),
+            (None, _) => Err(DbConnectorError::AdapterError("there is no transaction to end".to_string())),
+        }
+    }
 }
 
 
@@ -141,11 +505,12 @@ impl Adapter {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::config::ConnectionConfig;
 
 
     #[tokio::test]
     async fn test_run_command() {
-        let mut adpt = Adapter::default().await.unwrap();
+        let adpt = Adapter::<MySql>::new(&ConnectionConfig::default().url()).await.unwrap();
         let result = adpt.run_command("INSERT INTO `test` (`id`) VALUES (?)", Some(&vec![42.into()])).await;
         assert_eq!(result, Ok(CommandStatus { last_insert_id: 0, rows_affected: 1 }));
     }
@@ -159,21 +524,21 @@ mod test {
 
     #[tokio::test]
     async fn test_run_select_01() {
-        let mut adpt = Adapter::default().await.unwrap();
+        let adpt = Adapter::<MySql>::new(&ConnectionConfig::default().url()).await.unwrap();
         let rows: Vec<TestPrepared> = adpt.run_query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![1.into()])).await.unwrap();
         assert_eq!(rows, vec![TestPrepared { id: 1, test_name: "aaa".to_string() }]);
     }
 
     #[tokio::test]
     async fn test_run_select_02() {
-        let mut adpt = Adapter::default().await.unwrap();
+        let adpt = Adapter::<MySql>::new(&ConnectionConfig::default().url()).await.unwrap();
         let rows: Vec<TestPrepared> = adpt.run_query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![2.into()])).await.unwrap();
         assert_eq!(rows, vec![TestPrepared { id: 2, test_name: "bbb".to_string() }]);
     }
 
     #[tokio::test]
     async fn test_run_select_03() {
-        let mut adpt = Adapter::default().await.unwrap();
+        let adpt = Adapter::<MySql>::new(&ConnectionConfig::default().url()).await.unwrap();
         let rows: Vec<TestPrepared> = adpt.run_query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![3.into()])).await.unwrap();
         assert_eq!(rows, vec![TestPrepared { id: 3, test_name: "ccc".to_string() }]);
     }
@@ -186,7 +551,7 @@ mod test {
 
     #[tokio::test]
     async fn test_run_select_04() {
-        let mut adpt = Adapter::default().await.unwrap();
+        let adpt = Adapter::<MySql>::new(&ConnectionConfig::default().url()).await.unwrap();
         let rows: Vec<TestPrepared02> = adpt.run_query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![3.into()])).await.unwrap();
         assert_eq!(rows, vec![TestPrepared02 { id: 3, test_name: vec!['c' as u8, 'c' as u8, 'c' as u8] }]);
     }
@@ -1,9 +1,12 @@
 pub use sqlx::{FromRow, types::chrono};
-use tokio::runtime::Builder;
+use std::sync::Arc;
+use tokio::runtime::{Builder, Runtime};
 
 
+use crate::config::ConnectionConfig;
 use connection_adapter::Adapter;
-pub use connection_adapter::{DbInputType, CommandStatus};
+pub use connection_adapter::{DbInputType, CommandStatus, Dialect, hex_encode, TIMESTAMP_FORMAT};
+pub use sqlx::{Mssql, MySql, Postgres, Sqlite};
 mod connection_adapter;
 
 
@@ -25,6 +28,44 @@ mod connection_adapter;
 /// The inner SQL adapter encountered an error while interacting with the database.
 /// This is usually some SQL error (such as SQL command/query syntax error).
 ///
+///
+/// ```rust
+/// ConnectionError(String)
+/// ```
+/// The database could not be reached - the connection was lost or could not be established (the
+/// server is restarting, the network is down, ...).
+///
+///
+/// ```rust
+/// ConflictError(String)
+/// ```
+/// The statement was aborted because of concurrent transactions (deadlock, lock wait timeout, busy
+/// SQLite database, ...). Its transaction was rolled back and can be run again.
+///
+///
+/// ```rust
+/// DuplicateError(String)
+/// ```
+/// A row with the same unique key (e.g. the same name in a directory) already exists.
+///
+///
+/// ```rust
+/// NoSpaceError(String)
+/// ```
+/// The database ran out of disk space (or a table reached its size limit).
+///
+///
+/// ```rust
+/// ReadOnlyError(String)
+/// ```
+/// The database (or the connection) only allows reading.
+///
+///
+/// ```rust
+/// PermissionError(String)
+/// ```
+/// The database user is not allowed to run the statement.
+///
 /// # Conversion
 ///
 /// This enum can be easily converted into a `String` as it implements `std::fmt::Display` and
@@ -38,6 +79,19 @@ mod connection_adapter;
 pub enum DbConnectorError {
     RuntimeStartFail,
     AdapterError(String),
+    ConnectionError(String),
+    ConflictError(String),
+    DuplicateError(String),
+    NoSpaceError(String),
+    ReadOnlyError(String),
+    PermissionError(String),
+}
+
+impl DbConnectorError {
+    /// Whether the error goes away on its own, so the failed operation is worth running again
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::ConnectionError(_) | Self::ConflictError(_))
+    }
 }
 
 impl std::fmt::Display for DbConnectorError {
@@ -45,6 +99,12 @@ impl std::fmt::Display for DbConnectorError {
         write!(f, "db_connector_error: {}", match self {
             Self::RuntimeStartFail => "db_connector_error: could not start async runner".to_string(),
             Self::AdapterError(val) => format!("sql_adapter: {}", val),
+            Self::ConnectionError(val) => format!("connection: {}", val),
+            Self::ConflictError(val) => format!("conflict: {}", val),
+            Self::DuplicateError(val) => format!("duplicate: {}", val),
+            Self::NoSpaceError(val) => format!("no space: {}", val),
+            Self::ReadOnlyError(val) => format!("read-only: {}", val),
+            Self::PermissionError(val) => format!("permission denied: {}", val),
         })
     }
 }
@@ -54,8 +114,9 @@ impl Into<String> for DbConnectorError { fn into(self) -> String { format!("{}",
 /// Interface for database communication
 ///
 /// # Creating a `DbConnector`
-/// The recommended method is `DbConnector::default()` - this method will use values from
-/// `crate::settings` to find the host, port, user, password and database.
+/// The recommended method is `DbConnector::new(&config)` - this method will use the runtime
+/// [`ConnectionConfig`] to find the host, port, user, password and database.
+/// `DbConnector::default()` falls back to the compiled-in values from `crate::settings`.
 ///
 /// # Command
 /// A command is an SQL query that expects no results (just success or fail). Command has a separate method to save
@@ -69,32 +130,53 @@ impl Into<String> for DbConnectorError { fn into(self) -> String { format!("{}",
 /// let rows: Vec<MyStruct> = conn.query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![3.into()])).unwrap();
 /// ```
 ///
+/// # Runtime
+/// The connector owns one long-lived async runtime which is reused by every call. Cloning the
+/// connector is cheap - clones share both the runtime and the connection pool.
+///
+/// # Databases
+/// The connector is generic over the database [`Dialect`] and defaults to MySQL.
 #[derive(Debug)]
-pub struct DbConnector {
-    adapter: connection_adapter::Adapter,
+pub struct DbConnector<DB: Dialect = MySql> {
+    runtime: Arc<Runtime>,
+    adapter: connection_adapter::Adapter<DB>,
+}
+impl<DB: Dialect> Clone for DbConnector<DB> {
+    fn clone(&self) -> Self {
+        Self {
+            runtime: self.runtime.clone(),
+            adapter: self.adapter.clone(),
+        }
+    }
 }
 
 
-macro_rules! blockingify {
-    ($expr: expr) => {
-        let rt = Builder::new_current_thread()
-            .worker_threads(4)
+/// Number of runtime worker threads driving the database IO
+const RUNTIME_WORKER_THREADS: usize = 2;
+
+
+impl<DB: Dialect> DbConnector<DB>
+where
+    for<'c> &'c mut DB::Connection: sqlx::Executor<'c, Database = DB>,
+    for<'q> <DB as sqlx::database::HasArguments<'q>>::Arguments: sqlx::IntoArguments<'q, DB>,
+{
+    pub fn new(config: &ConnectionConfig) -> Result<Self, DbConnectorError> {
+        Self::from_url(&config.url())
+    }
+
+    pub fn from_url(url: &str) -> Result<Self, DbConnectorError> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(RUNTIME_WORKER_THREADS)
+            .thread_name("dbfs-db-io")
             .enable_time()
             .enable_io()
             .build()
             .map_err(|_| DbConnectorError::RuntimeStartFail)?;
-        return rt.block_on(async move {
-            $expr
-        });
-    }
-}
-
-
-impl DbConnector {
-    pub fn default() -> Result<Self, DbConnectorError> {
-        blockingify!(Ok(Self {
-                adapter: Adapter::default().await.map_err(|err| DbConnectorError::AdapterError(err))?,
-        }));
+        let adapter = runtime.block_on(Adapter::new(url))?;
+        Ok(Self {
+            runtime: Arc::new(runtime),
+            adapter,
+        })
     }
 
     /// A command is an SQL query with **no expected response data**.
@@ -107,11 +189,20 @@ impl DbConnector {
     /// ```rust
     /// conn.command("INSERT INTO `test` (`id`) VALUES (?)", Some(&vec![42.into()])).unwrap();
     /// ```
-    pub fn command<'a>(&mut self, command: &'a str, args: Option<&Vec<DbInputType>>) -> Result<CommandStatus, DbConnectorError> {
-        blockingify!({
-            let val = self.adapter.run_command(command, args).await.map_err(|err| DbConnectorError::AdapterError(err))?;
-            Ok(val)
-        });
+    pub fn command<'a>(&self, command: &'a str, args: Option<&Vec<DbInputType>>) -> Result<CommandStatus, DbConnectorError> {
+        self.runtime.block_on(self.adapter.run_command(command, args))
+    }
+
+    /// A script is a sequence of SQL commands with **no arguments and no response data**.
+    ///
+    /// `Err(DbConnectorError)` is returned on fail.
+    ///
+    /// # Example usage
+    /// ```rust
+    /// conn.script("CREATE TABLE `a` (`id` int); CREATE TABLE `b` (`id` int);").unwrap();
+    /// ```
+    pub fn script<'a>(&self, script: &'a str) -> Result<(), DbConnectorError> {
+        self.runtime.block_on(self.adapter.run_script(script))
     }
 
     /// A query is an SQL query with **expected response data**.
@@ -127,13 +218,47 @@ impl DbConnector {
     /// ```rust
     /// let rows: Vec<MyStruct> = conn.query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![3.into()])).unwrap();
     /// ```
-    pub fn query<'a, T>(&mut self, query: &'a str, args: Option<&Vec<DbInputType>>) -> Result<Vec<T>, DbConnectorError>
+    pub fn query<'a, T>(&self, query: &'a str, args: Option<&Vec<DbInputType>>) -> Result<Vec<T>, DbConnectorError>
     where
-        T: for<'r> FromRow<'r, sqlx::mysql::MySqlRow>
+        T: for<'r> FromRow<'r, DB::Row>
     {
-        blockingify!({
-            Ok(self.adapter.run_query(query, args).await.map_err(|err| DbConnectorError::AdapterError(err))?)
-        });
+        self.runtime.block_on(self.adapter.run_query(query, args))
+    }
+
+    /// Start a transaction.
+    ///
+    /// The returned connector runs all commands, queries and scripts inside the transaction until
+    /// `commit` or `rollback` is called on it. Starting a transaction on such a connector joins the
+    /// running one - only the outermost `commit`/`rollback` ends it, the inner ones do nothing.
+    ///
+    /// # Example usage
+    /// ```rust
+    /// let transaction = conn.begin().unwrap();
+    /// transaction.command("INSERT INTO `test` (`id`) VALUES (?)", Some(&vec![42.into()])).unwrap();
+    /// transaction.commit().unwrap();
+    /// ```
+    pub fn begin(&self) -> Result<Self, DbConnectorError> {
+        Ok(Self {
+            runtime: self.runtime.clone(),
+            adapter: self.runtime.block_on(self.adapter.begin())?,
+        })
+    }
+
+    /// Commit the transaction started by `begin`
+    pub fn commit(&self) -> Result<(), DbConnectorError> {
+        self.runtime.block_on(self.adapter.commit())
+    }
+
+    /// Roll back the transaction started by `begin`
+    pub fn rollback(&self) -> Result<(), DbConnectorError> {
+        self.runtime.block_on(self.adapter.rollback())
+    }
+}
+
+
+impl DbConnector<MySql> {
+    pub fn default() -> Result<Self, DbConnectorError> {
+        Self::new(&ConnectionConfig::default())
     }
 }
 
@@ -146,7 +271,7 @@ mod test {
 
     #[test]
     fn test_run_command() {
-        let mut conn = DbConnector::default().unwrap();
+        let conn = DbConnector::default().unwrap();
         let result = conn.command("INSERT INTO `test` (`id`) VALUES (?)", Some(&vec![42.into()]));
         assert_eq!(result, Ok(connection_adapter::CommandStatus { last_insert_id: 0, rows_affected: 1 }));
     }
@@ -160,21 +285,21 @@ mod test {
 
     #[test]
     fn test_run_select_01() {
-        let mut conn = DbConnector::default().unwrap();
+        let conn = DbConnector::default().unwrap();
         let rows: Vec<TestPrepared> = conn.query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![1.into()])).unwrap();
         assert_eq!(rows, vec![TestPrepared { id: 1, test_name: "aaa".to_string() }]);
     }
 
     #[test]
     fn test_run_select_02() {
-        let mut conn = DbConnector::default().unwrap();
+        let conn = DbConnector::default().unwrap();
         let rows: Vec<TestPrepared> = conn.query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![2.into()])).unwrap();
         assert_eq!(rows, vec![TestPrepared { id: 2, test_name: "bbb".to_string() }]);
     }
 
     #[test]
     fn test_run_select_03() {
-        let mut conn = DbConnector::default().unwrap();
+        let conn = DbConnector::default().unwrap();
         let rows: Vec<TestPrepared> = conn.query("SELECT * FROM `test_prepared` WHERE `id` = ?", Some(&vec![3.into()])).unwrap();
         assert_eq!(rows, vec![TestPrepared { id: 3, test_name: "ccc".to_string() }]);
     }
@@ -0,0 +1,121 @@
+use crate::sql_translation_layer::driver_objects::{AtimePolicy, FileAttr};
+use crate::sql_translation_layer::Error;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Attributes of the inodes read recently, shared by all FUSE workers
+///
+/// Whether a read updates the access time depends on the access, modification and change time of
+/// the inode (see [`AtimePolicy`]). They are kept here for a while, so reading a file does not cost
+/// a database round trip per `read(2)`. Workers forget the attributes of the inodes they change.
+pub struct AccessTimes {
+	policy: AtimePolicy,
+	/// How long the attributes of an inode are used before they are fetched again
+	ttl: Duration,
+	inodes: Mutex<HashMap<u64, Known>>
+}
+
+struct Known {
+	attr: FileAttr,
+	/// When the attributes were fetched from the database
+	since: Instant
+}
+
+impl AccessTimes {
+	pub fn new(policy: AtimePolicy, ttl: Duration) -> Self {
+		Self { policy, ttl, inodes: Mutex::new(HashMap::new()) }
+	}
+
+	fn inodes(&self) -> MutexGuard<'_, HashMap<u64, Known>> {
+		self.inodes.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+
+	/// Whether reading `inode` at `time` updates its access time, it is taken as updated afterwards
+	///
+	/// `getattr` gets the attributes known from an earlier read (`None` if there are none or they
+	/// are too old) and returns the current attributes. It is not called if the access time never
+	/// changes.
+	pub fn read(&self, inode: u64, time: SystemTime, getattr: impl FnOnce(Option<FileAttr>) -> Result<FileAttr, Error>) -> Result<bool, Error> {
+		if self.policy == AtimePolicy::Never {
+			return Ok(false)
+		}
+
+		let known = self.inodes().get(&inode)
+			.filter(|known| known.since.elapsed() < self.ttl)
+			.map(|known| (known.attr.clone(), known.since));
+		let fetched = known.is_none();
+		let since = known.as_ref().map_or_else(Instant::now, |(_, since)| *since);
+		let mut attr = getattr(known.map(|(attr, _)| attr))?;
+
+		let update = self.policy.update(&attr, time);
+		if update {
+			attr.atime = time;
+		}
+
+		let mut inodes = self.inodes();
+		if fetched {
+			inodes.retain(|_, known| known.since.elapsed() < self.ttl);
+		}
+		inodes.insert(inode, Known { attr, since });
+		Ok(update)
+	}
+
+	/// The inode was changed (or its access time could not be set), its attributes are fetched
+	/// again by the next read
+	pub fn forget(&self, inode: u64) {
+		self.inodes().remove(&inode);
+	}
+}
+
+
+
+#[cfg(test)]
+mod test {
+	use crate::sql_translation_layer::driver_objects::FileType;
+	use crate::sql_translation_layer::test_util::{create, volume};
+	use super::*;
+
+
+	#[test]
+	fn attributes_are_fetched_once() {
+		let tl = volume();
+		let ino = create(&tl, 1, "file", FileType::File);
+		let times = AccessTimes::new(AtimePolicy::Relative, Duration::from_secs(3600));
+		let fetched = std::cell::Cell::new(0);
+		let getattr = |known: Option<FileAttr>| {
+			if known.is_none() { fetched.set(fetched.get() + 1); }
+			known.map(Ok).unwrap_or_else(|| tl.getattr(ino))
+		};
+
+		// A new file was never read, so its access time is not newer than its change time
+		let now = SystemTime::now();
+		assert!(times.read(ino, now, getattr).unwrap());
+		assert!(!times.read(ino, now, getattr).unwrap());
+		assert!(!times.read(ino, now, getattr).unwrap());
+		assert_eq!(fetched.get(), 1);
+
+		times.forget(ino);
+		assert!(times.read(ino, now, getattr).unwrap());
+		assert_eq!(fetched.get(), 2);
+
+		let never = AccessTimes::new(AtimePolicy::Never, Duration::from_secs(3600));
+		assert!(!never.read(ino, now, |_| panic!("the attributes are not needed")).unwrap());
+	}
+
+	#[test]
+	fn outdated_attributes_are_fetched_again() {
+		let tl = volume();
+		let ino = create(&tl, 1, "file", FileType::File);
+		let times = AccessTimes::new(AtimePolicy::Relative, Duration::ZERO);
+		let now = SystemTime::now();
+
+		assert!(times.read(ino, now, |_| tl.getattr(ino)).unwrap());
+		// The access time was not set in the database
+		assert!(times.read(ino, now, |known| {
+			assert_eq!(known, None);
+			tl.getattr(ino)
+		}).unwrap());
+		assert!(times.inodes().len() <= 1);
+	}
+}
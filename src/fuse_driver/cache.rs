@@ -1,138 +1,496 @@
 use crate::debug;
-use crate::sql_translation_layer::TranslationLayer;
-use std::sync::{mpsc, Arc, Mutex};
-use std::time::Duration;
+use crate::sql_translation_layer::{driver_objects, Error, TranslationLayer};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::{Duration, Instant, SystemTime};
 
+/// Write-back cache shared by all FUSE workers
+///
+/// Writes are kept as dirty extents of their inode (overlapping and adjacent writes are merged)
+/// and are served to reads and `getattr` before they reach the database. The dirty data of an
+/// inode is written to the database when it is synced, when it has been dirty for longer than the
+/// flush interval or when the cache grows larger than its size. Writes which fail then are
+/// reported by the next [`WriteCache::sync`] of their inode.
 pub struct WriteCache {
-	cache_thread_tx: mpsc::Sender<CacheThreadMessage>,
-	cache_thread_rx: mpsc::Receiver<()>
+	tl: Arc<TranslationLayer>,
+	inodes: Mutex<HashMap<u64, Arc<Mutex<DirtyInode>>>>,
+	/// Bytes of dirty data in all inodes
+	used: AtomicUsize,
+	size: usize,
+	/// `inode -> errno` of the first failed write which was not reported yet
+	errors: Mutex<HashMap<u64, i32>>
 }
 
-pub struct WriteCommand {
-	pub inode: u64,
-	pub offset: u64,
-	pub data: Vec<u8>
+/// Dirty data of an inode
+struct DirtyInode {
+	/// `offset -> data`, the extents neither overlap nor touch each other
+	extents: BTreeMap<u64, Vec<u8>>,
+	/// Time of the last write, it becomes the modification time of the inode once the data is flushed
+	time: SystemTime,
+	/// When the inode became dirty
+	since: Instant,
+	/// The inode was flushed and removed from [`WriteCache::inodes`], writers have to look it up again
+	removed: bool
 }
 
-pub enum CacheThreadMessage {
-	Flush,
-	Write(WriteCommand)
-}
+impl DirtyInode {
+	/// End of the dirty data (the size of the file if it is larger than the size in the database)
+	fn end(&self) -> Option<u64> {
+		self.extents.last_key_value().map(|(offset, data)| offset + data.len() as u64)
+	}
+
+	/// Merge a write into the extents, returns the number of bytes removed from and added to them
+	fn insert(&mut self, offset: u64, data: &[u8]) -> (usize, usize) {
+		let end = offset + data.len() as u64;
+		// Extents overlapping or touching the write (in descending order)
+		let touching: Vec<(u64, u64)> = self.extents.range(..=end).rev()
+			.map(|(start, extent)| (*start, start + extent.len() as u64))
+			.take_while(|(_, stop)| *stop >= offset)
+			.collect();
+		let start = touching.iter().map(|(start, _)| *start).fold(offset, u64::min);
+		let stop = touching.iter().map(|(_, stop)| *stop).fold(end, u64::max);
 
-struct CacheThread {
-	tl: Arc<Mutex<TranslationLayer>>,
-	cache: Vec<u8>,
-	cache_ptr: usize,
-	cache_inode_offset: u64,
-	last_inode: u64,
-	tx: mpsc::Sender<()>,
-	rx: mpsc::Receiver<CacheThreadMessage>
+		// Sequential writes append to the first extent without copying it
+		let mut merged = match touching.last() {
+			Some((first, _)) if *first == start => self.extents.remove(first).unwrap_or_default(),
+			_ => Vec::new()
+		};
+		let mut removed = merged.len();
+		merged.resize((stop - start) as usize, 0);
+		for (extent_start, _) in &touching {
+			if let Some(extent) = self.extents.remove(extent_start) {
+				removed += extent.len();
+				let at = (extent_start - start) as usize;
+				merged[at..at + extent.len()].copy_from_slice(&extent);
+			}
+		}
+		let at = (offset - start) as usize;
+		merged[at..at + data.len()].copy_from_slice(data);
+
+		let added = merged.len();
+		self.extents.insert(start, merged);
+		(removed, added)
+	}
 }
 
-impl CacheThread {
-	fn write(&mut self, write: WriteCommand) {
-		if write.inode != self.last_inode || write.offset != self.cache_inode_offset + self.cache_ptr as u64 {
-			self.flush();
-			self.last_inode = write.inode;
-			self.cache_inode_offset = write.offset;
+impl WriteCache {
+	/// # Inputs
+	/// `size: usize` is the number of dirty bytes which makes the cache flush the oldest inodes
+	/// `interval: Duration` is how long data may stay dirty
+	pub fn new(tl: Arc<TranslationLayer>, size: usize, interval: Duration) -> Arc<Self> {
+		let cache = Arc::new(Self {
+			tl,
+			inodes: Mutex::new(HashMap::new()),
+			used: AtomicUsize::new(0),
+			size,
+			errors: Mutex::new(HashMap::new())
+		});
+
+		let weak = Arc::downgrade(&cache);
+		std::thread::spawn(move || Self::run(weak, interval));
+		cache
+	}
+
+	/// Flush the expired inodes until the cache is dropped
+	fn run(cache: Weak<Self>, interval: Duration) {
+		let tick = (interval / 4).max(Duration::from_millis(10));
+		loop {
+			std::thread::sleep(tick);
+			let Some(cache) = cache.upgrade() else { return };
+			for (inode, dirty) in cache.dirty_inodes() {
+				let dirty = cache.lock(&dirty);
+				if dirty.since.elapsed() >= interval {
+					cache.flush_dirty(inode, dirty);
+				}
+			}
 		}
+	}
 
-		let mut written = 0usize;
+	fn lock<'a>(&self, dirty: &'a Mutex<DirtyInode>) -> MutexGuard<'a, DirtyInode> {
+		dirty.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
 
-		while written < write.data.len() {
-			let avail_in_cache = self.cache.len() - self.cache_ptr;
-			let to_write = write.data.len() - written;
-			let will_write = usize::min(avail_in_cache, to_write);
+	fn dirty_inodes(&self) -> Vec<(u64, Arc<Mutex<DirtyInode>>)> {
+		let inodes = self.inodes.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		inodes.iter().map(|(inode, dirty)| (*inode, dirty.clone())).collect()
+	}
 
-			self.cache[self.cache_ptr..self.cache_ptr + will_write].copy_from_slice(&write.data[written..written + will_write]);
+	fn get(&self, inode: u64) -> Option<Arc<Mutex<DirtyInode>>> {
+		self.inodes.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get(&inode).cloned()
+	}
 
-			self.cache_ptr += will_write;
-			if self.cache_ptr >= self.cache.len() { self.flush(); }
+	/// Write the dirty data of an inode to the database and remove it from the cache
+	fn flush_dirty(&self, inode: u64, mut dirty: MutexGuard<'_, DirtyInode>) {
+		if dirty.removed { return; }
 
-			written += will_write;
+		for (offset, data) in std::mem::take(&mut dirty.extents) {
+			debug!("CACHE: flushing inode {}, offset {}, {} bytes", inode, offset, data.len());
+			if let Err(err) = self.tl.write_with_mtime(inode, offset, &data, dirty.time) {
+				debug!("CACHE: Err while flushing inode {}: {:?}", inode, &err);
+				let mut errors = self.errors.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+				errors.entry(inode).or_insert(err.into());
+			}
+			self.used.fetch_sub(data.len(), Ordering::SeqCst);
 		}
+
+		// Still holding the lock of the inode, so no write can get lost in the removed entry
+		dirty.removed = true;
+		self.inodes.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(&inode);
 	}
 
-	fn flush(&mut self) {
-		if self.cache_ptr == 0 { return; }
+	/// Write all cached data to the database
+	pub fn flush(&self) {
+		for (inode, dirty) in self.dirty_inodes() {
+			self.flush_dirty(inode, self.lock(&dirty));
+		}
+	}
 
-		debug!("CACHE: flushing inode {}, offset {}, {} bytes", self.last_inode, self.cache_inode_offset, self.cache_ptr);
+	/// Write the cached data of `inode` to the database
+	pub fn flush_inode(&self, inode: u64) {
+		if let Some(dirty) = self.get(inode) {
+			self.flush_dirty(inode, self.lock(&dirty));
+		}
+	}
 
-		let _ = self.tl.lock().unwrap().write(self.last_inode, self.cache_inode_offset, &self.cache[..self.cache_ptr]); // TODO - error handling
+	/// Write the cached data of `inode` to the database and report (once) the `errno` of a write to
+	/// it which failed since the last call
+	pub fn sync(&self, inode: u64) -> Result<(), i32> {
+		self.flush_inode(inode);
+		let mut errors = self.errors.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		errors.remove(&inode).map_or(Ok(()), Err)
+	}
 
-		self.cache_inode_offset += self.cache_ptr as u64;
-		self.cache_ptr = 0;
+	/// Whether `inode` has data which is not in the database yet
+	pub fn is_dirty(&self, inode: u64) -> bool {
+		self.get(inode).is_some()
 	}
-	
-	fn run_loop(&mut self) {
-		let msg = match self.rx.recv_timeout(Duration::from_millis(100)) {
-			Ok(val) => val,
-			Err(_) => {
-				self.flush();
-				return
-			}
-		};
 
-		match msg {
-			CacheThreadMessage::Write(write) => self.write(write),
-			CacheThreadMessage::Flush => self.flush()
+	pub fn write(&self, inode: u64, offset: u64, data: Vec<u8>) {
+		let time = SystemTime::now();
+		loop {
+			let dirty = self.inodes.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+				.entry(inode)
+				.or_insert_with(|| Arc::new(Mutex::new(DirtyInode { extents: BTreeMap::new(), time, since: Instant::now(), removed: false })))
+				.clone();
+			let mut dirty = self.lock(&dirty);
+			if dirty.removed { continue; }
+
+			let (removed, added) = dirty.insert(offset, &data);
+			dirty.time = time;
+			self.used.fetch_add(added, Ordering::SeqCst);
+			self.used.fetch_sub(removed, Ordering::SeqCst);
+			break
 		}
 
-		self.tx.send(()).unwrap();
+		if self.used.load(Ordering::SeqCst) > self.size {
+			self.shrink();
+		}
 	}
 
-	pub fn run(tl: Arc<Mutex<TranslationLayer>>, size: usize, tx: mpsc::Sender<()>, rx: mpsc::Receiver<CacheThreadMessage>) -> ! {
-		let mut new = Self {
-			tl,
-			cache: vec![0u8; size],
-			cache_ptr: 0usize,
-			cache_inode_offset: 0u64,
-			last_inode: u64::MAX,
-			tx,
-			rx
+	/// Flush the inodes which are dirty for the longest time until the cache is half empty
+	fn shrink(&self) {
+		let mut inodes: Vec<_> = self.dirty_inodes().into_iter()
+			.map(|(inode, dirty)| {
+				let since = self.lock(&dirty).since;
+				(since, inode, dirty)
+			})
+			.collect();
+		inodes.sort_by_key(|(since, inode, _)| (*since, *inode));
+
+		for (_, inode, dirty) in inodes {
+			if self.used.load(Ordering::SeqCst) <= self.size / 2 { break; }
+			self.flush_dirty(inode, self.lock(&dirty));
+		}
+	}
+
+	/// Read from an inode: `read_db` reads the data in the database into the buffer, the dirty data
+	/// is put on top of it
+	///
+	/// Returns the number of bytes read, which may be more than `read_db` found in the database.
+	pub fn read(&self, inode: u64, offset: u64, buffer: &mut [u8], read_db: impl FnOnce(&mut [u8]) -> Result<usize, Error>) -> Result<usize, Error> {
+		let Some(dirty) = self.get(inode) else {
+			return read_db(buffer)
 		};
+		// Holding the lock, the data can not be flushed between reading the database and the cache
+		let dirty = self.lock(&dirty);
 
-		loop {
-			new.run_loop();
+		let mut read = match read_db(buffer) {
+			Ok(read) => read,
+			// The dirty data extends the file beyond its end in the database
+			Err(Error::ClientError(_)) if dirty.end().is_some_and(|end| end > offset) => 0,
+			Err(err) => return Err(err)
+		};
+
+		let end = offset + buffer.len() as u64;
+		let first = dirty.extents.range(..=offset).next_back().map_or(offset, |(start, _)| *start);
+		for (start, data) in dirty.extents.range(first..end) {
+			let from = offset.max(*start);
+			let to = end.min(start + data.len() as u64);
+			if from >= to { continue; }
+			buffer[(from - offset) as usize..(to - offset) as usize].copy_from_slice(&data[(from - start) as usize..(to - start) as usize]);
+			read = read.max((to - offset) as usize);
+		}
+		Ok(read)
+	}
+
+	/// Attributes of an inode (fetched by `getattr`) including its dirty data: the size and the
+	/// modification and change time of the last write
+	///
+	/// The blocks of the data are only counted once it is written to the database.
+	pub fn getattr(&self, inode: u64, getattr: impl FnOnce() -> Result<driver_objects::FileAttr, Error>) -> Result<driver_objects::FileAttr, Error> {
+		let Some(dirty) = self.get(inode) else {
+			return getattr()
+		};
+		let dirty = self.lock(&dirty);
+
+		let mut attr = getattr()?;
+		if let Some(end) = dirty.end() {
+			attr.bytes = attr.bytes.max(end);
+			attr.mtime = dirty.time;
+			attr.ctime = dirty.time;
 		}
+		Ok(attr)
 	}
 }
 
-impl WriteCache {
-	pub fn new(tl: Arc<Mutex<TranslationLayer>>, size: usize) -> Self {
-		let (tx, rxsub) = mpsc::channel();
-		let (txsub, rx) = mpsc::channel();
 
-		std::thread::spawn(move || {
-			CacheThread::run(tl, size, txsub, rxsub);
-		});
 
-		Self {
-			cache_thread_tx: tx,
-			cache_thread_rx: rx
+#[cfg(test)]
+mod test {
+	use crate::sql_translation_layer::test_util;
+	use super::*;
+
+
+	const HOUR: Duration = Duration::from_secs(3600);
+
+	fn volume() -> Arc<TranslationLayer> {
+		Arc::new(test_util::volume())
+	}
+
+	fn create(tl: &TranslationLayer, name: &str) -> u64 {
+		test_util::create(tl, 1, name, driver_objects::FileType::File)
+	}
+
+	fn contents(tl: &TranslationLayer, inode: u64) -> Vec<u8> {
+		let mut buffer = vec![0; tl.filesize(inode).unwrap().bytes as usize];
+		tl.read(inode, 0, &mut buffer).unwrap();
+		buffer
+	}
+
+	/// Read through the cache like the driver does
+	fn read(cache: &WriteCache, tl: &TranslationLayer, inode: u64, offset: u64, size: usize) -> Vec<u8> {
+		let mut buffer = vec![0; size];
+		let read = cache.read(inode, offset, &mut buffer, |buffer| tl.read(inode, offset, buffer)).unwrap();
+		buffer.truncate(read);
+		buffer
+	}
+
+
+	#[test]
+	fn sequential_writes() {
+		let tl = volume();
+		let ino = create(&tl, "test.txt");
+		let cache = WriteCache::new(tl.clone(), 1024, HOUR);
+		cache.write(ino, 0, b"Hello, ".to_vec());
+		cache.write(ino, 7, b"world!".to_vec());
+		assert_eq!(cache.get(ino).unwrap().lock().unwrap().extents.len(), 1);
+		cache.flush();
+		assert_eq!(contents(&tl, ino), b"Hello, world!");
+	}
+
+	#[test]
+	fn writes_larger_than_cache() {
+		let tl = volume();
+		let ino = create(&tl, "test.bin");
+		let cache = WriteCache::new(tl.clone(), 16, HOUR);
+		let data: Vec<u8> = (0..100).collect();
+		cache.write(ino, 0, data[..50].to_vec());
+		assert!(!cache.is_dirty(ino));
+		cache.write(ino, 50, data[50..].to_vec());
+		cache.flush();
+		assert_eq!(contents(&tl, ino), data);
+		assert_eq!(cache.used.load(Ordering::SeqCst), 0);
+	}
+
+	#[test]
+	fn non_contiguous_writes() {
+		let tl = volume();
+		let ino = create(&tl, "test.txt");
+		let cache = WriteCache::new(tl.clone(), 1024, HOUR);
+		cache.write(ino, 0, b"aaaaaa".to_vec());
+		cache.write(ino, 2, b"bb".to_vec());
+		cache.write(ino, 8, b"cc".to_vec());
+		assert_eq!(cache.used.load(Ordering::SeqCst), 8);
+		cache.flush();
+		assert_eq!(contents(&tl, ino), b"aabbaa\0\0cc");
+	}
+
+	#[test]
+	fn random_writes_are_merged() {
+		let mut dirty = DirtyInode { extents: BTreeMap::new(), time: SystemTime::now(), since: Instant::now(), removed: false };
+		assert_eq!(dirty.insert(10, b"cc"), (0, 2));
+		assert_eq!(dirty.insert(0, b"aa"), (0, 2));
+		assert_eq!(dirty.insert(4, b"bb"), (0, 2));
+		assert_eq!(dirty.extents.len(), 3);
+
+		// Bridges the first two extents and touches the third one
+		assert_eq!(dirty.insert(1, b"xxxxxxxxx"), (6, 12));
+		assert_eq!(dirty.extents, BTreeMap::from([(0, b"axxxxxxxxxcc".to_vec())]));
+		assert_eq!(dirty.insert(3, b"y"), (12, 12));
+		assert_eq!(dirty.extents, BTreeMap::from([(0, b"axxyxxxxxxcc".to_vec())]));
+		assert_eq!(dirty.end(), Some(12));
+	}
+
+	#[test]
+	fn interleaved_inodes() {
+		let tl = volume();
+		let first = create(&tl, "first.txt");
+		let second = create(&tl, "second.txt");
+		let cache = WriteCache::new(tl.clone(), 1024, HOUR);
+		cache.write(first, 0, b"one".to_vec());
+		cache.write(second, 0, b"two".to_vec());
+		cache.write(first, 3, b"!".to_vec());
+		assert!(cache.is_dirty(first) && cache.is_dirty(second));
+		cache.flush();
+		assert_eq!(contents(&tl, first), b"one!");
+		assert_eq!(contents(&tl, second), b"two");
+	}
+
+	#[test]
+	fn flush_inode_only_flushes_its_inode() {
+		let tl = volume();
+		let first = create(&tl, "first.txt");
+		let second = create(&tl, "second.txt");
+		let cache = WriteCache::new(tl.clone(), 1024, HOUR);
+		cache.write(first, 0, b"one".to_vec());
+		cache.write(second, 0, b"two".to_vec());
+		cache.flush_inode(first);
+		assert_eq!(contents(&tl, first), b"one");
+		assert!(!cache.is_dirty(first));
+		assert!(cache.is_dirty(second));
+		assert_eq!(cache.used.load(Ordering::SeqCst), 3);
+	}
+
+	#[test]
+	fn reads_and_getattr_see_dirty_data() {
+		let tl = volume();
+		let ino = create(&tl, "test.txt");
+		tl.write(ino, 0, b"0123456789").unwrap();
+		let cache = WriteCache::new(tl.clone(), 1024, HOUR);
+		cache.write(ino, 2, b"ab".to_vec());
+		cache.write(ino, 14, b"cd".to_vec());
+
+		assert_eq!(read(&cache, &tl, ino, 0, 100), b"01ab456789\0\0\0\0cd");
+		assert_eq!(read(&cache, &tl, ino, 3, 2), b"b4");
+		assert_eq!(read(&cache, &tl, ino, 12, 100), b"\0\0cd");
+		let attr = cache.getattr(ino, || tl.getattr(ino)).unwrap();
+		assert_eq!(attr.bytes, 16);
+		assert_eq!(tl.getattr(ino).unwrap().bytes, 10);
+
+		// Nothing was flushed
+		assert!(cache.is_dirty(ino));
+		assert_eq!(contents(&tl, ino), b"0123456789");
+		cache.flush();
+		assert_eq!(contents(&tl, ino), b"01ab456789\0\0\0\0cd");
+		assert_eq!(attr.mtime, tl.getattr(ino).unwrap().mtime);
+	}
+
+	#[test]
+	fn empty_file_with_dirty_data() {
+		let tl = volume();
+		let ino = create(&tl, "test.txt");
+		let cache = WriteCache::new(tl.clone(), 1024, HOUR);
+		cache.write(ino, 0, b"data".to_vec());
+		assert_eq!(read(&cache, &tl, ino, 0, 10), b"data");
+		assert_eq!(read(&cache, &tl, ino, 2, 10), b"ta");
+		assert!(cache.read(ino, 4, &mut [0; 10], |buffer| tl.read(ino, 4, buffer)).is_err());
+	}
+
+	#[test]
+	fn flushed_after_interval() {
+		let tl = volume();
+		let ino = create(&tl, "test.txt");
+		let cache = WriteCache::new(tl.clone(), 1024, Duration::from_millis(20));
+		cache.write(ino, 0, b"data".to_vec());
+		for _ in 0..100 {
+			if !cache.is_dirty(ino) { break; }
+			std::thread::sleep(Duration::from_millis(10));
 		}
+		assert!(!cache.is_dirty(ino));
+		assert_eq!(contents(&tl, ino), b"data");
 	}
-	
-	pub fn send_msg(&mut self, msg: CacheThreadMessage) {
-		self.cache_thread_tx.send(msg).unwrap();
+
+	#[test]
+	fn memory_pressure_flushes_oldest_inodes() {
+		let tl = volume();
+		let first = create(&tl, "first.bin");
+		let second = create(&tl, "second.bin");
+		let cache = WriteCache::new(tl.clone(), 100, HOUR);
+		cache.write(first, 0, vec![1; 60]);
+		cache.write(second, 0, vec![2; 20]);
+		assert_eq!(cache.used.load(Ordering::SeqCst), 80);
+		cache.write(second, 20, vec![2; 30]);
+		assert!(!cache.is_dirty(first));
+		assert!(cache.is_dirty(second));
+		assert_eq!(contents(&tl, first), vec![1; 60]);
 	}
 
-	pub fn wait_for_thread(&mut self) {
-		self.cache_thread_rx.recv().unwrap();
+	#[test]
+	fn flush_keeps_time_of_write() {
+		let tl = volume();
+		let ino = create(&tl, "test.txt");
+		let cache = WriteCache::new(tl.clone(), 1024, HOUR);
+		let before = SystemTime::now();
+		cache.write(ino, 0, b"data".to_vec());
+		let written = SystemTime::now();
+		std::thread::sleep(Duration::from_millis(20));
+		cache.flush();
+
+		let mtime = tl.getattr(ino).unwrap().mtime;
+		assert!(before <= mtime && mtime <= written);
 	}
 
-	pub fn flush(&mut self) {
-		self.send_msg(CacheThreadMessage::Flush);
-		self.wait_for_thread();
+	#[test]
+	fn sync_reports_failed_writes_once() {
+		let tl = volume();
+		let ino = create(&tl, "test.txt");
+		let cache = WriteCache::new(tl.clone(), 1024, HOUR);
+		cache.write(ino, 0, b"data".to_vec());
+		assert_eq!(cache.sync(ino), Ok(()));
+
+		// The inode is gone before its data reaches the database
+		cache.write(12345, 0, b"lost".to_vec());
+		cache.write(ino, 4, b"!".to_vec());
+		cache.flush();
+		assert!(cache.sync(12345).is_err());
+		assert_eq!(cache.sync(12345), Ok(()));
+		assert_eq!(cache.sync(ino), Ok(()));
+		assert_eq!(contents(&tl, ino), b"data!");
 	}
 
-	pub fn write(&mut self, inode: u64, offset: u64, data: Vec<u8>) {
-		self.send_msg(CacheThreadMessage::Write(WriteCommand {
-			inode,
-			offset,
-			data
-		}));
-		self.wait_for_thread();
+	#[test]
+	fn shared_between_threads() {
+		let tl = volume();
+		let cache = WriteCache::new(tl.clone(), 64, Duration::from_millis(5));
+		let inodes: Vec<u64> = (0..4).map(|i| create(&tl, &format!("{}.bin", i))).collect();
+
+		let threads: Vec<_> = inodes.iter().map(|&ino| {
+			let cache = cache.clone();
+			std::thread::spawn(move || {
+				for offset in (0..1000u64).step_by(10) {
+					cache.write(ino, offset, vec![ino as u8; 10]);
+				}
+				cache.flush_inode(ino);
+			})
+		}).collect();
+		for thread in threads {
+			thread.join().unwrap();
+		}
+
+		for ino in inodes {
+			assert_eq!(contents(&tl, ino), vec![ino as u8; 1000]);
+		}
+		assert_eq!(cache.used.load(Ordering::SeqCst), 0);
 	}
 }
-
@@ -0,0 +1,146 @@
+//! Runs FUSE requests on a fixed set of worker threads
+//!
+//! Requests are sharded by inode: all requests on one inode are run by the same worker in the order
+//! they were received, while requests on different inodes are run in parallel (each worker blocks on
+//! its own connection from the database pool).
+
+use crate::debug;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+
+/// A request waiting for its worker, it gets the worker's own state
+pub type Job<S> = Box<dyn FnOnce(&mut S) + Send>;
+
+
+pub struct Dispatcher<S> {
+	workers: Vec<mpsc::Sender<Job<S>>>,
+	threads: Vec<JoinHandle<()>>
+}
+
+impl<S: Send + 'static> Dispatcher<S> {
+	/// Start `threads` workers (at least one), `state` creates the state of every worker
+	pub fn new<F: FnMut() -> S>(threads: usize, mut state: F) -> Self {
+		let mut workers = Vec::new();
+		let mut handles = Vec::new();
+
+		for i in 0..usize::max(threads, 1) {
+			let (tx, rx) = mpsc::channel::<Job<S>>();
+			let mut state = state();
+
+			let handle = std::thread::Builder::new()
+				.name(format!("dbfs-fuse-{}", i))
+				.spawn(move || {
+					// Ends once the dispatcher is dropped and the queue is drained
+					for job in rx {
+						// A panicking request must not take the other requests of this worker with
+						// it (its reply is dropped, which answers it with EIO)
+						if catch_unwind(AssertUnwindSafe(|| job(&mut state))).is_err() {
+							debug!("worker {}: request panicked", i);
+						}
+					}
+				})
+				.expect("could not start a FUSE worker thread");
+
+			workers.push(tx);
+			handles.push(handle);
+		}
+
+		Self {
+			workers,
+			threads: handles
+		}
+	}
+
+	/// Number of worker threads
+	pub fn threads(&self) -> usize {
+		self.workers.len()
+	}
+
+	/// Queue a request on the worker owning `inode`
+	pub fn dispatch<J: FnOnce(&mut S) + Send + 'static>(&self, inode: u64, job: J) {
+		let worker = &self.workers[(inode % self.workers.len() as u64) as usize];
+		worker.send(Box::new(job)).expect("a FUSE worker thread has stopped");
+	}
+}
+
+impl<S> Drop for Dispatcher<S> {
+	fn drop(&mut self) {
+		self.workers.clear();
+		for thread in self.threads.drain(..) {
+			let _ = thread.join();
+		}
+	}
+}
+
+
+
+#[cfg(test)]
+mod test {
+	use std::sync::{Arc, Barrier, Mutex};
+	use std::time::Duration;
+	use super::*;
+
+
+	#[test]
+	fn same_inode_keeps_order() {
+		let log = Arc::new(Mutex::new(Vec::new()));
+		let dispatcher = Dispatcher::new(4, || ());
+		for i in 0..100u64 {
+			let log = log.clone();
+			dispatcher.dispatch(7, move |_| {
+				// Later requests are faster, so they would overtake if they ran in parallel
+				std::thread::sleep(Duration::from_micros(100 - i));
+				log.lock().unwrap().push(i);
+			});
+		}
+		drop(dispatcher);
+		assert_eq!(*log.lock().unwrap(), (0..100).collect::<Vec<u64>>());
+	}
+
+	#[test]
+	fn different_inodes_run_in_parallel() {
+		// Deadlocks unless both requests run at the same time
+		let barrier = Arc::new(Barrier::new(2));
+		let (tx, rx) = mpsc::channel();
+		let dispatcher = Dispatcher::new(2, || ());
+		for inode in [1, 2] {
+			let barrier = barrier.clone();
+			let tx = tx.clone();
+			dispatcher.dispatch(inode, move |_| {
+				barrier.wait();
+				tx.send(inode).unwrap();
+			});
+		}
+		let mut done = vec![rx.recv_timeout(Duration::from_secs(10)).unwrap(), rx.recv_timeout(Duration::from_secs(10)).unwrap()];
+		done.sort();
+		assert_eq!(done, vec![1, 2]);
+	}
+
+	#[test]
+	fn workers_keep_their_state() {
+		let (tx, rx) = mpsc::channel();
+		let dispatcher = Dispatcher::new(3, || 0u64);
+		for _ in 0..5 {
+			let tx = tx.clone();
+			dispatcher.dispatch(4, move |count| {
+				*count += 1;
+				tx.send(*count).unwrap();
+			});
+		}
+		drop(tx);
+		drop(dispatcher);
+		assert_eq!(rx.iter().collect::<Vec<u64>>(), vec![1, 2, 3, 4, 5]);
+	}
+
+	#[test]
+	fn panicking_request_does_not_stop_the_worker() {
+		let (tx, rx) = mpsc::channel();
+		let dispatcher = Dispatcher::new(1, || ());
+		dispatcher.dispatch(1, |_| panic!("request failed"));
+		dispatcher.dispatch(1, move |_| tx.send(()).unwrap());
+		assert!(rx.recv_timeout(Duration::from_secs(10)).is_ok());
+		assert_eq!(dispatcher.threads(), 1);
+	}
+}
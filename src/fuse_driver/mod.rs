@@ -1,6 +1,8 @@
+mod access_times;
 mod cache;
+mod dispatcher;
+mod open_files;
 
-use crate::settings;
 use crate::sql_translation_layer::MAX_NAME_LEN;
 use crate::sql_translation_layer::driver_objects;
 use crate::sql_translation_layer::TranslationLayer;
@@ -8,16 +10,17 @@ use crate::sql_translation_layer::Error;
 use crate::debug;
 
 use fuser;
+use crate::db_connector::DbConnectorError;
 use libc::EINTR;
 use libc::EIO;
-use libc::{EINVAL, ENOENT, ENOTEMPTY};
+use libc::{E2BIG, EACCES, EEXIST, EINVAL, EISDIR, ENAMETOOLONG, ENODATA, ENOENT, ENOSPC, ENOTDIR, ENOTEMPTY, ERANGE, EROFS};
 
 use std::ffi::OsStr;
 use std::os::unix::fs::FileTypeExt;
 use std::os::unix::fs::MetadataExt;
 use std::io::Read;
 use std::time::Duration;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 const TTL: Duration = Duration::from_secs(1);
 
@@ -28,7 +31,9 @@ impl TryInto<driver_objects::FileType> for u32 {
 	fn try_into(self) -> Result<driver_objects::FileType, Self::Error> {
 	    match (self >> 12) & 0xF {
 			0x1 => Ok(driver_objects::FileType::NamedPipe),
+			0x2 => Ok(driver_objects::FileType::CharDevice),
 			0x4 => Ok(driver_objects::FileType::Directory),
+			0x6 => Ok(driver_objects::FileType::BlockDevice),
 			0x8 => Ok(driver_objects::FileType::File),
 			0xA => Ok(driver_objects::FileType::Symlink),
 			0xC => Ok(driver_objects::FileType::Socket),
@@ -45,6 +50,8 @@ impl Into<fuser::FileType> for driver_objects::FileType {
 			Self::Symlink => fuser::FileType::Symlink,
 			Self::NamedPipe => fuser::FileType::NamedPipe,
 			Self::Socket => fuser::FileType::Socket,
+			Self::BlockDevice => fuser::FileType::BlockDevice,
+			Self::CharDevice => fuser::FileType::CharDevice,
 		}
 	}
 }
@@ -75,13 +82,13 @@ impl Into<fuser::FileAttr> for driver_objects::FileAttr {
 			atime: self.atime,
 			mtime: self.mtime,
 			ctime: self.ctime,
-			crtime: self.ctime,
+			crtime: self.crtime,
 			kind: self.kind.into(),
 			perm: self.perm.into(),
 			nlink: self.hardlinks.into(),
 			uid: self.uid,
 			gid: self.gid,
-			rdev: 0,
+			rdev: self.rdev,
 			blksize: 4096,
 			flags: 0,
 		}
@@ -91,10 +98,24 @@ impl Into<fuser::FileAttr> for driver_objects::FileAttr {
 impl Into<i32> for Error {
 	fn into(self) -> i32 {
 	    match self {
+			Self::DbConnectorError(DbConnectorError::DuplicateError(_)) => EEXIST,
+			Self::DbConnectorError(DbConnectorError::NoSpaceError(_)) => ENOSPC,
+			Self::DbConnectorError(DbConnectorError::ReadOnlyError(_)) => EROFS,
+			Self::DbConnectorError(DbConnectorError::PermissionError(_)) => EACCES,
 			Self::DbConnectorError(_) => EIO,
 			Self::DbLockError => EIO,
 			Self::NotFoundError(_) => ENOENT,
 			Self::ClientError(_) => EINVAL,
+			Self::AlreadyExistsError(_) => EEXIST,
+			Self::NotADirectoryError(_) => ENOTDIR,
+			Self::IsADirectoryError(_) => EISDIR,
+			Self::NotEmptyError(_) => ENOTEMPTY,
+			Self::AccessError(_) => EACCES,
+			Self::NameTooLongError => ENAMETOOLONG,
+			Self::NoAttributeError(_) => ENODATA,
+			Self::RangeError(_) => ERANGE,
+			Self::TooBigError(_) => E2BIG,
+			Self::SchemaVersionError(_) => EIO,
 			Self::Unimplemented => EINTR,
 			Self::RuntimeError(_) => EIO
 		}
@@ -147,15 +168,98 @@ fn format_metadata(metadata: &std::fs::Metadata) -> String {
 	format!("{}{}{}{}, user {}, group {}", prefix, user, group, others, metadata.uid(), metadata.gid())
 }
 
+/// Device number of a host device node (glibc `dev_t`) in the 32-bit encoding of the kernel
+/// (`new_encode_dev`)
+fn encode_rdev(rdev: u64) -> u32 {
+	let major = ((rdev >> 8) & 0xfff) | ((rdev >> 32) & !0xfff);
+	let minor = (rdev & 0xff) | ((rdev >> 12) & !0xff);
+	((minor & 0xff) | (major << 8) | ((minor & !0xff) << 12)) as u32
+}
+
 #[derive(Debug)]
 struct HardLink {
 	src_inode: u64,
 	dbfs_inode: u64
 }
 
-fn import_recurse(tl: &mut TranslationLayer, path: &std::path::PathBuf, parent_inode: u64, links: &mut Vec<HardLink>) -> Result<(), Error> {
-	// TODO - xattr (error if any)
+/// Call an xattr syscall filling `buf`, first asking for the size (`buf` is resized to the result)
+fn read_xattr_buffer(mut call: impl FnMut(*mut libc::c_char, libc::size_t) -> libc::ssize_t) -> std::io::Result<Vec<u8>> {
+	loop {
+		let size = call(std::ptr::null_mut(), 0);
+		if size < 0 {
+			return Err(std::io::Error::last_os_error());
+		}
+		let mut buf = vec![0u8; size as usize];
+		let size = call(buf.as_mut_ptr() as *mut libc::c_char, buf.len());
+		if size >= 0 {
+			buf.truncate(size as usize);
+			return Ok(buf);
+		}
+		// The attribute grew between the two calls
+		let err = std::io::Error::last_os_error();
+		if err.raw_os_error() != Some(ERANGE) {
+			return Err(err);
+		}
+	}
+}
+
+/// Copy the extended attributes of a file (not following symlinks) to an inode
+fn import_xattrs(tl: &TranslationLayer, path: &std::path::Path, inode: u64) -> Result<(), Error> {
+	use std::os::unix::ffi::OsStrExt;
+
+	let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+		return Err(Error::RuntimeError("invalid path"))
+	};
+	let names = match read_xattr_buffer(|buf, size| unsafe { libc::llistxattr(c_path.as_ptr(), buf, size) }) {
+		Ok(val) => val,
+		Err(val) if val.raw_os_error() == Some(libc::ENOTSUP) => return Ok(()),
+		Err(val) => {
+			debug!("fs xattr error on {:?}: {:?}", path, val);
+			return Err(Error::RuntimeError("could not list extended attributes"))
+		}
+	};
+
+	for name in names.split(|byte| *byte == 0).filter(|name| !name.is_empty()) {
+		let c_name = std::ffi::CString::new(name).unwrap();
+		let value = match read_xattr_buffer(|buf, size| unsafe { libc::lgetxattr(c_path.as_ptr(), c_name.as_ptr(), buf as *mut libc::c_void, size) }) {
+			Ok(val) => val,
+			// Removed since it was listed
+			Err(val) if val.raw_os_error() == Some(ENODATA) => continue,
+			Err(val) => {
+				debug!("fs xattr error on {:?} ({:?}): {:?}", path, c_name, val);
+				return Err(Error::RuntimeError("could not read extended attribute"))
+			}
+		};
+		debug!(" -> xattr {:?} ({} bytes)", &c_name, value.len());
+		tl.setxattr(inode, OsStr::from_bytes(name), &value, driver_objects::SetXattrMode::Set)?;
+	}
+
+	Ok(())
+}
+
+/// Attributes of a file being imported, with its exact timestamps
+fn source_attr(metadata: &std::fs::Metadata) -> driver_objects::FileSetAttr {
+	let ctime = driver_objects::Timestamp { sec: metadata.ctime(), nsec: metadata.ctime_nsec() as u32 }.into();
+	driver_objects::FileSetAttr {
+		uid: metadata.uid(),
+		gid: metadata.gid(),
+		atime: metadata.accessed().unwrap(),
+		mtime: metadata.modified().unwrap(),
+		ctime,
+		// Not every filesystem records the birth time
+		crtime: metadata.created().unwrap_or(ctime),
+		perm: (metadata.mode() as u16).into()
+	}
+}
+
+/// Give an imported inode the times of its source again, since adding its contents (directory entries,
+/// data, extended attributes) changed them
+fn import_times(tl: &TranslationLayer, inode: u64, metadata: &std::fs::Metadata) -> Result<(), Error> {
+	tl.setattr(inode, source_attr(metadata))?;
+	Ok(())
+}
 
+fn import_recurse(tl: &TranslationLayer, path: &std::path::PathBuf, parent_inode: u64, links: &mut Vec<HardLink>) -> Result<(), Error> {
 	if parent_inode == 0 && !path.is_dir() {
 		return Err(Error::RuntimeError("source root is not a directory"))
 	}
@@ -170,14 +274,7 @@ fn import_recurse(tl: &mut TranslationLayer, path: &std::path::PathBuf, parent_i
 
 	println!("{} {:?}", format_metadata(&metadata), &path.as_os_str());
 
-	let attr = driver_objects::FileSetAttr {
-		uid: metadata.uid(),
-		gid: metadata.gid(),
-		atime: metadata.accessed().unwrap(),
-		mtime: metadata.modified().unwrap(),
-		ctime: metadata.created().unwrap(),
-		perm: (metadata.mode() as u16).into()
-	};
+	let attr = source_attr(&metadata);
 
 	let ftype = metadata.file_type();
 
@@ -189,6 +286,7 @@ fn import_recurse(tl: &mut TranslationLayer, path: &std::path::PathBuf, parent_i
 			tl.setattr(1, attr)?; // Root
 			1u64
 		};
+		import_xattrs(tl, path, parent_inode)?;
 
 		let entries = match std::fs::read_dir(&path) {
 			Ok(val) => val,
@@ -202,7 +300,7 @@ fn import_recurse(tl: &mut TranslationLayer, path: &std::path::PathBuf, parent_i
 			import_recurse(tl, &path, parent_inode, links)?;
 		}
 
-		return Ok(())
+		return import_times(tl, parent_inode, &metadata)
 	}
 
 	if ftype.is_symlink() {
@@ -220,8 +318,9 @@ fn import_recurse(tl: &mut TranslationLayer, path: &std::path::PathBuf, parent_i
 		
 		let ino = tl.mknod(parent_inode, name, driver_objects::FileType::Symlink, attr)?.ino as u64;
 		tl.write(ino, 0, link.as_bytes())?;
+		import_xattrs(tl, path, ino)?;
 
-		return Ok(())
+		return import_times(tl, ino, &metadata)
 	}
 
 	if ftype.is_file() {
@@ -232,11 +331,12 @@ fn import_recurse(tl: &mut TranslationLayer, path: &std::path::PathBuf, parent_i
 				if link.src_inode != metadata.ino() { continue; }
 
 				tl.link(parent_inode, name, link.dbfs_inode)?;
-				return Ok(())
+				return import_times(tl, link.dbfs_inode, &metadata)
 			}
 		}
 
 		let ino = tl.mknod(parent_inode, name, driver_objects::FileType::File, attr)?.ino as u64;
+		import_xattrs(tl, path, ino)?;
 
 		if metadata.nlink() > 1 {
 			links.push(HardLink { src_inode: metadata.ino(), dbfs_inode: ino });
@@ -260,44 +360,99 @@ fn import_recurse(tl: &mut TranslationLayer, path: &std::path::PathBuf, parent_i
 			offset += read;
 		}
 
-		return Ok(())
+		return import_times(tl, ino, &metadata)
 	}
 
 	if ftype.is_fifo() {
 		let name = path.components().last().unwrap().as_os_str();
 		
-		tl.mknod(parent_inode, name, driver_objects::FileType::NamedPipe, attr)?;
+		let ino = tl.mknod(parent_inode, name, driver_objects::FileType::NamedPipe, attr)?.ino as u64;
+		import_xattrs(tl, path, ino)?;
 
-		return Ok(())
+		return import_times(tl, ino, &metadata)
 	}
 
 	if ftype.is_socket() {
 		let name = path.components().last().unwrap().as_os_str();
 		
-		tl.mknod(parent_inode, name, driver_objects::FileType::Socket, attr)?;
+		let ino = tl.mknod(parent_inode, name, driver_objects::FileType::Socket, attr)?.ino as u64;
+		import_xattrs(tl, path, ino)?;
+
+		return import_times(tl, ino, &metadata)
+	}
+
+	if ftype.is_block_device() || ftype.is_char_device() {
+		let name = path.components().last().unwrap().as_os_str();
+		let kind = match ftype.is_block_device() {
+			true => driver_objects::FileType::BlockDevice,
+			false => driver_objects::FileType::CharDevice,
+		};
+
+		let ino = tl.mknod_with_umask(parent_inode, name, kind, attr, encode_rdev(metadata.rdev()), 0)?.ino as u64;
+		import_xattrs(tl, path, ino)?;
 
-		return Ok(())
+		return import_times(tl, ino, &metadata)
 	}
 
 	Err(Error::RuntimeError("invalid file"))
 }
 
+/// Copy a directory tree into the volume
+pub fn import(tl: &TranslationLayer, path: &std::path::Path) -> Result<(), Error> {
+	let path = std::path::PathBuf::from(path);
+	let mut links: Vec<HardLink> = Vec::new();
+	import_recurse(tl, &path, 0, &mut links)?;
+	println!("done, detected {} inodes with multiple links", links.len());
+	Ok(())
+}
+
+/// FUSE filesystem serving a volume
+///
+/// Requests are handed to a [`dispatcher::Dispatcher`], so requests on different inodes are served
+/// in parallel while requests on the same inode keep their order.
 pub struct DbfsDriver {
-	tl: Arc<Mutex<TranslationLayer>>,
+	dispatcher: dispatcher::Dispatcher<Worker>,
+	tl: Arc<TranslationLayer>,
+	cache: Arc<cache::WriteCache>
+}
+
+/// State of one worker thread (see [`dispatcher`])
+struct Worker {
+	tl: Arc<TranslationLayer>,
 	last_readdir_inode: u64,
 	last_readdir: Vec<driver_objects::DirectoryEntry>,
-	cache: cache::WriteCache
+	cache: Arc<cache::WriteCache>,
+	open_files: Arc<open_files::OpenFiles>,
+	access_times: Arc<access_times::AccessTimes>
 }
 
 impl DbfsDriver {
-	pub fn new(tl: TranslationLayer) -> Self {
-		let tl = Arc::new(Mutex::new(tl));
-
-		Self {
+	/// # Inputs
+	/// `threads: usize` is the number of worker threads serving the FUSE requests
+	/// `atime: AtimePolicy` decides when reads update the access time
+	/// `write_cache: usize` is the number of bytes which may be written before they are flushed to
+	/// the database
+	/// `flush_interval: Duration` is how long written data may stay in the cache
+	pub fn new(tl: TranslationLayer, threads: usize, atime: driver_objects::AtimePolicy, write_cache: usize, flush_interval: Duration) -> Self {
+		let tl = Arc::new(tl);
+		let cache = cache::WriteCache::new(tl.clone(), write_cache, flush_interval);
+		let open_files = Arc::new(open_files::OpenFiles::new());
+		let access_times = Arc::new(access_times::AccessTimes::new(atime, TTL));
+
+		let dispatcher = dispatcher::Dispatcher::new(threads, || Worker {
 			tl: tl.clone(),
 			last_readdir_inode: u64::MAX,
 			last_readdir: Vec::new(),
-			cache: cache::WriteCache::new(tl.clone(), 1 << 20)
+			cache: cache.clone(),
+			open_files: open_files.clone(),
+			access_times: access_times.clone()
+		});
+		debug!("serving requests on {} threads", dispatcher.threads());
+
+		Self {
+			dispatcher,
+			tl,
+			cache
 		}
 	}
 
@@ -308,71 +463,90 @@ impl DbfsDriver {
 		fuser::mount2(self, mountpoint, &options).unwrap();
 		panic!("FUSE driver crashed");
 	}
+}
 
-	pub fn format(&mut self) -> Result<(), Error> {
-		let mut tl = self.tl.lock().unwrap();
-		tl.format()
+impl Worker {
+	/// Update the access time of an inode after it was read (the read itself succeeded anyway)
+	///
+	/// The reply was sent already, so the database is not waited for if it is unreachable.
+	fn access(&self, inode: u64) {
+		let time = std::time::SystemTime::now();
+		let tl = self.tl.once();
+
+		// The attributes known from the last read only miss the writes held back by the cache
+		let result = self.access_times.read(inode, time, |known| self.cache.getattr(inode, || match known {
+			Some(attr) => Ok(attr),
+			None => tl.getattr(inode)
+		})).and_then(|update| match update {
+			true => tl.access(inode, time),
+			false => Ok(())
+		});
+		if let Err(err) = result {
+			debug!(" -> Err while updating the access time: {:?}", &err);
+			self.access_times.forget(inode);
+		}
 	}
 
-	pub fn import(&mut self, path: &std::path::Path) -> Result<(), Error> {
-		let mut tl = self.tl.lock().unwrap();
-		let path = std::path::PathBuf::from(path);
-		let mut links: Vec<HardLink> = Vec::new();
-		import_recurse(&mut tl, &path, 0, &mut links)?;
-		println!("done, detected {} inodes with multiple links", links.len());
-		Ok(())
+	/// `inode` was changed, the next read decides about its access time with its new attributes
+	fn changed(&self, inode: u64) {
+		self.access_times.forget(inode);
 	}
-}
 
-impl fuser::Filesystem for DbfsDriver {
-	fn lookup(&mut self, _req: &fuser::Request, parent_inode: u64, name: &OsStr, reply: fuser::ReplyEntry) {
+	/// A name of `inode` was removed (`None` if it could not be looked up beforehand)
+	fn unlinked(&self, inode: Option<u64>) {
+		if let Some(inode) = inode {
+			self.changed(inode);
+			if let Err(err) = self.open_files.unlinked(&self.tl, inode) {
+				debug!(" -> Err while reclaiming inode {}: {:?}", &inode, &err);
+			}
+		}
+	}
+
+	fn lookup(&mut self, parent_inode: u64, name: &OsStr, reply: fuser::ReplyEntry) {
 		debug!("lookup: inode {}, name {:?}", &parent_inode, &name);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		let tl = &self.tl;
 
-		match tl.lookup(name, parent_inode) {
+		let attr = tl.lookup(name, parent_inode).and_then(|attr| match self.cache.is_dirty(attr.ino as u64) {
+			true => self.cache.getattr(attr.ino as u64, || tl.getattr(attr.ino as u64)),
+			false => Ok(attr)
+		});
+		match attr {
 			Ok(attr) => {
 				debug!(" -> OK: {:?}", &attr);
 				reply.entry(&TTL, &attr.into(), 0);
 			},
 			Err(err) => {
 				debug!(" -> Err {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 			}
 		}
 	}
 
-	fn getattr(&mut self, _req: &fuser::Request, inode: u64, reply: fuser::ReplyAttr) {
+	fn getattr(&mut self, inode: u64, reply: fuser::ReplyAttr) {
 		debug!("getattr: inode {}", &inode);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		let tl = &self.tl;
 
-		match tl.getattr(inode) {
+		match self.cache.getattr(inode, || tl.getattr(inode)) {
 			Ok(attr) => {
 				debug!(" -> OK: {:?}", &attr);
 				reply.attr(&TTL, &attr.into());
 			},
 			Err(err) => {
 				debug!(" -> Err {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 			}
 		}
 	}
 
 	fn read(
 		&mut self,
-		_req: &fuser::Request,
 		inode: u64,
-		_fh: u64,
 		offset: i64,
 		size: u32,
-		_flags: i32,
-		_lock: Option<u64>,
 		reply: fuser::ReplyData,
 	) {
 		debug!("read: inode {}, offset {}, size {}", &inode, &offset, &size);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		let tl = &self.tl;
 
 		if size == 0 {
 			debug!(" -> OK, no read operation necessary");
@@ -381,22 +555,22 @@ impl fuser::Filesystem for DbfsDriver {
 		}
 
 		let mut buf = vec![0u8; size as usize];
-		match tl.read(inode, offset as u64, &mut buf) {
+		match self.cache.read(inode, offset as u64, &mut buf, |buf| tl.read(inode, offset as u64, buf)) {
 			Ok(read_bytes) => {
 				debug!(" -> OK (read {})", read_bytes);
 				reply.data(&buf[..read_bytes]);
+				self.access(inode);
 			},
 			Err(err) => {
 				debug!(" -> Err {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 			}
 		}
 	}
 
-	fn readlink(&mut self, _req: &fuser::Request<'_>, inode: u64, reply: fuser::ReplyData) {
+	fn readlink(&mut self, inode: u64, reply: fuser::ReplyData) {
 		debug!("readlink: inode {}", &inode);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		let tl = &self.tl;
 
 		let size: u32 = match tl.filesize(inode) {
 			Ok(size) => {
@@ -405,26 +579,22 @@ impl fuser::Filesystem for DbfsDriver {
 			},
 			Err(err) => {
 				debug!(" -> Err while determining link size: {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 				return
 			}
 		};
-		drop(tl);
 
-		self.read(_req, inode, 0, 0, size, 0, None, reply);
+		self.read(inode, 0, size, reply);
 	}
 
 	fn readdir(
 		&mut self,
-		_req: &fuser::Request,
 		inode: u64,
-		_fh: u64,
 		offset: i64,
 		mut reply: fuser::ReplyDirectory,
 	) {
 		debug!("readdir: inode {}, offset {}", &inode, &offset);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		let tl = &self.tl;
 
 		if inode != self.last_readdir_inode {
 			debug!(" -> cache miss, fetching from DB");
@@ -432,11 +602,12 @@ impl fuser::Filesystem for DbfsDriver {
 			self.last_readdir = match tl.readdir(inode) {
 				Ok(val) => {
 					debug!(" -> OK (inode {} has {} entries)", &inode, &val.len());
+					self.access(inode);
 					val
 				},
 				Err(err) => {
 					debug!(" -> Err {:?}", &err);
-					reply.error(ENOENT);
+					reply.error(err.into());
 					return
 				}
 			}
@@ -465,10 +636,9 @@ impl fuser::Filesystem for DbfsDriver {
 		reply.ok();
 	}
 
-	fn statfs(&mut self, _req: &fuser::Request<'_>, inode: u64, reply: fuser::ReplyStatfs) {
+	fn statfs(&mut self, inode: u64, reply: fuser::ReplyStatfs) {
 		debug!("statfs: inode {}", &inode);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		let tl = &self.tl;
 
 		let stat = match tl.statfs() {
 			Ok(val) => {
@@ -477,12 +647,13 @@ impl fuser::Filesystem for DbfsDriver {
 			},
 			Err(err) => {
 				debug!(" -> Err {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 				return
 			}
 		};
 
 		let total_blocks = 1 << 48;
+		let block_size = tl.superblock().block_size;
 
 		reply.statfs(
 			total_blocks,
@@ -490,89 +661,221 @@ impl fuser::Filesystem for DbfsDriver {
 			total_blocks - stat.used_blocks,
 			stat.used_inodes,
 			total_blocks - stat.used_blocks,
-			settings::FILE_BLOCK_SIZE_32,
+			block_size,
 			MAX_NAME_LEN,
-			settings::FILE_BLOCK_SIZE_32
+			block_size
 		);
 	}
 
 	fn mkdir(
 		&mut self,
-		req: &fuser::Request<'_>,
+		uid: u32,
+		gid: u32,
 		parent_inode: u64,
 		name: &OsStr,
 		mode: u32,
-		_umask: u32,
+		umask: u32,
 		reply: fuser::ReplyEntry,
 	) {
-		debug!("mkdir: parent inode {}, name {:?}, mode {:o}, user {}, group {}", &parent_inode, &name, &mode, req.uid(), req.gid());
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		debug!("mkdir: parent inode {}, name {:?}, mode {:o}, umask {:o}, user {}, group {}", &parent_inode, &name, &mode, &umask, uid, gid);
+		let tl = &self.tl;
 
 		let time = std::time::SystemTime::now();
 		let attr = driver_objects::FileSetAttr {
-			uid: req.uid(),
-			gid: req.gid(),
+			uid: uid,
+			gid: gid,
 			atime: time,
 			mtime: time,
 			ctime: time,
+			crtime: time,
 			perm: (mode as u16).into()
 		};
 
-		match tl.mknod(parent_inode, name, driver_objects::FileType::Directory, attr) {
+		self.changed(parent_inode);
+		match tl.mknod_with_umask(parent_inode, name, driver_objects::FileType::Directory, attr, 0, umask as u16) {
 			Ok(attr) => {
 				debug!(" -> OK {:?}", &attr);
 				reply.entry(&TTL, &attr.into(), 0);
 			},
 			Err(err) => {
 				debug!(" -> Err {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 			}
 		}
 	}
 
-	fn rmdir(&mut self, _req: &fuser::Request<'_>, parent_inode: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+	fn rmdir(&mut self, parent_inode: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
 		debug!("rmdir: parent inode {}, name {:?}", &parent_inode, &name);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		let tl = &self.tl;
 
-		let inode = match tl.lookup_id(name, parent_inode) {
-			Ok(inode) => inode,
+		self.changed(parent_inode);
+		if let Err(err) = tl.rmdir(parent_inode, name) {
+			debug!(" -> Err {:?}", &err);
+			reply.error(err.into());
+			return
+		}
+
+		debug!(" -> OK");
+		reply.ok();
+	}
+
+	fn unlink(&mut self, parent_inode: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+		debug!("unlink: parent inode {}, name {:?}", &parent_inode, &name);
+		let tl = &self.tl;
+
+		let inode = tl.lookup(name, parent_inode).ok().map(|attr| attr.ino as u64);
+		self.changed(parent_inode);
+		if let Err(err) = tl.unlink(parent_inode, name) {
+			debug!(" -> Err {:?}", &err);
+			reply.error(err.into());
+			return
+		}
+
+		debug!(" -> OK");
+		reply.ok();
+		self.unlinked(inode);
+	}
+
+	/// Create a handle for `inode`, a regular file opened for writing with `O_TRUNC` is truncated
+	fn open_handle(&self, inode: u64, flags: i32) -> Result<u64, Error> {
+		if flags & libc::O_TRUNC != 0 && flags & libc::O_ACCMODE != libc::O_RDONLY && self.tl.getattr(inode)?.kind == driver_objects::FileType::File {
+			self.cache.flush_inode(inode);
+			self.changed(inode);
+			self.tl.resize(inode, 0)?;
+		}
+		self.open_files.open(inode, flags)
+	}
+
+	fn open(&mut self, inode: u64, flags: i32, reply: fuser::ReplyOpen) {
+		debug!("open: inode {}, flags {:#x}", &inode, &flags);
+
+		match self.open_handle(inode, flags) {
+			Ok(handle) => {
+				debug!(" -> OK handle {}", &handle);
+				reply.opened(handle, 0);
+			},
 			Err(err) => {
-				debug!(" -> Err while performing lookup: {:?}", &err);
-				reply.error(ENOENT);
-				return
+				debug!(" -> Err {:?}", &err);
+				reply.error(err.into());
 			}
-		};
+		}
+	}
 
-		let children = match tl.count_children(inode) {
-			Ok(val) => val,
+	fn create(
+		&mut self,
+		uid: u32,
+		gid: u32,
+		parent_inode: u64,
+		name: &OsStr,
+		mode: u32,
+		umask: u32,
+		flags: i32,
+		reply: fuser::ReplyCreate,
+	) {
+		debug!("create: parent inode {}, name {:?}, mode {:o}, umask {:o}, flags {:#x}", &parent_inode, &name, &mode, &umask, &flags);
+		let tl = &self.tl;
+
+		self.changed(parent_inode);
+		let attr = match tl.mknod_with_umask(parent_inode, name, driver_objects::FileType::File, new_attr(uid, gid, mode), 0, umask as u16) {
+			Ok(attr) => attr,
+			// Another client created the file since the kernel looked it up, without O_EXCL it is opened
+			Err(Error::AlreadyExistsError(_) | Error::DbConnectorError(DbConnectorError::DuplicateError(_))) if flags & libc::O_EXCL == 0 => {
+				debug!(" -> file exists, opening it");
+				let attr = match tl.lookup(name, parent_inode) {
+					Ok(attr) if attr.kind == driver_objects::FileType::Directory => Err(Error::IsADirectoryError("directories can not be opened for writing")),
+					result => result
+				};
+				let opened = attr.and_then(|attr| {
+					let handle = self.open_handle(attr.ino as u64, flags)?;
+					Ok((self.cache.getattr(attr.ino as u64, || tl.getattr(attr.ino as u64))?, handle))
+				});
+				match opened {
+					Ok((attr, handle)) => {
+						debug!(" -> OK handle {}: {:?}", &handle, &attr);
+						reply.created(&TTL, &attr.into(), 0, handle, 0);
+					},
+					Err(err) => {
+						debug!(" -> Err {:?}", &err);
+						reply.error(err.into());
+					}
+				}
+				return
+			},
 			Err(err) => {
-				debug!(" -> Err while counting children: {:?}", &err);
-				reply.error(ENOENT);
+				debug!(" -> Err {:?}", &err);
+				reply.error(err.into());
 				return
 			}
 		};
-		drop(tl);
 
-		if children > 2 {
-			debug!(" -> Err, directory not empty");
-			reply.error(ENOTEMPTY);
+		match self.open_files.open(attr.ino as u64, flags) {
+			Ok(handle) => {
+				debug!(" -> OK handle {}: {:?}", &handle, &attr);
+				reply.created(&TTL, &attr.into(), 0, handle, 0);
+			},
+			Err(err) => {
+				debug!(" -> Err {:?}", &err);
+				reply.error(err.into());
+			}
+		}
+	}
+
+	/// Write the cached data of an inode to the database (`close(2)` and `fsync(2)`), failed
+	/// writes are reported here
+	///
+	/// Every write is committed once it leaves the cache, so this is all `fsync` has to do.
+	fn flush(&mut self, inode: u64, reply: fuser::ReplyEmpty) {
+		debug!("flush: inode {}", &inode);
+
+		if let Err(errno) = self.cache.sync(inode) {
+			debug!(" -> Err {}", &errno);
+			reply.error(errno);
 			return
 		}
 
-		debug!(" -> OK, safe number of children to delete {}, passing request to unlink", children);
-		self.unlink(_req, parent_inode, name, reply);
+		debug!(" -> OK");
+		reply.ok();
 	}
 
-	fn unlink(&mut self, _req: &fuser::Request<'_>, parent_inode: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
-		debug!("unlink: parent inode {}, name {:?}", &parent_inode, &name);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+	fn release(&mut self, inode: u64, handle: u64, reply: fuser::ReplyEmpty) {
+		debug!("release: inode {}, handle {}", &inode, &handle);
 
-		if let Err(err) = tl.unlink(parent_inode, name) {
+		// The error can not reach the application anymore, it was reported by flush if possible
+		if let Err(errno) = self.cache.sync(inode) {
+			debug!(" -> Err {} while flushing", &errno);
+		}
+
+		if let Err(err) = self.open_files.release(&self.tl, handle) {
 			debug!(" -> Err {:?}", &err);
-			reply.error(ENOENT);
+			reply.error(err.into());
+			return
+		}
+
+		debug!(" -> OK");
+		reply.ok();
+	}
+
+	fn opendir(&mut self, inode: u64, flags: i32, reply: fuser::ReplyOpen) {
+		debug!("opendir: inode {}, flags {:#x}", &inode, &flags);
+
+		match self.open_files.open(inode, flags) {
+			Ok(handle) => {
+				debug!(" -> OK handle {}", &handle);
+				reply.opened(handle, 0);
+			},
+			Err(err) => {
+				debug!(" -> Err {:?}", &err);
+				reply.error(err.into());
+			}
+		}
+	}
+
+	fn releasedir(&mut self, inode: u64, handle: u64, reply: fuser::ReplyEmpty) {
+		debug!("releasedir: inode {}, handle {}", &inode, &handle);
+
+		if let Err(err) = self.open_files.release(&self.tl, handle) {
+			debug!(" -> Err {:?}", &err);
+			reply.error(err.into());
 			return
 		}
 
@@ -582,27 +885,27 @@ impl fuser::Filesystem for DbfsDriver {
 
 	fn link(
 		&mut self,
-		_req: &fuser::Request<'_>,
 		inode: u64,
 		new_parent_inode: u64,
 		new_name: &OsStr,
 		reply: fuser::ReplyEntry,
 	) {
 		debug!("link: inode {}, new parent inode {}, new name {:?}", &inode, &new_parent_inode, &new_name);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		let tl = &self.tl;
 
+		self.changed(inode);
+		self.changed(new_parent_inode);
 		if let Err(err) = tl.link(new_parent_inode, new_name, inode) {
 			debug!(" -> Err while creating link: {:?}", &err);
-			reply.error(ENOENT);
+			reply.error(err.into());
 			return
 		}
 
-		let attr = match tl.getattr(inode) {
+		let attr = match self.cache.getattr(inode, || tl.getattr(inode)) {
 			Ok(attr) => attr,
 			Err(err) => {
 				debug!(" -> Err while fetching attributes: {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 				return
 			}
 		};
@@ -613,15 +916,15 @@ impl fuser::Filesystem for DbfsDriver {
 
 	fn symlink(
 		&mut self,
-		req: &fuser::Request<'_>,
+		uid: u32,
+		gid: u32,
 		parent_inode: u64,
 		link_name: &OsStr,
 		target: &std::path::Path,
 		reply: fuser::ReplyEntry,
 	) {
 		debug!("symlink: parent inode {}, name {:?}, target {:?}", &parent_inode, &link_name, &target);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		let tl = &self.tl;
 
 		let target = match target.to_str() {
 			Some(target) => target.as_bytes(),
@@ -634,26 +937,28 @@ impl fuser::Filesystem for DbfsDriver {
 
 		let time = std::time::SystemTime::now();
 		let attr = driver_objects::FileSetAttr {
-			uid: req.uid(),
-			gid: req.gid(),
+			uid: uid,
+			gid: gid,
 			atime: time,
 			ctime: time,
 			mtime: time,
+			crtime: time,
 			perm: driver_objects::Permissions { special: 0, owner: 7, group: 7, other: 7 }
 		};
 
+		self.changed(parent_inode);
 		let attr = match tl.mknod(parent_inode, link_name, driver_objects::FileType::Symlink, attr) {
 			Ok(attr) => attr,
 			Err(err) => {
 				debug!(" -> Err while creating node: {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 				return
 			}
 		};
 
 		if let Err(err) = tl.write(attr.ino.into(), 0, target) {
 			debug!(" -> Err while writing symlink data: {:?}", &err);
-			reply.error(ENOENT);
+			reply.error(err.into());
 			return
 		}
 
@@ -664,14 +969,13 @@ impl fuser::Filesystem for DbfsDriver {
 			},
 			Err(err) => {
 				debug!(" -> Err while fetching updated attributes: {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 			}
 		}
 	}
 
 	fn setattr(
 		&mut self,
-		_req: &fuser::Request<'_>,
 		inode: u64,
 		mode: Option<u32>,
 		uid: Option<u32>,
@@ -680,22 +984,18 @@ impl fuser::Filesystem for DbfsDriver {
 		atime: Option<fuser::TimeOrNow>,
 		mtime: Option<fuser::TimeOrNow>,
 		ctime: Option<std::time::SystemTime>,
-		_fh: Option<u64>,
-		_crtime: Option<std::time::SystemTime>,
-		_chgtime: Option<std::time::SystemTime>,
-		_bkuptime: Option<std::time::SystemTime>,
-		_flags: Option<u32>,
 		reply: fuser::ReplyAttr,
 	) {
 		debug!("setattr: inode {}", inode);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		self.cache.flush_inode(inode);
+		self.changed(inode);
+		let tl = &self.tl;
 
 		let oldattr = match tl.getattr(inode) {
 			Ok(attr) => attr,
 			Err(err) => {
 				debug!(" -> Err while fetching old attributes: {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 				return
 			}
 		};
@@ -705,7 +1005,7 @@ impl fuser::Filesystem for DbfsDriver {
 			debug!(" -> truncating from {} to {} bytes", &oldattr.bytes, &size);
 			if let Err(err) = tl.resize(inode, size) {
 				debug!(" -> Err while truncating: {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 				return
 			}
 		}
@@ -722,15 +1022,17 @@ impl fuser::Filesystem for DbfsDriver {
 			mtime: match mtime {
 				Some(fuser::TimeOrNow::SpecificTime(val)) => val,
 				Some(fuser::TimeOrNow::Now) => time,
+				// Truncating modifies the file
+				None if size.is_some() => time,
 				None => oldattr.mtime
 			},
-			ctime: ctime.unwrap_or_else(|| oldattr.ctime),
+			ctime: ctime.unwrap_or(time),
+			crtime: oldattr.crtime,
 			perm: match mode {
 				Some(mode) => {
-					match (mode.try_into(), oldattr.kind) {
-						(Ok(driver_objects::FileType::Directory), driver_objects::FileType::Directory) => {},
-						(Ok(driver_objects::FileType::File), driver_objects::FileType::File) => {},
-						(Ok(driver_objects::FileType::Symlink), driver_objects::FileType::Symlink) => {},
+					// The file type can not change
+					match (TryInto::<driver_objects::FileType>::try_into(mode), oldattr.kind) {
+						(Ok(kind), old_kind) if kind == old_kind => {},
 						modes @ _ => {
 							debug!(" -> Err - attempted to change mode from {:?} to {:?}", &modes.0, &modes.1);
 							reply.error(EINVAL);
@@ -748,7 +1050,7 @@ impl fuser::Filesystem for DbfsDriver {
 			Ok(attr) => attr,
 			Err(err) => {
 				debug!(" -> Err while setting attributes: {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 				return
 			}
 		};
@@ -759,78 +1061,177 @@ impl fuser::Filesystem for DbfsDriver {
 
 	fn mknod(
 		&mut self,
-		req: &fuser::Request<'_>,
+		uid: u32,
+		gid: u32,
 		parent_inode: u64,
 		name: &OsStr,
 		mode: u32,
-		_umask: u32,
-		_rdev: u32,
+		umask: u32,
+		rdev: u32,
 		reply: fuser::ReplyEntry,
 	) {
-		debug!("mknod: parent inode {}, name {:?}, mode {:o}", &parent_inode, &name, &mode);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		debug!("mknod: parent inode {}, name {:?}, mode {:o}, umask {:o}, rdev {:x}", &parent_inode, &name, &mode, &umask, &rdev);
+		let tl = &self.tl;
 
 		let kind = match mode.try_into() {
 			Ok(kind @ driver_objects::FileType::File) => kind,
 			Ok(kind @ driver_objects::FileType::Socket) => kind,
 			Ok(kind @ driver_objects::FileType::NamedPipe) => kind,
+			Ok(kind @ driver_objects::FileType::BlockDevice) => kind,
+			Ok(kind @ driver_objects::FileType::CharDevice) => kind,
 			kind @ _ => {
-				debug!(" -> Err - invalid mode, not regular file, socket or pipe: {:?}", &kind);
+				debug!(" -> Err - invalid mode, not regular file, socket, pipe or device: {:?}", &kind);
 				reply.error(EINVAL);
 				return
 			}
 		};
 
-		let time = std::time::SystemTime::now();
-		let attr = driver_objects::FileSetAttr {
-			uid: req.uid(),
-			gid: req.gid(),
-			atime: time,
-			mtime: time,
-			ctime: time,
-			perm: (mode as u16).into()
+		let attr = new_attr(uid, gid, mode);
+
+		// Only device nodes have a device number
+		let rdev = match kind {
+			driver_objects::FileType::BlockDevice | driver_objects::FileType::CharDevice => rdev,
+			_ => 0,
 		};
 
-		match tl.mknod(parent_inode, name, kind, attr) {
+		self.changed(parent_inode);
+		match tl.mknod_with_umask(parent_inode, name, kind, attr, rdev, umask as u16) {
 			Ok(attr) => {
 				debug!(" -> OK {:?}", &attr);
 				reply.entry(&TTL, &attr.into(), 0);
 			},
 			Err(err) => {
 				debug!(" -> Err {:?}", &err);
-				reply.error(ENOENT);
+				reply.error(err.into());
 			}
 		}
 	}
 
 	fn rename(
 		&mut self,
-		_req: &fuser::Request<'_>,
 		parent_inode: u64,
 		name: &OsStr,
 		new_parent_inode: u64,
 		new_name: &OsStr,
-		_flags: u32,
+		flags: u32,
 		reply: fuser::ReplyEmpty,
 	) {
-		debug!("rename: parent inode {}, name {:?} to parent inode {}, name {:?}", &parent_inode, &name, &new_parent_inode, &new_name);
-		self.cache.flush();
-		let mut tl = self.tl.lock().unwrap();
+		debug!("rename: parent inode {}, name {:?} to parent inode {}, name {:?}, flags {:#x}", &parent_inode, &name, &new_parent_inode, &new_name, &flags);
+		let tl = &self.tl;
+
+		// Whiteouts (of overlay filesystems) are not supported
+		let mode = match flags {
+			0 => driver_objects::RenameMode::Replace,
+			libc::RENAME_NOREPLACE => driver_objects::RenameMode::NoReplace,
+			libc::RENAME_EXCHANGE => driver_objects::RenameMode::Exchange,
+			_ => {
+				debug!(" -> Err invalid flags");
+				reply.error(EINVAL);
+				return
+			}
+		};
+
+		// A replaced destination loses a name
+		let replaced = match mode {
+			driver_objects::RenameMode::Replace => tl.lookup(new_name, new_parent_inode).ok().map(|attr| attr.ino as u64),
+			_ => None
+		};
+		self.changed(parent_inode);
+		self.changed(new_parent_inode);
+		if let Err(err) = tl.rename(parent_inode, name, new_parent_inode, new_name, mode) {
+			debug!(" -> Err while renaming: {:?}", &err);
+			reply.error(err.into());
+			return
+		}
 
-		if let Ok(_) = tl.lookup_id(new_name, new_parent_inode) {
-			debug!(" -> destination exists, deleting the existing file in the destination");
-			if let Err(err) = tl.unlink(new_parent_inode, new_name) {
-				debug!(" -> Err while deleting: {:?}", err);
-				reply.error(ENOENT);
+		debug!(" -> OK");
+		reply.ok();
+		self.unlinked(replaced);
+	}
+
+	fn write(
+		&mut self,
+		inode: u64,
+		handle: u64,
+		offset: i64,
+		data: Vec<u8>,
+		reply: fuser::ReplyWrite,
+	) {
+		debug!("write: inode {}, handle {}, offset {}, data len {}", &inode, &handle, &offset, &data.len());
+
+		// The kernel's idea of the file size may be outdated, appending writes go to the end of the
+		// file in the database
+		let append = match self.open_files.get(handle) {
+			Ok(handle) => handle.is_some_and(|handle| handle.flags & libc::O_APPEND != 0),
+			Err(err) => {
+				debug!(" -> Err {:?}", &err);
+				reply.error(err.into());
 				return
 			}
-			debug!(" -> OK");
+		};
+		let offset = match append {
+			false => offset as u64,
+			true => {
+				match self.cache.getattr(inode, || self.tl.getattr(inode)) {
+					Ok(attr) => attr.bytes,
+					Err(err) => {
+						debug!(" -> Err while determining the end of the file: {:?}", &err);
+						reply.error(err.into());
+						return
+					}
+				}
+			}
+		};
+
+		let written = data.len() as u32;
+		self.cache.write(inode, offset, data);
+		self.changed(inode);
+		// if let Err(err) = self.tl.unsafe_write(inode, offset as u64, &data) {
+		// 	debug!(" -> Err {:?}", err);
+		// 	reply.error(ENOENT);
+		// 	return
+		// }
+
+		debug!(" -> OK");
+		reply.written(written);
+	}
+
+	fn getxattr(&mut self, inode: u64, name: &OsStr, size: u32, reply: fuser::ReplyXattr) {
+		debug!("getxattr: inode {}, name {:?}, size {}", &inode, &name, &size);
+
+		match self.tl.getxattr(inode, name) {
+			Ok(value) => {
+				debug!(" -> OK {} bytes", value.len());
+				reply_xattr(&value, size, reply);
+			},
+			Err(err) => {
+				debug!(" -> Err {:?}", &err);
+				reply.error(err.into());
+			}
 		}
+	}
+
+	fn setxattr(&mut self, inode: u64, name: &OsStr, value: &[u8], flags: i32, position: u32, reply: fuser::ReplyEmpty) {
+		debug!("setxattr: inode {}, name {:?}, value len {}, flags {:#x}", &inode, &name, &value.len(), &flags);
+
+		// Positions are only used for resource forks on macOS
+		let mode = match (flags & libc::XATTR_CREATE != 0, flags & libc::XATTR_REPLACE != 0) {
+			_ if position != 0 => None,
+			(false, false) => Some(driver_objects::SetXattrMode::Set),
+			(true, false) => Some(driver_objects::SetXattrMode::Create),
+			(false, true) => Some(driver_objects::SetXattrMode::Replace),
+			(true, true) => None,
+		};
+		let Some(mode) = mode else {
+			debug!(" -> Err invalid flags or position");
+			reply.error(EINVAL);
+			return
+		};
 
-		if let Err(err) = tl.rename(parent_inode, name, new_parent_inode, new_name) {
-			debug!(" -> Err while renaming: {:?}", err);
-			reply.error(ENOENT);
+		self.changed(inode);
+		if let Err(err) = self.tl.setxattr(inode, name, value, mode) {
+			debug!(" -> Err {:?}", &err);
+			reply.error(err.into());
 			return
 		}
 
@@ -838,11 +1239,278 @@ impl fuser::Filesystem for DbfsDriver {
 		reply.ok();
 	}
 
+	fn listxattr(&mut self, inode: u64, size: u32, reply: fuser::ReplyXattr) {
+		debug!("listxattr: inode {}, size {}", &inode, &size);
+
+		match self.tl.listxattr(inode) {
+			Ok(names) => {
+				debug!(" -> OK {:?}", &names);
+				// Every name is terminated by a NUL byte
+				let list: Vec<u8> = names.iter().flat_map(|name| name.bytes().chain(std::iter::once(0))).collect();
+				reply_xattr(&list, size, reply);
+			},
+			Err(err) => {
+				debug!(" -> Err {:?}", &err);
+				reply.error(err.into());
+			}
+		}
+	}
+
+	fn removexattr(&mut self, inode: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+		debug!("removexattr: inode {}, name {:?}", &inode, &name);
+
+		self.changed(inode);
+		if let Err(err) = self.tl.removexattr(inode, name) {
+			debug!(" -> Err {:?}", &err);
+			reply.error(err.into());
+			return
+		}
+
+		debug!(" -> OK");
+		reply.ok();
+	}
+}
+
+/// Attributes of a new inode created by `uid` and `gid` now
+fn new_attr(uid: u32, gid: u32, mode: u32) -> driver_objects::FileSetAttr {
+	let time = std::time::SystemTime::now();
+	driver_objects::FileSetAttr {
+		uid,
+		gid,
+		atime: time,
+		mtime: time,
+		ctime: time,
+		crtime: time,
+		perm: (mode as u16).into()
+	}
+}
+
+/// Answer an xattr request: a `size` of zero asks for the size of the data only, a smaller buffer
+/// than the data is an error
+fn reply_xattr(data: &[u8], size: u32, reply: fuser::ReplyXattr) {
+	if size == 0 {
+		reply.size(data.len() as u32);
+	} else if (size as usize) < data.len() {
+		reply.error(ERANGE);
+	} else {
+		reply.data(data);
+	}
+}
+
+
+/// Requests are keyed by the inode they work on (the parent directory for operations on directory
+/// entries), the request's borrowed arguments are copied for the worker.
+impl fuser::Filesystem for DbfsDriver {
+	fn init(&mut self, _req: &fuser::Request<'_>, config: &mut fuser::KernelConfig) -> Result<(), libc::c_int> {
+		// The kernel checks POSIX ACLs itself, the umask and ACL inheritance are left to mknod
+		if config.add_capabilities(fuser::consts::FUSE_POSIX_ACL | fuser::consts::FUSE_DONT_MASK).is_err() {
+			eprintln!("dbfs: the kernel does not support POSIX ACLs on FUSE, only the permission bits are checked");
+		}
+		// Without it, the kernel truncates files opened with O_TRUNC by setattr
+		let _ = config.add_capabilities(fuser::consts::FUSE_ATOMIC_O_TRUNC);
+		Ok(())
+	}
+
+	fn destroy(&mut self) {
+		// Data of files which were written but never closed
+		self.cache.flush();
+
+		if let Some(stats) = self.tl.read_cache_stats() {
+			eprintln!("read cache: {} hits, {} misses ({:.1}% hit rate), {} evictions",
+				stats.hits, stats.misses, stats.hit_rate() * 100.0, stats.evictions);
+		}
+	}
+
+	fn lookup(&mut self, _req: &fuser::Request, parent_inode: u64, name: &OsStr, reply: fuser::ReplyEntry) {
+		let name = name.to_os_string();
+		self.dispatcher.dispatch(parent_inode, move |worker| worker.lookup(parent_inode, &name, reply));
+	}
+
+	fn getattr(&mut self, _req: &fuser::Request, inode: u64, reply: fuser::ReplyAttr) {
+		self.dispatcher.dispatch(inode, move |worker| worker.getattr(inode, reply));
+	}
+
+	fn read(
+		&mut self,
+		_req: &fuser::Request,
+		inode: u64,
+		_fh: u64,
+		offset: i64,
+		size: u32,
+		_flags: i32,
+		_lock: Option<u64>,
+		reply: fuser::ReplyData,
+	) {
+		self.dispatcher.dispatch(inode, move |worker| worker.read(inode, offset, size, reply));
+	}
+
+	fn readlink(&mut self, _req: &fuser::Request<'_>, inode: u64, reply: fuser::ReplyData) {
+		self.dispatcher.dispatch(inode, move |worker| worker.readlink(inode, reply));
+	}
+
+	fn readdir(
+		&mut self,
+		_req: &fuser::Request,
+		inode: u64,
+		_fh: u64,
+		offset: i64,
+		reply: fuser::ReplyDirectory,
+	) {
+		self.dispatcher.dispatch(inode, move |worker| worker.readdir(inode, offset, reply));
+	}
+
+	fn statfs(&mut self, _req: &fuser::Request<'_>, inode: u64, reply: fuser::ReplyStatfs) {
+		self.dispatcher.dispatch(inode, move |worker| worker.statfs(inode, reply));
+	}
+
+	fn mkdir(
+		&mut self,
+		req: &fuser::Request<'_>,
+		parent_inode: u64,
+		name: &OsStr,
+		mode: u32,
+		umask: u32,
+		reply: fuser::ReplyEntry,
+	) {
+		let (uid, gid, name) = (req.uid(), req.gid(), name.to_os_string());
+		self.dispatcher.dispatch(parent_inode, move |worker| worker.mkdir(uid, gid, parent_inode, &name, mode, umask, reply));
+	}
+
+	fn rmdir(&mut self, _req: &fuser::Request<'_>, parent_inode: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+		let name = name.to_os_string();
+		self.dispatcher.dispatch(parent_inode, move |worker| worker.rmdir(parent_inode, &name, reply));
+	}
+
+	fn unlink(&mut self, _req: &fuser::Request<'_>, parent_inode: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+		let name = name.to_os_string();
+		self.dispatcher.dispatch(parent_inode, move |worker| worker.unlink(parent_inode, &name, reply));
+	}
+
+	fn open(&mut self, _req: &fuser::Request<'_>, inode: u64, flags: i32, reply: fuser::ReplyOpen) {
+		self.dispatcher.dispatch(inode, move |worker| worker.open(inode, flags, reply));
+	}
+
+	fn create(
+		&mut self,
+		req: &fuser::Request<'_>,
+		parent_inode: u64,
+		name: &OsStr,
+		mode: u32,
+		umask: u32,
+		flags: i32,
+		reply: fuser::ReplyCreate,
+	) {
+		let (uid, gid, name) = (req.uid(), req.gid(), name.to_os_string());
+		self.dispatcher.dispatch(parent_inode, move |worker| worker.create(uid, gid, parent_inode, &name, mode, umask, flags, reply));
+	}
+
+	fn flush(&mut self, _req: &fuser::Request<'_>, inode: u64, _fh: u64, _lock_owner: u64, reply: fuser::ReplyEmpty) {
+		self.dispatcher.dispatch(inode, move |worker| worker.flush(inode, reply));
+	}
+
+	fn fsync(&mut self, _req: &fuser::Request<'_>, inode: u64, _fh: u64, _datasync: bool, reply: fuser::ReplyEmpty) {
+		self.dispatcher.dispatch(inode, move |worker| worker.flush(inode, reply));
+	}
+
+	fn release(
+		&mut self,
+		_req: &fuser::Request<'_>,
+		inode: u64,
+		fh: u64,
+		_flags: i32,
+		_lock_owner: Option<u64>,
+		_flush: bool,
+		reply: fuser::ReplyEmpty,
+	) {
+		self.dispatcher.dispatch(inode, move |worker| worker.release(inode, fh, reply));
+	}
+
+	fn opendir(&mut self, _req: &fuser::Request<'_>, inode: u64, flags: i32, reply: fuser::ReplyOpen) {
+		self.dispatcher.dispatch(inode, move |worker| worker.opendir(inode, flags, reply));
+	}
+
+	fn releasedir(&mut self, _req: &fuser::Request<'_>, inode: u64, fh: u64, _flags: i32, reply: fuser::ReplyEmpty) {
+		self.dispatcher.dispatch(inode, move |worker| worker.releasedir(inode, fh, reply));
+	}
+
+	fn link(
+		&mut self,
+		_req: &fuser::Request<'_>,
+		inode: u64,
+		new_parent_inode: u64,
+		new_name: &OsStr,
+		reply: fuser::ReplyEntry,
+	) {
+		let new_name = new_name.to_os_string();
+		self.dispatcher.dispatch(new_parent_inode, move |worker| worker.link(inode, new_parent_inode, &new_name, reply));
+	}
+
+	fn symlink(
+		&mut self,
+		req: &fuser::Request<'_>,
+		parent_inode: u64,
+		link_name: &OsStr,
+		target: &std::path::Path,
+		reply: fuser::ReplyEntry,
+	) {
+		let (uid, gid, link_name, target) = (req.uid(), req.gid(), link_name.to_os_string(), target.to_path_buf());
+		self.dispatcher.dispatch(parent_inode, move |worker| worker.symlink(uid, gid, parent_inode, &link_name, &target, reply));
+	}
+
+	fn setattr(
+		&mut self,
+		_req: &fuser::Request<'_>,
+		inode: u64,
+		mode: Option<u32>,
+		uid: Option<u32>,
+		gid: Option<u32>,
+		size: Option<u64>,
+		atime: Option<fuser::TimeOrNow>,
+		mtime: Option<fuser::TimeOrNow>,
+		ctime: Option<std::time::SystemTime>,
+		_fh: Option<u64>,
+		_crtime: Option<std::time::SystemTime>,
+		_chgtime: Option<std::time::SystemTime>,
+		_bkuptime: Option<std::time::SystemTime>,
+		_flags: Option<u32>,
+		reply: fuser::ReplyAttr,
+	) {
+		self.dispatcher.dispatch(inode, move |worker| worker.setattr(inode, mode, uid, gid, size, atime, mtime, ctime, reply));
+	}
+
+	fn mknod(
+		&mut self,
+		req: &fuser::Request<'_>,
+		parent_inode: u64,
+		name: &OsStr,
+		mode: u32,
+		umask: u32,
+		rdev: u32,
+		reply: fuser::ReplyEntry,
+	) {
+		let (uid, gid, name) = (req.uid(), req.gid(), name.to_os_string());
+		self.dispatcher.dispatch(parent_inode, move |worker| worker.mknod(uid, gid, parent_inode, &name, mode, umask, rdev, reply));
+	}
+
+	fn rename(
+		&mut self,
+		_req: &fuser::Request<'_>,
+		parent_inode: u64,
+		name: &OsStr,
+		new_parent_inode: u64,
+		new_name: &OsStr,
+		flags: u32,
+		reply: fuser::ReplyEmpty,
+	) {
+		let (name, new_name) = (name.to_os_string(), new_name.to_os_string());
+		self.dispatcher.dispatch(parent_inode, move |worker| worker.rename(parent_inode, &name, new_parent_inode, &new_name, flags, reply));
+	}
+
 	fn write(
 		&mut self,
 		_req: &fuser::Request<'_>,
 		inode: u64,
-		_fh: u64,
+		fh: u64,
 		offset: i64,
 		data: &[u8],
 		_write_flags: u32,
@@ -850,17 +1518,230 @@ impl fuser::Filesystem for DbfsDriver {
 		_lock_owner: Option<u64>,
 		reply: fuser::ReplyWrite,
 	) {
-		debug!("write: inode {}, offset {}, data len {}", &inode, &offset, &data.len());
+		let data = data.to_vec();
+		self.dispatcher.dispatch(inode, move |worker| worker.write(inode, fh, offset, data, reply));
+	}
 
-		self.cache.write(inode, offset as u64, data.to_vec());
-		// if let Err(err) = self.tl.lock().unwrap().unsafe_write(inode, offset as u64, data) {
-		// 	debug!(" -> Err {:?}", err);
-		// 	reply.error(ENOENT);
-		// 	return
-		// }
+	fn getxattr(&mut self, _req: &fuser::Request<'_>, inode: u64, name: &OsStr, size: u32, reply: fuser::ReplyXattr) {
+		let name = name.to_os_string();
+		self.dispatcher.dispatch(inode, move |worker| worker.getxattr(inode, &name, size, reply));
+	}
 
-		debug!(" -> OK");
-		reply.written(data.len() as u32);
+	fn setxattr(
+		&mut self,
+		_req: &fuser::Request<'_>,
+		inode: u64,
+		name: &OsStr,
+		value: &[u8],
+		flags: i32,
+		position: u32,
+		reply: fuser::ReplyEmpty,
+	) {
+		let (name, value) = (name.to_os_string(), value.to_vec());
+		self.dispatcher.dispatch(inode, move |worker| worker.setxattr(inode, &name, &value, flags, position, reply));
+	}
+
+	fn listxattr(&mut self, _req: &fuser::Request<'_>, inode: u64, size: u32, reply: fuser::ReplyXattr) {
+		self.dispatcher.dispatch(inode, move |worker| worker.listxattr(inode, size, reply));
+	}
+
+	fn removexattr(&mut self, _req: &fuser::Request<'_>, inode: u64, name: &OsStr, reply: fuser::ReplyEmpty) {
+		let name = name.to_os_string();
+		self.dispatcher.dispatch(inode, move |worker| worker.removexattr(inode, &name, reply));
 	}
 }
 
+
+
+#[cfg(test)]
+mod test {
+	use crate::sql_translation_layer::backend::{memory::MemoryBackend, DEFAULT_VOLUME};
+	use crate::sql_translation_layer::test_util::{file_attr, volume};
+	use super::*;
+
+
+	fn create(tl: &TranslationLayer, parent: u64, name: &str, kind: driver_objects::FileType) -> Result<u64, i32> {
+		tl.mknod(parent, OsStr::new(name), kind, file_attr()).map(|attr| attr.ino as u64).map_err(Error::into)
+	}
+
+	fn errno<T: std::fmt::Debug>(result: Result<T, Error>) -> i32 {
+		result.unwrap_err().into()
+	}
+
+	fn worker(tl: Arc<TranslationLayer>, atime: driver_objects::AtimePolicy) -> Worker {
+		Worker {
+			tl: tl.clone(),
+			last_readdir_inode: u64::MAX,
+			last_readdir: Vec::new(),
+			cache: cache::WriteCache::new(tl, 1024, Duration::from_secs(3600)),
+			open_files: Arc::new(open_files::OpenFiles::new()),
+			access_times: Arc::new(access_times::AccessTimes::new(atime, Duration::from_secs(3600)))
+		}
+	}
+
+
+	#[test]
+	fn missing_file_is_enoent() {
+		let tl = volume();
+		assert_eq!(errno(tl.lookup(OsStr::new("missing"), 1)), ENOENT);
+		assert_eq!(errno(tl.getattr(1234)), ENOENT);
+		assert_eq!(errno(tl.unlink(1, OsStr::new("missing"))), ENOENT);
+	}
+
+	#[test]
+	fn duplicate_name_is_eexist() {
+		let tl = volume();
+		create(&tl, 1, "file", driver_objects::FileType::File).unwrap();
+		assert_eq!(create(&tl, 1, "file", driver_objects::FileType::Directory), Err(EEXIST));
+		let dir = create(&tl, 1, "dir", driver_objects::FileType::Directory).unwrap();
+		assert_eq!(errno(tl.link(1, OsStr::new("file"), dir)), EEXIST);
+	}
+
+	#[test]
+	fn parent_file_is_enotdir() {
+		let tl = volume();
+		let file = create(&tl, 1, "file", driver_objects::FileType::File).unwrap();
+		assert_eq!(create(&tl, file, "child", driver_objects::FileType::File), Err(ENOTDIR));
+		assert_eq!(errno(tl.rename(1, OsStr::new("file"), file, OsStr::new("moved"), driver_objects::RenameMode::Replace)), ENOTDIR);
+		assert_eq!(errno(tl.rmdir(1, OsStr::new("file"))), ENOTDIR);
+	}
+
+	#[test]
+	fn unlinking_directory_is_eisdir() {
+		let tl = volume();
+		create(&tl, 1, "dir", driver_objects::FileType::Directory).unwrap();
+		assert_eq!(errno(tl.unlink(1, OsStr::new("dir"))), EISDIR);
+		tl.rmdir(1, OsStr::new("dir")).unwrap();
+	}
+
+	#[test]
+	fn non_empty_directory_is_enotempty() {
+		let tl = volume();
+		let dir = create(&tl, 1, "dir", driver_objects::FileType::Directory).unwrap();
+		create(&tl, dir, "file", driver_objects::FileType::File).unwrap();
+		assert_eq!(errno(tl.rmdir(1, OsStr::new("dir"))), ENOTEMPTY);
+		tl.unlink(dir, OsStr::new("file")).unwrap();
+		tl.rmdir(1, OsStr::new("dir")).unwrap();
+	}
+
+	#[test]
+	fn open_truncates_for_writing() {
+		let tl = Arc::new(volume());
+		let file = create(&tl, 1, "file", driver_objects::FileType::File).unwrap();
+		let pipe = create(&tl, 1, "pipe", driver_objects::FileType::NamedPipe).unwrap();
+		tl.write(file, 0, b"data").unwrap();
+		let worker = worker(tl.clone(), driver_objects::AtimePolicy::Never);
+
+		worker.open_handle(file, libc::O_RDONLY | libc::O_TRUNC).unwrap();
+		assert_eq!(tl.getattr(file).unwrap().bytes, 4);
+		worker.cache.write(file, 4, b"!".to_vec());
+		let handle = worker.open_handle(file, libc::O_WRONLY | libc::O_TRUNC).unwrap();
+		assert_eq!(tl.getattr(file).unwrap().bytes, 0);
+		assert_eq!(worker.open_files.get(handle).unwrap().unwrap().inode, file);
+		worker.open_handle(pipe, libc::O_WRONLY | libc::O_TRUNC).unwrap();
+	}
+
+	#[test]
+	fn reads_do_not_fetch_the_access_time() {
+		let backend = MemoryBackend::new();
+		let tl = Arc::new(TranslationLayer::with_backend(Box::new(backend.clone()), DEFAULT_VOLUME).unwrap());
+		let file = create(&tl, 1, "file", driver_objects::FileType::File).unwrap();
+		tl.write(file, 0, b"data").unwrap();
+		let atime = || tl.getattr(file).unwrap().atime;
+
+		// The file was modified after it was last read
+		let relatime = worker(tl.clone(), driver_objects::AtimePolicy::Relative);
+		let before = atime();
+		relatime.access(file);
+		assert!(atime() > before);
+
+		let calls = backend.calls();
+		for _ in 0..10 {
+			relatime.access(file);
+		}
+		assert_eq!(backend.calls(), calls);
+
+		// Writes held back by the cache modify the file too
+		relatime.cache.write(file, 4, b"!".to_vec());
+		let before = atime();
+		relatime.access(file);
+		assert!(atime() > before);
+
+		// Strict updates are throttled
+		let strictatime = worker(tl.clone(), driver_objects::AtimePolicy::Strict);
+		strictatime.access(file);
+		let calls = backend.calls();
+		strictatime.access(file);
+		assert_eq!(backend.calls(), calls);
+
+		// A lost connection is not waited for, the reply was sent already
+		backend.fail(0, 1000);
+		let start = std::time::Instant::now();
+		worker(tl.clone(), driver_objects::AtimePolicy::Relative).access(file);
+		assert!(start.elapsed() < Duration::from_secs(1));
+	}
+
+	#[test]
+	fn rename_errors() {
+		let tl = volume();
+		let dir = create(&tl, 1, "dir", driver_objects::FileType::Directory).unwrap();
+		let sub = create(&tl, dir, "sub", driver_objects::FileType::Directory).unwrap();
+		create(&tl, sub, "file", driver_objects::FileType::File).unwrap();
+		create(&tl, 1, "file", driver_objects::FileType::File).unwrap();
+
+		let rename = |src_parent, src, dest_parent, dest, mode| errno(tl.rename(src_parent, OsStr::new(src), dest_parent, OsStr::new(dest), mode));
+		assert_eq!(rename(1, "dir", sub, "dir", driver_objects::RenameMode::Replace), EINVAL);
+		assert_eq!(rename(1, "file", dir, "sub", driver_objects::RenameMode::Replace), EISDIR);
+		assert_eq!(rename(dir, "sub", 1, "file", driver_objects::RenameMode::Replace), ENOTDIR);
+		assert_eq!(rename(1, "file", 1, "dir", driver_objects::RenameMode::NoReplace), EEXIST);
+		assert_eq!(rename(1, "file", 1, "missing", driver_objects::RenameMode::Exchange), ENOENT);
+
+		let empty = create(&tl, 1, "empty", driver_objects::FileType::Directory).unwrap();
+		assert_eq!(rename(1, "empty", 1, "dir", driver_objects::RenameMode::Replace), ENOTEMPTY);
+		assert_eq!(tl.lookup_id(OsStr::new("empty"), 1).unwrap(), empty);
+		assert_eq!(tl.lookup_id(OsStr::new("dir"), 1).unwrap(), dir);
+	}
+
+	#[test]
+	fn long_name_is_enametoolong() {
+		let tl = volume();
+		let name = "x".repeat(MAX_NAME_LEN as usize + 1);
+		assert_eq!(create(&tl, 1, &name, driver_objects::FileType::File), Err(ENAMETOOLONG));
+		assert_eq!(errno(tl.lookup(OsStr::new(&name), 1)), ENAMETOOLONG);
+		create(&tl, 1, &name[1..], driver_objects::FileType::File).unwrap();
+		assert_eq!(errno(tl.rename(1, OsStr::new(&name[1..]), 1, OsStr::new(&name), driver_objects::RenameMode::Replace)), ENAMETOOLONG);
+	}
+
+	#[test]
+	fn xattr_errors() {
+		let tl = volume();
+		let ino = create(&tl, 1, "file", driver_objects::FileType::File).unwrap();
+		assert_eq!(errno(tl.getxattr(ino, OsStr::new("user.missing"))), ENODATA);
+		assert_eq!(errno(tl.setxattr(ino, OsStr::new(&"x".repeat(256)), b"", driver_objects::SetXattrMode::Set)), ERANGE);
+		assert_eq!(errno(tl.setxattr(ino, OsStr::new("user.big"), &vec![0; 65537], driver_objects::SetXattrMode::Set)), E2BIG);
+		tl.setxattr(ino, OsStr::new("user.a"), b"", driver_objects::SetXattrMode::Create).unwrap();
+		assert_eq!(errno(tl.setxattr(ino, OsStr::new("user.a"), b"", driver_objects::SetXattrMode::Create)), EEXIST);
+		assert_eq!(errno(tl.removexattr(ino, OsStr::new("user.b"))), ENODATA);
+	}
+
+	#[test]
+	fn device_modes_and_numbers() {
+		assert_eq!(Ok(driver_objects::FileType::BlockDevice), (libc::S_IFBLK | 0o660).try_into());
+		assert_eq!(Ok(driver_objects::FileType::CharDevice), (libc::S_IFCHR | 0o666).try_into());
+		// 1:3 and 259:0x12345 in the glibc layout
+		assert_eq!(encode_rdev(0x103), 0x103);
+		assert_eq!(encode_rdev(0x0000_0000_1231_0345), 0x1231_0345);
+	}
+
+	#[test]
+	fn database_errors() {
+		let errno = |err: DbConnectorError| -> i32 { Error::DbConnectorError(err).into() };
+		assert_eq!(errno(DbConnectorError::DuplicateError(String::new())), EEXIST);
+		assert_eq!(errno(DbConnectorError::NoSpaceError(String::new())), ENOSPC);
+		assert_eq!(errno(DbConnectorError::ReadOnlyError(String::new())), EROFS);
+		assert_eq!(errno(DbConnectorError::PermissionError(String::new())), EACCES);
+		assert_eq!(errno(DbConnectorError::ConnectionError(String::new())), EIO);
+		assert_eq!(errno(DbConnectorError::ConflictError(String::new())), EIO);
+		assert_eq!(errno(DbConnectorError::AdapterError(String::new())), EIO);
+	}
+}
@@ -0,0 +1,171 @@
+use crate::sql_translation_layer::{Error, TranslationLayer};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Handles of the open files and directories, shared by all FUSE workers
+///
+/// An inode which loses its last name while it is open becomes an orphan (see
+/// [`TranslationLayer::unlink`]) and is reclaimed when its last handle is released.
+pub struct OpenFiles(Mutex<Table>);
+
+/// An open file or directory
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Handle {
+	pub inode: u64,
+	/// Flags passed to `open(2)` (`O_APPEND`, ...)
+	pub flags: i32
+}
+
+struct Table {
+	handles: HashMap<u64, Handle>,
+	inodes: HashMap<u64, OpenFile>,
+	/// Handle 0 is never used, FUSE passes it for requests without a handle
+	next_handle: u64
+}
+
+#[derive(Default)]
+struct OpenFile {
+	count: u64,
+	/// A name of the inode was removed while it was open
+	unlinked: bool
+}
+
+impl OpenFiles {
+	pub fn new() -> Self {
+		Self(Mutex::new(Table { handles: HashMap::new(), inodes: HashMap::new(), next_handle: 1 }))
+	}
+
+	fn table(&self) -> Result<std::sync::MutexGuard<'_, Table>, Error> {
+		self.0.lock().map_err(|_| Error::RuntimeError("could not lock the open files"))
+	}
+
+	/// Open an inode and return the new handle
+	pub fn open(&self, inode: u64, flags: i32) -> Result<u64, Error> {
+		let mut table = self.table()?;
+		let handle = table.next_handle;
+		table.next_handle += 1;
+		table.handles.insert(handle, Handle { inode, flags });
+		table.inodes.entry(inode).or_default().count += 1;
+		Ok(handle)
+	}
+
+	pub fn get(&self, handle: u64) -> Result<Option<Handle>, Error> {
+		Ok(self.table()?.handles.get(&handle).copied())
+	}
+
+	/// Close a handle, its inode is reclaimed if this was its last handle and it lost its last name
+	pub fn release(&self, tl: &TranslationLayer, handle: u64) -> Result<(), Error> {
+		let mut table = self.table()?;
+		let Some(Handle { inode, .. }) = table.handles.remove(&handle) else {
+			return Err(Error::ClientError("the file handle is not open"))
+		};
+		let Some(file) = table.inodes.get_mut(&inode) else {
+			return Ok(())
+		};
+		file.count -= 1;
+		if file.count != 0 {
+			return Ok(())
+		}
+
+		let unlinked = file.unlinked;
+		table.inodes.remove(&inode);
+		// The lock is held, so the inode can not be opened again while it is deleted
+		if unlinked { tl.reclaim(inode) } else { Ok(()) }
+	}
+
+	/// A name of an inode was removed, it is reclaimed right away unless it is open
+	///
+	/// Inodes which still have other names are left untouched by [`TranslationLayer::reclaim`].
+	pub fn unlinked(&self, tl: &TranslationLayer, inode: u64) -> Result<(), Error> {
+		let mut table = self.table()?;
+		match table.inodes.get_mut(&inode) {
+			Some(file) => {
+				file.unlinked = true;
+				Ok(())
+			},
+			None => tl.reclaim(inode)
+		}
+	}
+}
+
+
+
+#[cfg(test)]
+mod test {
+	use std::ffi::OsStr;
+	use crate::sql_translation_layer::driver_objects;
+	use crate::sql_translation_layer::test_util::{self, volume};
+	use super::*;
+
+
+	fn create(tl: &TranslationLayer, name: &str) -> u64 {
+		test_util::create(tl, 1, name, driver_objects::FileType::File)
+	}
+
+
+	#[test]
+	fn unlinked_file_lives_until_last_release() {
+		let tl = volume();
+		let ino = create(&tl, "temp");
+		tl.write(ino, 0, b"scratch").unwrap();
+		let files = OpenFiles::new();
+		let first = files.open(ino, libc::O_RDWR).unwrap();
+		let second = files.open(ino, libc::O_RDONLY).unwrap();
+
+		tl.unlink(1, OsStr::new("temp")).unwrap();
+		files.unlinked(&tl, ino).unwrap();
+		let mut buffer = [0u8; 7];
+		assert_eq!(tl.read(ino, 0, &mut buffer).unwrap(), 7);
+		assert_eq!(&buffer, b"scratch");
+
+		files.release(&tl, first).unwrap();
+		assert_eq!(tl.getattr(ino).unwrap().hardlinks, 0);
+		files.release(&tl, second).unwrap();
+		assert!(tl.getattr(ino).is_err());
+	}
+
+	#[test]
+	fn closed_file_is_reclaimed_on_unlink() {
+		let tl = volume();
+		let ino = create(&tl, "file");
+		let files = OpenFiles::new();
+		let handle = files.open(ino, libc::O_RDONLY).unwrap();
+		files.release(&tl, handle).unwrap();
+
+		tl.unlink(1, OsStr::new("file")).unwrap();
+		files.unlinked(&tl, ino).unwrap();
+		assert!(tl.getattr(ino).is_err());
+	}
+
+	#[test]
+	fn release_keeps_linked_file() {
+		let tl = volume();
+		let ino = create(&tl, "file");
+		tl.link(1, OsStr::new("link"), ino).unwrap();
+		let files = OpenFiles::new();
+		let handle = files.open(ino, libc::O_RDONLY).unwrap();
+
+		tl.unlink(1, OsStr::new("file")).unwrap();
+		files.unlinked(&tl, ino).unwrap();
+		files.release(&tl, handle).unwrap();
+		assert_eq!(tl.getattr(ino).unwrap().hardlinks, 1);
+	}
+
+	#[test]
+	fn handles() {
+		let tl = volume();
+		let ino = create(&tl, "file");
+		let files = OpenFiles::new();
+		let first = files.open(ino, libc::O_WRONLY | libc::O_APPEND).unwrap();
+		let second = files.open(ino, libc::O_RDONLY).unwrap();
+		assert_ne!(first, 0);
+		assert_ne!(first, second);
+		assert_eq!(files.get(first).unwrap(), Some(Handle { inode: ino, flags: libc::O_WRONLY | libc::O_APPEND }));
+		assert_eq!(files.get(0).unwrap(), None);
+
+		files.release(&tl, first).unwrap();
+		assert_eq!(files.get(first).unwrap(), None);
+		assert!(files.release(&tl, first).is_err());
+		assert_eq!(files.get(second).unwrap().map(|handle| handle.inode), Some(ino));
+	}
+}
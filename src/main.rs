@@ -1,4 +1,5 @@
 mod settings;
+mod config;
 mod cmd_args;
 pub mod db_connector;
 pub mod sql_translation_layer;
@@ -20,37 +21,157 @@ macro_rules! debug {
 	}
 }
 
-fn create_driver() -> Option<fuse_driver::DbfsDriver> {
+fn load_config(args: &cmd_args::ArgConnection) -> Option<config::ConnectionConfig> {
+	match config::ConnectionConfig::load(args) {
+		Ok(val) => Some(val),
+		Err(err) => {
+			eprintln!("{}", err);
+			None
+		}
+	}
+}
+
+fn open_volume(config: &config::ConnectionConfig, volume: &str, create_schema: bool) -> Option<sql_translation_layer::TranslationLayer> {
 	debug!("connecting to db...");
-	let tl = match sql_translation_layer::TranslationLayer::new() {
+	let tl = match create_schema {
+		true => sql_translation_layer::TranslationLayer::new_with_schema(config, volume),
+		false => sql_translation_layer::TranslationLayer::new(config, volume),
+	};
+	match tl {
+		Ok(val) => Some(val),
+		Err(err) => {
+			eprintln!("{}", err);
+			None
+		}
+	}
+}
+
+fn mount(args: cmd_args::ArgMount) {
+	let Some(config) = load_config(&args.connection()) else { std::process::exit(1) };
+	let volume = args.volume.as_deref().unwrap_or(sql_translation_layer::backend::DEFAULT_VOLUME);
+	let atime = match args.atime.as_deref().unwrap_or("relatime").parse() {
 		Ok(val) => val,
 		Err(err) => {
 			eprintln!("{}", err);
-			return None;
+			std::process::exit(1)
 		}
 	};
-
+	let Some(mut tl) = open_volume(&config, volume, false) else { std::process::exit(1) };
+	tl.set_retry(match args.hard {
+		true => None,
+		false => Some(std::time::Duration::from_secs(args.timeout.unwrap_or(settings::DB_RETRY_TIMEOUT_SECS))),
+	});
+	tl.set_read_cache(args.read_cache.unwrap_or(settings::READ_CACHE_SIZE_MIB) << 20);
+	// Files which were still open when the volume was last unmounted (or crashed)
+	match tl.reclaim_orphans() {
+		Ok(0) => {},
+		Ok(count) => eprintln!("reclaimed {} orphaned inodes", count),
+		Err(err) => eprintln!("could not reclaim orphaned inodes: {}", err),
+	}
 	debug!("starting FUSE driver");
-	Some(fuse_driver::DbfsDriver::new(tl))
+	let driver = fuse_driver::DbfsDriver::new(
+		tl,
+		args.threads.unwrap_or(settings::FUSE_WORKER_THREADS),
+		atime,
+		args.write_cache.unwrap_or(settings::WRITE_CACHE_SIZE_MIB) << 20,
+		std::time::Duration::from_millis(args.flush_interval.unwrap_or(settings::WRITE_CACHE_FLUSH_INTERVAL_MS))
+	);
+	driver.run_forever(&args.mountpoint, args.allow_root, args.allow_other);
 }
 
-fn mount(args: cmd_args::ArgMount) {
-	if let Some(driver) = create_driver() {
-		driver.run_forever(&args.mountpoint, args.allow_root, args.allow_other);
+/// Returns whether the volume was formatted (errors are printed)
+fn format(config: &config::ConnectionConfig, volume: &str, block_size: u32, label: &str) -> bool {
+	let Some(mut tl) = open_volume(config, volume, true) else { return false };
+	debug!("erasing fs...");
+	match tl.format(block_size, label) {
+		Ok(_) => true,
+		Err(err) => {
+			eprintln!("{}", err);
+			false
+		}
 	}
 }
 
-fn format() {
-	if let Some(mut driver) = create_driver() {
-		debug!("erasing fs...");
-		debug!("{:?}", driver.format());
+fn import(args: cmd_args::ArgImport) {
+	let Some(config) = load_config(&args.connection()) else { std::process::exit(1) };
+	let volume = args.volume.as_deref().unwrap_or(sql_translation_layer::backend::DEFAULT_VOLUME);
+	// Nothing is imported into a volume which could not be erased
+	if !format(&config, volume, settings::FILE_BLOCK_SIZE_32, "") { std::process::exit(1) }
+	let Some(tl) = open_volume(&config, volume, false) else { std::process::exit(1) };
+	debug!("importing fs...");
+	if let Err(err) = fuse_driver::import(&tl, std::path::Path::new(&args.source)) {
+		eprintln!("{}", err);
+		std::process::exit(1)
 	}
 }
 
-fn import(args: cmd_args::ArgImport) {
-	if let Some(mut driver) = create_driver() {
-		debug!("erasing fs...");
-		debug!("{:?}", driver.import(std::path::Path::new(&args.source)));
+fn init(args: cmd_args::ArgInit) {
+	use sql_translation_layer::backend;
+
+	// Provisioning scripts rely on the exit status
+	let Some(config) = load_config(&args.connection()) else { std::process::exit(1) };
+	let result = backend::open(&config).and_then(|backend| {
+		backend::init(backend.as_ref(), args.force)?;
+		backend::check_schema(backend.as_ref())
+	});
+	match result {
+		Ok(()) => println!("schema is initialized (version {})", backend::SCHEMA_VERSION),
+		Err(err) => {
+			eprintln!("{}", err);
+			std::process::exit(1)
+		},
+	}
+}
+
+fn migrate(args: cmd_args::ArgMigrate) {
+	use sql_translation_layer::backend;
+
+	let Some(config) = load_config(&args.connection()) else { std::process::exit(1) };
+	let result = backend::open(&config).and_then(|backend| backend::migrate(backend.as_ref()));
+	match result {
+		Ok(previous) if previous == backend::SCHEMA_VERSION => println!("schema is up to date (version {})", previous),
+		Ok(previous) => println!("schema upgraded from version {} to {}", previous, backend::SCHEMA_VERSION),
+		Err(err) => {
+			eprintln!("{}", err);
+			std::process::exit(1)
+		},
+	}
+}
+
+fn volumes(args: cmd_args::ArgVolumes) {
+	use cmd_args::ArgVolumesCommand;
+	use sql_translation_layer::backend;
+
+	let connection = match &args.command {
+		ArgVolumesCommand::List(args) => args.connection(),
+		ArgVolumesCommand::Create(args) => args.connection(),
+		ArgVolumesCommand::Delete(args) => args.connection(),
+	};
+	let Some(config) = load_config(&connection) else { std::process::exit(1) };
+	let result = backend::open(&config).and_then(|backend| {
+		backend::check_schema(backend.as_ref())?;
+		match args.command {
+			ArgVolumesCommand::List(_) => {
+				println!("NAME\tBLOCK SIZE\tUUID\tLABEL");
+				for volume in backend.list_volumes()? {
+					println!("{}\t{}\t{}\t{}", volume.name, volume.superblock.block_size, volume.superblock.uuid, volume.superblock.label);
+				}
+			},
+			ArgVolumesCommand::Create(args) => {
+				let superblock = sql_translation_layer::new_superblock(args.block_size.unwrap_or(settings::FILE_BLOCK_SIZE_32), args.label.as_deref().unwrap_or(""))?;
+				let volume = backend::create_volume(backend.as_ref(), &args.name, &superblock)?;
+				println!("created volume {} ({})", volume.name, volume.superblock.uuid);
+			},
+			ArgVolumesCommand::Delete(args) => {
+				backend::delete_volume(backend.as_ref(), &args.name)?;
+				println!("deleted volume {}", args.name);
+			},
+		}
+		Ok(())
+	});
+	if let Err(err) = result {
+		eprintln!("{}", err);
+		std::process::exit(1)
 	}
 }
 
@@ -59,8 +180,17 @@ fn main() {
 
 	match args.command {
 		cmd_args::ArgCommand::Mount(args) => mount(args),
-		cmd_args::ArgCommand::Format(_) => format(),
-		cmd_args::ArgCommand::Import(args) => { format(); import(args); }
+		cmd_args::ArgCommand::Format(args) => {
+			let Some(config) = load_config(&args.connection()) else { std::process::exit(1) };
+			let volume = args.volume.as_deref().unwrap_or(sql_translation_layer::backend::DEFAULT_VOLUME);
+			if !format(&config, volume, args.block_size.unwrap_or(settings::FILE_BLOCK_SIZE_32), args.label.as_deref().unwrap_or("")) {
+				std::process::exit(1)
+			}
+		},
+		cmd_args::ArgCommand::Import(args) => import(args),
+		cmd_args::ArgCommand::Init(args) => init(args),
+		cmd_args::ArgCommand::Migrate(args) => migrate(args),
+		cmd_args::ArgCommand::Volumes(args) => volumes(args),
 	}
 }
 
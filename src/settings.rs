@@ -1,3 +1,4 @@
+// Connection defaults, overridable at runtime (see `crate::config`)
 #[doc(hidden)]
 pub const SQL_HOST: &'static str = "127.0.0.1:3306";
 #[doc(hidden)]
@@ -16,16 +17,28 @@ macro_rules! block_size{
 }
 
 
+// Block size of newly formatted volumes (the block size of a volume is stored in its superblock)
 // Please don't touch this
 #[cfg(feature = "integration_testing")]
-pub const FILE_BLOCK_SIZE: u64 = 4096;
-#[cfg(feature = "integration_testing")]
 pub const FILE_BLOCK_SIZE_32: u32 = 4096;
-#[cfg(feature = "integration_testing")]
-pub const FILE_BLOCK_SIZE_USIZE: usize = 4096;
-#[cfg(not(feature = "integration_testing"))]
-pub const FILE_BLOCK_SIZE: u64 = block_size!();
 #[cfg(not(feature = "integration_testing"))]
 pub const FILE_BLOCK_SIZE_32: u32 = block_size!();
-#[cfg(not(feature = "integration_testing"))]
-pub const FILE_BLOCK_SIZE_USIZE: usize = block_size!();
+
+
+// Number of threads serving FUSE requests (each one can use its own database connection, the
+// connection pools hold 10 connections - 4 for SQLite)
+pub const FUSE_WORKER_THREADS: usize = 8;
+
+
+// Size of the write cache in MiB and how long written data may stay in it before it is flushed to
+// the database (closing or syncing a file flushes it right away)
+pub const WRITE_CACHE_SIZE_MIB: usize = 32;
+pub const WRITE_CACHE_FLUSH_INTERVAL_MS: u64 = 1000;
+
+// Size of the cache of blocks read from the database in MiB (0 disables it)
+pub const READ_CACHE_SIZE_MIB: usize = 64;
+
+
+// How long filesystem operations are retried while the database is unreachable before they fail
+// with EIO (a soft mount, `dbfs mount --hard` retries forever)
+pub const DB_RETRY_TIMEOUT_SECS: u64 = 30;
@@ -0,0 +1,249 @@
+//! POSIX access control lists
+//!
+//! ACLs are stored as the extended attributes `system.posix_acl_access` and
+//! `system.posix_acl_default` in the format the Linux kernel uses for these attributes (a version
+//! header followed by `tag`, `perm`, `id` entries, all little endian). The kernel checks them on
+//! access, dbfs keeps them consistent with the permission bits of the inode: the group bits of the
+//! mode are the ACL mask (see `acl(5)`).
+
+use super::driver_objects::Permissions;
+use super::Error;
+
+
+/// Name of the extended attribute holding the access ACL
+pub const ACCESS_ACL: &'static str = "system.posix_acl_access";
+
+/// Name of the extended attribute holding the default ACL of a directory
+pub const DEFAULT_ACL: &'static str = "system.posix_acl_default";
+
+const ACL_VERSION: u32 = 2;
+const ACL_UNDEFINED_ID: u32 = u32::MAX;
+
+const ACL_USER_OBJ: u16 = 0x01;
+const ACL_USER: u16 = 0x02;
+const ACL_GROUP_OBJ: u16 = 0x04;
+const ACL_GROUP: u16 = 0x08;
+const ACL_MASK: u16 = 0x10;
+const ACL_OTHER: u16 = 0x20;
+
+const INVALID_ACL: Error = Error::ClientError("invalid ACL");
+
+
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+	tag: u16,
+	perm: u16,
+	id: u32,
+}
+
+
+/// A valid ACL, entries are ordered by tag and id
+#[derive(Clone, Debug, PartialEq)]
+pub struct Acl(Vec<Entry>);
+
+impl Acl {
+	/// Read an ACL from its extended attribute value, an empty value or an ACL without entries is
+	/// `None` (setting it removes the ACL)
+	pub fn parse(value: &[u8]) -> Result<Option<Self>, Error> {
+		if value.is_empty() {
+			return Ok(None);
+		}
+		if value.len() < 4 || (value.len() - 4) % 8 != 0 {
+			return Err(INVALID_ACL);
+		}
+		if u32::from_le_bytes(value[0..4].try_into().unwrap()) != ACL_VERSION {
+			return Err(INVALID_ACL);
+		}
+
+		let entries: Vec<Entry> = value[4..].chunks(8).map(|entry| Entry {
+			tag: u16::from_le_bytes(entry[0..2].try_into().unwrap()),
+			perm: u16::from_le_bytes(entry[2..4].try_into().unwrap()),
+			id: u32::from_le_bytes(entry[4..8].try_into().unwrap()),
+		}).collect();
+		if entries.is_empty() {
+			return Ok(None);
+		}
+
+		// Exactly one owner, owning group and other entry, named entries require a mask
+		let count = |tag: u16| entries.iter().filter(|entry| entry.tag == tag).count();
+		if count(ACL_USER_OBJ) != 1 || count(ACL_GROUP_OBJ) != 1 || count(ACL_OTHER) != 1 || count(ACL_MASK) > 1 {
+			return Err(INVALID_ACL);
+		}
+		if count(ACL_MASK) == 0 && count(ACL_USER) + count(ACL_GROUP) != 0 {
+			return Err(INVALID_ACL);
+		}
+		for entry in &entries {
+			let named = matches!(entry.tag, ACL_USER | ACL_GROUP);
+			let known = matches!(entry.tag, ACL_USER_OBJ | ACL_USER | ACL_GROUP_OBJ | ACL_GROUP | ACL_MASK | ACL_OTHER);
+			if !known || entry.perm > 7 || (named && entry.id == ACL_UNDEFINED_ID) {
+				return Err(INVALID_ACL);
+			}
+		}
+		if entries.windows(2).any(|pair| (pair[0].tag, pair[0].id) >= (pair[1].tag, pair[1].id)) {
+			return Err(INVALID_ACL);
+		}
+
+		Ok(Some(Self(entries)))
+	}
+
+	/// Extended attribute value of the ACL
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut value = Vec::with_capacity(4 + 8 * self.0.len());
+		value.extend(ACL_VERSION.to_le_bytes());
+		for entry in &self.0 {
+			value.extend(entry.tag.to_le_bytes());
+			value.extend(entry.perm.to_le_bytes());
+			value.extend(entry.id.to_le_bytes());
+		}
+		value
+	}
+
+	/// Whether the ACL only has the owner, group and other entries (the permission bits say the
+	/// same, so it is not stored)
+	pub fn is_minimal(&self) -> bool {
+		self.0.iter().all(|entry| matches!(entry.tag, ACL_USER_OBJ | ACL_GROUP_OBJ | ACL_OTHER))
+	}
+
+	/// Permission bits matching the ACL, the group bits are the mask if there is one
+	pub fn permissions(&self, special: u8) -> Permissions {
+		let perm = |tag: u16| self.0.iter().find(|entry| entry.tag == tag).map(|entry| entry.perm as u8);
+		Permissions {
+			special,
+			owner: perm(ACL_USER_OBJ).unwrap_or(0),
+			group: perm(ACL_MASK).or(perm(ACL_GROUP_OBJ)).unwrap_or(0),
+			other: perm(ACL_OTHER).unwrap_or(0),
+		}
+	}
+
+	/// Set the owner, mask (or group) and other entries to the permission bits (`chmod`)
+	pub fn chmod(&mut self, perm: &Permissions) {
+		let has_mask = self.0.iter().any(|entry| entry.tag == ACL_MASK);
+		for entry in self.0.iter_mut() {
+			match entry.tag {
+				ACL_USER_OBJ => entry.perm = perm.owner as u16,
+				ACL_MASK => entry.perm = perm.group as u16,
+				ACL_GROUP_OBJ if !has_mask => entry.perm = perm.group as u16,
+				ACL_OTHER => entry.perm = perm.other as u16,
+				_ => {},
+			}
+		}
+	}
+
+	/// Access ACL of a new file created with `perm` in a directory with this default ACL, the
+	/// entries matching the permission bits lose the permissions the mode does not grant
+	pub fn inherit(&self, perm: &Permissions) -> Self {
+		let has_mask = self.0.iter().any(|entry| entry.tag == ACL_MASK);
+		let mut acl = self.clone();
+		for entry in acl.0.iter_mut() {
+			match entry.tag {
+				ACL_USER_OBJ => entry.perm &= perm.owner as u16,
+				ACL_MASK => entry.perm &= perm.group as u16,
+				ACL_GROUP_OBJ if !has_mask => entry.perm &= perm.group as u16,
+				ACL_OTHER => entry.perm &= perm.other as u16,
+				_ => {},
+			}
+		}
+		acl
+	}
+}
+
+
+
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+
+	fn acl(entries: &[(u16, u16, u32)]) -> Vec<u8> {
+		Acl(entries.iter().map(|&(tag, perm, id)| Entry { tag, perm, id }).collect()).to_bytes()
+	}
+
+	fn perm(owner: u8, group: u8, other: u8) -> Permissions {
+		Permissions { special: 0, owner, group, other }
+	}
+
+	fn extended() -> Vec<u8> {
+		acl(&[
+			(ACL_USER_OBJ, 7, ACL_UNDEFINED_ID),
+			(ACL_USER, 6, 1000),
+			(ACL_GROUP_OBJ, 5, ACL_UNDEFINED_ID),
+			(ACL_MASK, 7, ACL_UNDEFINED_ID),
+			(ACL_OTHER, 4, ACL_UNDEFINED_ID),
+		])
+	}
+
+
+	#[test]
+	fn parse_and_serialize() {
+		let value = extended();
+		let parsed = Acl::parse(&value).unwrap().unwrap();
+		assert_eq!(parsed.to_bytes(), value);
+		assert!(!parsed.is_minimal());
+		assert_eq!(parsed.permissions(1), Permissions { special: 1, owner: 7, group: 7, other: 4 });
+
+		assert_eq!(Acl::parse(&ACL_VERSION.to_le_bytes()).unwrap(), None);
+		assert_eq!(Acl::parse(&[]).unwrap(), None);
+	}
+
+	#[test]
+	fn invalid_acls() {
+		assert!(Acl::parse(&[2, 0]).is_err());
+		assert!(Acl::parse(&extended()[..12]).is_err());
+		// Wrong version
+		let mut value = extended();
+		value[0] = 1;
+		assert!(Acl::parse(&value).is_err());
+		// Named entry without a mask
+		assert!(Acl::parse(&acl(&[
+			(ACL_USER_OBJ, 7, ACL_UNDEFINED_ID),
+			(ACL_USER, 6, 1000),
+			(ACL_GROUP_OBJ, 5, ACL_UNDEFINED_ID),
+			(ACL_OTHER, 4, ACL_UNDEFINED_ID),
+		])).is_err());
+		// Missing other entry
+		assert!(Acl::parse(&acl(&[(ACL_USER_OBJ, 7, ACL_UNDEFINED_ID), (ACL_GROUP_OBJ, 5, ACL_UNDEFINED_ID)])).is_err());
+		// Out of order
+		assert!(Acl::parse(&acl(&[
+			(ACL_GROUP_OBJ, 5, ACL_UNDEFINED_ID),
+			(ACL_USER_OBJ, 7, ACL_UNDEFINED_ID),
+			(ACL_OTHER, 4, ACL_UNDEFINED_ID),
+		])).is_err());
+	}
+
+	#[test]
+	fn chmod_sets_the_mask() {
+		let mut parsed = Acl::parse(&extended()).unwrap().unwrap();
+		parsed.chmod(&perm(6, 4, 0));
+		assert_eq!(parsed.to_bytes(), acl(&[
+			(ACL_USER_OBJ, 6, ACL_UNDEFINED_ID),
+			(ACL_USER, 6, 1000),
+			(ACL_GROUP_OBJ, 5, ACL_UNDEFINED_ID),
+			(ACL_MASK, 4, ACL_UNDEFINED_ID),
+			(ACL_OTHER, 0, ACL_UNDEFINED_ID),
+		]));
+
+		let mut minimal = Acl::parse(&acl(&[
+			(ACL_USER_OBJ, 7, ACL_UNDEFINED_ID),
+			(ACL_GROUP_OBJ, 5, ACL_UNDEFINED_ID),
+			(ACL_OTHER, 5, ACL_UNDEFINED_ID),
+		])).unwrap().unwrap();
+		assert!(minimal.is_minimal());
+		minimal.chmod(&perm(6, 4, 0));
+		assert_eq!(minimal.permissions(0), perm(6, 4, 0));
+	}
+
+	#[test]
+	fn inherit_masks_with_the_mode() {
+		let parsed = Acl::parse(&extended()).unwrap().unwrap();
+		let child = parsed.inherit(&perm(6, 6, 6));
+		assert_eq!(child.to_bytes(), acl(&[
+			(ACL_USER_OBJ, 6, ACL_UNDEFINED_ID),
+			(ACL_USER, 6, 1000),
+			(ACL_GROUP_OBJ, 5, ACL_UNDEFINED_ID),
+			(ACL_MASK, 6, ACL_UNDEFINED_ID),
+			(ACL_OTHER, 4, ACL_UNDEFINED_ID),
+		]));
+		assert_eq!(child.permissions(0), perm(6, 6, 4));
+	}
+}
@@ -0,0 +1,773 @@
+//! In-memory backend for unit tests
+//!
+//! Behaves like the MySQL schema (`sql/mysql/dbfs.sql`): foreign keys and primary keys are
+//! enforced, blocks are deleted together with their inode, inode ids are never reused and
+//! timestamps only have a precision of one second.
+
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use crate::db_connector::{chrono, DbConnectorError};
+use crate::settings;
+use super::super::{database_objects, driver_objects, Error};
+use super::{Backend, BlockHead, WriteInfo, DEFAULT_VOLUME, SCHEMA_VERSION};
+
+
+/// Clones share the same database (like two connections to one server)
+///
+/// Transactions are not isolated: their changes are visible to everyone right away and a rollback
+/// restores the whole database to its state at [`Backend::begin`].
+#[derive(Clone)]
+pub struct MemoryBackend(Arc<Mutex<Database>>, Option<Transaction>, Arc<Calls>);
+
+
+/// Calls made to the database and failures injected by tests (kept by rollbacks)
+#[derive(Default)]
+struct Calls {
+	count: AtomicU64,
+	/// Calls with a number in `failing.0..failing.1` fail with a lost connection
+	failing: Mutex<(u64, u64)>,
+}
+
+
+/// Snapshot a transaction rolls back to (`None` once it has ended)
+#[derive(Clone)]
+struct Transaction {
+	snapshot: Arc<Mutex<Option<Database>>>,
+	outermost: bool,
+}
+
+
+#[derive(Default, Clone)]
+struct Database {
+	users: BTreeMap<u32, String>,
+	groups: BTreeMap<u32, String>,
+	volumes: BTreeMap<u64, driver_objects::Volume>,
+	inodes: BTreeMap<u64, database_objects::Inode>,
+	/// `inode_id -> volume_id`
+	inode_volumes: BTreeMap<u64, u64>,
+	/// `(parent_inode_id, name) -> inode_id`
+	files: BTreeMap<(u64, String), u64>,
+	/// `(inode_id, block_id) -> data`
+	blocks: BTreeMap<(u64, u64), Vec<u8>>,
+	/// `(inode_id, key) -> value`
+	xattrs: BTreeMap<(u64, String), Vec<u8>>,
+	/// `inode_id` of the `orphan` rows
+	orphans: BTreeSet<u64>,
+	/// Next `AUTO_INCREMENT` value of `inode`.`id`
+	next_inode_id: u64,
+	/// Next `AUTO_INCREMENT` value of `volume`.`id`
+	next_volume_id: u64,
+	/// `schema_version` of the `metadata` table
+	schema_version: u32,
+	/// Whether the tables exist (cleared by [`Backend::drop_schema`])
+	schema: bool,
+}
+
+
+impl MemoryBackend {
+	/// Create a database with the default volume (see [`Backend::create_schema`])
+	pub fn new() -> Self {
+		Self::with_schema_version(SCHEMA_VERSION)
+	}
+
+
+	/// Create a database with the default volume which claims to have another schema version
+	pub fn with_schema_version(version: u32) -> Self {
+		Self(Arc::new(Mutex::new(Database::new(version))), None, Arc::new(Calls::default()))
+	}
+
+
+	/// Number of calls made to the database so far (by this backend and its clones)
+	pub fn calls(&self) -> u64 {
+		self.2.count.load(Ordering::Relaxed)
+	}
+
+
+	/// Let `count` calls fail with a lost connection, after `skip` more calls succeeded
+	pub fn fail(&self, skip: u64, count: u64) {
+		let next = self.calls();
+		*self.2.failing.lock().unwrap() = (next + skip, next + skip + count);
+	}
+
+
+	/// The database for one call (which may fail, see [`MemoryBackend::fail`])
+	fn database(&self) -> Result<std::sync::MutexGuard<'_, Database>, Error> {
+		let call = self.2.count.fetch_add(1, Ordering::Relaxed);
+		let (from, to) = *self.2.failing.lock().map_err(|_| Error::DbLockError)?;
+		if (from..to).contains(&call) {
+			return Err(Error::DbConnectorError(DbConnectorError::ConnectionError(String::from("connection refused"))));
+		}
+		self.0.lock().map_err(|_| Error::DbLockError)
+	}
+
+
+	/// Take the snapshot out of a backend which started the transaction (`None` if it joined one)
+	fn end(&self) -> Result<Option<Database>, Error> {
+		match &self.1 {
+			Some(Transaction { snapshot, outermost: true }) => {
+				let snapshot = snapshot.lock().map_err(|_| Error::DbLockError)?.take();
+				snapshot.map(Some).ok_or(Error::DbConnectorError(DbConnectorError::AdapterError(String::from("the transaction has already ended"))))
+			},
+			Some(_) => Ok(None),
+			None => Err(Error::DbConnectorError(DbConnectorError::AdapterError(String::from("there is no transaction to end")))),
+		}
+	}
+}
+
+
+/// Mimic an error returned by the database
+fn constraint_error(message: &str) -> Error {
+	Error::DbConnectorError(DbConnectorError::AdapterError(String::from(message)))
+}
+
+/// Mimic the error returned by the database for a duplicate key
+fn duplicate_error(message: &str) -> Error {
+	Error::DbConnectorError(DbConnectorError::DuplicateError(String::from(message)))
+}
+
+
+/// `CURRENT_TIMESTAMP()` of a `timestamp` column (second precision)
+fn now() -> chrono::DateTime<chrono::Utc> {
+	truncate(chrono::Utc::now())
+}
+
+
+fn truncate(time: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+	chrono::DateTime::from_timestamp(time.timestamp(), 0).unwrap_or(time)
+}
+
+
+/// Inode timestamp as stored in its seconds and nanoseconds columns
+fn timestamp(time: SystemTime) -> (i64, u32) {
+	let time = driver_objects::Timestamp::from(time);
+	(time.sec, time.nsec)
+}
+
+
+/// Superblock as stored in a `timestamp` column
+fn stored(superblock: &driver_objects::Superblock) -> driver_objects::Superblock {
+	driver_objects::Superblock { created_at: truncate(superblock.created_at.into()).into(), ..superblock.clone() }
+}
+
+
+impl Database {
+	/// Rows inserted by the schema script: the root user and group and the default volume
+	fn new(schema_version: u32) -> Self {
+		let mut database = Self {
+			users: BTreeMap::from([(0, String::from("root")), (1000, String::from("user"))]),
+			groups: BTreeMap::from([(0, String::from("root")), (1000, String::from("user"))]),
+			next_inode_id: 2,
+			next_volume_id: 2,
+			schema_version,
+			schema: true,
+			..Default::default()
+		};
+		database.volumes.insert(1, driver_objects::Volume {
+			id: 1,
+			name: String::from(DEFAULT_VOLUME),
+			root_inode: Some(1),
+			superblock: driver_objects::Superblock {
+				block_size: settings::FILE_BLOCK_SIZE_32,
+				uuid: uuid::Uuid::new_v4().to_string(),
+				label: String::new(),
+				created_at: now().into(),
+				features: 0,
+			},
+		});
+		database.inodes.insert(1, database_objects::Inode {
+			id: 1,
+			owner: 0,
+			group: 0,
+			file_type: String::from("d"),
+			special_bits: 0,
+			user_perm: 7,
+			group_perm: 5,
+			other_perm: 5,
+			rdev: 0,
+			atime: 1729792372,
+			atime_nsec: 0,
+			mtime: 1729792390,
+			mtime_nsec: 0,
+			ctime: 1729792372,
+			ctime_nsec: 0,
+			crtime: 1729792372,
+			crtime_nsec: 0,
+		});
+		database.inode_volumes.insert(1, 1);
+		database.files.insert((1, String::from("/")), 1);
+		database
+	}
+
+
+	/// Ids of all inodes of a volume
+	fn volume_inodes(&self, volume: u64) -> BTreeSet<u64> {
+		self.inode_volumes.iter().filter(|(_, vol)| **vol == volume).map(|(ino, _)| *ino).collect()
+	}
+
+
+	/// Blocks of an inode ordered by `block_id`
+	fn inode_blocks(&self, inode: u64) -> std::collections::btree_map::Range<'_, (u64, u64), Vec<u8>> {
+		self.blocks.range((inode, 0)..=(inode, u64::MAX))
+	}
+
+
+	fn upsert_block(&mut self, inode: u64, block_id: u64, data: Vec<u8>) -> Result<(), Error> {
+		if !self.inodes.contains_key(&inode) {
+			return Err(constraint_error("foreign key constraint `block_inode` fails"));
+		}
+		self.blocks.insert((inode, block_id), data);
+		Ok(())
+	}
+}
+
+
+impl Backend for MemoryBackend {
+	fn get_users(&self) -> Result<Vec<database_objects::User>, Error> {
+		Ok(self.database()?.users.iter().map(|(id, name)| database_objects::User { id: *id, name: name.clone() }).collect())
+	}
+
+
+	fn get_groups(&self) -> Result<Vec<database_objects::Group>, Error> {
+		Ok(self.database()?.groups.iter().map(|(id, name)| database_objects::Group { id: *id, name: name.clone() }).collect())
+	}
+
+
+	fn insert_user(&self, id: u32, name: &str) -> Result<(), Error> {
+		let mut database = self.database()?;
+		if database.users.contains_key(&id) {
+			return Err(constraint_error("duplicate entry for key `user`.`PRIMARY`"));
+		}
+		database.users.insert(id, String::from(name));
+		Ok(())
+	}
+
+
+	fn insert_group(&self, id: u32, name: &str) -> Result<(), Error> {
+		let mut database = self.database()?;
+		if database.groups.contains_key(&id) {
+			return Err(constraint_error("duplicate entry for key `group`.`PRIMARY`"));
+		}
+		database.groups.insert(id, String::from(name));
+		Ok(())
+	}
+
+
+	fn get_inode(&self, inode: u64) -> Result<Option<database_objects::Inode>, Error> {
+		Ok(self.database()?.inodes.get(&inode).cloned())
+	}
+
+
+	fn create_inode(&self, volume: u64, kind: driver_objects::FileType, attr: &driver_objects::FileSetAttr, rdev: u32) -> Result<u64, Error> {
+		let mut database = self.database()?;
+		if !database.volumes.contains_key(&volume) {
+			return Err(constraint_error("foreign key constraint `inode_volume` fails"));
+		}
+		if !database.users.contains_key(&attr.uid) {
+			return Err(constraint_error("foreign key constraint `inode_owner` fails"));
+		}
+		if !database.groups.contains_key(&attr.gid) {
+			return Err(constraint_error("foreign key constraint `inode_group` fails"));
+		}
+		let id = database.next_inode_id;
+		let (atime, atime_nsec) = timestamp(attr.atime);
+		let (mtime, mtime_nsec) = timestamp(attr.mtime);
+		let (ctime, ctime_nsec) = timestamp(attr.ctime);
+		let (crtime, crtime_nsec) = timestamp(attr.crtime);
+		database.inodes.insert(id, database_objects::Inode {
+			id: id.try_into().map_err(|_| constraint_error("out of range value for column `inode`.`id`"))?,
+			owner: attr.uid,
+			group: attr.gid,
+			file_type: kind.into(),
+			special_bits: attr.perm.special,
+			user_perm: attr.perm.owner,
+			group_perm: attr.perm.group,
+			other_perm: attr.perm.other,
+			rdev,
+			atime,
+			atime_nsec,
+			mtime,
+			mtime_nsec,
+			ctime,
+			ctime_nsec,
+			crtime,
+			crtime_nsec,
+		});
+		database.inode_volumes.insert(id, volume);
+		database.next_inode_id += 1;
+		Ok(id)
+	}
+
+
+	fn update_inode(&self, inode: u64, attr: &driver_objects::FileSetAttr) -> Result<u64, Error> {
+		let mut database = self.database()?;
+		if !database.users.contains_key(&attr.uid) {
+			return Err(constraint_error("foreign key constraint `inode_owner` fails"));
+		}
+		if !database.groups.contains_key(&attr.gid) {
+			return Err(constraint_error("foreign key constraint `inode_group` fails"));
+		}
+		let Some(row) = database.inodes.get_mut(&inode) else {
+			return Ok(0);
+		};
+		row.owner = attr.uid;
+		row.group = attr.gid;
+		(row.atime, row.atime_nsec) = timestamp(attr.atime);
+		(row.mtime, row.mtime_nsec) = timestamp(attr.mtime);
+		(row.ctime, row.ctime_nsec) = timestamp(attr.ctime);
+		row.special_bits = attr.perm.special;
+		row.user_perm = attr.perm.owner;
+		row.group_perm = attr.perm.group;
+		row.other_perm = attr.perm.other;
+		Ok(1)
+	}
+
+
+	fn touch_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		if let Some(row) = self.database()?.inodes.get_mut(&inode) {
+			(row.mtime, row.mtime_nsec) = timestamp(time);
+			(row.ctime, row.ctime_nsec) = timestamp(time);
+		}
+		Ok(())
+	}
+
+
+	fn change_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		if let Some(row) = self.database()?.inodes.get_mut(&inode) {
+			(row.ctime, row.ctime_nsec) = timestamp(time);
+		}
+		Ok(())
+	}
+
+
+	fn access_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		if let Some(row) = self.database()?.inodes.get_mut(&inode) {
+			(row.atime, row.atime_nsec) = timestamp(time);
+		}
+		Ok(())
+	}
+
+
+	fn delete_inode(&self, inode: u64) -> Result<u64, Error> {
+		let mut database = self.database()?;
+		if database.files.iter().any(|((parent, _), ino)| *parent == inode || *ino == inode) {
+			return Err(constraint_error("cannot delete a parent row: foreign key constraint `file_inode` fails"));
+		}
+		if database.inodes.remove(&inode).is_none() {
+			return Ok(0);
+		}
+		database.inode_volumes.remove(&inode);
+		database.blocks.retain(|(ino, _), _| *ino != inode);
+		database.xattrs.retain(|(ino, _), _| *ino != inode);
+		database.orphans.remove(&inode);
+		Ok(1)
+	}
+
+
+	fn create_orphan(&self, inode: u64) -> Result<(), Error> {
+		let mut database = self.database()?;
+		if !database.inodes.contains_key(&inode) {
+			return Err(constraint_error("cannot add a child row: foreign key constraint `orphan_inode` fails"));
+		}
+		if !database.orphans.insert(inode) {
+			return Err(duplicate_error("duplicate entry for key `orphan`.`PRIMARY`"));
+		}
+		Ok(())
+	}
+
+
+	fn delete_orphan(&self, inode: u64) -> Result<u64, Error> {
+		if !self.database()?.orphans.contains(&inode) {
+			return Ok(0);
+		}
+		self.delete_inode(inode)
+	}
+
+
+	fn list_orphans(&self, volume: u64) -> Result<Vec<u64>, Error> {
+		let database = self.database()?;
+		Ok(database.orphans.iter().copied().filter(|ino| database.inode_volumes.get(ino) == Some(&volume)).collect())
+	}
+
+
+	fn count_hardlinks(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.database()?.files.values().filter(|ino| **ino == inode).count() as u64)
+	}
+
+
+	fn count_subdirectories(&self, inode: u64) -> Result<u64, Error> {
+		let database = self.database()?;
+		let mut children: Vec<u64> = database.files.iter()
+			.filter(|((parent, _), ino)| *parent == inode && **ino != inode)
+			.map(|(_, ino)| *ino)
+			.filter(|ino| database.inodes.get(ino).is_some_and(|row| row.file_type == "d"))
+			.collect();
+		children.sort();
+		children.dedup();
+		Ok(children.len() as u64)
+	}
+
+
+	fn lookup(&self, parent_inode: u64, name: &str) -> Result<Option<u64>, Error> {
+		Ok(self.database()?.files.get(&(parent_inode, String::from(name))).copied())
+	}
+
+
+	fn list_directory(&self, inode: u64) -> Result<Vec<database_objects::DirectoryEntry>, Error> {
+		let database = self.database()?;
+		let mut entries = database.files.iter()
+			.filter(|((parent, _), ino)| *parent == inode && **ino != inode)
+			.map(|((_, name), ino)| Ok(database_objects::DirectoryEntry {
+				name: name.clone(),
+				inode_id: database.inodes.get(ino).ok_or(Error::RuntimeError("dangling file entry"))?.id,
+				file_type: database.inodes.get(ino).ok_or(Error::RuntimeError("dangling file entry"))?.file_type.clone(),
+			}))
+			.collect::<Result<Vec<_>, Error>>()?;
+		entries.sort_by(|a, b| (a.inode_id, &a.name).cmp(&(b.inode_id, &b.name)));
+		Ok(entries)
+	}
+
+
+	fn get_parent(&self, inode: u64) -> Result<Option<u64>, Error> {
+		Ok(self.database()?.files.iter().find(|(_, ino)| **ino == inode).map(|((parent, _), _)| *parent))
+	}
+
+
+	fn count_children(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.database()?.files.keys().filter(|(parent, _)| *parent == inode).count() as u64)
+	}
+
+
+	fn create_file(&self, parent_inode: u64, name: &str, inode: u64) -> Result<u64, Error> {
+		let mut database = self.database()?;
+		if !database.inodes.contains_key(&parent_inode) {
+			return Err(constraint_error("foreign key constraint `file_parent_inode` fails"));
+		}
+		if !database.inodes.contains_key(&inode) {
+			return Err(constraint_error("foreign key constraint `file_inode` fails"));
+		}
+		let key = (parent_inode, String::from(name));
+		if database.files.contains_key(&key) {
+			return Err(duplicate_error("duplicate entry for key `file`.`PRIMARY`"));
+		}
+		database.files.insert(key, inode);
+		Ok(1)
+	}
+
+
+	fn delete_file(&self, parent_inode: u64, name: &str) -> Result<u64, Error> {
+		Ok(match self.database()?.files.remove(&(parent_inode, String::from(name))) {
+			Some(_) => 1,
+			None => 0,
+		})
+	}
+
+
+	fn rename_file(&self, src_parent_inode: u64, src_name: &str, dest_parent_inode: u64, dest_name: &str) -> Result<u64, Error> {
+		let mut database = self.database()?;
+		let src = (src_parent_inode, String::from(src_name));
+		let dest = (dest_parent_inode, String::from(dest_name));
+		let Some(inode) = database.files.get(&src).copied() else {
+			return Ok(0);
+		};
+		if src == dest {
+			return Ok(1);
+		}
+		if !database.inodes.contains_key(&dest_parent_inode) {
+			return Err(constraint_error("foreign key constraint `file_parent_inode` fails"));
+		}
+		if database.files.contains_key(&dest) {
+			return Err(duplicate_error("duplicate entry for key `file`.`PRIMARY`"));
+		}
+		database.files.remove(&src);
+		database.files.insert(dest, inode);
+		Ok(1)
+	}
+
+
+	fn file_size(&self, inode: u64, block_size: u64) -> Result<Option<database_objects::FileSize>, Error> {
+		Ok(self.database()?.inode_blocks(inode).next_back().map(|((_, block_id), data)| database_objects::FileSize {
+			bytes: (block_id - 1) * block_size + data.len() as u64,
+			blocks: *block_id,
+		}))
+	}
+
+
+	fn block_head(&self, inode: u64) -> Result<BlockHead, Error> {
+		let database = self.database()?;
+		Ok(BlockHead {
+			blocks: database.inode_blocks(inode).count() as u64,
+			last_block_id: database.inode_blocks(inode).next_back().map(|((_, block_id), _)| *block_id).unwrap_or(0),
+		})
+	}
+
+
+	fn write_info(&self, inode: u64, start_block: Option<u64>, end_block: Option<u64>) -> Result<WriteInfo, Error> {
+		let database = self.database()?;
+		let block_data = |block: Option<u64>| match block {
+			Some(block) => database.inode_blocks(inode).nth(block as usize).map(|(_, data)| data.clone()).unwrap_or_default(),
+			None => Vec::new(),
+		};
+		Ok(WriteInfo {
+			blocks: database.inode_blocks(inode).count() as u64,
+			start_block_data: block_data(start_block),
+			end_block_data: block_data(end_block),
+		})
+	}
+
+
+	fn read_blocks(&self, inode: u64, offset_blocks: u64, max_blocks: u64) -> Result<Vec<Vec<u8>>, Error> {
+		Ok(self.database()?.inode_blocks(inode).skip(offset_blocks as usize).take(max_blocks as usize).map(|(_, data)| data.clone()).collect())
+	}
+
+
+	fn write_blocks(&self, inode: u64, first_block_id: u64, data: Vec<Vec<u8>>) -> Result<(), Error> {
+		let mut database = self.database()?;
+		for (block_id, block) in (first_block_id..).zip(data) {
+			database.upsert_block(inode, block_id, block)?;
+		}
+		Ok(())
+	}
+
+
+	fn pad_blocks(&self, inode: u64, first_block_id: u64, until_block_id: u64, block_size: u64) -> Result<(), Error> {
+		let mut database = self.database()?;
+		for block_id in first_block_id..until_block_id {
+			database.upsert_block(inode, block_id, vec![0; block_size as usize])?;
+		}
+		Ok(())
+	}
+
+
+	fn resize_last_block(&self, inode: u64, length: u64) -> Result<(), Error> {
+		if let Some((_, data)) = self.database()?.blocks.range_mut((inode, 0)..=(inode, u64::MAX)).next_back() {
+			data.resize(length as usize, 0);
+		}
+		Ok(())
+	}
+
+
+	fn trim_blocks(&self, inode: u64, count: u64) -> Result<(), Error> {
+		let mut database = self.database()?;
+		let trimmed: Vec<(u64, u64)> = database.inode_blocks(inode).rev().take(count as usize).map(|(key, _)| *key).collect();
+		for key in trimmed {
+			database.blocks.remove(&key);
+		}
+		Ok(())
+	}
+
+
+	fn drop_blocks(&self, inode: u64) -> Result<(), Error> {
+		self.database()?.blocks.retain(|(ino, _), _| *ino != inode);
+		Ok(())
+	}
+
+
+	fn get_xattr(&self, inode: u64, key: &str) -> Result<Option<Vec<u8>>, Error> {
+		Ok(self.database()?.xattrs.get(&(inode, String::from(key))).cloned())
+	}
+
+
+	fn list_xattrs(&self, inode: u64) -> Result<Vec<String>, Error> {
+		Ok(self.database()?.xattrs.keys().filter(|(ino, _)| *ino == inode).map(|(_, key)| key.clone()).collect())
+	}
+
+
+	fn create_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		let mut database = self.database()?;
+		if !database.inodes.contains_key(&inode) {
+			return Err(constraint_error("foreign key constraint `xattr_inode` fails"));
+		}
+		let key = (inode, String::from(key));
+		if database.xattrs.contains_key(&key) {
+			return Err(duplicate_error("duplicate entry for key `extended_attributes`.`PRIMARY`"));
+		}
+		database.xattrs.insert(key, value.to_vec());
+		Ok(1)
+	}
+
+
+	fn update_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		Ok(match self.database()?.xattrs.get_mut(&(inode, String::from(key))) {
+			Some(row) => {
+				*row = value.to_vec();
+				1
+			},
+			None => 0,
+		})
+	}
+
+
+	fn delete_xattr(&self, inode: u64, key: &str) -> Result<u64, Error> {
+		Ok(match self.database()?.xattrs.remove(&(inode, String::from(key))) {
+			Some(_) => 1,
+			None => 0,
+		})
+	}
+
+
+	fn list_volumes(&self) -> Result<Vec<driver_objects::Volume>, Error> {
+		Ok(self.database()?.volumes.values().cloned().collect())
+	}
+
+
+	fn get_volume(&self, name: &str) -> Result<Option<driver_objects::Volume>, Error> {
+		Ok(self.database()?.volumes.values().find(|volume| volume.name == name).cloned())
+	}
+
+
+	fn create_volume(&self, name: &str, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut database = self.database()?;
+		if database.volumes.values().any(|volume| volume.name == name) {
+			return Err(constraint_error("duplicate entry for key `volume`.`volume_name`"));
+		}
+		let id = database.next_volume_id;
+		database.volumes.insert(id, driver_objects::Volume {
+			id,
+			name: String::from(name),
+			root_inode: None,
+			superblock: stored(superblock),
+		});
+		database.next_volume_id += 1;
+		Ok(id)
+	}
+
+
+	fn update_volume(&self, volume: u64, root_inode: u64, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut database = self.database()?;
+		let Some(row) = database.volumes.get_mut(&volume) else {
+			return Ok(0);
+		};
+		row.root_inode = Some(root_inode);
+		row.superblock = stored(superblock);
+		Ok(1)
+	}
+
+
+	fn erase_volume(&self, volume: u64) -> Result<(), Error> {
+		let mut database = self.database()?;
+		if let Some(row) = database.volumes.get_mut(&volume) {
+			row.root_inode = None;
+		}
+		let inodes = database.volume_inodes(volume);
+		database.blocks.retain(|(ino, _), _| !inodes.contains(ino));
+		database.xattrs.retain(|(ino, _), _| !inodes.contains(ino));
+		database.orphans.retain(|ino| !inodes.contains(ino));
+		database.files.retain(|_, ino| !inodes.contains(ino));
+		database.inodes.retain(|ino, _| !inodes.contains(ino));
+		database.inode_volumes.retain(|ino, _| !inodes.contains(ino));
+		Ok(())
+	}
+
+
+	fn delete_volume(&self, volume: u64) -> Result<u64, Error> {
+		let mut database = self.database()?;
+		if database.inode_volumes.values().any(|vol| *vol == volume) {
+			return Err(constraint_error("cannot delete a parent row: foreign key constraint `inode_volume` fails"));
+		}
+		Ok(match database.volumes.remove(&volume) {
+			Some(_) => 1,
+			None => 0,
+		})
+	}
+
+
+	fn stat(&self, volume: u64) -> Result<driver_objects::FilesystemStat, Error> {
+		let database = self.database()?;
+		let inodes = database.volume_inodes(volume);
+		Ok(driver_objects::FilesystemStat {
+			used_blocks: database.blocks.keys().filter(|(ino, _)| inodes.contains(ino)).count() as u64,
+			used_inodes: inodes.len() as u64,
+		})
+	}
+
+
+	fn schema_version(&self) -> Result<u32, Error> {
+		Ok(self.database()?.schema_version)
+	}
+
+
+	fn migrate(&self, version: u32) -> Result<(), Error> {
+		let mut database = self.database()?;
+		if version != database.schema_version + 1 {
+			return Err(Error::ClientError("there is no migration to the requested schema version"));
+		}
+		database.schema_version = version;
+		Ok(())
+	}
+
+
+	fn create_schema(&self) -> Result<(), Error> {
+		let mut database = self.database()?;
+		if !database.schema {
+			*database = Database::new(SCHEMA_VERSION);
+		}
+		Ok(())
+	}
+
+
+	fn drop_schema(&self) -> Result<(), Error> {
+		*self.database()? = Database::default();
+		Ok(())
+	}
+
+
+	fn begin(&self) -> Result<Box<dyn Backend>, Error> {
+		let transaction = match &self.1 {
+			Some(transaction) => Transaction { outermost: false, ..transaction.clone() },
+			None => Transaction { snapshot: Arc::new(Mutex::new(Some(self.database()?.clone()))), outermost: true },
+		};
+		Ok(Box::new(Self(self.0.clone(), Some(transaction), self.2.clone())))
+	}
+
+
+	fn commit(&self) -> Result<(), Error> {
+		self.end()?;
+		Ok(())
+	}
+
+
+	fn rollback(&self) -> Result<(), Error> {
+		// Rolling back never fails, even while calls are failing
+		if let Some(snapshot) = self.end()? {
+			*self.0.lock().map_err(|_| Error::DbLockError)? = snapshot;
+		}
+		Ok(())
+	}
+}
+
+
+#[cfg(test)]
+mod test {
+	use super::super::super::test_util::file_attr;
+	use super::*;
+
+
+	#[test]
+	fn unknown_owner_is_rejected() {
+		let backend = MemoryBackend::new();
+		assert!(backend.create_inode(1, driver_objects::FileType::File, &driver_objects::FileSetAttr { uid: 4242, ..file_attr() }, 0).is_err());
+		assert_eq!(backend.stat(1).unwrap().used_inodes, 1);
+	}
+
+	#[test]
+	fn transactions() {
+		let backend = MemoryBackend::new();
+		let transaction = backend.begin().unwrap();
+		let ino = transaction.create_inode(1, driver_objects::FileType::File, &file_attr(), 0).unwrap();
+		transaction.rollback().unwrap();
+		assert!(backend.get_inode(ino).unwrap().is_none());
+		assert!(transaction.commit().is_err());
+
+		// Only the outermost transaction ends it
+		let transaction = backend.begin().unwrap();
+		let nested = transaction.begin().unwrap();
+		let ino = nested.create_inode(1, driver_objects::FileType::File, &file_attr(), 0).unwrap();
+		nested.rollback().unwrap();
+		transaction.commit().unwrap();
+		assert!(backend.get_inode(ino).unwrap().is_some());
+		assert!(backend.commit().is_err());
+	}
+}
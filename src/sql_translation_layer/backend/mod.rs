@@ -0,0 +1,489 @@
+//! Storage backends for the [`super::TranslationLayer`]
+//!
+//! A backend stores the inode/file/block schema and exposes the primitive operations the
+//! translation layer is built from. All filesystem logic (block pointer arithmetic, link counting,
+//! ...) stays in the translation layer, so every backend only needs to provide the storage itself.
+
+
+#[cfg(test)]
+pub mod memory;
+pub mod mssql;
+pub mod mysql;
+pub mod postgres;
+pub mod sqlite;
+
+
+use std::time::SystemTime;
+
+use crate::config::ConnectionConfig;
+use crate::db_connector::{chrono, DbInputType};
+use super::{database_objects, driver_objects, Error, MAX_NAME_LEN};
+
+
+/// Schema version this build works with (stored as `schema_version` in the `metadata` table)
+pub const SCHEMA_VERSION: u32 = 6;
+
+/// Volume created together with the schema (used when no `--volume` is given)
+pub const DEFAULT_VOLUME: &'static str = "default";
+
+const SCHEMA_VERSION_KEY: &'static str = "schema_version";
+
+
+/// Open the backend matching the scheme of the configured connection URL
+///
+/// # Inputs
+/// - `mysql://...` opens a [`mysql::MysqlBackend`]
+/// - `postgres://...` (or `postgresql://...`) opens a [`postgres::PostgresBackend`]
+/// - `sqlite://...` (or `sqlite::memory:`) opens a [`sqlite::SqliteBackend`]
+/// - `mssql://...` (or `sqlserver://...`) opens a [`mssql::MssqlBackend`]
+pub fn open(config: &ConnectionConfig) -> Result<Box<dyn Backend>, Error> {
+	let url = config.url();
+	match url.split_once(':').map(|(scheme, _)| scheme) {
+		Some("mysql") => Ok(Box::new(mysql::MysqlBackend::new(config)?)),
+		Some("postgres") | Some("postgresql") => Ok(Box::new(postgres::PostgresBackend::new(config)?)),
+		Some("sqlite") => Ok(Box::new(sqlite::SqliteBackend::new(config)?)),
+		Some("mssql") | Some("sqlserver") => Ok(Box::new(mssql::MssqlBackend::new(config)?)),
+		_ => Err(Error::ClientError("unsupported database URL scheme (expected mysql://, postgres://, sqlite:// or mssql://)")),
+	}
+}
+
+
+/// Create the schema of a new volume, an existing schema is left untouched
+///
+/// # Inputs
+/// `force: bool` drops all tables first, which erases the whole volume
+pub fn init(backend: &dyn Backend, force: bool) -> Result<(), Error> {
+	if force {
+		backend.drop_schema()?;
+	}
+	backend.create_schema()
+}
+
+
+/// Fail unless the volume has exactly the schema version this build works with
+pub fn check_schema(backend: &dyn Backend) -> Result<(), Error> {
+	match backend.schema_version()? {
+		SCHEMA_VERSION => Ok(()),
+		version => Err(Error::SchemaVersionError(version)),
+	}
+}
+
+
+/// Upgrade the volume to [`SCHEMA_VERSION`], one migration at a time
+///
+/// # Outputs
+/// The schema version of the volume before the upgrade
+pub fn migrate(backend: &dyn Backend) -> Result<u32, Error> {
+	let previous = backend.schema_version()?;
+	if previous > SCHEMA_VERSION {
+		return Err(Error::SchemaVersionError(previous));
+	}
+	for version in previous + 1..=SCHEMA_VERSION {
+		backend.migrate(version)?;
+	}
+	Ok(previous)
+}
+
+
+/// Run `operation` in one transaction of `backend`, see [`finish`]
+pub fn transaction<T>(backend: &dyn Backend, operation: impl FnOnce(&dyn Backend) -> Result<T, Error>) -> Result<T, Error> {
+	let transaction = backend.begin()?;
+	let result = operation(transaction.as_ref());
+	finish(transaction.as_ref(), result)
+}
+
+
+/// End the transaction of a backend returned by [`Backend::begin`]: commit it if the operation
+/// succeeded and roll it back otherwise
+pub fn finish<T>(transaction: &dyn Backend, result: Result<T, Error>) -> Result<T, Error> {
+	match result {
+		Ok(value) => {
+			transaction.commit()?;
+			Ok(value)
+		},
+		Err(err) => {
+			// The error of the operation is more useful than a failed rollback (the database
+			// rolls the transaction back anyway once the connection is closed)
+			let _ = transaction.rollback();
+			Err(err)
+		},
+	}
+}
+
+
+/// Erase a volume and create its new root directory
+///
+/// # Outputs
+/// The inode id of the new root directory
+pub fn format(backend: &dyn Backend, volume: u64, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+	transaction(backend, |backend| {
+		backend.erase_volume(volume)?;
+		let root = backend.create_inode(volume, driver_objects::FileType::Directory, &driver_objects::FileSetAttr {
+			uid: 0,
+			gid: 0,
+			atime: superblock.created_at,
+			mtime: superblock.created_at,
+			ctime: superblock.created_at,
+			crtime: superblock.created_at,
+			perm: driver_objects::Permissions { special: 0, owner: 7, group: 5, other: 5 },
+		}, 0)?;
+		if backend.create_file(root, "/", root)? != 1 {
+			return Err(Error::RuntimeError("could not create the root directory"));
+		}
+		match backend.update_volume(volume, root, superblock)? {
+			1 => Ok(root),
+			_ => Err(Error::NotFoundError("the volume does not exist")),
+		}
+	})
+}
+
+
+/// Create and format a new volume
+pub fn create_volume(backend: &dyn Backend, name: &str, superblock: &driver_objects::Superblock) -> Result<driver_objects::Volume, Error> {
+	if name.is_empty() {
+		return Err(Error::ClientError("the volume name must not be empty"));
+	}
+	if name.chars().count() > MAX_NAME_LEN as usize {
+		return Err(Error::ClientError("the volume name is too long"));
+	}
+	if backend.get_volume(name)?.is_some() {
+		return Err(Error::ClientError("a volume with this name already exists"));
+	}
+
+	transaction(backend, |backend| {
+		let volume = backend.create_volume(name, superblock)?;
+		format(backend, volume, superblock)?;
+		backend.get_volume(name)?.ok_or(Error::RuntimeError("could not create the volume"))
+	})
+}
+
+
+/// Delete a volume together with all of its files
+pub fn delete_volume(backend: &dyn Backend, name: &str) -> Result<(), Error> {
+	let volume = backend.get_volume(name)?.ok_or(Error::NotFoundError("no volume with this name exists"))?;
+	transaction(backend, |backend| {
+		backend.erase_volume(volume.id)?;
+		match backend.delete_volume(volume.id)? {
+			1 => Ok(()),
+			_ => Err(Error::RuntimeError("could not delete the volume")),
+		}
+	})
+}
+
+
+/// Parse the `schema_version` row of the `metadata` table
+fn parse_schema_version(value: Option<&database_objects::MetadataValue>) -> Result<u32, Error> {
+	value
+		.ok_or(Error::RuntimeError("the metadata table does not contain the schema version"))?
+		.value.parse()
+		.map_err(|_| Error::RuntimeError("the schema version stored in the metadata table is not a number"))
+}
+
+
+/// Pick the script upgrading a volume to `version` from a dialect's list of migrations
+fn migration(scripts: &'static [&'static str], version: u32) -> Result<&'static str, Error> {
+	version.checked_sub(1)
+		.and_then(|index| scripts.get(index as usize))
+		.copied()
+		.ok_or(Error::ClientError("there is no migration to the requested schema version"))
+}
+
+
+/// Binds of the superblock columns of the `SQL_CREATE_VOLUME` and `SQL_UPDATE_VOLUME` statements of
+/// every dialect
+fn superblock_binds(superblock: &driver_objects::Superblock) -> Vec<DbInputType> {
+	vec![
+		superblock.block_size.into(),
+		superblock.uuid.as_str().into(),
+		superblock.label.as_str().into(),
+		Into::<chrono::DateTime<chrono::Utc>>::into(superblock.created_at).into(),
+		superblock.features.into(),
+	]
+}
+
+
+/// Binds of inode timestamps, the seconds and nanoseconds column of each time
+fn timestamp_binds(times: &[SystemTime]) -> Vec<DbInputType> {
+	times.iter().flat_map(|time| {
+		let time = driver_objects::Timestamp::from(*time);
+		[time.sec.into(), time.nsec.into()]
+	}).collect()
+}
+
+
+/// Block count and id of the last block of an inode
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BlockHead {
+	pub blocks: u64,
+	pub last_block_id: u64,
+}
+
+
+/// Data needed to merge a partial write into the existing blocks
+#[derive(Debug, PartialEq)]
+pub struct WriteInfo {
+	/// Number of blocks of the inode
+	pub blocks: u64,
+	/// Contents of the first written block (empty if not requested or non-existent)
+	pub start_block_data: Vec<u8>,
+	/// Contents of the last written block (empty if not requested or non-existent)
+	pub end_block_data: Vec<u8>,
+}
+
+
+/// Storage operations required by the [`super::TranslationLayer`]
+///
+/// Block indices passed as `offset_blocks`/`start_block`/`end_block` are zero-based positions
+/// within the file, while `block_id`s are the one-based ids stored in the database.
+///
+/// Methods returning `u64` for a modification return the number of affected rows.
+pub trait Backend: Send + Sync {
+	/// All users known to the filesystem
+	fn get_users(&self) -> Result<Vec<database_objects::User>, Error>;
+
+	/// All groups known to the filesystem
+	fn get_groups(&self) -> Result<Vec<database_objects::Group>, Error>;
+
+	fn insert_user(&self, id: u32, name: &str) -> Result<(), Error>;
+
+	fn insert_group(&self, id: u32, name: &str) -> Result<(), Error>;
+
+
+	/// Fetch an inode by its id (`None` if it does not exist)
+	fn get_inode(&self, inode: u64) -> Result<Option<database_objects::Inode>, Error>;
+
+	/// Create a new inode in a volume and return its id (`rdev` is the device number of a device)
+	fn create_inode(&self, volume: u64, kind: driver_objects::FileType, attr: &driver_objects::FileSetAttr, rdev: u32) -> Result<u64, Error>;
+
+	/// Overwrite owner, permissions and timestamps (except the creation time) of an inode
+	fn update_inode(&self, inode: u64, attr: &driver_objects::FileSetAttr) -> Result<u64, Error>;
+
+	/// Set the modification and change time of an inode
+	fn touch_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error>;
+
+	/// Set the change time of an inode
+	fn change_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error>;
+
+	/// Set the access time of an inode
+	fn access_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error>;
+
+	/// Delete an inode (its blocks are deleted with it)
+	fn delete_inode(&self, inode: u64) -> Result<u64, Error>;
+
+	/// Mark an inode without names as an orphan, to be deleted when it is no longer open
+	fn create_orphan(&self, inode: u64) -> Result<(), Error>;
+
+	/// Delete an inode if it is an orphan
+	fn delete_orphan(&self, inode: u64) -> Result<u64, Error>;
+
+	/// Ids of the orphaned inodes of a volume
+	fn list_orphans(&self, volume: u64) -> Result<Vec<u64>, Error>;
+
+	/// Number of files (names) referencing an inode
+	fn count_hardlinks(&self, inode: u64) -> Result<u64, Error>;
+
+	/// Number of directories directly inside a directory (excluding the directory itself)
+	fn count_subdirectories(&self, inode: u64) -> Result<u64, Error>;
+
+
+	/// Find the inode id of a file in a directory
+	fn lookup(&self, parent_inode: u64, name: &str) -> Result<Option<u64>, Error>;
+
+	/// List the entries of a directory (without "." and ".."), ordered by inode id
+	fn list_directory(&self, inode: u64) -> Result<Vec<database_objects::DirectoryEntry>, Error>;
+
+	/// Find the parent directory of a directory
+	fn get_parent(&self, inode: u64) -> Result<Option<u64>, Error>;
+
+	/// Number of entries in a directory (without "." and "..")
+	fn count_children(&self, inode: u64) -> Result<u64, Error>;
+
+	/// Create a new name for an inode inside a directory
+	fn create_file(&self, parent_inode: u64, name: &str, inode: u64) -> Result<u64, Error>;
+
+	/// Remove a name from a directory
+	fn delete_file(&self, parent_inode: u64, name: &str) -> Result<u64, Error>;
+
+	/// Move a name to another directory and/or change it
+	fn rename_file(&self, src_parent_inode: u64, src_name: &str, dest_parent_inode: u64, dest_name: &str) -> Result<u64, Error>;
+
+
+	/// Size of the file data (`None` if the inode has no blocks)
+	fn file_size(&self, inode: u64, block_size: u64) -> Result<Option<database_objects::FileSize>, Error>;
+
+	/// Block count and last block id of an inode
+	fn block_head(&self, inode: u64) -> Result<BlockHead, Error>;
+
+	/// Block count and the contents of the requested blocks
+	fn write_info(&self, inode: u64, start_block: Option<u64>, end_block: Option<u64>) -> Result<WriteInfo, Error>;
+
+	/// Read at most `max_blocks` blocks starting at the `offset_blocks`-th block
+	fn read_blocks(&self, inode: u64, offset_blocks: u64, max_blocks: u64) -> Result<Vec<Vec<u8>>, Error>;
+
+	/// Insert or overwrite consecutive blocks starting at `first_block_id`
+	fn write_blocks(&self, inode: u64, first_block_id: u64, data: Vec<Vec<u8>>) -> Result<(), Error>;
+
+	/// Insert or overwrite zero-filled blocks with ids from `first_block_id` up to (excluding) `until_block_id`
+	fn pad_blocks(&self, inode: u64, first_block_id: u64, until_block_id: u64, block_size: u64) -> Result<(), Error>;
+
+	/// Truncate or zero-pad the last block of an inode to `length` bytes
+	fn resize_last_block(&self, inode: u64, length: u64) -> Result<(), Error>;
+
+	/// Delete the last `count` blocks of an inode
+	fn trim_blocks(&self, inode: u64, count: u64) -> Result<(), Error>;
+
+	/// Delete all blocks of an inode
+	fn drop_blocks(&self, inode: u64) -> Result<(), Error>;
+
+
+	/// Value of an extended attribute (`None` if the inode does not have it)
+	fn get_xattr(&self, inode: u64, key: &str) -> Result<Option<Vec<u8>>, Error>;
+
+	/// Names of all extended attributes of an inode, ordered by name
+	fn list_xattrs(&self, inode: u64) -> Result<Vec<String>, Error>;
+
+	/// Add an extended attribute to an inode
+	fn create_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error>;
+
+	/// Overwrite the value of an extended attribute
+	fn update_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error>;
+
+	/// Remove an extended attribute from an inode
+	fn delete_xattr(&self, inode: u64, key: &str) -> Result<u64, Error>;
+
+
+	/// All volumes, ordered by id
+	fn list_volumes(&self) -> Result<Vec<driver_objects::Volume>, Error>;
+
+	/// Find a volume by its name
+	fn get_volume(&self, name: &str) -> Result<Option<driver_objects::Volume>, Error>;
+
+	/// Create a volume without a root directory and return its id (see [`create_volume`])
+	fn create_volume(&self, name: &str, superblock: &driver_objects::Superblock) -> Result<u64, Error>;
+
+	/// Set the root directory and overwrite the superblock of a volume
+	fn update_volume(&self, volume: u64, root_inode: u64, superblock: &driver_objects::Superblock) -> Result<u64, Error>;
+
+	/// Delete all inodes, files and blocks of a volume (the volume itself is kept)
+	fn erase_volume(&self, volume: u64) -> Result<(), Error>;
+
+	/// Delete an erased volume
+	fn delete_volume(&self, volume: u64) -> Result<u64, Error>;
+
+
+	/// Used inode and block counts of a volume
+	fn stat(&self, volume: u64) -> Result<driver_objects::FilesystemStat, Error>;
+
+	/// Schema version of the volume (0 for volumes created before the schema was versioned)
+	fn schema_version(&self) -> Result<u32, Error>;
+
+	/// Upgrade the schema from `version - 1` to `version`
+	fn migrate(&self, version: u32) -> Result<(), Error>;
+
+	/// Create all tables, their static data (file types, permissions, ...) and the default volume if
+	/// the schema does not exist yet
+	fn create_schema(&self) -> Result<(), Error>;
+
+	/// Drop all tables together with their data
+	fn drop_schema(&self) -> Result<(), Error>;
+
+
+	/// Start a transaction and return a backend running all operations inside it
+	///
+	/// Beginning a transaction on a backend returned by `begin` joins the running transaction, so
+	/// only the outermost [`Backend::commit`]/[`Backend::rollback`] ends it.
+	fn begin(&self) -> Result<Box<dyn Backend>, Error>;
+
+	/// Commit the transaction started by [`Backend::begin`]
+	fn commit(&self) -> Result<(), Error>;
+
+	/// Roll back the transaction started by [`Backend::begin`]
+	fn rollback(&self) -> Result<(), Error>;
+}
+
+
+#[cfg(test)]
+mod test {
+	use std::ffi::OsStr;
+	use super::*;
+	use super::memory::MemoryBackend;
+	use super::super::{commands, commands_postgres, commands_sqlite, commands_tsql, TranslationLayer, ROOT_INODE};
+	use super::super::test_util::{create, volume};
+
+
+	#[test]
+	fn migration_per_version() {
+		for scripts in [commands::SQL_MIGRATIONS, commands_postgres::SQL_MIGRATIONS, commands_sqlite::SQL_MIGRATIONS, commands_tsql::SQL_MIGRATIONS] {
+			assert_eq!(scripts.len(), SCHEMA_VERSION as usize);
+			assert!(migration(scripts, 0).is_err());
+			assert_eq!(migration(scripts, 1).unwrap(), scripts[0]);
+			assert!(migration(scripts, SCHEMA_VERSION + 1).is_err());
+		}
+	}
+
+	#[test]
+	fn schema_version_value() {
+		let value = |val: &str| database_objects::MetadataValue { value: String::from(val) };
+		assert_eq!(parse_schema_version(Some(&value("1"))).unwrap(), 1);
+		assert!(parse_schema_version(Some(&value("one"))).is_err());
+		assert!(parse_schema_version(None).is_err());
+	}
+
+	#[test]
+	fn check_schema_version() {
+		assert!(check_schema(&MemoryBackend::new()).is_ok());
+		assert!(matches!(check_schema(&MemoryBackend::with_schema_version(0)), Err(Error::SchemaVersionError(0))));
+		let newer = SCHEMA_VERSION + 1;
+		assert!(matches!(check_schema(&MemoryBackend::with_schema_version(newer)), Err(Error::SchemaVersionError(version)) if version == newer));
+	}
+
+	#[test]
+	fn migrate_schema() {
+		let backend = MemoryBackend::with_schema_version(0);
+		assert_eq!(migrate(&backend).unwrap(), 0);
+		assert_eq!(backend.schema_version().unwrap(), SCHEMA_VERSION);
+		assert!(check_schema(&backend).is_ok());
+		assert_eq!(migrate(&backend).unwrap(), SCHEMA_VERSION);
+
+		let newer = MemoryBackend::with_schema_version(SCHEMA_VERSION + 1);
+		assert!(matches!(migrate(&newer), Err(Error::SchemaVersionError(_))));
+	}
+
+	#[test]
+	fn init_keeps_or_erases_volume() {
+		let tl = volume();
+		let ino = create(&tl, 1, "kept.txt", driver_objects::FileType::File);
+		tl.write(ino, 0, b"data").unwrap();
+
+		init(tl.0.as_ref(), false).unwrap();
+		assert_eq!(tl.lookup_id(OsStr::new("kept.txt"), 1).unwrap(), ino);
+
+		init(tl.0.as_ref(), true).unwrap();
+		assert!(matches!(tl.lookup_id(OsStr::new("kept.txt"), 1), Err(Error::NotFoundError(_))));
+		assert_eq!(tl.readdir(1).unwrap().len(), 2);
+		assert!(check_schema(tl.0.as_ref()).is_ok());
+	}
+
+	#[test]
+	fn create_list_and_delete_volumes() {
+		let backend = MemoryBackend::new();
+		let superblock = super::super::new_superblock(4096, "").unwrap();
+		let volume = create_volume(&backend, "second", &superblock).unwrap();
+		assert_eq!(volume.name, "second");
+		assert_eq!(volume.superblock.uuid, superblock.uuid);
+		assert!(matches!(create_volume(&backend, "second", &superblock), Err(Error::ClientError(_))));
+		assert!(matches!(create_volume(&backend, "", &superblock), Err(Error::ClientError(_))));
+		assert!(matches!(create_volume(&backend, &"x".repeat(300), &superblock), Err(Error::ClientError(_))));
+		let names: Vec<String> = backend.list_volumes().unwrap().into_iter().map(|volume| volume.name).collect();
+		assert_eq!(names, vec![DEFAULT_VOLUME, "second"]);
+
+		let tl = TranslationLayer::with_backend(Box::new(backend.clone()), "second").unwrap();
+		create(&tl, ROOT_INODE, "test.txt", driver_objects::FileType::File);
+		delete_volume(&backend, "second").unwrap();
+		assert_eq!(backend.list_volumes().unwrap().len(), 1);
+		assert_eq!(backend.stat(volume.id).unwrap().used_inodes, 0);
+		assert_eq!(backend.stat(1).unwrap().used_inodes, 1);
+		assert!(matches!(delete_volume(&backend, "second"), Err(Error::NotFoundError(_))));
+		assert!(matches!(TranslationLayer::with_backend(Box::new(backend), "second"), Err(Error::NotFoundError(_))));
+	}
+}
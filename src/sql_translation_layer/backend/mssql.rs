@@ -0,0 +1,669 @@
+//! Microsoft SQL Server backend (schema in `sql/mssql/dbfs.sql`)
+//!
+//! The MSSQL driver can neither transfer binary data nor dates, so block data travels as hex
+//! strings split into chunks and timestamps as ISO 8601 strings (see [`commands_tsql`]).
+
+
+use std::time::SystemTime;
+
+use crate::config::ConnectionConfig;
+use crate::db_connector::{chrono, DbConnector, DbInputType, FromRow, Mssql};
+use super::super::{commands_tsql, database_objects, driver_objects, Error, DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE};
+use super::{migration, parse_schema_version, superblock_binds, timestamp_binds, Backend, BlockHead, WriteInfo, SCHEMA_VERSION_KEY};
+
+
+pub struct MssqlBackend(DbConnector<Mssql>);
+
+
+impl MssqlBackend {
+	pub fn new(config: &ConnectionConfig) -> Result<Self, Error> {
+		Ok(Self(DbConnector::new(config)?))
+	}
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct Inode {
+	id: i64,
+	owner: i64,
+	group: i64,
+	file_type: String,
+	special_bits: u8,
+	user_perm: u8,
+	group_perm: u8,
+	other_perm: u8,
+	rdev: i64,
+	atime: i64,
+	atime_nsec: i64,
+	mtime: i64,
+	mtime_nsec: i64,
+	ctime: i64,
+	ctime_nsec: i64,
+	crtime: i64,
+	crtime_nsec: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct Passwd {
+	id: i64,
+	name: String,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct DirectoryEntry {
+	name: String,
+	inode_id: i64,
+	file_type: String,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct RowId {
+	id: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct InodeLookup {
+	inode_id: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct DirectoryParent {
+	parent_inode_id: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct FileSize {
+	bytes: i64,
+	blocks: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct Volume {
+	id: i64,
+	name: String,
+	root_inode_id: Option<i64>,
+	block_size: i64,
+	uuid: String,
+	label: String,
+	created_at: String,
+	features: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct FileHead {
+	bc: i64,
+	last_block_id: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct BlockChunk {
+	block_id: i64,
+	chunk: i32,
+	data: String,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct XattrChunk {
+	chunk: i32,
+	data: String,
+}
+
+
+fn to_u64(value: i64) -> Result<u64, Error> {
+	value.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))
+}
+
+
+fn to_u32(value: i64) -> Result<u32, Error> {
+	value.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))
+}
+
+
+fn to_timestamp(value: &str) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+	chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+		.map(|time| time.and_utc())
+		.map_err(|_| Error::RuntimeError("could not parse database's timestamp"))
+}
+
+
+/// Decode a hex string as returned by `CONVERT(varchar, ..., 2)`
+fn hex_decode(hex: &str) -> Result<Vec<u8>, Error> {
+	if hex.len() % 2 != 0 {
+		return Err(Error::RuntimeError("could not decode database's hex data"));
+	}
+	(0..hex.len()).step_by(2)
+		.map(|i| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("-"), 16).map_err(|_| Error::RuntimeError("could not decode database's hex data")))
+		.collect()
+}
+
+
+/// Join the chunks of [`commands_tsql::SQL_READ_FILE`] (ordered by block and chunk) into blocks
+fn assemble_blocks(chunks: Vec<BlockChunk>) -> Result<Vec<Vec<u8>>, Error> {
+	let mut blocks: Vec<(i64, Vec<u8>)> = Vec::new();
+	for chunk in chunks {
+		let data = hex_decode(&chunk.data)?;
+		match blocks.last_mut() {
+			Some((block_id, block)) if *block_id == chunk.block_id && chunk.chunk != 0 => block.extend(data),
+			_ => blocks.push((chunk.block_id, data)),
+		}
+	}
+	Ok(blocks.into_iter().map(|(_, block)| block).collect())
+}
+
+
+/// Binds of the `sql_create_xattr`/`sql_update_xattr` statements (the value split into hex chunks)
+fn xattr_binds(inode: u64, key: &str, value: &[u8]) -> Vec<DbInputType> {
+	let mut args: Vec<DbInputType> = vec![inode.into(), key.into()];
+	match value.len() {
+		0 => args.push(Vec::new().into()),
+		_ => args.extend(value.chunks(commands_tsql::WRITE_CHUNK_SIZE).map(|chunk| chunk.to_vec().into())),
+	}
+	args
+}
+
+
+impl TryFrom<Inode> for database_objects::Inode {
+	type Error = Error;
+	fn try_from(value: Inode) -> Result<Self, Self::Error> {
+		Ok(Self {
+			id: to_u32(value.id)?,
+			owner: to_u32(value.owner)?,
+			group: to_u32(value.group)?,
+			file_type: value.file_type,
+			special_bits: value.special_bits,
+			user_perm: value.user_perm,
+			group_perm: value.group_perm,
+			other_perm: value.other_perm,
+			rdev: to_u32(value.rdev)?,
+			atime: value.atime,
+			atime_nsec: to_u32(value.atime_nsec)?,
+			mtime: value.mtime,
+			mtime_nsec: to_u32(value.mtime_nsec)?,
+			ctime: value.ctime,
+			ctime_nsec: to_u32(value.ctime_nsec)?,
+			crtime: value.crtime,
+			crtime_nsec: to_u32(value.crtime_nsec)?,
+		})
+	}
+}
+
+
+impl MssqlBackend {
+	fn read_block(&self, inode: u64, offset_block: u64) -> Result<Vec<u8>, Error> {
+		Ok(self.read_blocks(inode, offset_block, 1)?.pop().unwrap_or_default())
+	}
+}
+
+
+impl TryFrom<Volume> for driver_objects::Volume {
+	type Error = Error;
+	fn try_from(value: Volume) -> Result<Self, Self::Error> {
+		Ok(Self {
+			id: to_u64(value.id)?,
+			name: value.name,
+			root_inode: value.root_inode_id.map(to_u64).transpose()?,
+			superblock: driver_objects::Superblock {
+				block_size: to_u32(value.block_size)?,
+				uuid: value.uuid,
+				label: value.label,
+				created_at: to_timestamp(&value.created_at)?.into(),
+				// Stored as `bigint`, the flags are the bit pattern of the signed value
+				features: value.features as u64,
+			},
+		})
+	}
+}
+
+
+impl Backend for MssqlBackend {
+	fn get_users(&self) -> Result<Vec<database_objects::User>, Error> {
+		let users: Vec<Passwd> = self.0.query(commands_tsql::SQL_GET_USERS, None)?;
+		users.into_iter().map(|user| Ok(database_objects::User { id: to_u32(user.id)?, name: user.name })).collect()
+	}
+
+
+	fn get_groups(&self) -> Result<Vec<database_objects::Group>, Error> {
+		let groups: Vec<Passwd> = self.0.query(commands_tsql::SQL_GET_GROUPS, None)?;
+		groups.into_iter().map(|group| Ok(database_objects::Group { id: to_u32(group.id)?, name: group.name })).collect()
+	}
+
+
+	fn insert_user(&self, id: u32, name: &str) -> Result<(), Error> {
+		self.0.command(commands_tsql::SQL_INSERT_USER, Some(&vec![id.into(), name.into()]))?;
+		Ok(())
+	}
+
+
+	fn insert_group(&self, id: u32, name: &str) -> Result<(), Error> {
+		self.0.command(commands_tsql::SQL_INSERT_GROUP, Some(&vec![id.into(), name.into()]))?;
+		Ok(())
+	}
+
+
+	fn get_inode(&self, inode: u64) -> Result<Option<database_objects::Inode>, Error> {
+		let mut inodes: Vec<Inode> = self.0.query(commands_tsql::SQL_GET_INODE, Some(&vec![inode.into()]))?;
+		Ok(match inodes.len() {
+			0 => None,
+			_ => Some(inodes.swap_remove(0).try_into()?),
+		})
+	}
+
+
+	fn create_inode(&self, volume: u64, kind: driver_objects::FileType, attr: &driver_objects::FileSetAttr, rdev: u32) -> Result<u64, Error> {
+		let mut args: Vec<DbInputType> = vec![
+			volume.into(),
+			attr.uid.into(),
+			attr.gid.into(),
+			Into::<String>::into(kind).into(),
+			attr.perm.special.into(),
+			attr.perm.owner.into(),
+			attr.perm.group.into(),
+			attr.perm.other.into(),
+			rdev.into(),
+		];
+		args.extend(timestamp_binds(&[attr.atime, attr.mtime, attr.ctime, attr.crtime]));
+		let inode: Vec<RowId> = self.0.query(commands_tsql::SQL_CREATE_INODE, Some(&args))?;
+		to_u64(inode.get(0).ok_or(Error::RuntimeError("could not create inode"))?.id)
+	}
+
+
+	fn update_inode(&self, inode: u64, attr: &driver_objects::FileSetAttr) -> Result<u64, Error> {
+		let mut args: Vec<DbInputType> = vec![
+			attr.uid.into(),
+			attr.gid.into(),
+			attr.perm.special.into(),
+			attr.perm.owner.into(),
+			attr.perm.group.into(),
+			attr.perm.other.into(),
+		];
+		args.extend(timestamp_binds(&[attr.atime, attr.mtime, attr.ctime]));
+		args.push(inode.into());
+		Ok(self.0.command(commands_tsql::SQL_UPDATE_INODE, Some(&args))?.rows_affected)
+	}
+
+
+	fn touch_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time, time]);
+		args.push(inode.into());
+		self.0.command(commands_tsql::SQL_UPDATE_INODE_CTIME_MTIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn change_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time]);
+		args.push(inode.into());
+		self.0.command(commands_tsql::SQL_UPDATE_INODE_CTIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn access_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time]);
+		args.push(inode.into());
+		self.0.command(commands_tsql::SQL_UPDATE_INODE_ATIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn delete_inode(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands_tsql::SQL_DELETE_INODE, Some(&vec![inode.into()]))?.rows_affected)
+	}
+
+
+	fn create_orphan(&self, inode: u64) -> Result<(), Error> {
+		self.0.command(commands_tsql::SQL_CREATE_ORPHAN, Some(&vec![inode.into()]))?;
+		Ok(())
+	}
+
+
+	fn delete_orphan(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands_tsql::SQL_DELETE_ORPHAN, Some(&vec![inode.into()]))?.rows_affected)
+	}
+
+
+	fn list_orphans(&self, volume: u64) -> Result<Vec<u64>, Error> {
+		let orphans: Vec<InodeLookup> = self.0.query(commands_tsql::SQL_LIST_ORPHANS, Some(&vec![volume.into()]))?;
+		orphans.into_iter().map(|val| to_u64(val.inode_id)).collect()
+	}
+
+
+	fn count_hardlinks(&self, inode: u64) -> Result<u64, Error> {
+		let hardlinks: Vec<database_objects::FileHardlinks> = self.0.query(commands_tsql::SQL_COUNT_HARDLINKS, Some(&vec![inode.into()]))?;
+		to_u64(hardlinks.get(0).ok_or(Error::RuntimeError("could not count hardlinks"))?.hardlinks)
+	}
+
+
+	fn count_subdirectories(&self, inode: u64) -> Result<u64, Error> {
+		let subdirs: Vec<database_objects::DirectoryChildrenDirectory> = self.0.query(commands_tsql::SQL_COUNT_CHILDREN_OF_TYPE_DIRECTORY, Some(&vec![inode.into()]))?;
+		to_u64(subdirs.get(0).ok_or(Error::RuntimeError("could not count subdirectories"))?.children_dirs)
+	}
+
+
+	fn lookup(&self, parent_inode: u64, name: &str) -> Result<Option<u64>, Error> {
+		let inode: Vec<InodeLookup> = self.0.query(commands_tsql::SQL_LOOKUP_INODE_ID, Some(&vec![name.into(), parent_inode.into()]))?;
+		inode.get(0).map(|val| to_u64(val.inode_id)).transpose()
+	}
+
+
+	fn list_directory(&self, inode: u64) -> Result<Vec<database_objects::DirectoryEntry>, Error> {
+		let entries: Vec<DirectoryEntry> = self.0.query(commands_tsql::SQL_LIST_DIRECTORY, Some(&vec![inode.into()]))?;
+		entries.into_iter().map(|entry| Ok(database_objects::DirectoryEntry {
+			name: entry.name,
+			inode_id: to_u32(entry.inode_id)?,
+			file_type: entry.file_type,
+		})).collect()
+	}
+
+
+	fn get_parent(&self, inode: u64) -> Result<Option<u64>, Error> {
+		let parent: Vec<DirectoryParent> = self.0.query(commands_tsql::SQL_GET_DIRECTORY_PARENT, Some(&vec![inode.into()]))?;
+		parent.get(0).map(|val| to_u64(val.parent_inode_id)).transpose()
+	}
+
+
+	fn count_children(&self, inode: u64) -> Result<u64, Error> {
+		let count: Vec<database_objects::ChildrenCount> = self.0.query(commands_tsql::SQL_COUNT_DIRECTORY_CHILDREN, Some(&vec![inode.into()]))?;
+		to_u64(count.get(0).ok_or(Error::RuntimeError("could not determine children count"))?.children)
+	}
+
+
+	fn create_file(&self, parent_inode: u64, name: &str, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands_tsql::SQL_CREATE_FILE, Some(&vec![parent_inode.into(), name.into(), inode.into()]))?.rows_affected)
+	}
+
+
+	fn delete_file(&self, parent_inode: u64, name: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands_tsql::SQL_DELETE_FILE, Some(&vec![name.into(), parent_inode.into()]))?.rows_affected)
+	}
+
+
+	fn rename_file(&self, src_parent_inode: u64, src_name: &str, dest_parent_inode: u64, dest_name: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands_tsql::SQL_RENAME_FILE, Some(&vec![
+			dest_parent_inode.into(),
+			dest_name.into(),
+			src_parent_inode.into(),
+			src_name.into()
+		]))?.rows_affected)
+	}
+
+
+	fn file_size(&self, inode: u64, block_size: u64) -> Result<Option<database_objects::FileSize>, Error> {
+		let size: Vec<FileSize> = self.0.query(commands_tsql::SQL_GET_FILE_SIZE, Some(&vec![inode.into(), block_size.into()]))?;
+		Ok(match size.get(0) {
+			Some(size) => Some(database_objects::FileSize { bytes: to_u64(size.bytes)?, blocks: to_u64(size.blocks)? }),
+			None => None,
+		})
+	}
+
+
+	fn block_head(&self, inode: u64) -> Result<BlockHead, Error> {
+		let head: Vec<FileHead> = self.0.query(commands_tsql::SQL_GET_FILE_HEAD, Some(&vec![inode.into()]))?;
+		let head = head.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
+		Ok(BlockHead {
+			blocks: to_u64(head.bc)?,
+			last_block_id: to_u64(head.last_block_id)?,
+		})
+	}
+
+
+	fn write_info(&self, inode: u64, start_block: Option<u64>, end_block: Option<u64>) -> Result<WriteInfo, Error> {
+		// Block data can not be selected next to the block count, so it is read separately
+		let result: Vec<database_objects::FileWriteInfoSizeOnly> = self.0.query(commands_tsql::SQL_GET_SIZE_ONLY, Some(&vec![inode.into()]))?;
+		let result = result.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
+		Ok(WriteInfo {
+			blocks: to_u64(result.blocks)?,
+			start_block_data: match start_block { Some(block) => self.read_block(inode, block)?, None => Vec::new() },
+			end_block_data: match end_block { Some(block) => self.read_block(inode, block)?, None => Vec::new() },
+		})
+	}
+
+
+	fn read_blocks(&self, inode: u64, offset_blocks: u64, max_blocks: u64) -> Result<Vec<Vec<u8>>, Error> {
+		let chunks: Vec<BlockChunk> = self.0.query(commands_tsql::SQL_READ_FILE, Some(&vec![inode.into(), max_blocks.into(), offset_blocks.into()]))?;
+		assemble_blocks(chunks)
+	}
+
+
+	fn write_blocks(&self, inode: u64, first_block_id: u64, data: Vec<Vec<u8>>) -> Result<(), Error> {
+		let mut block_id = first_block_id;
+		let mut chunks: Vec<usize> = Vec::new();
+		let mut args: Vec<DbInputType> = Vec::new();
+		for block in data {
+			let block_chunks: Vec<DbInputType> = match block.len() {
+				0 => vec![Vec::new().into()],
+				_ => block.chunks(commands_tsql::WRITE_CHUNK_SIZE).map(|chunk| chunk.to_vec().into()).collect(),
+			};
+			if args.len() + block_chunks.len() > commands_tsql::MAX_BINDS {
+				self.0.command(commands_tsql::dynamic_queries::sql_write(inode, block_id, &chunks).as_str(), Some(&args))?;
+				block_id += chunks.len() as u64;
+				chunks.clear();
+				args.clear();
+			}
+			chunks.push(block_chunks.len());
+			args.extend(block_chunks);
+		}
+		if chunks.len() > 0 {
+			self.0.command(commands_tsql::dynamic_queries::sql_write(inode, block_id, &chunks).as_str(), Some(&args))?;
+		}
+		Ok(())
+	}
+
+
+	fn pad_blocks(&self, inode: u64, first_block_id: u64, until_block_id: u64, block_size: u64) -> Result<(), Error> {
+		if first_block_id >= until_block_id { return Ok(()); }
+		let command = commands_tsql::dynamic_queries::sql_pad_until(inode, first_block_id, until_block_id, block_size);
+		self.0.command(command.as_str(), None)?;
+		Ok(())
+	}
+
+
+	fn resize_last_block(&self, inode: u64, length: u64) -> Result<(), Error> {
+		self.0.command(commands_tsql::SQL_RESIZE_LAST_BLOCK, Some(&vec![inode.into(), length.into()]))?;
+		Ok(())
+	}
+
+
+	fn trim_blocks(&self, inode: u64, count: u64) -> Result<(), Error> {
+		self.0.command(commands_tsql::SQL_TRIM_BLOCKS, Some(&vec![inode.into(), count.into()]))?;
+		Ok(())
+	}
+
+
+	fn drop_blocks(&self, inode: u64) -> Result<(), Error> {
+		self.0.command(commands_tsql::SQL_DROP_BLOCKS, Some(&vec![inode.into()]))?;
+		Ok(())
+	}
+
+
+	fn get_xattr(&self, inode: u64, key: &str) -> Result<Option<Vec<u8>>, Error> {
+		let chunks: Vec<XattrChunk> = self.0.query(commands_tsql::SQL_GET_XATTR, Some(&vec![inode.into(), key.into()]))?;
+		if chunks.is_empty() {
+			return Ok(None);
+		}
+		let mut value = Vec::new();
+		for chunk in chunks {
+			value.extend(hex_decode(&chunk.data)?);
+		}
+		Ok(Some(value))
+	}
+
+
+	fn list_xattrs(&self, inode: u64) -> Result<Vec<String>, Error> {
+		let keys: Vec<database_objects::XattrKey> = self.0.query(commands_tsql::SQL_LIST_XATTRS, Some(&vec![inode.into()]))?;
+		Ok(keys.into_iter().map(|val| val.key).collect())
+	}
+
+
+	fn create_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		let args = xattr_binds(inode, key, value);
+		Ok(self.0.command(commands_tsql::dynamic_queries::sql_create_xattr(args.len() - 2).as_str(), Some(&args))?.rows_affected)
+	}
+
+
+	fn update_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		let args = xattr_binds(inode, key, value);
+		Ok(self.0.command(commands_tsql::dynamic_queries::sql_update_xattr(args.len() - 2).as_str(), Some(&args))?.rows_affected)
+	}
+
+
+	fn delete_xattr(&self, inode: u64, key: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands_tsql::SQL_DELETE_XATTR, Some(&vec![inode.into(), key.into()]))?.rows_affected)
+	}
+
+
+	fn list_volumes(&self) -> Result<Vec<driver_objects::Volume>, Error> {
+		let volumes: Vec<Volume> = self.0.query(commands_tsql::SQL_LIST_VOLUMES, None)?;
+		volumes.into_iter().map(|val| val.try_into()).collect()
+	}
+
+
+	fn get_volume(&self, name: &str) -> Result<Option<driver_objects::Volume>, Error> {
+		let mut volume: Vec<Volume> = self.0.query(commands_tsql::SQL_GET_VOLUME, Some(&vec![name.into()]))?;
+		volume.pop().map(|val| val.try_into()).transpose()
+	}
+
+
+	fn create_volume(&self, name: &str, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut binds = vec![name.into()];
+		binds.append(&mut superblock_binds(superblock));
+		let volume: Vec<RowId> = self.0.query(commands_tsql::SQL_CREATE_VOLUME, Some(&binds))?;
+		to_u64(volume.get(0).ok_or(Error::RuntimeError("could not create volume"))?.id)
+	}
+
+
+	fn update_volume(&self, volume: u64, root_inode: u64, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut binds = vec![root_inode.into()];
+		binds.append(&mut superblock_binds(superblock));
+		binds.push(volume.into());
+		Ok(self.0.command(commands_tsql::SQL_UPDATE_VOLUME, Some(&binds))?.rows_affected)
+	}
+
+
+	fn erase_volume(&self, volume: u64) -> Result<(), Error> {
+		for cmd in commands_tsql::SQL_ERASE_VOLUME_SEQUENCE {
+			self.0.command(cmd, Some(&vec![volume.into()]))?;
+		}
+		Ok(())
+	}
+
+
+	fn delete_volume(&self, volume: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands_tsql::SQL_DELETE_VOLUME, Some(&vec![volume.into()]))?.rows_affected)
+	}
+
+
+	fn stat(&self, volume: u64) -> Result<driver_objects::FilesystemStat, Error> {
+		let stat: Vec<database_objects::FilesystemStat> = self.0.query(commands_tsql::SQL_GET_FS_STAT, Some(&vec![volume.into()]))?;
+		let stat = stat.get(0).ok_or(Error::RuntimeError("could not determine fs stat"))?;
+		Ok(driver_objects::FilesystemStat {
+			used_blocks: to_u64(stat.used_blocks)?,
+			used_inodes: to_u64(stat.used_inodes)?,
+		})
+	}
+
+
+	fn schema_version(&self) -> Result<u32, Error> {
+		let exists: Vec<database_objects::TableCount> = self.0.query(commands_tsql::SQL_METADATA_EXISTS, None)?;
+		if exists.get(0).map(|val| val.tables).unwrap_or(0) == 0 {
+			return Ok(0);
+		}
+		let version: Vec<database_objects::MetadataValue> = self.0.query(commands_tsql::SQL_GET_METADATA, Some(&vec![SCHEMA_VERSION_KEY.into()]))?;
+		parse_schema_version(version.get(0))
+	}
+
+
+	fn migrate(&self, version: u32) -> Result<(), Error> {
+		Ok(self.0.script(migration(commands_tsql::SQL_MIGRATIONS, version)?)?)
+	}
+
+
+	fn create_schema(&self) -> Result<(), Error> {
+		let exists: Vec<database_objects::TableCount> = self.0.query(commands_tsql::SQL_SCHEMA_EXISTS, None)?;
+		if exists.get(0).map(|val| val.tables).unwrap_or(0) == 0 {
+			self.0.script(commands_tsql::SQL_SCHEMA)?;
+		}
+		Ok(())
+	}
+
+
+	fn drop_schema(&self) -> Result<(), Error> {
+		Ok(self.0.script(commands_tsql::SQL_DROP_SCHEMA)?)
+	}
+
+	fn begin(&self) -> Result<Box<dyn Backend>, Error> {
+		Ok(Box::new(Self(self.0.begin()?)))
+	}
+
+
+	fn commit(&self) -> Result<(), Error> {
+		Ok(self.0.commit()?)
+	}
+
+
+	fn rollback(&self) -> Result<(), Error> {
+		Ok(self.0.rollback()?)
+	}
+}
+
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::db_connector::hex_encode;
+
+
+	#[test]
+	fn hex_roundtrip() {
+		let data: Vec<u8> = (0..=255).collect();
+		assert_eq!(hex_decode(&hex_encode(&data)).unwrap(), data);
+		assert_eq!(hex_decode("00FFa0").unwrap(), vec![0x00, 0xff, 0xa0]);
+		assert!(hex_decode("0").is_err());
+		assert!(hex_decode("0G").is_err());
+	}
+
+
+	#[test]
+	fn assemble_chunks() {
+		let chunk = |block_id, chunk, data: &str| BlockChunk { block_id, chunk, data: String::from(data) };
+		let blocks = assemble_blocks(vec![
+			chunk(1, 0, "0102"),
+			chunk(1, 1, "03"),
+			chunk(2, 0, ""),
+			chunk(3, 0, "FF"),
+		]).unwrap();
+		assert_eq!(blocks, vec![vec![1, 2, 3], vec![], vec![0xff]]);
+	}
+
+
+	#[test]
+	fn write_query_binds() {
+		let query = commands_tsql::dynamic_queries::sql_write(7, 3, &[2, 1]);
+		assert!(query.contains("(7, 3, CONVERT(varbinary(max), @p1, 2) + CONVERT(varbinary(max), @p2, 2))"));
+		assert!(query.contains("(7, 4, CONVERT(varbinary(max), @p3, 2))"));
+		assert!(!query.contains("@p4"));
+	}
+
+	#[test]
+	fn xattr_binds_are_chunked() {
+		let value = vec![0xab; commands_tsql::WRITE_CHUNK_SIZE + 1];
+		let args = xattr_binds(5, "user.test", &value);
+		assert_eq!(args.len(), 4);
+		let query = commands_tsql::dynamic_queries::sql_create_xattr(args.len() - 2);
+		assert!(query.ends_with("VALUES (@p1, @p2, CONVERT(varbinary(max), @p3, 2) + CONVERT(varbinary(max), @p4, 2))"));
+
+		// An empty value still needs one bind
+		assert_eq!(xattr_binds(5, "user.test", &[]).len(), 3);
+		assert!(commands_tsql::dynamic_queries::sql_update_xattr(1).contains("SET [value] = CONVERT(varbinary(max), @p3, 2) WHERE"));
+	}
+}
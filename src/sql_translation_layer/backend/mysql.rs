@@ -0,0 +1,393 @@
+//! MySQL/MariaDB backend (schema in `sql/mysql/dbfs.sql`)
+
+
+use std::time::SystemTime;
+
+use crate::config::ConnectionConfig;
+use crate::db_connector::{DbConnector, DbInputType};
+use super::super::{commands, database_objects, driver_objects, Error, DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE};
+use super::{migration, parse_schema_version, superblock_binds, timestamp_binds, Backend, BlockHead, WriteInfo, SCHEMA_VERSION_KEY};
+
+
+pub struct MysqlBackend(DbConnector);
+
+
+impl MysqlBackend {
+	pub fn new(config: &ConnectionConfig) -> Result<Self, Error> {
+		Ok(Self(DbConnector::new(config)?))
+	}
+}
+
+
+fn to_u64(value: i64) -> Result<u64, Error> {
+	value.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))
+}
+
+
+impl Backend for MysqlBackend {
+	fn get_users(&self) -> Result<Vec<database_objects::User>, Error> {
+		Ok(self.0.query(commands::SQL_GET_USERS, None)?)
+	}
+
+
+	fn get_groups(&self) -> Result<Vec<database_objects::Group>, Error> {
+		Ok(self.0.query(commands::SQL_GET_GROUPS, None)?)
+	}
+
+
+	fn insert_user(&self, id: u32, name: &str) -> Result<(), Error> {
+		self.0.command(commands::SQL_INSERT_USER, Some(&vec![id.into(), name.into()]))?;
+		Ok(())
+	}
+
+
+	fn insert_group(&self, id: u32, name: &str) -> Result<(), Error> {
+		self.0.command(commands::SQL_INSERT_GROUP, Some(&vec![id.into(), name.into()]))?;
+		Ok(())
+	}
+
+
+	fn get_inode(&self, inode: u64) -> Result<Option<database_objects::Inode>, Error> {
+		let mut inodes: Vec<database_objects::Inode> = self.0.query(commands::SQL_GET_INODE, Some(&vec![inode.into()]))?;
+		Ok(match inodes.len() {
+			0 => None,
+			_ => Some(inodes.swap_remove(0)),
+		})
+	}
+
+
+	fn create_inode(&self, volume: u64, kind: driver_objects::FileType, attr: &driver_objects::FileSetAttr, rdev: u32) -> Result<u64, Error> {
+		let mut args: Vec<DbInputType> = vec![
+			volume.into(),
+			attr.uid.into(),
+			attr.gid.into(),
+			Into::<String>::into(kind).into(),
+			attr.perm.special.into(),
+			attr.perm.owner.into(),
+			attr.perm.group.into(),
+			attr.perm.other.into(),
+			rdev.into(),
+		];
+		args.extend(timestamp_binds(&[attr.atime, attr.mtime, attr.ctime, attr.crtime]));
+		Ok(self.0.command(commands::SQL_CREATE_INODE, Some(&args))?.last_insert_id)
+	}
+
+
+	fn update_inode(&self, inode: u64, attr: &driver_objects::FileSetAttr) -> Result<u64, Error> {
+		let mut args: Vec<DbInputType> = vec![
+			attr.uid.into(),
+			attr.gid.into(),
+			attr.perm.special.into(),
+			attr.perm.owner.into(),
+			attr.perm.group.into(),
+			attr.perm.other.into(),
+		];
+		args.extend(timestamp_binds(&[attr.atime, attr.mtime, attr.ctime]));
+		args.push(inode.into());
+		Ok(self.0.command(commands::SQL_UPDATE_INODE, Some(&args))?.rows_affected)
+	}
+
+
+	fn touch_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time, time]);
+		args.push(inode.into());
+		self.0.command(commands::SQL_UPDATE_INODE_CTIME_MTIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn change_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time]);
+		args.push(inode.into());
+		self.0.command(commands::SQL_UPDATE_INODE_CTIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn access_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time]);
+		args.push(inode.into());
+		self.0.command(commands::SQL_UPDATE_INODE_ATIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn delete_inode(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_INODE, Some(&vec![inode.into()]))?.rows_affected)
+	}
+
+
+	fn create_orphan(&self, inode: u64) -> Result<(), Error> {
+		self.0.command(commands::SQL_CREATE_ORPHAN, Some(&vec![inode.into()]))?;
+		Ok(())
+	}
+
+
+	fn delete_orphan(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_ORPHAN, Some(&vec![inode.into()]))?.rows_affected)
+	}
+
+
+	fn list_orphans(&self, volume: u64) -> Result<Vec<u64>, Error> {
+		let orphans: Vec<database_objects::InodeLookup> = self.0.query(commands::SQL_LIST_ORPHANS, Some(&vec![volume.into()]))?;
+		Ok(orphans.into_iter().map(|val| val.inode_id.into()).collect())
+	}
+
+
+	fn count_hardlinks(&self, inode: u64) -> Result<u64, Error> {
+		let hardlinks: Vec<database_objects::FileHardlinks> = self.0.query(commands::SQL_COUNT_HARDLINKS, Some(&vec![inode.into()]))?;
+		to_u64(hardlinks.get(0).ok_or(Error::RuntimeError("could not count hardlinks"))?.hardlinks)
+	}
+
+
+	fn count_subdirectories(&self, inode: u64) -> Result<u64, Error> {
+		let subdirs: Vec<database_objects::DirectoryChildrenDirectory> = self.0.query(commands::SQL_COUNT_CHILDREN_OF_TYPE_DIRECTORY, Some(&vec![inode.into()]))?;
+		to_u64(subdirs.get(0).ok_or(Error::RuntimeError("could not count subdirectories"))?.children_dirs)
+	}
+
+
+	fn lookup(&self, parent_inode: u64, name: &str) -> Result<Option<u64>, Error> {
+		let inode: Vec<database_objects::InodeLookup> = self.0.query(commands::SQL_LOOKUP_INODE_ID, Some(&vec![name.into(), parent_inode.into()]))?;
+		Ok(inode.get(0).map(|val| val.inode_id.into()))
+	}
+
+
+	fn list_directory(&self, inode: u64) -> Result<Vec<database_objects::DirectoryEntry>, Error> {
+		Ok(self.0.query(commands::SQL_LIST_DIRECTORY, Some(&vec![inode.into()]))?)
+	}
+
+
+	fn get_parent(&self, inode: u64) -> Result<Option<u64>, Error> {
+		let parent: Vec<database_objects::DirectoryParent> = self.0.query(commands::SQL_GET_DIRECTORY_PARENT, Some(&vec![inode.into()]))?;
+		Ok(parent.get(0).map(|val| val.parent_inode_id.into()))
+	}
+
+
+	fn count_children(&self, inode: u64) -> Result<u64, Error> {
+		let count: Vec<database_objects::ChildrenCount> = self.0.query(commands::SQL_COUNT_DIRECTORY_CHILDREN, Some(&vec![inode.into()]))?;
+		to_u64(count.get(0).ok_or(Error::RuntimeError("could not determine children count"))?.children)
+	}
+
+
+	fn create_file(&self, parent_inode: u64, name: &str, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_CREATE_FILE, Some(&vec![parent_inode.into(), name.into(), inode.into()]))?.rows_affected)
+	}
+
+
+	fn delete_file(&self, parent_inode: u64, name: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_FILE, Some(&vec![name.into(), parent_inode.into()]))?.rows_affected)
+	}
+
+
+	fn rename_file(&self, src_parent_inode: u64, src_name: &str, dest_parent_inode: u64, dest_name: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_RENAME_FILE, Some(&vec![
+			dest_parent_inode.into(),
+			dest_name.into(),
+			src_parent_inode.into(),
+			src_name.into()
+		]))?.rows_affected)
+	}
+
+
+	fn file_size(&self, inode: u64, block_size: u64) -> Result<Option<database_objects::FileSize>, Error> {
+		let size: Vec<database_objects::FileSize> = self.0.query(commands::SQL_GET_FILE_SIZE, Some(&vec![block_size.into(), inode.into()]))?;
+		Ok(size.get(0).copied())
+	}
+
+
+	fn block_head(&self, inode: u64) -> Result<BlockHead, Error> {
+		let head: Vec<database_objects::FileHead> = self.0.query(commands::SQL_GET_FILE_HEAD, Some(&vec![inode.into()]))?;
+		let head = head.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
+		Ok(BlockHead {
+			blocks: to_u64(head.bc)?,
+			last_block_id: head.last_block_id.into(),
+		})
+	}
+
+
+	fn write_info(&self, inode: u64, start_block: Option<u64>, end_block: Option<u64>) -> Result<WriteInfo, Error> {
+		match (start_block, end_block) {
+			(None, None) => {
+				let result: Vec<database_objects::FileWriteInfoSizeOnly> = self.0.query(commands::SQL_GET_SIZE_ONLY, Some(&vec![inode.into()]))?;
+				let result = result.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
+				Ok(WriteInfo { blocks: to_u64(result.blocks)?, start_block_data: Vec::new(), end_block_data: Vec::new() })
+			},
+			(Some(block), None) | (None, Some(block)) => {
+				let mut result: Vec<database_objects::FileWriteInfoSingleBlock> = self.0.query(commands::SQL_GET_SIZE_AND_SINGLE_BLOCK_DATA, Some(&vec![inode.into(), block.into()]))?;
+				if result.len() == 0 { return Err(Error::NotFoundError("could not get filesize and block")); }
+				let result = result.swap_remove(0);
+				let blocks = to_u64(result.blocks)?;
+				Ok(match start_block {
+					Some(_) => WriteInfo { blocks, start_block_data: result.block_data, end_block_data: Vec::new() },
+					None => WriteInfo { blocks, start_block_data: Vec::new(), end_block_data: result.block_data },
+				})
+			},
+			(Some(start_block), Some(end_block)) => {
+				let mut result: Vec<database_objects::FileWriteInfo> = self.0.query(commands::SQL_GET_SIZE_AND_BLOCK_DATA, Some(&vec![inode.into(), start_block.into(), end_block.into()]))?;
+				if result.len() == 0 { return Err(Error::NotFoundError("could not get filesize and block")); }
+				let result = result.swap_remove(0);
+				Ok(WriteInfo { blocks: to_u64(result.blocks)?, start_block_data: result.start_block_data, end_block_data: result.end_block_data })
+			}
+		}
+	}
+
+
+	fn read_blocks(&self, inode: u64, offset_blocks: u64, max_blocks: u64) -> Result<Vec<Vec<u8>>, Error> {
+		let blocks: Vec<database_objects::BlockData> = self.0.query(commands::SQL_READ_FILE, Some(&vec![inode.into(), max_blocks.into(), offset_blocks.into()]))?;
+		Ok(blocks.into_iter().map(|block| block.data).collect())
+	}
+
+
+	fn write_blocks(&self, inode: u64, first_block_id: u64, data: Vec<Vec<u8>>) -> Result<(), Error> {
+		if data.len() == 0 { return Ok(()); }
+		let command = commands::dynamic_queries::sql_write(inode, first_block_id, first_block_id + data.len() as u64 - 1);
+		self.0.command(command.as_str(), Some(&data.into_iter().map(|block| block.into()).collect()))?;
+		Ok(())
+	}
+
+
+	fn pad_blocks(&self, inode: u64, first_block_id: u64, until_block_id: u64, block_size: u64) -> Result<(), Error> {
+		if first_block_id >= until_block_id { return Ok(()); }
+		let command = commands::dynamic_queries::sql_pad_until(inode, first_block_id, until_block_id, block_size);
+		self.0.command(command.as_str(), None)?;
+		Ok(())
+	}
+
+
+	fn resize_last_block(&self, inode: u64, length: u64) -> Result<(), Error> {
+		self.0.command(commands::SQL_RESIZE_LAST_BLOCK, Some(&vec![length.into(), length.into(), inode.into()]))?;
+		Ok(())
+	}
+
+
+	fn trim_blocks(&self, inode: u64, count: u64) -> Result<(), Error> {
+		self.0.command(commands::SQL_TRIM_BLOCKS, Some(&vec![inode.into(), count.into()]))?;
+		Ok(())
+	}
+
+
+	fn drop_blocks(&self, inode: u64) -> Result<(), Error> {
+		self.0.command(commands::SQL_DROP_BLOCKS, Some(&vec![inode.into()]))?;
+		Ok(())
+	}
+
+
+	fn get_xattr(&self, inode: u64, key: &str) -> Result<Option<Vec<u8>>, Error> {
+		let value: Vec<database_objects::XattrValue> = self.0.query(commands::SQL_GET_XATTR, Some(&vec![inode.into(), key.into()]))?;
+		Ok(value.into_iter().next().map(|val| val.value))
+	}
+
+
+	fn list_xattrs(&self, inode: u64) -> Result<Vec<String>, Error> {
+		let keys: Vec<database_objects::XattrKey> = self.0.query(commands::SQL_LIST_XATTRS, Some(&vec![inode.into()]))?;
+		Ok(keys.into_iter().map(|val| val.key).collect())
+	}
+
+
+	fn create_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_CREATE_XATTR, Some(&vec![inode.into(), key.into(), value.to_vec().into()]))?.rows_affected)
+	}
+
+
+	fn update_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_UPDATE_XATTR, Some(&vec![value.to_vec().into(), inode.into(), key.into()]))?.rows_affected)
+	}
+
+
+	fn delete_xattr(&self, inode: u64, key: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_XATTR, Some(&vec![inode.into(), key.into()]))?.rows_affected)
+	}
+
+
+	fn list_volumes(&self) -> Result<Vec<driver_objects::Volume>, Error> {
+		let volumes: Vec<database_objects::Volume> = self.0.query(commands::SQL_LIST_VOLUMES, None)?;
+		Ok(volumes.into_iter().map(|val| val.into()).collect())
+	}
+
+
+	fn get_volume(&self, name: &str) -> Result<Option<driver_objects::Volume>, Error> {
+		let mut volume: Vec<database_objects::Volume> = self.0.query(commands::SQL_GET_VOLUME, Some(&vec![name.into()]))?;
+		Ok(volume.pop().map(|val| val.into()))
+	}
+
+
+	fn create_volume(&self, name: &str, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut binds = vec![name.into()];
+		binds.append(&mut superblock_binds(superblock));
+		Ok(self.0.command(commands::SQL_CREATE_VOLUME, Some(&binds))?.last_insert_id)
+	}
+
+
+	fn update_volume(&self, volume: u64, root_inode: u64, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut binds = vec![root_inode.into()];
+		binds.append(&mut superblock_binds(superblock));
+		binds.push(volume.into());
+		Ok(self.0.command(commands::SQL_UPDATE_VOLUME, Some(&binds))?.rows_affected)
+	}
+
+
+	fn erase_volume(&self, volume: u64) -> Result<(), Error> {
+		for cmd in commands::SQL_ERASE_VOLUME_SEQUENCE {
+			self.0.command(cmd, Some(&vec![volume.into()]))?;
+		}
+		Ok(())
+	}
+
+
+	fn delete_volume(&self, volume: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_VOLUME, Some(&vec![volume.into()]))?.rows_affected)
+	}
+
+
+	fn stat(&self, volume: u64) -> Result<driver_objects::FilesystemStat, Error> {
+		let stat: Vec<database_objects::FilesystemStat> = self.0.query(commands::SQL_GET_FS_STAT, Some(&vec![volume.into(), volume.into()]))?;
+		let stat = stat.get(0).ok_or(Error::RuntimeError("could not determine fs stat"))?;
+		Ok(driver_objects::FilesystemStat {
+			used_blocks: to_u64(stat.used_blocks)?,
+			used_inodes: to_u64(stat.used_inodes)?,
+		})
+	}
+
+
+	fn schema_version(&self) -> Result<u32, Error> {
+		let exists: Vec<database_objects::TableCount> = self.0.query(commands::SQL_METADATA_EXISTS, None)?;
+		if exists.get(0).map(|val| val.tables).unwrap_or(0) == 0 {
+			return Ok(0);
+		}
+		let version: Vec<database_objects::MetadataValue> = self.0.query(commands::SQL_GET_METADATA, Some(&vec![SCHEMA_VERSION_KEY.into()]))?;
+		parse_schema_version(version.get(0))
+	}
+
+
+	fn migrate(&self, version: u32) -> Result<(), Error> {
+		Ok(self.0.script(migration(commands::SQL_MIGRATIONS, version)?)?)
+	}
+
+
+	fn create_schema(&self) -> Result<(), Error> {
+		let exists: Vec<database_objects::TableCount> = self.0.query(commands::SQL_SCHEMA_EXISTS, None)?;
+		if exists.get(0).map(|val| val.tables).unwrap_or(0) == 0 {
+			self.0.script(commands::SQL_SCHEMA)?;
+		}
+		Ok(())
+	}
+
+
+	fn drop_schema(&self) -> Result<(), Error> {
+		Ok(self.0.script(commands::SQL_DROP_SCHEMA)?)
+	}
+
+	fn begin(&self) -> Result<Box<dyn Backend>, Error> {
+		Ok(Box::new(Self(self.0.begin()?)))
+	}
+
+
+	fn commit(&self) -> Result<(), Error> {
+		Ok(self.0.commit()?)
+	}
+
+
+	fn rollback(&self) -> Result<(), Error> {
+		Ok(self.0.rollback()?)
+	}
+}
@@ -0,0 +1,546 @@
+//! PostgreSQL backend (schema in `sql/postgres/dbfs.sql`)
+//!
+//! PostgreSQL has no unsigned types, so rows are decoded into the signed structs below and
+//! converted into [`database_objects`].
+
+
+use std::time::SystemTime;
+
+use crate::config::ConnectionConfig;
+use crate::db_connector::{chrono, DbConnector, DbInputType, FromRow, Postgres};
+use super::super::{commands_postgres, database_objects, driver_objects, Error, DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE};
+use super::{migration, parse_schema_version, superblock_binds, timestamp_binds, Backend, BlockHead, WriteInfo, SCHEMA_VERSION_KEY};
+
+
+pub struct PostgresBackend(DbConnector<Postgres>);
+
+
+impl PostgresBackend {
+	pub fn new(config: &ConnectionConfig) -> Result<Self, Error> {
+		Ok(Self(DbConnector::new(config)?))
+	}
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct Inode {
+	id: i64,
+	owner: i64,
+	group: i64,
+	file_type: String,
+	special_bits: i16,
+	user_perm: i16,
+	group_perm: i16,
+	other_perm: i16,
+	rdev: i64,
+	atime: i64,
+	atime_nsec: i64,
+	mtime: i64,
+	mtime_nsec: i64,
+	ctime: i64,
+	ctime_nsec: i64,
+	crtime: i64,
+	crtime_nsec: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct Passwd {
+	id: i64,
+	name: String,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct DirectoryEntry {
+	name: String,
+	inode_id: i64,
+	file_type: String,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct RowId {
+	id: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct InodeLookup {
+	inode_id: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct DirectoryParent {
+	parent_inode_id: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct FileSize {
+	bytes: i64,
+	blocks: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct Volume {
+	id: i64,
+	name: String,
+	root_inode_id: Option<i64>,
+	block_size: i64,
+	uuid: String,
+	label: String,
+	created_at: chrono::DateTime<chrono::Utc>,
+	features: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct FileHead {
+	bc: i64,
+	last_block_id: i64,
+}
+
+
+fn to_u64(value: i64) -> Result<u64, Error> {
+	value.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))
+}
+
+
+fn to_u32(value: i64) -> Result<u32, Error> {
+	value.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))
+}
+
+
+fn to_u8(value: i16) -> Result<u8, Error> {
+	value.try_into().map_err(|_| Error::RuntimeError("could not convert database's i16 to u8 for the driver"))
+}
+
+
+impl TryFrom<Inode> for database_objects::Inode {
+	type Error = Error;
+	fn try_from(value: Inode) -> Result<Self, Self::Error> {
+		Ok(Self {
+			id: to_u32(value.id)?,
+			owner: to_u32(value.owner)?,
+			group: to_u32(value.group)?,
+			file_type: value.file_type,
+			special_bits: to_u8(value.special_bits)?,
+			user_perm: to_u8(value.user_perm)?,
+			group_perm: to_u8(value.group_perm)?,
+			other_perm: to_u8(value.other_perm)?,
+			rdev: to_u32(value.rdev)?,
+			atime: value.atime,
+			atime_nsec: to_u32(value.atime_nsec)?,
+			mtime: value.mtime,
+			mtime_nsec: to_u32(value.mtime_nsec)?,
+			ctime: value.ctime,
+			ctime_nsec: to_u32(value.ctime_nsec)?,
+			crtime: value.crtime,
+			crtime_nsec: to_u32(value.crtime_nsec)?,
+		})
+	}
+}
+
+
+impl TryFrom<Volume> for driver_objects::Volume {
+	type Error = Error;
+	fn try_from(value: Volume) -> Result<Self, Self::Error> {
+		Ok(Self {
+			id: to_u64(value.id)?,
+			name: value.name,
+			root_inode: value.root_inode_id.map(to_u64).transpose()?,
+			superblock: driver_objects::Superblock {
+				block_size: to_u32(value.block_size)?,
+				uuid: value.uuid,
+				label: value.label,
+				created_at: value.created_at.into(),
+				// Stored as `bigint`, the flags are the bit pattern of the signed value
+				features: value.features as u64,
+			},
+		})
+	}
+}
+
+
+impl Backend for PostgresBackend {
+	fn get_users(&self) -> Result<Vec<database_objects::User>, Error> {
+		let users: Vec<Passwd> = self.0.query(commands_postgres::SQL_GET_USERS, None)?;
+		users.into_iter().map(|user| Ok(database_objects::User { id: to_u32(user.id)?, name: user.name })).collect()
+	}
+
+
+	fn get_groups(&self) -> Result<Vec<database_objects::Group>, Error> {
+		let groups: Vec<Passwd> = self.0.query(commands_postgres::SQL_GET_GROUPS, None)?;
+		groups.into_iter().map(|group| Ok(database_objects::Group { id: to_u32(group.id)?, name: group.name })).collect()
+	}
+
+
+	fn insert_user(&self, id: u32, name: &str) -> Result<(), Error> {
+		self.0.command(commands_postgres::SQL_INSERT_USER, Some(&vec![id.into(), name.into()]))?;
+		Ok(())
+	}
+
+
+	fn insert_group(&self, id: u32, name: &str) -> Result<(), Error> {
+		self.0.command(commands_postgres::SQL_INSERT_GROUP, Some(&vec![id.into(), name.into()]))?;
+		Ok(())
+	}
+
+
+	fn get_inode(&self, inode: u64) -> Result<Option<database_objects::Inode>, Error> {
+		let mut inodes: Vec<Inode> = self.0.query(commands_postgres::SQL_GET_INODE, Some(&vec![inode.into()]))?;
+		Ok(match inodes.len() {
+			0 => None,
+			_ => Some(inodes.swap_remove(0).try_into()?),
+		})
+	}
+
+
+	fn create_inode(&self, volume: u64, kind: driver_objects::FileType, attr: &driver_objects::FileSetAttr, rdev: u32) -> Result<u64, Error> {
+		let mut args: Vec<DbInputType> = vec![
+			volume.into(),
+			attr.uid.into(),
+			attr.gid.into(),
+			Into::<String>::into(kind).into(),
+			attr.perm.special.into(),
+			attr.perm.owner.into(),
+			attr.perm.group.into(),
+			attr.perm.other.into(),
+			rdev.into(),
+		];
+		args.extend(timestamp_binds(&[attr.atime, attr.mtime, attr.ctime, attr.crtime]));
+		let inode: Vec<RowId> = self.0.query(commands_postgres::SQL_CREATE_INODE, Some(&args))?;
+		to_u64(inode.get(0).ok_or(Error::RuntimeError("could not create inode"))?.id)
+	}
+
+
+	fn update_inode(&self, inode: u64, attr: &driver_objects::FileSetAttr) -> Result<u64, Error> {
+		let mut args: Vec<DbInputType> = vec![
+			attr.uid.into(),
+			attr.gid.into(),
+			attr.perm.special.into(),
+			attr.perm.owner.into(),
+			attr.perm.group.into(),
+			attr.perm.other.into(),
+		];
+		args.extend(timestamp_binds(&[attr.atime, attr.mtime, attr.ctime]));
+		args.push(inode.into());
+		Ok(self.0.command(commands_postgres::SQL_UPDATE_INODE, Some(&args))?.rows_affected)
+	}
+
+
+	fn touch_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time, time]);
+		args.push(inode.into());
+		self.0.command(commands_postgres::SQL_UPDATE_INODE_CTIME_MTIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn change_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time]);
+		args.push(inode.into());
+		self.0.command(commands_postgres::SQL_UPDATE_INODE_CTIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn access_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time]);
+		args.push(inode.into());
+		self.0.command(commands_postgres::SQL_UPDATE_INODE_ATIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn delete_inode(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands_postgres::SQL_DELETE_INODE, Some(&vec![inode.into()]))?.rows_affected)
+	}
+
+
+	fn create_orphan(&self, inode: u64) -> Result<(), Error> {
+		self.0.command(commands_postgres::SQL_CREATE_ORPHAN, Some(&vec![inode.into()]))?;
+		Ok(())
+	}
+
+
+	fn delete_orphan(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands_postgres::SQL_DELETE_ORPHAN, Some(&vec![inode.into()]))?.rows_affected)
+	}
+
+
+	fn list_orphans(&self, volume: u64) -> Result<Vec<u64>, Error> {
+		let orphans: Vec<InodeLookup> = self.0.query(commands_postgres::SQL_LIST_ORPHANS, Some(&vec![volume.into()]))?;
+		orphans.into_iter().map(|val| to_u64(val.inode_id)).collect()
+	}
+
+
+	fn count_hardlinks(&self, inode: u64) -> Result<u64, Error> {
+		let hardlinks: Vec<database_objects::FileHardlinks> = self.0.query(commands_postgres::SQL_COUNT_HARDLINKS, Some(&vec![inode.into()]))?;
+		to_u64(hardlinks.get(0).ok_or(Error::RuntimeError("could not count hardlinks"))?.hardlinks)
+	}
+
+
+	fn count_subdirectories(&self, inode: u64) -> Result<u64, Error> {
+		let subdirs: Vec<database_objects::DirectoryChildrenDirectory> = self.0.query(commands_postgres::SQL_COUNT_CHILDREN_OF_TYPE_DIRECTORY, Some(&vec![inode.into()]))?;
+		to_u64(subdirs.get(0).ok_or(Error::RuntimeError("could not count subdirectories"))?.children_dirs)
+	}
+
+
+	fn lookup(&self, parent_inode: u64, name: &str) -> Result<Option<u64>, Error> {
+		let inode: Vec<InodeLookup> = self.0.query(commands_postgres::SQL_LOOKUP_INODE_ID, Some(&vec![name.into(), parent_inode.into()]))?;
+		inode.get(0).map(|val| to_u64(val.inode_id)).transpose()
+	}
+
+
+	fn list_directory(&self, inode: u64) -> Result<Vec<database_objects::DirectoryEntry>, Error> {
+		let entries: Vec<DirectoryEntry> = self.0.query(commands_postgres::SQL_LIST_DIRECTORY, Some(&vec![inode.into()]))?;
+		entries.into_iter().map(|entry| Ok(database_objects::DirectoryEntry {
+			name: entry.name,
+			inode_id: to_u32(entry.inode_id)?,
+			file_type: entry.file_type,
+		})).collect()
+	}
+
+
+	fn get_parent(&self, inode: u64) -> Result<Option<u64>, Error> {
+		let parent: Vec<DirectoryParent> = self.0.query(commands_postgres::SQL_GET_DIRECTORY_PARENT, Some(&vec![inode.into()]))?;
+		parent.get(0).map(|val| to_u64(val.parent_inode_id)).transpose()
+	}
+
+
+	fn count_children(&self, inode: u64) -> Result<u64, Error> {
+		let count: Vec<database_objects::ChildrenCount> = self.0.query(commands_postgres::SQL_COUNT_DIRECTORY_CHILDREN, Some(&vec![inode.into()]))?;
+		to_u64(count.get(0).ok_or(Error::RuntimeError("could not determine children count"))?.children)
+	}
+
+
+	fn create_file(&self, parent_inode: u64, name: &str, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands_postgres::SQL_CREATE_FILE, Some(&vec![parent_inode.into(), name.into(), inode.into()]))?.rows_affected)
+	}
+
+
+	fn delete_file(&self, parent_inode: u64, name: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands_postgres::SQL_DELETE_FILE, Some(&vec![name.into(), parent_inode.into()]))?.rows_affected)
+	}
+
+
+	fn rename_file(&self, src_parent_inode: u64, src_name: &str, dest_parent_inode: u64, dest_name: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands_postgres::SQL_RENAME_FILE, Some(&vec![
+			dest_parent_inode.into(),
+			dest_name.into(),
+			src_parent_inode.into(),
+			src_name.into()
+		]))?.rows_affected)
+	}
+
+
+	fn file_size(&self, inode: u64, block_size: u64) -> Result<Option<database_objects::FileSize>, Error> {
+		let size: Vec<FileSize> = self.0.query(commands_postgres::SQL_GET_FILE_SIZE, Some(&vec![inode.into(), block_size.into()]))?;
+		Ok(match size.get(0) {
+			Some(size) => Some(database_objects::FileSize { bytes: to_u64(size.bytes)?, blocks: to_u64(size.blocks)? }),
+			None => None,
+		})
+	}
+
+
+	fn block_head(&self, inode: u64) -> Result<BlockHead, Error> {
+		let head: Vec<FileHead> = self.0.query(commands_postgres::SQL_GET_FILE_HEAD, Some(&vec![inode.into()]))?;
+		let head = head.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
+		Ok(BlockHead {
+			blocks: to_u64(head.bc)?,
+			last_block_id: to_u64(head.last_block_id)?,
+		})
+	}
+
+
+	fn write_info(&self, inode: u64, start_block: Option<u64>, end_block: Option<u64>) -> Result<WriteInfo, Error> {
+		match (start_block, end_block) {
+			(None, None) => {
+				let result: Vec<database_objects::FileWriteInfoSizeOnly> = self.0.query(commands_postgres::SQL_GET_SIZE_ONLY, Some(&vec![inode.into()]))?;
+				let result = result.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
+				Ok(WriteInfo { blocks: to_u64(result.blocks)?, start_block_data: Vec::new(), end_block_data: Vec::new() })
+			},
+			(Some(block), None) | (None, Some(block)) => {
+				let mut result: Vec<database_objects::FileWriteInfoSingleBlock> = self.0.query(commands_postgres::SQL_GET_SIZE_AND_SINGLE_BLOCK_DATA, Some(&vec![inode.into(), block.into()]))?;
+				if result.len() == 0 { return Err(Error::NotFoundError("could not get filesize and block")); }
+				let result = result.swap_remove(0);
+				let blocks = to_u64(result.blocks)?;
+				Ok(match start_block {
+					Some(_) => WriteInfo { blocks, start_block_data: result.block_data, end_block_data: Vec::new() },
+					None => WriteInfo { blocks, start_block_data: Vec::new(), end_block_data: result.block_data },
+				})
+			},
+			(Some(start_block), Some(end_block)) => {
+				let mut result: Vec<database_objects::FileWriteInfo> = self.0.query(commands_postgres::SQL_GET_SIZE_AND_BLOCK_DATA, Some(&vec![inode.into(), start_block.into(), end_block.into()]))?;
+				if result.len() == 0 { return Err(Error::NotFoundError("could not get filesize and block")); }
+				let result = result.swap_remove(0);
+				Ok(WriteInfo { blocks: to_u64(result.blocks)?, start_block_data: result.start_block_data, end_block_data: result.end_block_data })
+			}
+		}
+	}
+
+
+	fn read_blocks(&self, inode: u64, offset_blocks: u64, max_blocks: u64) -> Result<Vec<Vec<u8>>, Error> {
+		let blocks: Vec<database_objects::BlockData> = self.0.query(commands_postgres::SQL_READ_FILE, Some(&vec![inode.into(), max_blocks.into(), offset_blocks.into()]))?;
+		Ok(blocks.into_iter().map(|block| block.data).collect())
+	}
+
+
+	fn write_blocks(&self, inode: u64, first_block_id: u64, data: Vec<Vec<u8>>) -> Result<(), Error> {
+		if data.len() == 0 { return Ok(()); }
+		let command = commands_postgres::dynamic_queries::sql_write(inode, first_block_id, first_block_id + data.len() as u64 - 1);
+		self.0.command(command.as_str(), Some(&data.into_iter().map(|block| block.into()).collect()))?;
+		Ok(())
+	}
+
+
+	fn pad_blocks(&self, inode: u64, first_block_id: u64, until_block_id: u64, block_size: u64) -> Result<(), Error> {
+		if first_block_id >= until_block_id { return Ok(()); }
+		let command = commands_postgres::dynamic_queries::sql_pad_until(inode, first_block_id, until_block_id, block_size);
+		self.0.command(command.as_str(), None)?;
+		Ok(())
+	}
+
+
+	fn resize_last_block(&self, inode: u64, length: u64) -> Result<(), Error> {
+		self.0.command(commands_postgres::SQL_RESIZE_LAST_BLOCK, Some(&vec![inode.into(), length.into()]))?;
+		Ok(())
+	}
+
+
+	fn trim_blocks(&self, inode: u64, count: u64) -> Result<(), Error> {
+		self.0.command(commands_postgres::SQL_TRIM_BLOCKS, Some(&vec![inode.into(), count.into()]))?;
+		Ok(())
+	}
+
+
+	fn drop_blocks(&self, inode: u64) -> Result<(), Error> {
+		self.0.command(commands_postgres::SQL_DROP_BLOCKS, Some(&vec![inode.into()]))?;
+		Ok(())
+	}
+
+
+	fn get_xattr(&self, inode: u64, key: &str) -> Result<Option<Vec<u8>>, Error> {
+		let value: Vec<database_objects::XattrValue> = self.0.query(commands_postgres::SQL_GET_XATTR, Some(&vec![inode.into(), key.into()]))?;
+		Ok(value.into_iter().next().map(|val| val.value))
+	}
+
+
+	fn list_xattrs(&self, inode: u64) -> Result<Vec<String>, Error> {
+		let keys: Vec<database_objects::XattrKey> = self.0.query(commands_postgres::SQL_LIST_XATTRS, Some(&vec![inode.into()]))?;
+		Ok(keys.into_iter().map(|val| val.key).collect())
+	}
+
+
+	fn create_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		Ok(self.0.command(commands_postgres::SQL_CREATE_XATTR, Some(&vec![inode.into(), key.into(), value.to_vec().into()]))?.rows_affected)
+	}
+
+
+	fn update_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		Ok(self.0.command(commands_postgres::SQL_UPDATE_XATTR, Some(&vec![value.to_vec().into(), inode.into(), key.into()]))?.rows_affected)
+	}
+
+
+	fn delete_xattr(&self, inode: u64, key: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands_postgres::SQL_DELETE_XATTR, Some(&vec![inode.into(), key.into()]))?.rows_affected)
+	}
+
+
+	fn list_volumes(&self) -> Result<Vec<driver_objects::Volume>, Error> {
+		let volumes: Vec<Volume> = self.0.query(commands_postgres::SQL_LIST_VOLUMES, None)?;
+		volumes.into_iter().map(|val| val.try_into()).collect()
+	}
+
+
+	fn get_volume(&self, name: &str) -> Result<Option<driver_objects::Volume>, Error> {
+		let mut volume: Vec<Volume> = self.0.query(commands_postgres::SQL_GET_VOLUME, Some(&vec![name.into()]))?;
+		volume.pop().map(|val| val.try_into()).transpose()
+	}
+
+
+	fn create_volume(&self, name: &str, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut binds = vec![name.into()];
+		binds.append(&mut superblock_binds(superblock));
+		let volume: Vec<RowId> = self.0.query(commands_postgres::SQL_CREATE_VOLUME, Some(&binds))?;
+		to_u64(volume.get(0).ok_or(Error::RuntimeError("could not create volume"))?.id)
+	}
+
+
+	fn update_volume(&self, volume: u64, root_inode: u64, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut binds = vec![root_inode.into()];
+		binds.append(&mut superblock_binds(superblock));
+		binds.push(volume.into());
+		Ok(self.0.command(commands_postgres::SQL_UPDATE_VOLUME, Some(&binds))?.rows_affected)
+	}
+
+
+	fn erase_volume(&self, volume: u64) -> Result<(), Error> {
+		for cmd in commands_postgres::SQL_ERASE_VOLUME_SEQUENCE {
+			self.0.command(cmd, Some(&vec![volume.into()]))?;
+		}
+		Ok(())
+	}
+
+
+	fn delete_volume(&self, volume: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands_postgres::SQL_DELETE_VOLUME, Some(&vec![volume.into()]))?.rows_affected)
+	}
+
+
+	fn stat(&self, volume: u64) -> Result<driver_objects::FilesystemStat, Error> {
+		let stat: Vec<database_objects::FilesystemStat> = self.0.query(commands_postgres::SQL_GET_FS_STAT, Some(&vec![volume.into()]))?;
+		let stat = stat.get(0).ok_or(Error::RuntimeError("could not determine fs stat"))?;
+		Ok(driver_objects::FilesystemStat {
+			used_blocks: to_u64(stat.used_blocks)?,
+			used_inodes: to_u64(stat.used_inodes)?,
+		})
+	}
+
+
+	fn schema_version(&self) -> Result<u32, Error> {
+		let exists: Vec<database_objects::TableCount> = self.0.query(commands_postgres::SQL_METADATA_EXISTS, None)?;
+		if exists.get(0).map(|val| val.tables).unwrap_or(0) == 0 {
+			return Ok(0);
+		}
+		let version: Vec<database_objects::MetadataValue> = self.0.query(commands_postgres::SQL_GET_METADATA, Some(&vec![SCHEMA_VERSION_KEY.into()]))?;
+		parse_schema_version(version.get(0))
+	}
+
+
+	fn migrate(&self, version: u32) -> Result<(), Error> {
+		Ok(self.0.script(migration(commands_postgres::SQL_MIGRATIONS, version)?)?)
+	}
+
+
+	fn create_schema(&self) -> Result<(), Error> {
+		let exists: Vec<database_objects::TableCount> = self.0.query(commands_postgres::SQL_SCHEMA_EXISTS, None)?;
+		if exists.get(0).map(|val| val.tables).unwrap_or(0) == 0 {
+			self.0.script(commands_postgres::SQL_SCHEMA)?;
+		}
+		Ok(())
+	}
+
+
+	fn drop_schema(&self) -> Result<(), Error> {
+		Ok(self.0.script(commands_postgres::SQL_DROP_SCHEMA)?)
+	}
+
+	fn begin(&self) -> Result<Box<dyn Backend>, Error> {
+		Ok(Box::new(Self(self.0.begin()?)))
+	}
+
+
+	fn commit(&self) -> Result<(), Error> {
+		Ok(self.0.commit()?)
+	}
+
+
+	fn rollback(&self) -> Result<(), Error> {
+		Ok(self.0.rollback()?)
+	}
+}
@@ -0,0 +1,645 @@
+//! SQLite backend (schema in `sql/sqlite/dbfs.sql`)
+//!
+//! The whole volume lives in a single database file. The schema is created automatically when the
+//! file is new (or empty).
+
+
+use std::time::SystemTime;
+
+use crate::config::ConnectionConfig;
+use crate::db_connector::{chrono, DbConnector, DbInputType, FromRow, Sqlite};
+use super::super::{commands, commands_sqlite, database_objects, driver_objects, Error, DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE};
+use super::{migration, parse_schema_version, superblock_binds, timestamp_binds, Backend, BlockHead, WriteInfo, SCHEMA_VERSION_KEY};
+
+
+pub struct SqliteBackend(DbConnector<Sqlite>);
+
+
+/// SQLite can not decode into `u64` - the size is read signed and converted
+#[derive(Debug, PartialEq, FromRow)]
+struct FileSize {
+	bytes: i64,
+	blocks: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+struct Volume {
+	id: i64,
+	name: String,
+	root_inode_id: Option<i64>,
+	block_size: i64,
+	uuid: String,
+	label: String,
+	created_at: chrono::DateTime<chrono::Utc>,
+	features: i64,
+}
+
+
+impl SqliteBackend {
+	pub fn new(config: &ConnectionConfig) -> Result<Self, Error> {
+		Self::from_url(&config.url())
+	}
+
+
+	/// # Inputs
+	/// `url` is a SQLite connection URL (`sqlite://volume.db`, `sqlite::memory:`)
+	pub fn from_url(url: &str) -> Result<Self, Error> {
+		let backend = Self(DbConnector::from_url(url)?);
+		backend.create_schema()?;
+		Ok(backend)
+	}
+}
+
+
+fn to_u64(value: i64) -> Result<u64, Error> {
+	value.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))
+}
+
+
+impl TryFrom<Volume> for driver_objects::Volume {
+	type Error = Error;
+	fn try_from(value: Volume) -> Result<Self, Self::Error> {
+		Ok(Self {
+			id: to_u64(value.id)?,
+			name: value.name,
+			root_inode: value.root_inode_id.map(to_u64).transpose()?,
+			superblock: driver_objects::Superblock {
+				block_size: to_u64(value.block_size)?.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?,
+				uuid: value.uuid,
+				label: value.label,
+				created_at: value.created_at.into(),
+				// Stored as `bigint`, the flags are the bit pattern of the signed value
+				features: value.features as u64,
+			},
+		})
+	}
+}
+
+
+impl Backend for SqliteBackend {
+	fn get_users(&self) -> Result<Vec<database_objects::User>, Error> {
+		Ok(self.0.query(commands::SQL_GET_USERS, None)?)
+	}
+
+
+	fn get_groups(&self) -> Result<Vec<database_objects::Group>, Error> {
+		Ok(self.0.query(commands::SQL_GET_GROUPS, None)?)
+	}
+
+
+	fn insert_user(&self, id: u32, name: &str) -> Result<(), Error> {
+		self.0.command(commands::SQL_INSERT_USER, Some(&vec![id.into(), name.into()]))?;
+		Ok(())
+	}
+
+
+	fn insert_group(&self, id: u32, name: &str) -> Result<(), Error> {
+		self.0.command(commands::SQL_INSERT_GROUP, Some(&vec![id.into(), name.into()]))?;
+		Ok(())
+	}
+
+
+	fn get_inode(&self, inode: u64) -> Result<Option<database_objects::Inode>, Error> {
+		let mut inodes: Vec<database_objects::Inode> = self.0.query(commands::SQL_GET_INODE, Some(&vec![inode.into()]))?;
+		Ok(match inodes.len() {
+			0 => None,
+			_ => Some(inodes.swap_remove(0)),
+		})
+	}
+
+
+	fn create_inode(&self, volume: u64, kind: driver_objects::FileType, attr: &driver_objects::FileSetAttr, rdev: u32) -> Result<u64, Error> {
+		let mut args: Vec<DbInputType> = vec![
+			volume.into(),
+			attr.uid.into(),
+			attr.gid.into(),
+			Into::<String>::into(kind).into(),
+			attr.perm.special.into(),
+			attr.perm.owner.into(),
+			attr.perm.group.into(),
+			attr.perm.other.into(),
+			rdev.into(),
+		];
+		args.extend(timestamp_binds(&[attr.atime, attr.mtime, attr.ctime, attr.crtime]));
+		Ok(self.0.command(commands::SQL_CREATE_INODE, Some(&args))?.last_insert_id)
+	}
+
+
+	fn update_inode(&self, inode: u64, attr: &driver_objects::FileSetAttr) -> Result<u64, Error> {
+		let mut args: Vec<DbInputType> = vec![
+			attr.uid.into(),
+			attr.gid.into(),
+			attr.perm.special.into(),
+			attr.perm.owner.into(),
+			attr.perm.group.into(),
+			attr.perm.other.into(),
+		];
+		args.extend(timestamp_binds(&[attr.atime, attr.mtime, attr.ctime]));
+		args.push(inode.into());
+		Ok(self.0.command(commands::SQL_UPDATE_INODE, Some(&args))?.rows_affected)
+	}
+
+
+	fn touch_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time, time]);
+		args.push(inode.into());
+		self.0.command(commands::SQL_UPDATE_INODE_CTIME_MTIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn change_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time]);
+		args.push(inode.into());
+		self.0.command(commands::SQL_UPDATE_INODE_CTIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn access_inode(&self, inode: u64, time: SystemTime) -> Result<(), Error> {
+		let mut args = timestamp_binds(&[time]);
+		args.push(inode.into());
+		self.0.command(commands::SQL_UPDATE_INODE_ATIME, Some(&args))?;
+		Ok(())
+	}
+
+
+	fn delete_inode(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_INODE, Some(&vec![inode.into()]))?.rows_affected)
+	}
+
+
+	fn create_orphan(&self, inode: u64) -> Result<(), Error> {
+		self.0.command(commands::SQL_CREATE_ORPHAN, Some(&vec![inode.into()]))?;
+		Ok(())
+	}
+
+
+	fn delete_orphan(&self, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_ORPHAN, Some(&vec![inode.into()]))?.rows_affected)
+	}
+
+
+	fn list_orphans(&self, volume: u64) -> Result<Vec<u64>, Error> {
+		let orphans: Vec<database_objects::InodeLookup> = self.0.query(commands::SQL_LIST_ORPHANS, Some(&vec![volume.into()]))?;
+		Ok(orphans.into_iter().map(|val| val.inode_id.into()).collect())
+	}
+
+
+	fn count_hardlinks(&self, inode: u64) -> Result<u64, Error> {
+		let hardlinks: Vec<database_objects::FileHardlinks> = self.0.query(commands::SQL_COUNT_HARDLINKS, Some(&vec![inode.into()]))?;
+		to_u64(hardlinks.get(0).ok_or(Error::RuntimeError("could not count hardlinks"))?.hardlinks)
+	}
+
+
+	fn count_subdirectories(&self, inode: u64) -> Result<u64, Error> {
+		let subdirs: Vec<database_objects::DirectoryChildrenDirectory> = self.0.query(commands::SQL_COUNT_CHILDREN_OF_TYPE_DIRECTORY, Some(&vec![inode.into()]))?;
+		to_u64(subdirs.get(0).ok_or(Error::RuntimeError("could not count subdirectories"))?.children_dirs)
+	}
+
+
+	fn lookup(&self, parent_inode: u64, name: &str) -> Result<Option<u64>, Error> {
+		let inode: Vec<database_objects::InodeLookup> = self.0.query(commands::SQL_LOOKUP_INODE_ID, Some(&vec![name.into(), parent_inode.into()]))?;
+		Ok(inode.get(0).map(|val| val.inode_id.into()))
+	}
+
+
+	fn list_directory(&self, inode: u64) -> Result<Vec<database_objects::DirectoryEntry>, Error> {
+		Ok(self.0.query(commands::SQL_LIST_DIRECTORY, Some(&vec![inode.into()]))?)
+	}
+
+
+	fn get_parent(&self, inode: u64) -> Result<Option<u64>, Error> {
+		let parent: Vec<database_objects::DirectoryParent> = self.0.query(commands::SQL_GET_DIRECTORY_PARENT, Some(&vec![inode.into()]))?;
+		Ok(parent.get(0).map(|val| val.parent_inode_id.into()))
+	}
+
+
+	fn count_children(&self, inode: u64) -> Result<u64, Error> {
+		let count: Vec<database_objects::ChildrenCount> = self.0.query(commands::SQL_COUNT_DIRECTORY_CHILDREN, Some(&vec![inode.into()]))?;
+		to_u64(count.get(0).ok_or(Error::RuntimeError("could not determine children count"))?.children)
+	}
+
+
+	fn create_file(&self, parent_inode: u64, name: &str, inode: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_CREATE_FILE, Some(&vec![parent_inode.into(), name.into(), inode.into()]))?.rows_affected)
+	}
+
+
+	fn delete_file(&self, parent_inode: u64, name: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_FILE, Some(&vec![name.into(), parent_inode.into()]))?.rows_affected)
+	}
+
+
+	fn rename_file(&self, src_parent_inode: u64, src_name: &str, dest_parent_inode: u64, dest_name: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_RENAME_FILE, Some(&vec![
+			dest_parent_inode.into(),
+			dest_name.into(),
+			src_parent_inode.into(),
+			src_name.into()
+		]))?.rows_affected)
+	}
+
+
+	fn file_size(&self, inode: u64, block_size: u64) -> Result<Option<database_objects::FileSize>, Error> {
+		let size: Vec<FileSize> = self.0.query(commands_sqlite::SQL_GET_FILE_SIZE, Some(&vec![block_size.into(), inode.into()]))?;
+		Ok(match size.get(0) {
+			Some(size) => Some(database_objects::FileSize { bytes: to_u64(size.bytes)?, blocks: to_u64(size.blocks)? }),
+			None => None,
+		})
+	}
+
+
+	fn block_head(&self, inode: u64) -> Result<BlockHead, Error> {
+		let head: Vec<database_objects::FileHead> = self.0.query(commands_sqlite::SQL_GET_FILE_HEAD, Some(&vec![inode.into()]))?;
+		let head = head.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
+		Ok(BlockHead {
+			blocks: to_u64(head.bc)?,
+			last_block_id: head.last_block_id.into(),
+		})
+	}
+
+
+	fn write_info(&self, inode: u64, start_block: Option<u64>, end_block: Option<u64>) -> Result<WriteInfo, Error> {
+		match (start_block, end_block) {
+			(None, None) => {
+				let result: Vec<database_objects::FileWriteInfoSizeOnly> = self.0.query(commands::SQL_GET_SIZE_ONLY, Some(&vec![inode.into()]))?;
+				let result = result.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
+				Ok(WriteInfo { blocks: to_u64(result.blocks)?, start_block_data: Vec::new(), end_block_data: Vec::new() })
+			},
+			(Some(block), None) | (None, Some(block)) => {
+				let mut result: Vec<database_objects::FileWriteInfoSingleBlock> = self.0.query(commands_sqlite::SQL_GET_SIZE_AND_SINGLE_BLOCK_DATA, Some(&vec![inode.into(), block.into()]))?;
+				if result.len() == 0 { return Err(Error::NotFoundError("could not get filesize and block")); }
+				let result = result.swap_remove(0);
+				let blocks = to_u64(result.blocks)?;
+				Ok(match start_block {
+					Some(_) => WriteInfo { blocks, start_block_data: result.block_data, end_block_data: Vec::new() },
+					None => WriteInfo { blocks, start_block_data: Vec::new(), end_block_data: result.block_data },
+				})
+			},
+			(Some(start_block), Some(end_block)) => {
+				let mut result: Vec<database_objects::FileWriteInfo> = self.0.query(commands_sqlite::SQL_GET_SIZE_AND_BLOCK_DATA, Some(&vec![inode.into(), start_block.into(), end_block.into()]))?;
+				if result.len() == 0 { return Err(Error::NotFoundError("could not get filesize and block")); }
+				let result = result.swap_remove(0);
+				Ok(WriteInfo { blocks: to_u64(result.blocks)?, start_block_data: result.start_block_data, end_block_data: result.end_block_data })
+			}
+		}
+	}
+
+
+	fn read_blocks(&self, inode: u64, offset_blocks: u64, max_blocks: u64) -> Result<Vec<Vec<u8>>, Error> {
+		let blocks: Vec<database_objects::BlockData> = self.0.query(commands::SQL_READ_FILE, Some(&vec![inode.into(), max_blocks.into(), offset_blocks.into()]))?;
+		Ok(blocks.into_iter().map(|block| block.data).collect())
+	}
+
+
+	fn write_blocks(&self, inode: u64, first_block_id: u64, data: Vec<Vec<u8>>) -> Result<(), Error> {
+		if data.len() == 0 { return Ok(()); }
+		let command = commands_sqlite::dynamic_queries::sql_write(inode, first_block_id, first_block_id + data.len() as u64 - 1);
+		self.0.command(command.as_str(), Some(&data.into_iter().map(|block| block.into()).collect()))?;
+		Ok(())
+	}
+
+
+	fn pad_blocks(&self, inode: u64, first_block_id: u64, until_block_id: u64, block_size: u64) -> Result<(), Error> {
+		if first_block_id >= until_block_id { return Ok(()); }
+		let command = commands_sqlite::dynamic_queries::sql_pad_until(inode, first_block_id, until_block_id, block_size);
+		self.0.command(command.as_str(), None)?;
+		Ok(())
+	}
+
+
+	fn resize_last_block(&self, inode: u64, length: u64) -> Result<(), Error> {
+		self.0.command(commands_sqlite::SQL_RESIZE_LAST_BLOCK, Some(&vec![inode.into(), length.into()]))?;
+		Ok(())
+	}
+
+
+	fn trim_blocks(&self, inode: u64, count: u64) -> Result<(), Error> {
+		self.0.command(commands_sqlite::SQL_TRIM_BLOCKS, Some(&vec![inode.into(), count.into()]))?;
+		Ok(())
+	}
+
+
+	fn drop_blocks(&self, inode: u64) -> Result<(), Error> {
+		self.0.command(commands::SQL_DROP_BLOCKS, Some(&vec![inode.into()]))?;
+		Ok(())
+	}
+
+
+	fn get_xattr(&self, inode: u64, key: &str) -> Result<Option<Vec<u8>>, Error> {
+		let value: Vec<database_objects::XattrValue> = self.0.query(commands::SQL_GET_XATTR, Some(&vec![inode.into(), key.into()]))?;
+		Ok(value.into_iter().next().map(|val| val.value))
+	}
+
+
+	fn list_xattrs(&self, inode: u64) -> Result<Vec<String>, Error> {
+		let keys: Vec<database_objects::XattrKey> = self.0.query(commands::SQL_LIST_XATTRS, Some(&vec![inode.into()]))?;
+		Ok(keys.into_iter().map(|val| val.key).collect())
+	}
+
+
+	fn create_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_CREATE_XATTR, Some(&vec![inode.into(), key.into(), value.to_vec().into()]))?.rows_affected)
+	}
+
+
+	fn update_xattr(&self, inode: u64, key: &str, value: &[u8]) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_UPDATE_XATTR, Some(&vec![value.to_vec().into(), inode.into(), key.into()]))?.rows_affected)
+	}
+
+
+	fn delete_xattr(&self, inode: u64, key: &str) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_XATTR, Some(&vec![inode.into(), key.into()]))?.rows_affected)
+	}
+
+
+	fn list_volumes(&self) -> Result<Vec<driver_objects::Volume>, Error> {
+		let volumes: Vec<Volume> = self.0.query(commands::SQL_LIST_VOLUMES, None)?;
+		volumes.into_iter().map(|val| val.try_into()).collect()
+	}
+
+
+	fn get_volume(&self, name: &str) -> Result<Option<driver_objects::Volume>, Error> {
+		let mut volume: Vec<Volume> = self.0.query(commands::SQL_GET_VOLUME, Some(&vec![name.into()]))?;
+		volume.pop().map(|val| val.try_into()).transpose()
+	}
+
+
+	fn create_volume(&self, name: &str, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut binds = vec![name.into()];
+		binds.append(&mut superblock_binds(superblock));
+		Ok(self.0.command(commands::SQL_CREATE_VOLUME, Some(&binds))?.last_insert_id)
+	}
+
+
+	fn update_volume(&self, volume: u64, root_inode: u64, superblock: &driver_objects::Superblock) -> Result<u64, Error> {
+		let mut binds = vec![root_inode.into()];
+		binds.append(&mut superblock_binds(superblock));
+		binds.push(volume.into());
+		Ok(self.0.command(commands::SQL_UPDATE_VOLUME, Some(&binds))?.rows_affected)
+	}
+
+
+	fn erase_volume(&self, volume: u64) -> Result<(), Error> {
+		for cmd in commands::SQL_ERASE_VOLUME_SEQUENCE {
+			self.0.command(cmd, Some(&vec![volume.into()]))?;
+		}
+		Ok(())
+	}
+
+
+	fn delete_volume(&self, volume: u64) -> Result<u64, Error> {
+		Ok(self.0.command(commands::SQL_DELETE_VOLUME, Some(&vec![volume.into()]))?.rows_affected)
+	}
+
+
+	fn stat(&self, volume: u64) -> Result<driver_objects::FilesystemStat, Error> {
+		let stat: Vec<database_objects::FilesystemStat> = self.0.query(commands::SQL_GET_FS_STAT, Some(&vec![volume.into(), volume.into()]))?;
+		let stat = stat.get(0).ok_or(Error::RuntimeError("could not determine fs stat"))?;
+		Ok(driver_objects::FilesystemStat {
+			used_blocks: to_u64(stat.used_blocks)?,
+			used_inodes: to_u64(stat.used_inodes)?,
+		})
+	}
+
+
+	fn schema_version(&self) -> Result<u32, Error> {
+		let exists: Vec<database_objects::TableCount> = self.0.query(commands_sqlite::SQL_METADATA_EXISTS, None)?;
+		if exists.get(0).map(|val| val.tables).unwrap_or(0) == 0 {
+			return Ok(0);
+		}
+		let version: Vec<database_objects::MetadataValue> = self.0.query(commands::SQL_GET_METADATA, Some(&vec![SCHEMA_VERSION_KEY.into()]))?;
+		parse_schema_version(version.get(0))
+	}
+
+
+	fn migrate(&self, version: u32) -> Result<(), Error> {
+		Ok(self.0.script(migration(commands_sqlite::SQL_MIGRATIONS, version)?)?)
+	}
+
+
+	fn create_schema(&self) -> Result<(), Error> {
+		let exists: Vec<database_objects::TableCount> = self.0.query(commands_sqlite::SQL_SCHEMA_EXISTS, None)?;
+		if exists.get(0).map(|val| val.tables).unwrap_or(0) == 0 {
+			self.0.script(commands_sqlite::SQL_SCHEMA)?;
+		}
+		Ok(())
+	}
+
+
+	fn drop_schema(&self) -> Result<(), Error> {
+		Ok(self.0.script(commands_sqlite::SQL_DROP_SCHEMA)?)
+	}
+
+	fn begin(&self) -> Result<Box<dyn Backend>, Error> {
+		Ok(Box::new(Self(self.0.begin()?)))
+	}
+
+
+	fn commit(&self) -> Result<(), Error> {
+		Ok(self.0.commit()?)
+	}
+
+
+	fn rollback(&self) -> Result<(), Error> {
+		Ok(self.0.rollback()?)
+	}
+}
+
+
+#[cfg(test)]
+mod test {
+	use std::ffi::OsStr;
+	use std::time::SystemTime;
+	use crate::db_connector::DbConnectorError;
+	use crate::settings;
+	use super::super::super::TranslationLayer;
+	use super::super::super::test_util::file_attr;
+	use super::*;
+
+
+	fn memory_volume() -> TranslationLayer {
+		TranslationLayer::with_backend(Box::new(SqliteBackend::from_url("sqlite::memory:").unwrap()), super::super::DEFAULT_VOLUME).unwrap()
+	}
+
+
+	#[test]
+	fn new_volume_has_root() {
+		let tl = memory_volume();
+		let attr = tl.getattr(1).unwrap();
+		assert_eq!(attr.kind, driver_objects::FileType::Directory);
+		assert_eq!(tl.readdir(1).unwrap().len(), 2);
+	}
+
+	#[test]
+	fn migrate_unversioned_volume() {
+		let backend = SqliteBackend::from_url("sqlite::memory:").unwrap();
+		assert_eq!(backend.schema_version().unwrap(), super::super::SCHEMA_VERSION);
+
+		// Unversioned volumes have the timestamp columns replaced by version 5
+		backend.0.script("ALTER TABLE `inode` ADD COLUMN `created_at` timestamp NOT NULL DEFAULT '2024-10-24 17:52:52.250';
+ALTER TABLE `inode` ADD COLUMN `modified_at` timestamp NOT NULL DEFAULT '2024-10-24 17:53:10';
+ALTER TABLE `inode` ADD COLUMN `accessed_at` timestamp NOT NULL DEFAULT '2024-10-24 17:52:52';
+DROP TABLE `metadata`").unwrap();
+		assert_eq!(backend.schema_version().unwrap(), 0);
+		assert_eq!(super::super::migrate(&backend).unwrap(), 0);
+		assert!(super::super::check_schema(&backend).is_ok());
+
+		let root = backend.get_inode(1).unwrap().unwrap();
+		assert_eq!((root.ctime, root.ctime_nsec), (1729792372, 250_000_000));
+		assert_eq!((root.crtime, root.crtime_nsec), (1729792372, 250_000_000));
+		assert_eq!((root.mtime, root.mtime_nsec), (1729792390, 0));
+	}
+
+	#[test]
+	fn failed_operation_is_rolled_back() {
+		let tl = memory_volume();
+		let ino = tl.mknod(1, OsStr::new("test.bin"), driver_objects::FileType::File, file_attr()).unwrap().ino as u64;
+		tl.write(ino, 0, b"data").unwrap();
+
+		// The new inode is rolled back together with the refused name
+		assert!(tl.mknod(1, OsStr::new("test.bin"), driver_objects::FileType::File, file_attr()).is_err());
+		assert_eq!(tl.statfs().unwrap().used_inodes, 2);
+
+		// Committed and rolled back transactions on the connector
+		let transaction = tl.0.begin().unwrap();
+		transaction.drop_blocks(ino).unwrap();
+		transaction.rollback().unwrap();
+		assert_eq!(tl.filesize(ino).unwrap().bytes, 4);
+		let transaction = tl.0.begin().unwrap();
+		transaction.drop_blocks(ino).unwrap();
+		transaction.commit().unwrap();
+		assert_eq!(tl.filesize(ino).unwrap().bytes, 0);
+	}
+
+	#[test]
+	fn force_init_recreates_schema() {
+		let tl = memory_volume();
+		tl.mknod(1, OsStr::new("test.bin"), driver_objects::FileType::File, file_attr()).unwrap();
+		super::super::init(tl.0.as_ref(), true).unwrap();
+		assert_eq!(tl.readdir(1).unwrap().len(), 2);
+		assert!(super::super::check_schema(tl.0.as_ref()).is_ok());
+	}
+
+	#[test]
+	fn write_read_across_blocks() {
+		let tl = memory_volume();
+		let ino = tl.mknod(1, OsStr::new("test.bin"), driver_objects::FileType::File, file_attr()).unwrap().ino as u64;
+		let block_size = tl.superblock().block_size as usize;
+		let data: Vec<u8> = (0..block_size * 2 + 5).map(|val| (val % 251) as u8).collect();
+		tl.write(ino, 3, &data).unwrap();
+
+		assert_eq!(tl.filesize(ino).unwrap().bytes, data.len() as u64 + 3);
+		let mut buffer = vec![0xff; data.len() + 3];
+		assert_eq!(tl.read(ino, 0, &mut buffer).unwrap(), data.len() + 3);
+		assert_eq!(&buffer[..3], &[0, 0, 0]);
+		assert_eq!(&buffer[3..], &data[..]);
+	}
+
+	#[test]
+	fn resize_pads_and_trims() {
+		let tl = memory_volume();
+		let ino = tl.mknod(1, OsStr::new("test.bin"), driver_objects::FileType::File, file_attr()).unwrap().ino as u64;
+		tl.write(ino, 0, b"hello").unwrap();
+
+		let block_size = tl.superblock().block_size as u64;
+		tl.resize(ino, block_size + 10).unwrap();
+		assert_eq!(tl.filesize(ino).unwrap().bytes, block_size + 10);
+		let mut buffer = vec![0xff; 10];
+		tl.read(ino, block_size - 5, &mut buffer).unwrap();
+		assert_eq!(buffer, vec![0; 10]);
+
+		tl.resize(ino, 3).unwrap();
+		assert_eq!(tl.filesize(ino).unwrap().bytes, 3);
+		let mut buffer = vec![0; 3];
+		tl.read(ino, 0, &mut buffer).unwrap();
+		assert_eq!(&buffer, b"hel");
+	}
+
+	#[test]
+	fn setattr_timestamps() {
+		let tl = memory_volume();
+		let ino = tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, file_attr()).unwrap().ino as u64;
+		let crtime = tl.getattr(ino).unwrap().crtime;
+		let atime = SystemTime::UNIX_EPOCH - std::time::Duration::new(1, 250_000_000);
+		let mtime = SystemTime::UNIX_EPOCH + std::time::Duration::new(1_700_000_000, 123_456_789);
+		let result = tl.setattr(ino, driver_objects::FileSetAttr { atime, mtime, ..file_attr() }).unwrap();
+		assert_eq!(result.atime, atime);
+		assert_eq!(result.mtime, mtime);
+		assert_eq!(result.crtime, crtime);
+
+		tl.access(ino, SystemTime::now()).unwrap();
+		assert!(tl.getattr(ino).unwrap().atime > mtime);
+	}
+
+	#[test]
+	fn unlink_and_format() {
+		let mut tl = memory_volume();
+		let ino = tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, file_attr()).unwrap().ino as u64;
+		tl.write(ino, 0, b"data").unwrap();
+		tl.link(1, OsStr::new("link.txt"), ino).unwrap();
+		assert_eq!(tl.count_hardlinks(ino).unwrap(), 2);
+
+		tl.unlink(1, OsStr::new("test.txt")).unwrap();
+		assert_eq!(tl.lookup_id(OsStr::new("link.txt"), 1).unwrap(), ino);
+
+		tl.format(settings::FILE_BLOCK_SIZE_32, "").unwrap();
+		assert_eq!(tl.readdir(1).unwrap().len(), 2);
+		assert_eq!(tl.statfs().unwrap().used_blocks, 0);
+	}
+
+	#[test]
+	fn database_errors_are_classified() {
+		let tl = memory_volume();
+		tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, file_attr()).unwrap();
+		let err = tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, file_attr()).unwrap_err();
+		assert!(matches!(err, Error::DbConnectorError(DbConnectorError::DuplicateError(_))));
+
+		let path = std::env::temp_dir().join(format!("dbfs-test-{}.db", std::process::id()));
+		let url = format!("sqlite://{}", path.display());
+		TranslationLayer::with_backend(Box::new(SqliteBackend::from_url(&url).unwrap()), super::super::DEFAULT_VOLUME).unwrap();
+		let tl = TranslationLayer::with_backend(Box::new(SqliteBackend::from_url(&format!("{}?mode=ro", url)).unwrap()), super::super::DEFAULT_VOLUME).unwrap();
+		let err = tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, file_attr()).unwrap_err();
+		let _ = std::fs::remove_file(&path);
+		assert!(matches!(err, Error::DbConnectorError(DbConnectorError::ReadOnlyError(_))), "{:?}", err);
+	}
+
+	#[test]
+	fn orphans() {
+		let tl = memory_volume();
+		let ino = tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, file_attr()).unwrap().ino as u64;
+		tl.write(ino, 0, b"data").unwrap();
+		tl.unlink(1, OsStr::new("test.txt")).unwrap();
+		assert_eq!(tl.0.list_orphans(tl.2.id).unwrap(), vec![ino]);
+		let mut buffer = [0u8; 4];
+		tl.read(ino, 0, &mut buffer).unwrap();
+		assert_eq!(&buffer, b"data");
+
+		assert_eq!(tl.reclaim_orphans().unwrap(), 1);
+		assert!(tl.getattr(ino).is_err());
+		assert!(tl.0.list_orphans(tl.2.id).unwrap().is_empty());
+	}
+
+	#[test]
+	fn xattrs() {
+		let tl = memory_volume();
+		let ino = tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, file_attr()).unwrap().ino as u64;
+		let value: Vec<u8> = (0..=255).collect();
+
+		tl.setxattr(ino, OsStr::new("user.b"), &value, driver_objects::SetXattrMode::Create).unwrap();
+		tl.setxattr(ino, OsStr::new("user.a"), &[], driver_objects::SetXattrMode::Set).unwrap();
+		assert_eq!(tl.getxattr(ino, OsStr::new("user.b")).unwrap(), value);
+		assert_eq!(tl.getxattr(ino, OsStr::new("user.a")).unwrap(), Vec::<u8>::new());
+		assert_eq!(tl.listxattr(ino).unwrap(), vec!["user.a", "user.b"]);
+
+		tl.setxattr(ino, OsStr::new("user.b"), b"new", driver_objects::SetXattrMode::Replace).unwrap();
+		assert_eq!(tl.getxattr(ino, OsStr::new("user.b")).unwrap(), b"new");
+
+		// Attributes go with the inode
+		tl.unlink(1, OsStr::new("test.txt")).unwrap();
+		tl.reclaim(ino).unwrap();
+		assert_eq!(tl.0.list_xattrs(ino).unwrap(), Vec::<String>::new());
+	}
+}
@@ -0,0 +1,212 @@
+//! Cache of the data blocks read from the database
+//!
+//! Sequential reads ask for much less than a block at a time, so the blocks they fetch are kept
+//! here and the least recently used ones are evicted once the cache is full. Writes, resizes and
+//! deleted inodes invalidate the blocks of their inode.
+
+use super::driver_objects::CacheStats;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+
+#[derive(Debug)]
+pub struct BlockCache {
+	/// Bytes of block data the cache holds at most
+	size: usize,
+	state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+	/// `(inode_id, block_id) -> block`
+	blocks: BTreeMap<(u64, u64), Entry>,
+	/// `last use -> (inode_id, block_id)`, the least recently used block first
+	uses: BTreeMap<u64, (u64, u64)>,
+	/// Counts the uses of blocks
+	clock: u64,
+	/// Changed by every invalidation, blocks read from the database before it are not cached
+	generation: u64,
+	stats: CacheStats,
+}
+
+#[derive(Debug)]
+struct Entry {
+	data: Arc<Vec<u8>>,
+	/// Value of the clock when the block was last used
+	used: u64,
+}
+
+impl State {
+	fn touch(&mut self, key: (u64, u64)) {
+		self.clock += 1;
+		if let Some(entry) = self.blocks.get_mut(&key) {
+			self.uses.remove(&entry.used);
+			entry.used = self.clock;
+			self.uses.insert(self.clock, key);
+		}
+	}
+
+	fn remove(&mut self, key: (u64, u64)) {
+		if let Some(entry) = self.blocks.remove(&key) {
+			self.uses.remove(&entry.used);
+			self.stats.bytes -= entry.data.len() as u64;
+		}
+	}
+}
+
+impl BlockCache {
+	/// `size` is the number of bytes of block data the cache holds at most
+	pub fn new(size: usize) -> Self {
+		Self { size, state: Mutex::new(State::default()) }
+	}
+
+
+	fn state(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+
+
+	/// `count` consecutive blocks of an inode starting at `first_block`, if all of them are cached
+	///
+	/// A block shorter than `block_size` is the last block of the inode, the blocks end there.
+	pub fn get(&self, inode: u64, first_block: u64, count: u64, block_size: usize) -> Option<Vec<Arc<Vec<u8>>>> {
+		let mut state = self.state();
+		let mut blocks = Vec::new();
+		for block in first_block..first_block + count {
+			let Some(data) = state.blocks.get(&(inode, block)).map(|entry| entry.data.clone()) else {
+				state.stats.misses += 1;
+				return None
+			};
+			state.touch((inode, block));
+			let last = data.len() < block_size;
+			blocks.push(data);
+			if last { break; }
+		}
+		state.stats.hits += 1;
+		Some(blocks)
+	}
+
+
+	/// The current generation, to be passed to [`BlockCache::insert`] with the blocks read afterwards
+	pub fn generation(&self) -> u64 {
+		self.state().generation
+	}
+
+
+	/// Cache consecutive blocks of an inode starting at `first_block` (read from the database in
+	/// `generation`), dropping the least recently used blocks to make room
+	pub fn insert(&self, inode: u64, first_block: u64, blocks: &[Arc<Vec<u8>>], generation: u64) {
+		let mut state = self.state();
+		// The blocks may have been changed since they were read
+		if state.generation != generation { return; }
+
+		for (block, data) in (first_block..).zip(blocks) {
+			if data.len() > self.size { continue; }
+			state.remove((inode, block));
+			state.clock += 1;
+			let used = state.clock;
+			state.blocks.insert((inode, block), Entry { data: data.clone(), used });
+			state.uses.insert(used, (inode, block));
+			state.stats.bytes += data.len() as u64;
+		}
+
+		while state.stats.bytes > self.size as u64 {
+			let Some(&key) = state.uses.values().next() else { break };
+			state.remove(key);
+			state.stats.evictions += 1;
+		}
+	}
+
+
+	/// Drop the cached blocks of an inode
+	pub fn invalidate(&self, inode: u64) {
+		let mut state = self.state();
+		state.generation += 1;
+		let keys: Vec<(u64, u64)> = state.blocks.range((inode, 0)..=(inode, u64::MAX)).map(|(key, _)| *key).collect();
+		for key in keys {
+			state.remove(key);
+		}
+	}
+
+
+	/// Drop all cached blocks
+	pub fn clear(&self) {
+		let mut state = self.state();
+		state.generation += 1;
+		state.blocks.clear();
+		state.uses.clear();
+		state.stats.bytes = 0;
+	}
+
+
+	pub fn stats(&self) -> CacheStats {
+		self.state().stats
+	}
+}
+
+
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+
+	fn blocks(data: &[&[u8]]) -> Vec<Arc<Vec<u8>>> {
+		data.iter().map(|block| Arc::new(block.to_vec())).collect()
+	}
+
+
+	#[test]
+	fn hits_and_misses() {
+		let cache = BlockCache::new(1024);
+		assert_eq!(cache.get(2, 0, 1, 4), None);
+		cache.insert(2, 0, &blocks(&[b"aaaa", b"bbbb", b"cc"]), cache.generation());
+
+		assert_eq!(cache.get(2, 1, 1, 4), Some(blocks(&[b"bbbb"])));
+		assert_eq!(cache.get(2, 0, 2, 4), Some(blocks(&[b"aaaa", b"bbbb"])));
+		// The short block is the last one
+		assert_eq!(cache.get(2, 1, 5, 4), Some(blocks(&[b"bbbb", b"cc"])));
+		assert_eq!(cache.get(3, 0, 1, 4), None);
+		assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 2, evictions: 0, bytes: 10 });
+		assert_eq!(cache.stats().hit_rate(), 0.6);
+	}
+
+	#[test]
+	fn least_recently_used_blocks_are_evicted() {
+		let cache = BlockCache::new(12);
+		cache.insert(2, 0, &blocks(&[b"aaaa", b"bbbb", b"cccc"]), cache.generation());
+		assert!(cache.get(2, 0, 1, 4).is_some());
+
+		cache.insert(3, 0, &blocks(&[b"dddd"]), cache.generation());
+		assert_eq!(cache.get(2, 1, 1, 4), None);
+		assert!(cache.get(2, 0, 1, 4).is_some());
+		assert!(cache.get(2, 2, 1, 4).is_some());
+		assert!(cache.get(3, 0, 1, 4).is_some());
+		assert_eq!(cache.stats().evictions, 1);
+		assert_eq!(cache.stats().bytes, 12);
+
+		// Blocks larger than the cache are not cached at all
+		cache.insert(4, 0, &blocks(&[&[0; 13]]), cache.generation());
+		assert_eq!(cache.get(4, 0, 1, 16), None);
+		assert_eq!(cache.stats().bytes, 12);
+	}
+
+	#[test]
+	fn invalidation() {
+		let cache = BlockCache::new(1024);
+		cache.insert(2, 0, &blocks(&[b"aaaa", b"bb"]), cache.generation());
+		cache.insert(3, 0, &blocks(&[b"cc"]), cache.generation());
+
+		// Blocks read before the invalidation are outdated
+		let generation = cache.generation();
+		cache.invalidate(2);
+		cache.insert(2, 0, &blocks(&[b"aaaa", b"bb"]), generation);
+		assert_eq!(cache.get(2, 0, 1, 4), None);
+		assert!(cache.get(3, 0, 1, 4).is_some());
+		assert_eq!(cache.stats().bytes, 2);
+
+		cache.clear();
+		assert_eq!(cache.get(3, 0, 1, 4), None);
+		assert_eq!(cache.stats().bytes, 0);
+	}
+}
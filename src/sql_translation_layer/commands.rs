@@ -1,10 +1,6 @@
 //! SQL Command for use in `sql_translation_layer` module
 
 
-use const_format::formatcp;
-use crate::settings;
-
-
 /// # Binds
 ///
 /// # Columns
@@ -34,31 +30,16 @@ pub const SQL_INSERT_GROUP: &'static str = r#"INSERT INTO `group` (`id`, `name`)
 
 
 /// # Binds
+/// - `block_size`
 /// - `inode_id`
 ///
 /// # Columns
 /// - `bytes`
 /// - `blocks`
-pub const SQL_GET_FILE_SIZE: &'static str = formatcp!(r#"SELECT
-	(`block_id` - 1) * {block_size} + OCTET_LENGTH(`data`) as `bytes`,
+pub const SQL_GET_FILE_SIZE: &'static str = r#"SELECT
+	(`block_id` - 1) * ? + OCTET_LENGTH(`data`) as `bytes`,
 	`block_id` as `blocks`
-FROM `block` WHERE `inode_id` = ? ORDER BY `block_id` DESC LIMIT 1"#, block_size=settings::FILE_BLOCK_SIZE);
-
-
-/// # Binds
-/// - `inode_id`
-///
-/// # Columns
-/// - `bytes`
-/// - `blocks`
-/// - `last_block_id`
-pub const SQL_GET_SIZE_AND_HEAD: &'static str = formatcp!(r#"WITH `ino` AS (SELECT ? AS `ino`), `file_tmp` (`blocks`) AS (
-    SELECT COUNT(*) FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`)
-) SELECT
-    `blocks` * {block_size} - (SELECT {block_size} - OCTET_LENGTH(`data`) FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`) ORDER BY `block_id` DESC LIMIT 1) AS bytes,
-    `blocks` AS blocks,
-    (IFNULL((SELECT `block_id` FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`) ORDER BY `block_id` DESC LIMIT 1), CAST(0 AS UNSIGNED))) AS `last_block_id`
-FROM `file_tmp`"#, block_size=settings::FILE_BLOCK_SIZE);
+FROM `block` WHERE `inode_id` = ? ORDER BY `block_id` DESC LIMIT 1"#;
 
 
 /// # Binds
@@ -126,36 +107,69 @@ pub const SQL_GET_INODE: &'static str = r#"SELECT * FROM `inode` WHERE `id` = ?"
 /// # Binds
 /// - `owner`
 /// - `group`
-/// - `accessed_at`
-/// - `modified_at`
-/// - `created_at`
 /// - `special_bits`
 /// - `user_perm`
 /// - `group_perm`
 /// - `other_perm`
+/// - `atime`
+/// - `atime_nsec`
+/// - `mtime`
+/// - `mtime_nsec`
+/// - `ctime`
+/// - `ctime_nsec`
 /// - `id`
 pub const SQL_UPDATE_INODE: &'static str = r#"UPDATE `inode` SET
 `owner` = ?,
 `group` = ?,
-`accessed_at` = ?,
-`modified_at` = ?,
-`created_at` = ?,
 `special_bits` = ?,
 `user_perm` = ?,
 `group_perm` = ?,
-`other_perm` = ?
+`other_perm` = ?,
+`atime` = ?,
+`atime_nsec` = ?,
+`mtime` = ?,
+`mtime_nsec` = ?,
+`ctime` = ?,
+`ctime_nsec` = ?
 WHERE `id` = ?"#;
 
 
 /// # Binds
+/// - `mtime`
+/// - `mtime_nsec`
+/// - `ctime`
+/// - `ctime_nsec`
 /// - `id`
 pub const SQL_UPDATE_INODE_CTIME_MTIME: &'static str = r#"UPDATE `inode` SET
-`modified_at` = CURRENT_TIMESTAMP(),
-`created_at` = CURRENT_TIMESTAMP()
+`mtime` = ?,
+`mtime_nsec` = ?,
+`ctime` = ?,
+`ctime_nsec` = ?
+WHERE `id` = ?"#;
+
+
+/// # Binds
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE_CTIME: &'static str = r#"UPDATE `inode` SET
+`ctime` = ?,
+`ctime_nsec` = ?
+WHERE `id` = ?"#;
+
+
+/// # Binds
+/// - `atime`
+/// - `atime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE_ATIME: &'static str = r#"UPDATE `inode` SET
+`atime` = ?,
+`atime_nsec` = ?
 WHERE `id` = ?"#;
 
 
 /// # Binds
+/// - `volume_id`
 /// - `owner`
 /// - `group`
 /// - `file_type`
@@ -163,12 +177,21 @@ WHERE `id` = ?"#;
 /// - `user_perm`
 /// - `group_perm`
 /// - `other_perm`
+/// - `rdev`
+/// - `atime`
+/// - `atime_nsec`
+/// - `mtime`
+/// - `mtime_nsec`
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `crtime`
+/// - `crtime_nsec`
 ///
 /// # Columns
 /// - `id`
 pub const SQL_CREATE_INODE: &'static str = r#"INSERT INTO `inode`
-(`owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`)
-VALUES (?, ?, ?, ?, ?, ?, ?)"#;
+(`volume_id`, `owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`, `rdev`, `atime`, `atime_nsec`, `mtime`, `mtime_nsec`, `ctime`, `ctime_nsec`, `crtime`, `crtime_nsec`)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#;
 
 
 /// # Binds
@@ -213,13 +236,15 @@ pub const SQL_LOOKUP_INODE_ID: &'static str = r#"SELECT `inode_id` FROM `file` W
 
 
 /// # Binds
-/// 
+/// - `volume_id`
+/// - `volume_id`
+///
 /// # Columns
 /// - `used_inodes`
 /// - `used_blocks`
 pub const SQL_GET_FS_STAT: &'static str = r#"SELECT
-(SELECT COUNT(*) FROM `inode`) AS `used_inodes`,
-(SELECT COUNT(*) FROM `block`) AS `used_blocks`"#;
+(SELECT COUNT(*) FROM `inode` WHERE `volume_id` = ?) AS `used_inodes`,
+(SELECT COUNT(*) FROM `block` INNER JOIN `inode` ON `inode`.`id` = `block`.`inode_id` WHERE `inode`.`volume_id` = ?) AS `used_blocks`"#;
 
 
 /// # Binds
@@ -243,6 +268,28 @@ pub const SQL_DELETE_FILE: &'static str = r#"DELETE FROM `file` WHERE `name` = ?
 pub const SQL_DELETE_INODE: &'static str = r#"DELETE FROM `inode` WHERE `id` = ?"#;
 
 
+/// # Binds
+/// - `inode_id`
+pub const SQL_CREATE_ORPHAN: &'static str = r#"INSERT INTO `orphan` (`inode_id`) VALUES (?)"#;
+
+
+/// Delete an inode if it is an orphan (its `orphan` row is deleted with it)
+///
+/// # Binds
+/// - `id`
+pub const SQL_DELETE_ORPHAN: &'static str = r#"DELETE FROM `inode` WHERE `id` = ? AND `id` IN (SELECT `inode_id` FROM `orphan`)"#;
+
+
+/// # Binds
+/// - `volume_id`
+///
+/// # Columns
+/// - `inode_id`
+pub const SQL_LIST_ORPHANS: &'static str = r#"SELECT `orphan`.`inode_id` FROM `orphan`
+INNER JOIN `inode` ON `inode`.`id` = `orphan`.`inode_id`
+WHERE `inode`.`volume_id` = ?"#;
+
+
 /// # Binds
 /// - `inode_id`
 /// - `max_blocks`
@@ -286,39 +333,150 @@ pub const SQL_RESIZE_LAST_BLOCK: &'static str = r#"UPDATE `block` SET `data` = R
 
 
 /// # Binds
-/// - `last_block_length`
 /// - `inode_id`
-pub const SQL_PAD_LAST_BLOCK: &'static str = r#"UPDATE `block` SET `data` = RPAD(`data`, ?, CHAR(0)) WHERE `inode_id` = ? ORDER BY `block_id` DESC LIMIT 1"#;
+pub const SQL_DROP_BLOCKS: &'static str = r#"DELETE FROM `block` WHERE `inode_id` = ?"#;
+
+
+/// # Columns
+/// - `tables`
+pub const SQL_SCHEMA_EXISTS: &'static str = r#"SELECT COUNT(*) AS `tables` FROM `information_schema`.`tables` WHERE `table_schema` = DATABASE() AND `table_name` = 'inode'"#;
+
+
+/// # Columns
+/// - `tables`
+pub const SQL_METADATA_EXISTS: &'static str = r#"SELECT COUNT(*) AS `tables` FROM `information_schema`.`tables` WHERE `table_schema` = DATABASE() AND `table_name` = 'metadata'"#;
+
+
+/// # Binds
+/// - `key`
+///
+/// # Columns
+/// - `value`
+pub const SQL_GET_METADATA: &'static str = r#"SELECT `value` FROM `metadata` WHERE `key` = ?"#;
+
+
+/// Schema upgrades, the n-th script upgrades a volume from version n to version n + 1
+pub const SQL_MIGRATIONS: &'static [&'static str] = &[
+    include_str!("../../sql/mysql/migrations/0001_metadata.sql"),
+    include_str!("../../sql/mysql/migrations/0002_superblock.sql"),
+    include_str!("../../sql/mysql/migrations/0003_volumes.sql"),
+    include_str!("../../sql/mysql/migrations/0004_device_numbers.sql"),
+    include_str!("../../sql/mysql/migrations/0005_timestamps.sql"),
+    include_str!("../../sql/mysql/migrations/0006_orphans.sql"),
+];
+
+
+/// # Binds
+/// - `name`
+///
+/// # Columns
+/// - `id`
+/// - `name`
+/// - `root_inode_id`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+pub const SQL_GET_VOLUME: &'static str = r#"SELECT `id`, `name`, `root_inode_id`, `block_size`, `uuid`, `label`, `created_at`, `features` FROM `volume` WHERE `name` = ?"#;
+
+
+/// # Columns
+/// - `id`
+/// - `name`
+/// - `root_inode_id`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+pub const SQL_LIST_VOLUMES: &'static str = r#"SELECT `id`, `name`, `root_inode_id`, `block_size`, `uuid`, `label`, `created_at`, `features` FROM `volume` ORDER BY `id`"#;
+
+
+/// # Binds
+/// - `name`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+pub const SQL_CREATE_VOLUME: &'static str = r#"INSERT INTO `volume` (`name`, `block_size`, `uuid`, `label`, `created_at`, `features`) VALUES (?, ?, ?, ?, ?, ?)"#;
+
+
+/// # Binds
+/// - `root_inode_id`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+/// - `id`
+pub const SQL_UPDATE_VOLUME: &'static str = r#"UPDATE `volume` SET `root_inode_id` = ?, `block_size` = ?, `uuid` = ?, `label` = ?, `created_at` = ?, `features` = ? WHERE `id` = ?"#;
+
+
+/// # Binds
+/// - `id`
+pub const SQL_DELETE_VOLUME: &'static str = r#"DELETE FROM `volume` WHERE `id` = ?"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `key`
+///
+/// # Columns
+/// - `value`
+pub const SQL_GET_XATTR: &'static str = r#"SELECT `value` FROM `extended_attributes` WHERE `inode_id` = ? AND `key` = ?"#;
 
 
 /// # Binds
 /// - `inode_id`
-pub const SQL_DROP_BLOCKS: &'static str = r#"DELETE FROM `block` WHERE `inode_id` = ?"#;
+///
+/// # Columns
+/// - `key`
+pub const SQL_LIST_XATTRS: &'static str = r#"SELECT `key` FROM `extended_attributes` WHERE `inode_id` = ? ORDER BY `key`"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `key`
+/// - `value`
+pub const SQL_CREATE_XATTR: &'static str = r#"INSERT INTO `extended_attributes` (`inode_id`, `key`, `value`) VALUES (?, ?, ?)"#;
+
+
+/// # Binds
+/// - `value`
+/// - `inode_id`
+/// - `key`
+pub const SQL_UPDATE_XATTR: &'static str = r#"UPDATE `extended_attributes` SET `value` = ? WHERE `inode_id` = ? AND `key` = ?"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `key`
+pub const SQL_DELETE_XATTR: &'static str = r#"DELETE FROM `extended_attributes` WHERE `inode_id` = ? AND `key` = ?"#;
+
+
+/// Complete schema (used to initialize an empty database)
+pub const SQL_SCHEMA: &'static str = include_str!("../../sql/mysql/dbfs.sql");
 
 
-pub const SQL_FORMAT_DELETE_SEQUENCE: &'static [&'static str] = &[
-    "DELETE FROM `block`",
-    "DELETE FROM `file`",
-    "DELETE FROM `inode`",
-    "DELETE FROM `user`",
-    "DELETE FROM `group`",
-    "INSERT INTO `user` (`id`, `name`) VALUES (1, 'root'), (1000, 'user')",
-    "INSERT INTO `group` (`id`, `name`) VALUES (1, 'root'), (1000, 'user')",
-    "UPDATE `user` SET `id` = 0 WHERE `name` = 'root'",
-    "UPDATE `group` SET `id` = 0 WHERE `name` = 'root'",
-    "INSERT INTO `inode`
-        (`id`, `owner`, `group`, `file_type`, `special_bits`, `user_perm`, `group_perm`, `other_perm`, `created_at`, `modified_at`, `accessed_at`) VALUES
-        (1, 0, 0, 'd', 0, 7, 5, 5, '2024-10-24 17:52:52', '2024-10-24 17:53:10', '2024-10-24 17:52:52')",
-    "INSERT INTO `file`
-        (`parent_inode_id`, `name`, `inode_id`) VALUES
-        (1, '/', 1)"
+/// Drop all tables (referencing tables first)
+pub const SQL_DROP_SCHEMA: &'static str = "DROP TABLE IF EXISTS `metadata`, `orphan`, `extended_attributes`, `block`, `file`, `inode`, `volume`, `user`, `group`, `special_bits`, `permissions`, `file_types`";
+
+
+/// Delete all inodes, files and blocks of a volume
+///
+/// # Binds
+/// - `volume_id` _for every statement_
+pub const SQL_ERASE_VOLUME_SEQUENCE: &'static [&'static str] = &[
+    "UPDATE `volume` SET `root_inode_id` = NULL WHERE `id` = ?",
+    "DELETE FROM `block` WHERE `inode_id` IN (SELECT `id` FROM `inode` WHERE `volume_id` = ?)",
+    "DELETE FROM `file` WHERE `inode_id` IN (SELECT `id` FROM `inode` WHERE `volume_id` = ?)",
+    "DELETE FROM `inode` WHERE `volume_id` = ?",
 ];
 
 
 pub mod dynamic_queries {
-    use const_format::formatcp;
-    use crate::settings;
-
     /// # Binds
     /// - `data` _for every block_
     pub fn sql_write(inode_id: u64, start_block_id: u64, end_block_id: u64) -> String {
@@ -337,7 +495,7 @@ pub mod dynamic_queries {
         query
     }
 
-    pub fn sql_pad_until(inode_id: u64, start_block_id: u64, until_block_id: u64) -> String {
+    pub fn sql_pad_until(inode_id: u64, start_block_id: u64, until_block_id: u64, block_size: u64) -> String {
         let inode_id = inode_id.to_string();
         let mut query = String::with_capacity(500);
         query.push_str("INSERT INTO `block` (`inode_id`, `block_id`, `data`) VALUES");
@@ -346,24 +504,12 @@ pub mod dynamic_queries {
             query.push_str(&inode_id);
             query.push_str(", ");
             query.push_str(&block_id.to_string());
-            query.push_str(formatcp!(", REPEAT(CHAR(0), {block_size})),", block_size=settings::FILE_BLOCK_SIZE));
+            query.push_str(", REPEAT(CHAR(0), ");
+            query.push_str(&block_size.to_string());
+            query.push_str(")),");
         }
         query.pop();
         query.push_str(" ON DUPLICATE KEY UPDATE `inode_id`=VALUES(`inode_id`), `block_id`=VALUES(`block_id`), `data`=VALUES(`data`)");
         query
     }
-
-    pub fn sql_pad_file(inode_id: u32, last_block_id: u32, count: u32) -> String {
-        let mut query = String::with_capacity(500);
-        query.push_str("INSERT INTO `block` (`inode_id`, `block_id`, `data`) VALUES");
-        for block in 1..=count {
-            query.push_str(" (");
-            query.push_str(&inode_id.to_string());
-            query.push_str(", ");
-            query.push_str(&(last_block_id + block).to_string());
-            query.push_str(formatcp!(", REPEAT(CHAR(0), {block_size})),", block_size=settings::FILE_BLOCK_SIZE));
-        }
-        query.pop();
-        query
-    }
 }
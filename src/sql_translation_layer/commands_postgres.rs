@@ -0,0 +1,527 @@
+//! PostgreSQL variant Commands for use in `sql_translation_layer` module
+//!
+//! PostgreSQL uses numbered binds (`$1`), so a bind used multiple times is only passed once.
+//! All ids are `bigint` and permissions are `smallint` (there are no unsigned types).
+
+
+/// # Binds
+///
+/// # Columns
+/// - `id`
+/// - `name`
+pub const SQL_GET_USERS: &'static str = r#"SELECT "id", "name" FROM "user""#;
+
+
+/// # Binds
+///
+/// # Columns
+/// - `id`
+/// - `name`
+pub const SQL_GET_GROUPS: &'static str = r#"SELECT "id", "name" FROM "group""#;
+
+
+/// # Binds
+/// - `user_id`
+/// - `user_name`
+pub const SQL_INSERT_USER: &'static str = r#"INSERT INTO "user" ("id", "name") VALUES ($1, $2)"#;
+
+
+/// # Binds
+/// - `group_id`
+/// - `group_name`
+pub const SQL_INSERT_GROUP: &'static str = r#"INSERT INTO "group" ("id", "name") VALUES ($1, $2)"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `block_size`
+///
+/// # Columns
+/// - `bytes`
+/// - `blocks`
+pub const SQL_GET_FILE_SIZE: &'static str = r#"SELECT
+	("block_id" - 1) * $2 + OCTET_LENGTH("data") AS "bytes",
+	"block_id" AS "blocks"
+FROM "block" WHERE "inode_id" = $1 ORDER BY "block_id" DESC LIMIT 1"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `start_block_id`
+/// - `end_block_id`
+///
+/// # Columns
+/// - `blocks`
+/// - `start_block_data`
+/// - `end_block_data`
+pub const SQL_GET_SIZE_AND_BLOCK_DATA: &'static str = r#"SELECT
+    (SELECT COUNT(*) FROM "block" WHERE "inode_id" = $1) AS "blocks",
+    COALESCE((SELECT "data" FROM "block" WHERE "inode_id" = $1 ORDER BY "block_id" ASC LIMIT 1 OFFSET $2), '\x'::bytea) AS "start_block_data",
+    COALESCE((SELECT "data" FROM "block" WHERE "inode_id" = $1 ORDER BY "block_id" ASC LIMIT 1 OFFSET $3), '\x'::bytea) AS "end_block_data""#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `block_id`
+///
+/// # Columns
+/// - `blocks`
+/// - `block_data`
+pub const SQL_GET_SIZE_AND_SINGLE_BLOCK_DATA: &'static str = r#"SELECT
+    (SELECT COUNT(*) FROM "block" WHERE "inode_id" = $1) AS "blocks",
+    COALESCE((SELECT "data" FROM "block" WHERE "inode_id" = $1 ORDER BY "block_id" ASC LIMIT 1 OFFSET $2), '\x'::bytea) AS "block_data""#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `blocks`
+pub const SQL_GET_SIZE_ONLY: &'static str = r#"SELECT COUNT(*) AS "blocks" FROM "block" WHERE "inode_id" = $1"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `children_dirs`
+pub const SQL_COUNT_CHILDREN_OF_TYPE_DIRECTORY: &'static str = r#"SELECT COUNT(*) AS "children_dirs" FROM "inode" WHERE "id" IN (SELECT "inode_id" FROM "file" WHERE "parent_inode_id" = $1) AND "id" != $1 AND "file_type" = 'd'"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `hardlinks`
+pub const SQL_COUNT_HARDLINKS: &'static str = r#"SELECT COUNT(*) AS "hardlinks" FROM "file" WHERE "inode_id" = $1"#;
+
+
+/// # Binds
+/// - `parent_inode_id`
+///
+/// # Columns
+/// - `children`
+pub const SQL_COUNT_DIRECTORY_CHILDREN: &'static str = r#"SELECT COUNT(*) AS "children" FROM "file" WHERE "parent_inode_id" = $1"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// _all `inode` fields_
+pub const SQL_GET_INODE: &'static str = r#"SELECT * FROM "inode" WHERE "id" = $1"#;
+
+
+/// # Binds
+/// - `owner`
+/// - `group`
+/// - `special_bits`
+/// - `user_perm`
+/// - `group_perm`
+/// - `other_perm`
+/// - `atime`
+/// - `atime_nsec`
+/// - `mtime`
+/// - `mtime_nsec`
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE: &'static str = r#"UPDATE "inode" SET
+"owner" = $1,
+"group" = $2,
+"special_bits" = $3,
+"user_perm" = $4,
+"group_perm" = $5,
+"other_perm" = $6,
+"atime" = $7,
+"atime_nsec" = $8,
+"mtime" = $9,
+"mtime_nsec" = $10,
+"ctime" = $11,
+"ctime_nsec" = $12
+WHERE "id" = $13"#;
+
+
+/// # Binds
+/// - `mtime`
+/// - `mtime_nsec`
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE_CTIME_MTIME: &'static str = r#"UPDATE "inode" SET
+"mtime" = $1,
+"mtime_nsec" = $2,
+"ctime" = $3,
+"ctime_nsec" = $4
+WHERE "id" = $5"#;
+
+
+/// # Binds
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE_CTIME: &'static str = r#"UPDATE "inode" SET
+"ctime" = $1,
+"ctime_nsec" = $2
+WHERE "id" = $3"#;
+
+
+/// # Binds
+/// - `atime`
+/// - `atime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE_ATIME: &'static str = r#"UPDATE "inode" SET
+"atime" = $1,
+"atime_nsec" = $2
+WHERE "id" = $3"#;
+
+
+/// # Binds
+/// - `volume_id`
+/// - `owner`
+/// - `group`
+/// - `file_type`
+/// - `special_bits`
+/// - `user_perm`
+/// - `group_perm`
+/// - `other_perm`
+/// - `rdev`
+/// - `atime`
+/// - `atime_nsec`
+/// - `mtime`
+/// - `mtime_nsec`
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `crtime`
+/// - `crtime_nsec`
+///
+/// # Columns
+/// - `id`
+pub const SQL_CREATE_INODE: &'static str = r#"INSERT INTO "inode"
+("volume_id", "owner", "group", "file_type", "special_bits", "user_perm", "group_perm", "other_perm", "rdev", "atime", "atime_nsec", "mtime", "mtime_nsec", "ctime", "ctime_nsec", "crtime", "crtime_nsec")
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+RETURNING "id""#;
+
+
+/// # Binds
+/// - `parent_inode_id`
+/// - `name`
+/// - `inode_id`
+pub const SQL_CREATE_FILE: &'static str = r#"INSERT INTO "file"
+("parent_inode_id", "name", "inode_id")
+VALUES ($1, $2, $3)"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+///
+/// - `name`
+/// - `inode_id`
+/// - `file_type`
+pub const SQL_LIST_DIRECTORY: &'static str = r#"SELECT
+    "file"."name" AS "name",
+    "file"."inode_id" AS "inode_id",
+    "inode"."file_type" AS "file_type"
+FROM "file" JOIN "inode" ON "inode"."id" = "file"."inode_id"
+WHERE "file"."parent_inode_id" = $1 AND "file"."inode_id" != $1
+ORDER BY "file"."inode_id", "file"."name""#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `parent_inode_id`
+pub const SQL_GET_DIRECTORY_PARENT: &'static str = r#"SELECT "parent_inode_id" FROM "file" WHERE "inode_id" = $1"#;
+
+
+/// # Binds
+/// - `name`
+/// - `parent_inode_id`
+///
+/// # Columns
+/// - `inode_id`
+pub const SQL_LOOKUP_INODE_ID: &'static str = r#"SELECT "inode_id" FROM "file" WHERE "name" = $1 AND "parent_inode_id" = $2"#;
+
+
+/// # Binds
+/// - `volume_id`
+///
+/// # Columns
+/// - `used_inodes`
+/// - `used_blocks`
+pub const SQL_GET_FS_STAT: &'static str = r#"SELECT
+(SELECT COUNT(*) FROM "inode" WHERE "volume_id" = $1) AS "used_inodes",
+(SELECT COUNT(*) FROM "block" INNER JOIN "inode" ON "inode"."id" = "block"."inode_id" WHERE "inode"."volume_id" = $1) AS "used_blocks""#;
+
+
+/// # Binds
+/// - `dest_parent_inode_id`
+/// - `dest_name`
+/// - `src_parent_inode_id`
+/// - `src_name`
+pub const SQL_RENAME_FILE: &'static str = r#"UPDATE "file"
+SET "parent_inode_id" = $1, "name" = $2
+WHERE "parent_inode_id" = $3 AND "name" = $4"#;
+
+
+/// # Binds
+/// - `name`
+/// - `parent_inode_id`
+pub const SQL_DELETE_FILE: &'static str = r#"DELETE FROM "file" WHERE "name" = $1 AND "parent_inode_id" = $2"#;
+
+
+/// # Binds
+/// - `id`
+pub const SQL_DELETE_INODE: &'static str = r#"DELETE FROM "inode" WHERE "id" = $1"#;
+
+
+/// # Binds
+/// - `inode_id`
+pub const SQL_CREATE_ORPHAN: &'static str = r#"INSERT INTO "orphan" ("inode_id") VALUES ($1)"#;
+
+
+/// Delete an inode if it is an orphan (its `orphan` row is deleted with it)
+///
+/// # Binds
+/// - `id`
+pub const SQL_DELETE_ORPHAN: &'static str = r#"DELETE FROM "inode" WHERE "id" = $1 AND "id" IN (SELECT "inode_id" FROM "orphan")"#;
+
+
+/// # Binds
+/// - `volume_id`
+///
+/// # Columns
+/// - `inode_id`
+pub const SQL_LIST_ORPHANS: &'static str = r#"SELECT "orphan"."inode_id" FROM "orphan"
+INNER JOIN "inode" ON "inode"."id" = "orphan"."inode_id"
+WHERE "inode"."volume_id" = $1"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `max_blocks`
+/// - `offset_blocks`
+///
+/// # Columns
+/// - `data`
+pub const SQL_READ_FILE: &'static str = r#"SELECT "data" FROM "block" WHERE "inode_id" = $1 ORDER BY "block_id" ASC LIMIT $2 OFFSET $3"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `bc` (block count)
+/// - `last_block_id`
+pub const SQL_GET_FILE_HEAD: &'static str = r#"SELECT COUNT(*) AS "bc", COALESCE(MAX("block_id"), 0) AS "last_block_id" FROM "block" WHERE "inode_id" = $1"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `strip_blocks_count`
+pub const SQL_TRIM_BLOCKS: &'static str = r#"DELETE FROM "block" WHERE "inode_id" = $1 AND "block_id" IN (
+    SELECT "block_id" FROM "block" WHERE "inode_id" = $1 ORDER BY "block_id" DESC LIMIT $2
+)"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `last_block_length`
+pub const SQL_RESIZE_LAST_BLOCK: &'static str = r#"UPDATE "block" SET "data" = CASE
+    WHEN OCTET_LENGTH("data") >= $2 THEN SUBSTRING("data" FROM 1 FOR CAST($2 AS integer))
+    ELSE "data" || DECODE(REPEAT('00', CAST($2 AS integer) - OCTET_LENGTH("data")), 'hex')
+END
+WHERE "inode_id" = $1 AND "block_id" = (SELECT MAX("block_id") FROM "block" WHERE "inode_id" = $1)"#;
+
+
+/// # Binds
+/// - `inode_id`
+pub const SQL_DROP_BLOCKS: &'static str = r#"DELETE FROM "block" WHERE "inode_id" = $1"#;
+
+
+/// # Columns
+/// - `tables`
+pub const SQL_SCHEMA_EXISTS: &'static str = r#"SELECT COUNT(*) AS "tables" FROM "information_schema"."tables" WHERE "table_schema" = current_schema() AND "table_name" = 'inode'"#;
+
+
+/// # Columns
+/// - `tables`
+pub const SQL_METADATA_EXISTS: &'static str = r#"SELECT COUNT(*) AS "tables" FROM "information_schema"."tables" WHERE "table_schema" = current_schema() AND "table_name" = 'metadata'"#;
+
+
+/// # Binds
+/// - `key`
+///
+/// # Columns
+/// - `value`
+pub const SQL_GET_METADATA: &'static str = r#"SELECT "value" FROM "metadata" WHERE "key" = $1"#;
+
+
+/// Schema upgrades, the n-th script upgrades a volume from version n to version n + 1
+pub const SQL_MIGRATIONS: &'static [&'static str] = &[
+    include_str!("../../sql/postgres/migrations/0001_metadata.sql"),
+    include_str!("../../sql/postgres/migrations/0002_superblock.sql"),
+    include_str!("../../sql/postgres/migrations/0003_volumes.sql"),
+    include_str!("../../sql/postgres/migrations/0004_device_numbers.sql"),
+    include_str!("../../sql/postgres/migrations/0005_timestamps.sql"),
+    include_str!("../../sql/postgres/migrations/0006_orphans.sql"),
+];
+
+
+/// # Binds
+/// - `name`
+///
+/// # Columns
+/// - `id`
+/// - `name`
+/// - `root_inode_id`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+pub const SQL_GET_VOLUME: &'static str = r#"SELECT "id", "name", "root_inode_id", "block_size", "uuid", "label", "created_at", "features" FROM "volume" WHERE "name" = $1"#;
+
+
+/// # Columns
+/// - `id`
+/// - `name`
+/// - `root_inode_id`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+pub const SQL_LIST_VOLUMES: &'static str = r#"SELECT "id", "name", "root_inode_id", "block_size", "uuid", "label", "created_at", "features" FROM "volume" ORDER BY "id""#;
+
+
+/// # Binds
+/// - `name`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+///
+/// # Columns
+/// - `id`
+pub const SQL_CREATE_VOLUME: &'static str = r#"INSERT INTO "volume" ("name", "block_size", "uuid", "label", "created_at", "features") VALUES ($1, $2, $3, $4, $5, $6) RETURNING "id""#;
+
+
+/// # Binds
+/// - `root_inode_id`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+/// - `id`
+pub const SQL_UPDATE_VOLUME: &'static str = r#"UPDATE "volume" SET "root_inode_id" = $1, "block_size" = $2, "uuid" = $3, "label" = $4, "created_at" = $5, "features" = $6 WHERE "id" = $7"#;
+
+
+/// # Binds
+/// - `id`
+pub const SQL_DELETE_VOLUME: &'static str = r#"DELETE FROM "volume" WHERE "id" = $1"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `key`
+///
+/// # Columns
+/// - `value`
+pub const SQL_GET_XATTR: &'static str = r#"SELECT "value" FROM "extended_attributes" WHERE "inode_id" = $1 AND "key" = $2"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `key`
+pub const SQL_LIST_XATTRS: &'static str = r#"SELECT "key" FROM "extended_attributes" WHERE "inode_id" = $1 ORDER BY "key""#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `key`
+/// - `value`
+pub const SQL_CREATE_XATTR: &'static str = r#"INSERT INTO "extended_attributes" ("inode_id", "key", "value") VALUES ($1, $2, $3)"#;
+
+
+/// # Binds
+/// - `value`
+/// - `inode_id`
+/// - `key`
+pub const SQL_UPDATE_XATTR: &'static str = r#"UPDATE "extended_attributes" SET "value" = $1 WHERE "inode_id" = $2 AND "key" = $3"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `key`
+pub const SQL_DELETE_XATTR: &'static str = r#"DELETE FROM "extended_attributes" WHERE "inode_id" = $1 AND "key" = $2"#;
+
+
+/// Complete schema (tables and static data are only created if missing)
+pub const SQL_SCHEMA: &'static str = include_str!("../../sql/postgres/dbfs.sql");
+
+
+/// Drop all tables (referencing tables first)
+pub const SQL_DROP_SCHEMA: &'static str = r#"DROP TABLE IF EXISTS "metadata", "orphan", "extended_attributes", "block", "file", "inode", "volume", "user", "group", "special_bits", "permissions", "file_types" CASCADE"#;
+
+
+/// Delete all inodes, files and blocks of a volume
+///
+/// # Binds
+/// - `volume_id` _for every statement_
+pub const SQL_ERASE_VOLUME_SEQUENCE: &'static [&'static str] = &[
+    r#"UPDATE "volume" SET "root_inode_id" = NULL WHERE "id" = $1"#,
+    r#"DELETE FROM "block" WHERE "inode_id" IN (SELECT "id" FROM "inode" WHERE "volume_id" = $1)"#,
+    r#"DELETE FROM "file" WHERE "inode_id" IN (SELECT "id" FROM "inode" WHERE "volume_id" = $1)"#,
+    r#"DELETE FROM "inode" WHERE "volume_id" = $1"#,
+];
+
+
+pub mod dynamic_queries {
+    const UPSERT: &'static str = r#" ON CONFLICT ("inode_id", "block_id") DO UPDATE SET "data" = EXCLUDED."data""#;
+
+    /// # Binds
+    /// - `data` _for every block_
+    pub fn sql_write(inode_id: u64, start_block_id: u64, end_block_id: u64) -> String {
+        let inode_id = inode_id.to_string();
+        let mut query = String::with_capacity(500);
+        query.push_str(r#"INSERT INTO "block" ("inode_id", "block_id", "data") VALUES"#);
+        for (bind, block_id) in (start_block_id..=end_block_id).enumerate() {
+            query.push_str(" (");
+            query.push_str(&inode_id);
+            query.push_str(",");
+            query.push_str(&block_id.to_string());
+            query.push_str(",$");
+            query.push_str(&(bind + 1).to_string());
+            query.push_str("),");
+        }
+        query.pop();
+        query.push_str(UPSERT);
+        query
+    }
+
+    pub fn sql_pad_until(inode_id: u64, start_block_id: u64, until_block_id: u64, block_size: u64) -> String {
+        let inode_id = inode_id.to_string();
+        let mut query = String::with_capacity(500);
+        query.push_str(r#"INSERT INTO "block" ("inode_id", "block_id", "data") VALUES"#);
+        for block_id in start_block_id..until_block_id {
+            query.push_str(" (");
+            query.push_str(&inode_id);
+            query.push_str(", ");
+            query.push_str(&block_id.to_string());
+            query.push_str(", DECODE(REPEAT('00', ");
+            query.push_str(&block_size.to_string());
+            query.push_str("), 'hex')),");
+        }
+        query.pop();
+        query.push_str(UPSERT);
+        query
+    }
+}
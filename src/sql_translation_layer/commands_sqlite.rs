@@ -0,0 +1,151 @@
+//! SQLite variant Commands for use in `sql_translation_layer` module
+//!
+//! Only the statements which differ from the MySQL ones in [`super::commands`] are listed here.
+
+
+/// # Binds
+/// - `block_size`
+/// - `inode_id`
+///
+/// # Columns
+/// - `bytes`
+/// - `blocks`
+pub const SQL_GET_FILE_SIZE: &'static str = r#"SELECT
+	(`block_id` - 1) * ? + LENGTH(`data`) as `bytes`,
+	`block_id` as `blocks`
+FROM `block` WHERE `inode_id` = ? ORDER BY `block_id` DESC LIMIT 1"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `start_block_id`
+/// - `end_block_id`
+///
+/// # Columns
+/// - `blocks`
+/// - `start_block_data`
+/// - `end_block_data`
+pub const SQL_GET_SIZE_AND_BLOCK_DATA: &'static str = r#"WITH `ino` AS (SELECT ? AS `ino`) SELECT (SELECT COUNT(*) FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`)) AS `blocks`, IFNULL((SELECT `data` FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`) ORDER BY `block_id` ASC LIMIT 1 OFFSET ?), X'') AS `start_block_data`, IFNULL((SELECT `data` FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`) ORDER BY `block_id` ASC LIMIT 1 OFFSET ?), X'') AS `end_block_data`"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `block_id`
+///
+/// # Columns
+/// - `blocks`
+/// - `block_data`
+pub const SQL_GET_SIZE_AND_SINGLE_BLOCK_DATA: &'static str = r#"WITH `ino` AS (SELECT ? AS `ino`) SELECT (SELECT COUNT(*) FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`)) AS `blocks`, IFNULL((SELECT `data` FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`) ORDER BY `block_id` ASC LIMIT 1 OFFSET ?), X'') AS `block_data`"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `bc` (block count)
+/// - `last_block_id`
+pub const SQL_GET_FILE_HEAD: &'static str = r#"WITH `ino` AS (SELECT ? AS `ino`) SELECT COUNT(*) AS `bc`, IFNULL(MAX(`block_id`), 0) AS `last_block_id` FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`)"#;
+
+
+/// SQLite has no `DELETE ... ORDER BY ... LIMIT` (unless compiled with it)
+///
+/// # Binds
+/// - `inode_id`
+/// - `strip_blocks_count`
+pub const SQL_TRIM_BLOCKS: &'static str = r#"WITH `ino` AS (SELECT ? AS `ino`) DELETE FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`) AND `block_id` IN (
+    SELECT `block_id` FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `ino`) ORDER BY `block_id` DESC LIMIT ?
+)"#;
+
+
+/// SQLite has no `RPAD` - the block is zero-padded with `zeroblob` instead.
+/// The result of `||` is text, so it has to be cast back to a blob.
+///
+/// # Binds
+/// - `inode_id`
+/// - `last_block_length`
+pub const SQL_RESIZE_LAST_BLOCK: &'static str = r#"WITH `args` AS (SELECT ? AS `ino`, ? AS `len`) UPDATE `block` SET `data` = CASE
+    WHEN LENGTH(`data`) >= (SELECT `len` FROM `args`) THEN SUBSTR(`data`, 1, (SELECT `len` FROM `args`))
+    ELSE CAST(`data` || zeroblob((SELECT `len` FROM `args`) - LENGTH(`data`)) AS BLOB)
+END
+WHERE `inode_id` = (SELECT `ino` FROM `args`) AND `block_id` = (SELECT MAX(`block_id`) FROM `block` WHERE `inode_id` = (SELECT `ino` FROM `args`))"#;
+
+
+/// # Columns
+/// - `tables`
+pub const SQL_SCHEMA_EXISTS: &'static str = r#"SELECT COUNT(*) AS `tables` FROM `sqlite_master` WHERE `type` = 'table' AND `name` = 'inode'"#;
+
+
+/// # Columns
+/// - `tables`
+pub const SQL_METADATA_EXISTS: &'static str = r#"SELECT COUNT(*) AS `tables` FROM `sqlite_master` WHERE `type` = 'table' AND `name` = 'metadata'"#;
+
+
+/// Schema upgrades, the n-th script upgrades a volume from version n to version n + 1
+pub const SQL_MIGRATIONS: &'static [&'static str] = &[
+    include_str!("../../sql/sqlite/migrations/0001_metadata.sql"),
+    include_str!("../../sql/sqlite/migrations/0002_superblock.sql"),
+    include_str!("../../sql/sqlite/migrations/0003_volumes.sql"),
+    include_str!("../../sql/sqlite/migrations/0004_device_numbers.sql"),
+    include_str!("../../sql/sqlite/migrations/0005_timestamps.sql"),
+    include_str!("../../sql/sqlite/migrations/0006_orphans.sql"),
+];
+
+
+/// Complete schema (used to initialize a new database file)
+pub const SQL_SCHEMA: &'static str = include_str!("../../sql/sqlite/dbfs.sql");
+
+
+/// Drop all tables (referencing tables first)
+pub const SQL_DROP_SCHEMA: &'static str = "DROP TABLE IF EXISTS `metadata`;
+DROP TABLE IF EXISTS `orphan`;
+DROP TABLE IF EXISTS `extended_attributes`;
+DROP TABLE IF EXISTS `block`;
+DROP TABLE IF EXISTS `file`;
+DROP TABLE IF EXISTS `inode`;
+DROP TABLE IF EXISTS `volume`;
+DROP TABLE IF EXISTS `user`;
+DROP TABLE IF EXISTS `group`;
+DROP TABLE IF EXISTS `special_bits`;
+DROP TABLE IF EXISTS `permissions`;
+DROP TABLE IF EXISTS `file_types`;";
+
+
+pub mod dynamic_queries {
+    const UPSERT: &'static str = " ON CONFLICT (`inode_id`, `block_id`) DO UPDATE SET `data` = excluded.`data`";
+
+    /// # Binds
+    /// - `data` _for every block_
+    pub fn sql_write(inode_id: u64, start_block_id: u64, end_block_id: u64) -> String {
+        let inode_id = inode_id.to_string();
+        let mut query = String::with_capacity(500);
+        query.push_str("INSERT INTO `block` (`inode_id`, `block_id`, `data`) VALUES");
+        for block_id in start_block_id..=end_block_id {
+            query.push_str(" (");
+            query.push_str(&inode_id);
+            query.push_str(",");
+            query.push_str(&block_id.to_string());
+            query.push_str(",?),");
+        }
+        query.pop();
+        query.push_str(UPSERT);
+        query
+    }
+
+    pub fn sql_pad_until(inode_id: u64, start_block_id: u64, until_block_id: u64, block_size: u64) -> String {
+        let inode_id = inode_id.to_string();
+        let mut query = String::with_capacity(500);
+        query.push_str("INSERT INTO `block` (`inode_id`, `block_id`, `data`) VALUES");
+        for block_id in start_block_id..until_block_id {
+            query.push_str(" (");
+            query.push_str(&inode_id);
+            query.push_str(", ");
+            query.push_str(&block_id.to_string());
+            query.push_str(", zeroblob(");
+            query.push_str(&block_size.to_string());
+            query.push_str(")),");
+        }
+        query.pop();
+        query.push_str(UPSERT);
+        query
+    }
+}
@@ -1,50 +1,593 @@
 //! Microsoft SQL variant Commands for use in `sql_translation_layer` module
+//!
+//! Binds are positional (`@p1`, `@p2`, ...), so a bind used multiple times is only passed once.
+//!
+//! The MSSQL driver can not transfer binary data, dates or `(max)` sized values. Because of that:
+//! - block data is read as hex chunks of [`READ_CHUNK_SIZE`] bytes (`CONVERT(varchar(8000), ..., 2)`)
+//! - block data is written as hex binds of [`WRITE_CHUNK_SIZE`] bytes (`CONVERT(varbinary(max), @p1, 2)`)
+//! - timestamps are transferred as ISO 8601 strings (`CONVERT(..., 126)`)
 
 
 use const_format::formatcp;
-use crate::settings;
+
+
+/// Maximum number of bytes of block data per returned column (8000 hex characters)
+pub const READ_CHUNK_SIZE: u64 = 4000;
+
+/// Maximum number of bytes of block data per bind (4000 hex characters = 8000 bytes of `nvarchar`)
+pub const WRITE_CHUNK_SIZE: usize = 2000;
+
+/// Maximum number of binds of a single statement
+pub const MAX_BINDS: usize = 2000;
+
+
+
+/// # Binds
+///
+/// # Columns
+/// - `id`
+/// - `name`
+pub const SQL_GET_USERS: &'static str = r#"SELECT [id], [name] FROM [user]"#;
+
+
+/// # Binds
+///
+/// # Columns
+/// - `id`
+/// - `name`
+pub const SQL_GET_GROUPS: &'static str = r#"SELECT [id], [name] FROM [group]"#;
+
+
+/// # Binds
+/// - `user_id`
+/// - `user_name`
+pub const SQL_INSERT_USER: &'static str = r#"INSERT INTO [user] ([id], [name]) VALUES (@p1, @p2)"#;
+
+
+/// # Binds
+/// - `group_id`
+/// - `group_name`
+pub const SQL_INSERT_GROUP: &'static str = r#"INSERT INTO [group] ([id], [name]) VALUES (@p1, @p2)"#;
 
 
 /// # Binds
 /// - `inode_id`
+/// - `block_size`
 ///
 /// # Columns
 /// - `bytes`
 /// - `blocks`
-pub const SQL_GET_FILE_SIZE: &'static str = formatcp!(r#"SELECT
-    ([block_id] - 1) * {block_size} + DATALENGTH([data]) as [bytes],
+pub const SQL_GET_FILE_SIZE: &'static str = r#"SELECT
+    ([block_id] - 1) * @p2 + DATALENGTH([data]) as [bytes],
     [block_id] as [blocks]
-FROM [block] WHERE [inode_id] = @inode_id 
-ORDER BY [block_id] DESC 
-OFFSET 0 ROWS FETCH FIRST 1 ROW ONLY"#, block_size=settings::FILE_BLOCK_SIZE);
+FROM [block] WHERE [inode_id] = @p1
+ORDER BY [block_id] DESC
+OFFSET 0 ROWS FETCH FIRST 1 ROW ONLY"#;
 
 
 /// # Binds
 /// - `inode_id`
 ///
 /// # Columns
-/// - `bytes`
 /// - `blocks`
+pub const SQL_GET_SIZE_ONLY: &'static str = r#"SELECT COUNT_BIG(*) AS [blocks] FROM [block] WHERE [inode_id] = @p1"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `children_dirs`
+pub const SQL_COUNT_CHILDREN_OF_TYPE_DIRECTORY: &'static str = r#"SELECT COUNT_BIG(*) AS [children_dirs] FROM [inode] WHERE [id] IN (SELECT [inode_id] FROM [file] WHERE [parent_inode_id] = @p1) AND [id] != @p1 AND [file_type] = 'd'"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `hardlinks`
+pub const SQL_COUNT_HARDLINKS: &'static str = r#"SELECT COUNT_BIG(*) AS [hardlinks] FROM [file] WHERE [inode_id] = @p1"#;
+
+
+/// # Binds
+/// - `parent_inode_id`
+///
+/// # Columns
+/// - `children`
+pub const SQL_COUNT_DIRECTORY_CHILDREN: &'static str = r#"SELECT COUNT_BIG(*) AS [children] FROM [file] WHERE [parent_inode_id] = @p1"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// _all `inode` fields_
+pub const SQL_GET_INODE: &'static str = r#"SELECT
+    [id], [owner], [group], [file_type], [special_bits], [user_perm], [group_perm], [other_perm], [rdev],
+    [atime], [atime_nsec], [mtime], [mtime_nsec], [ctime], [ctime_nsec], [crtime], [crtime_nsec]
+FROM [inode] WHERE [id] = @p1"#;
+
+
+/// # Binds
+/// - `owner`
+/// - `group`
+/// - `special_bits`
+/// - `user_perm`
+/// - `group_perm`
+/// - `other_perm`
+/// - `atime`
+/// - `atime_nsec`
+/// - `mtime`
+/// - `mtime_nsec`
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE: &'static str = r#"UPDATE [inode] SET
+[owner] = @p1,
+[group] = @p2,
+[special_bits] = @p3,
+[user_perm] = @p4,
+[group_perm] = @p5,
+[other_perm] = @p6,
+[atime] = @p7,
+[atime_nsec] = @p8,
+[mtime] = @p9,
+[mtime_nsec] = @p10,
+[ctime] = @p11,
+[ctime_nsec] = @p12
+WHERE [id] = @p13"#;
+
+
+/// # Binds
+/// - `mtime`
+/// - `mtime_nsec`
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE_CTIME_MTIME: &'static str = r#"UPDATE [inode] SET
+[mtime] = @p1,
+[mtime_nsec] = @p2,
+[ctime] = @p3,
+[ctime_nsec] = @p4
+WHERE [id] = @p5"#;
+
+
+/// # Binds
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE_CTIME: &'static str = r#"UPDATE [inode] SET
+[ctime] = @p1,
+[ctime_nsec] = @p2
+WHERE [id] = @p3"#;
+
+
+/// # Binds
+/// - `atime`
+/// - `atime_nsec`
+/// - `id`
+pub const SQL_UPDATE_INODE_ATIME: &'static str = r#"UPDATE [inode] SET
+[atime] = @p1,
+[atime_nsec] = @p2
+WHERE [id] = @p3"#;
+
+
+/// # Binds
+/// - `volume_id`
+/// - `owner`
+/// - `group`
+/// - `file_type`
+/// - `special_bits`
+/// - `user_perm`
+/// - `group_perm`
+/// - `other_perm`
+/// - `rdev`
+/// - `atime`
+/// - `atime_nsec`
+/// - `mtime`
+/// - `mtime_nsec`
+/// - `ctime`
+/// - `ctime_nsec`
+/// - `crtime`
+/// - `crtime_nsec`
+///
+/// # Columns
+/// - `id`
+pub const SQL_CREATE_INODE: &'static str = r#"INSERT INTO [inode]
+([volume_id], [owner], [group], [file_type], [special_bits], [user_perm], [group_perm], [other_perm], [rdev], [atime], [atime_nsec], [mtime], [mtime_nsec], [ctime], [ctime_nsec], [crtime], [crtime_nsec])
+OUTPUT INSERTED.[id]
+VALUES (@p1, @p2, @p3, @p4, @p5, @p6, @p7, @p8, @p9, @p10, @p11, @p12, @p13, @p14, @p15, @p16, @p17)"#;
+
+
+/// # Binds
+/// - `parent_inode_id`
+/// - `name`
+/// - `inode_id`
+pub const SQL_CREATE_FILE: &'static str = r#"INSERT INTO [file]
+([parent_inode_id], [name], [inode_id])
+VALUES (@p1, @p2, @p3)"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+///
+/// - `name`
+/// - `inode_id`
+/// - `file_type`
+pub const SQL_LIST_DIRECTORY: &'static str = r#"SELECT
+    [file].[name] AS [name],
+    [file].[inode_id] AS [inode_id],
+    [inode].[file_type] AS [file_type]
+FROM [file] JOIN [inode] ON [inode].[id] = [file].[inode_id]
+WHERE [file].[parent_inode_id] = @p1 AND [file].[inode_id] != @p1
+ORDER BY [file].[inode_id], [file].[name]"#;
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `parent_inode_id`
+pub const SQL_GET_DIRECTORY_PARENT: &'static str = r#"SELECT [parent_inode_id] FROM [file] WHERE [inode_id] = @p1"#;
+
+
+/// # Binds
+/// - `name`
+/// - `parent_inode_id`
+///
+/// # Columns
+/// - `inode_id`
+pub const SQL_LOOKUP_INODE_ID: &'static str = r#"SELECT [inode_id] FROM [file] WHERE [name] = @p1 AND [parent_inode_id] = @p2"#;
+
+
+/// # Binds
+/// - `volume_id`
+///
+/// # Columns
+/// - `used_inodes`
+/// - `used_blocks`
+pub const SQL_GET_FS_STAT: &'static str = r#"SELECT
+(SELECT COUNT_BIG(*) FROM [inode] WHERE [volume_id] = @p1) AS [used_inodes],
+(SELECT COUNT_BIG(*) FROM [block] INNER JOIN [inode] ON [inode].[id] = [block].[inode_id] WHERE [inode].[volume_id] = @p1) AS [used_blocks]"#;
+
+
+/// # Binds
+/// - `dest_parent_inode_id`
+/// - `dest_name`
+/// - `src_parent_inode_id`
+/// - `src_name`
+pub const SQL_RENAME_FILE: &'static str = r#"UPDATE [file]
+SET [parent_inode_id] = @p1, [name] = @p2
+WHERE [parent_inode_id] = @p3 AND [name] = @p4"#;
+
+
+/// # Binds
+/// - `name`
+/// - `parent_inode_id`
+pub const SQL_DELETE_FILE: &'static str = r#"DELETE FROM [file] WHERE [name] = @p1 AND [parent_inode_id] = @p2"#;
+
+
+/// # Binds
+/// - `id`
+pub const SQL_DELETE_INODE: &'static str = r#"DELETE FROM [inode] WHERE [id] = @p1"#;
+
+
+/// # Binds
+/// - `inode_id`
+pub const SQL_CREATE_ORPHAN: &'static str = r#"INSERT INTO [orphan] ([inode_id]) VALUES (@p1)"#;
+
+
+/// Delete an inode if it is an orphan (its `orphan` row is deleted with it)
+///
+/// # Binds
+/// - `id`
+pub const SQL_DELETE_ORPHAN: &'static str = r#"DELETE FROM [inode] WHERE [id] = @p1 AND [id] IN (SELECT [inode_id] FROM [orphan])"#;
+
+
+/// # Binds
+/// - `volume_id`
+///
+/// # Columns
+/// - `inode_id`
+pub const SQL_LIST_ORPHANS: &'static str = r#"SELECT [orphan].[inode_id] FROM [orphan]
+INNER JOIN [inode] ON [inode].[id] = [orphan].[inode_id]
+WHERE [inode].[volume_id] = @p1"#;
+
+
+/// Every block is split into chunks of [`READ_CHUNK_SIZE`] bytes (empty blocks have one empty
+/// chunk). The number of chunks is taken from the largest block, so it does not depend on the
+/// block size of the volume.
+///
+/// # Binds
+/// - `inode_id`
+/// - `max_blocks`
+/// - `offset_blocks`
+///
+/// # Columns
+/// - `block_id`
+/// - `chunk`
+/// - `data` (hex)
+pub const SQL_READ_FILE: &'static str = formatcp!(r#"WITH [blocks] AS (
+    SELECT [block_id], [data] FROM [block] WHERE [inode_id] = @p1
+    ORDER BY [block_id] ASC
+    OFFSET @p3 ROWS FETCH NEXT @p2 ROWS ONLY
+), [chunks] ([n], [count]) AS (
+    SELECT 0, (SELECT (MAX(DATALENGTH([data])) + {chunk_size} - 1) / {chunk_size} FROM [blocks])
+    UNION ALL SELECT [n] + 1, [count] FROM [chunks] WHERE [n] + 1 < [count]
+) SELECT
+    [block_id],
+    [n] AS [chunk],
+    CONVERT(varchar(8000), SUBSTRING([data], [n] * {chunk_size} + 1, {chunk_size}), 2) AS [data]
+FROM [blocks] JOIN [chunks] ON [n] = 0 OR [n] * {chunk_size} < DATALENGTH([data])
+ORDER BY [block_id], [n]
+OPTION (MAXRECURSION 0)"#, chunk_size=READ_CHUNK_SIZE);
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `bc` (block count)
 /// - `last_block_id`
-pub const SQL_GET_SIZE_AND_HEAD: &'static str = formatcp!(r#"WITH [ino] AS (SELECT @inode_id AS [ino]), [file_tmp] ([blocks]) AS (
-    SELECT COUNT(*) FROM [block] WHERE [inode_id] = (SELECT [ino] FROM [ino])
+pub const SQL_GET_FILE_HEAD: &'static str = r#"SELECT COUNT_BIG(*) AS [bc], ISNULL(MAX([block_id]), CAST(0 AS BIGINT)) AS [last_block_id] FROM [block] WHERE [inode_id] = @p1"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `strip_blocks_count`
+pub const SQL_TRIM_BLOCKS: &'static str = r#"DELETE FROM [block] WHERE [inode_id] = @p1 AND [block_id] IN (
+    SELECT TOP (@p2) [block_id] FROM [block] WHERE [inode_id] = @p1 ORDER BY [block_id] DESC
+)"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `last_block_length`
+pub const SQL_RESIZE_LAST_BLOCK: &'static str = r#"UPDATE [block] SET [data] = CASE
+    WHEN DATALENGTH([data]) >= @p2 THEN SUBSTRING([data], 1, @p2)
+    ELSE [data] + CONVERT(varbinary(max), REPLICATE(CONVERT(varchar(max), '00'), @p2 - DATALENGTH([data])), 2)
+END
+WHERE [inode_id] = @p1 AND [block_id] = (SELECT MAX([block_id]) FROM [block] WHERE [inode_id] = @p1)"#;
+
+
+/// # Binds
+/// - `inode_id`
+pub const SQL_DROP_BLOCKS: &'static str = r#"DELETE FROM [block] WHERE [inode_id] = @p1"#;
+
+
+/// # Columns
+/// - `tables`
+pub const SQL_SCHEMA_EXISTS: &'static str = r#"SELECT COUNT_BIG(*) AS [tables] FROM [INFORMATION_SCHEMA].[TABLES] WHERE [TABLE_SCHEMA] = SCHEMA_NAME() AND [TABLE_NAME] = 'inode'"#;
+
+
+/// # Columns
+/// - `tables`
+pub const SQL_METADATA_EXISTS: &'static str = r#"SELECT COUNT_BIG(*) AS [tables] FROM [INFORMATION_SCHEMA].[TABLES] WHERE [TABLE_SCHEMA] = SCHEMA_NAME() AND [TABLE_NAME] = 'metadata'"#;
+
+
+/// # Binds
+/// - `key`
+///
+/// # Columns
+/// - `value`
+pub const SQL_GET_METADATA: &'static str = r#"SELECT [value] FROM [metadata] WHERE [key] = @p1"#;
+
+
+/// Schema upgrades, the n-th script upgrades a volume from version n to version n + 1
+pub const SQL_MIGRATIONS: &'static [&'static str] = &[
+    include_str!("../../sql/mssql/migrations/0001_metadata.sql"),
+    include_str!("../../sql/mssql/migrations/0002_superblock.sql"),
+    include_str!("../../sql/mssql/migrations/0003_volumes.sql"),
+    include_str!("../../sql/mssql/migrations/0004_device_numbers.sql"),
+    include_str!("../../sql/mssql/migrations/0005_timestamps.sql"),
+    include_str!("../../sql/mssql/migrations/0006_orphans.sql"),
+];
+
+
+/// # Binds
+/// - `name`
+///
+/// # Columns
+/// - `id`
+/// - `name`
+/// - `root_inode_id`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+pub const SQL_GET_VOLUME: &'static str = r#"SELECT
+    [id], [name], [root_inode_id], [block_size], [uuid], [label],
+    CONVERT(varchar(33), [created_at], 126) AS [created_at],
+    [features]
+FROM [volume] WHERE [name] = @p1"#;
+
+
+/// # Columns
+/// - `id`
+/// - `name`
+/// - `root_inode_id`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+pub const SQL_LIST_VOLUMES: &'static str = r#"SELECT
+    [id], [name], [root_inode_id], [block_size], [uuid], [label],
+    CONVERT(varchar(33), [created_at], 126) AS [created_at],
+    [features]
+FROM [volume] ORDER BY [id]"#;
+
+
+/// # Binds
+/// - `name`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+///
+/// # Columns
+/// - `id`
+pub const SQL_CREATE_VOLUME: &'static str = r#"INSERT INTO [volume] ([name], [block_size], [uuid], [label], [created_at], [features]) OUTPUT INSERTED.[id] VALUES (@p1, @p2, @p3, @p4, CONVERT(datetime2, @p5, 126), @p6)"#;
+
+
+/// # Binds
+/// - `root_inode_id`
+/// - `block_size`
+/// - `uuid`
+/// - `label`
+/// - `created_at`
+/// - `features`
+/// - `id`
+pub const SQL_UPDATE_VOLUME: &'static str = r#"UPDATE [volume] SET [root_inode_id] = @p1, [block_size] = @p2, [uuid] = @p3, [label] = @p4, [created_at] = CONVERT(datetime2, @p5, 126), [features] = @p6 WHERE [id] = @p7"#;
+
+
+/// # Binds
+/// - `id`
+pub const SQL_DELETE_VOLUME: &'static str = r#"DELETE FROM [volume] WHERE [id] = @p1"#;
+
+
+/// The value is split into chunks of [`READ_CHUNK_SIZE`] bytes like the blocks of
+/// [`SQL_READ_FILE`] (an empty value has one empty chunk, a missing attribute none).
+///
+/// # Binds
+/// - `inode_id`
+/// - `key`
+///
+/// # Columns
+/// - `chunk`
+/// - `data` (hex)
+pub const SQL_GET_XATTR: &'static str = formatcp!(r#"WITH [attribute] AS (
+    SELECT [value] FROM [extended_attributes] WHERE [inode_id] = @p1 AND [key] = @p2
+), [chunks] ([n], [count]) AS (
+    SELECT 0, (SELECT (DATALENGTH([value]) + {chunk_size} - 1) / {chunk_size} FROM [attribute])
+    UNION ALL SELECT [n] + 1, [count] FROM [chunks] WHERE [n] + 1 < [count]
 ) SELECT
-    [blocks] * {block_size} - (
-        SELECT {block_size} - DATALENGTH([data]) 
-        FROM [block] 
-        WHERE [inode_id] = (SELECT [ino] FROM [ino]) 
-        ORDER BY [block_id] DESC 
-        OFFSET 0 ROWS FETCH FIRST 1 ROW ONLY
-    ) AS [bytes],
-    [blocks] AS [blocks],
-    ISNULL(
-        (
-            SELECT [block_id] 
-            FROM [block] 
-            WHERE [inode_id] = (SELECT [ino] FROM [ino]) 
-            ORDER BY [block_id] DESC 
-            OFFSET 0 ROWS FETCH FIRST 1 ROW ONLY
-        ), 
-        CAST(0 AS BIGINT)
-    ) AS [last_block_id]
-FROM [file_tmp]"#, block_size=settings::FILE_BLOCK_SIZE);
+    [n] AS [chunk],
+    CONVERT(varchar(8000), SUBSTRING([value], [n] * {chunk_size} + 1, {chunk_size}), 2) AS [data]
+FROM [attribute] JOIN [chunks] ON [n] = 0 OR [n] * {chunk_size} < DATALENGTH([value])
+ORDER BY [n]
+OPTION (MAXRECURSION 0)"#, chunk_size=READ_CHUNK_SIZE);
+
+
+/// # Binds
+/// - `inode_id`
+///
+/// # Columns
+/// - `key`
+pub const SQL_LIST_XATTRS: &'static str = r#"SELECT [key] FROM [extended_attributes] WHERE [inode_id] = @p1 ORDER BY [key]"#;
+
+
+/// # Binds
+/// - `inode_id`
+/// - `key`
+pub const SQL_DELETE_XATTR: &'static str = r#"DELETE FROM [extended_attributes] WHERE [inode_id] = @p1 AND [key] = @p2"#;
+
+
+/// Complete schema (tables and static data are only created if missing)
+pub const SQL_SCHEMA: &'static str = include_str!("../../sql/mssql/dbfs.sql");
+
+
+/// Drop all tables (referencing tables first)
+pub const SQL_DROP_SCHEMA: &'static str = "DROP TABLE IF EXISTS [metadata], [orphan], [extended_attributes], [block], [file], [inode], [volume], [user], [group], [special_bits], [permissions], [file_types]";
+
+
+/// Delete all inodes, files and blocks of a volume
+///
+/// # Binds
+/// - `volume_id` _for every statement_
+pub const SQL_ERASE_VOLUME_SEQUENCE: &'static [&'static str] = &[
+    "UPDATE [volume] SET [root_inode_id] = NULL WHERE [id] = @p1",
+    "DELETE FROM [block] WHERE [inode_id] IN (SELECT [id] FROM [inode] WHERE [volume_id] = @p1)",
+    "DELETE FROM [file] WHERE [inode_id] IN (SELECT [id] FROM [inode] WHERE [volume_id] = @p1)",
+    "DELETE FROM [inode] WHERE [volume_id] = @p1",
+];
+
+
+pub mod dynamic_queries {
+    const MERGE_ON: &'static str = " AS [source] ([inode_id], [block_id], [data]) ON [target].[inode_id] = [source].[inode_id] AND [target].[block_id] = [source].[block_id]
+WHEN MATCHED THEN UPDATE SET [data] = [source].[data]
+WHEN NOT MATCHED THEN INSERT ([inode_id], [block_id], [data]) VALUES ([source].[inode_id], [source].[block_id], [source].[data]);";
+
+    /// `chunks` holds the number of hex binds of every written block (at least one per block)
+    ///
+    /// # Binds
+    /// - `data` _for every chunk of every block (hex)_
+    pub fn sql_write(inode_id: u64, start_block_id: u64, chunks: &[usize]) -> String {
+        let inode_id = inode_id.to_string();
+        let mut bind = 0;
+        let mut query = String::with_capacity(500);
+        query.push_str("MERGE [block] WITH (HOLDLOCK) AS [target] USING (VALUES");
+        for (block_id, chunk_count) in (start_block_id..).zip(chunks.iter()) {
+            query.push_str(" (");
+            query.push_str(&inode_id);
+            query.push_str(", ");
+            query.push_str(&block_id.to_string());
+            query.push_str(", ");
+            for _ in 0..*chunk_count {
+                bind += 1;
+                query.push_str("CONVERT(varbinary(max), @p");
+                query.push_str(&bind.to_string());
+                query.push_str(", 2) + ");
+            }
+            query.truncate(query.len() - 3);
+            query.push_str("),");
+        }
+        query.pop();
+        query.push_str(")");
+        query.push_str(MERGE_ON);
+        query
+    }
+
+    /// Concatenation of `chunks` hex binds starting at `@p{first_bind}` as one `varbinary(max)`
+    fn hex_chunks(first_bind: usize, chunks: usize) -> String {
+        (first_bind..first_bind + usize::max(chunks, 1))
+            .map(|bind| format!("CONVERT(varbinary(max), @p{}, 2)", bind))
+            .collect::<Vec<String>>()
+            .join(" + ")
+    }
+
+    /// `chunks` is the number of hex binds of the value (at least one)
+    ///
+    /// # Binds
+    /// - `inode_id`
+    /// - `key`
+    /// - `value` _for every chunk (hex)_
+    pub fn sql_create_xattr(chunks: usize) -> String {
+        format!("INSERT INTO [extended_attributes] ([inode_id], [key], [value]) VALUES (@p1, @p2, {})", hex_chunks(3, chunks))
+    }
+
+    /// `chunks` is the number of hex binds of the value (at least one)
+    ///
+    /// # Binds
+    /// - `inode_id`
+    /// - `key`
+    /// - `value` _for every chunk (hex)_
+    pub fn sql_update_xattr(chunks: usize) -> String {
+        format!("UPDATE [extended_attributes] SET [value] = {} WHERE [inode_id] = @p1 AND [key] = @p2", hex_chunks(3, chunks))
+    }
+
+    pub fn sql_pad_until(inode_id: u64, start_block_id: u64, until_block_id: u64, block_size: u64) -> String {
+        let inode_id = inode_id.to_string();
+        let mut query = String::with_capacity(500);
+        query.push_str("MERGE [block] WITH (HOLDLOCK) AS [target] USING (VALUES");
+        for block_id in start_block_id..until_block_id {
+            query.push_str(" (");
+            query.push_str(&inode_id);
+            query.push_str(", ");
+            query.push_str(&block_id.to_string());
+            query.push_str(", ");
+            query.push_str("CONVERT(varbinary(max), REPLICATE(CONVERT(varchar(max), '00'), ");
+            query.push_str(&block_size.to_string());
+            query.push_str("), 2)");
+            query.push_str("),");
+        }
+        query.pop();
+        query.push_str(")");
+        query.push_str(MERGE_ON);
+        query
+    }
+}
@@ -4,6 +4,8 @@ pub enum FileType {
 	SymbolicLink,
 	NamedPipe,
 	Socket,
+	BlockDevice,
+	CharDevice,
 	Unknown,
 }
 impl From<&String> for FileType {
@@ -14,6 +16,8 @@ impl From<&String> for FileType {
 			"l" => Self::SymbolicLink,
 			"p" => Self::NamedPipe,
 			"s" => Self::Socket,
+			"b" => Self::BlockDevice,
+			"c" => Self::CharDevice,
  			_ => Self::Unknown,
 		}
 	}
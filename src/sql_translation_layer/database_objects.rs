@@ -30,7 +30,7 @@ pub struct DirectoryChildrenDirectory {
 }
 
 
-#[derive(Debug, PartialEq, FromRow)]
+#[derive(Debug, PartialEq, FromRow, Clone)]
 pub struct Inode {
     pub id: u32,
     pub owner: u32,
@@ -40,9 +40,15 @@ pub struct Inode {
     pub user_perm: u8,
     pub group_perm: u8,
     pub other_perm: u8,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-    pub modified_at: chrono::DateTime<chrono::Utc>,
-    pub accessed_at: chrono::DateTime<chrono::Utc>,
+    pub rdev: u32,
+    pub atime: i64,
+    pub atime_nsec: u32,
+    pub mtime: i64,
+    pub mtime_nsec: u32,
+    pub ctime: i64,
+    pub ctime_nsec: u32,
+    pub crtime: i64,
+    pub crtime_nsec: u32,
 }
 
 
@@ -121,6 +127,18 @@ pub struct FileWriteInfoSizeOnly {
 }
 
 
+#[derive(Debug, PartialEq, FromRow)]
+pub struct XattrValue {
+    pub value: Vec<u8>,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+pub struct XattrKey {
+    pub key: String,
+}
+
+
 #[derive(Debug, PartialEq, FromRow)]
 pub struct User {
     pub id: u32,
@@ -133,3 +151,28 @@ pub struct Group {
     pub id: u32,
     pub name: String,
 }
+
+
+#[derive(Debug, PartialEq, FromRow)]
+pub struct TableCount {
+    pub tables: i64,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+pub struct MetadataValue {
+    pub value: String,
+}
+
+
+#[derive(Debug, PartialEq, FromRow)]
+pub struct Volume {
+    pub id: u32,
+    pub name: String,
+    pub root_inode_id: Option<u32>,
+    pub block_size: u32,
+    pub uuid: String,
+    pub label: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub features: u64,
+}
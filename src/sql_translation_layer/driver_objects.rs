@@ -4,7 +4,7 @@
 
 
 use std::ffi::OsString;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use super::{database_enums, database_objects};
 
@@ -16,7 +16,9 @@ pub enum FileType {
 	Directory,
 	Symlink,
 	NamedPipe,
-	Socket
+	Socket,
+	BlockDevice,
+	CharDevice,
 }
 impl TryFrom<database_enums::FileType> for FileType {
 	type Error = super::Error;
@@ -28,6 +30,8 @@ impl TryFrom<database_enums::FileType> for FileType {
 			database_enums::FileType::Directory => Self::Directory,
 			database_enums::FileType::NamedPipe => Self::NamedPipe,
 			database_enums::FileType::Socket => Self::Socket,
+			database_enums::FileType::BlockDevice => Self::BlockDevice,
+			database_enums::FileType::CharDevice => Self::CharDevice,
 			database_enums::FileType::Unknown => Err(super::Error::RuntimeError("unknown filetype"))?,
 		})
 	}
@@ -39,7 +43,9 @@ impl Into<String> for FileType {
 			FileType::Directory => "d".to_string(),
 			FileType::Symlink => "l".to_string(),
 			FileType::NamedPipe => "p".to_string(),
-			FileType::Socket => "s".to_string()
+			FileType::Socket => "s".to_string(),
+			FileType::BlockDevice => "b".to_string(),
+			FileType::CharDevice => "c".to_string(),
 		}
 	}
 }
@@ -77,7 +83,7 @@ impl TryFrom<&database_objects::DirectoryEntry> for DirectoryEntry {
 ///
 /// # Values in fields
 /// The values in fields in this struct are the sum of all permissions for the user or group.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Permissions {
 	pub special: u8,
 	pub owner: u8,
@@ -86,8 +92,43 @@ pub struct Permissions {
 }
 
 
+/// Inode timestamp, stored as seconds and nanoseconds since the Unix epoch
+///
+/// Seconds are signed, so times before 1970 are negative (with non-negative nanoseconds).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Timestamp {
+	pub sec: i64,
+	pub nsec: u32,
+}
+impl From<SystemTime> for Timestamp {
+	fn from(value: SystemTime) -> Self {
+		match value.duration_since(SystemTime::UNIX_EPOCH) {
+			Ok(after) => Self { sec: after.as_secs().try_into().unwrap_or(i64::MAX), nsec: after.subsec_nanos() },
+			Err(err) => {
+				let before = err.duration();
+				let sec = -i64::try_from(before.as_secs()).unwrap_or(i64::MAX);
+				match before.subsec_nanos() {
+					0 => Self { sec, nsec: 0 },
+					nsec => Self { sec: sec - 1, nsec: 1_000_000_000 - nsec },
+				}
+			}
+		}
+	}
+}
+impl Into<SystemTime> for Timestamp {
+	fn into(self) -> SystemTime {
+		let nsec = Duration::from_nanos(self.nsec.into());
+		let time = match self.sec >= 0 {
+			true => SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(self.sec as u64)),
+			false => SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(self.sec.unsigned_abs())),
+		};
+		time.and_then(|time| time.checked_add(nsec)).unwrap_or(SystemTime::UNIX_EPOCH)
+	}
+}
+
+
 /// Database supported `FileAttr`ibute
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FileAttr {
 	/// Inode id
 	pub ino: u32,
@@ -105,12 +146,16 @@ pub struct FileAttr {
 	pub atime: SystemTime,
 	/// Time of last modification
 	pub mtime: SystemTime,
-	/// Time of creation
+	/// Time of last status change
 	pub ctime: SystemTime,
+	/// Time of creation
+	pub crtime: SystemTime,
 	/// Kind of file (see [`FileType`] for more info)
 	pub kind: FileType,
 	/// User + Group permissions (see [`Permissions`] for more info)
 	pub perm: Permissions,
+	/// Device number of block and character devices (Linux encoding, 0 for other files)
+	pub rdev: u32,
 }
 
 
@@ -128,8 +173,10 @@ pub struct FileSetAttr {
 	pub atime: SystemTime,
 	/// Time of last modification
 	pub mtime: SystemTime,
-	/// Time of creation
+	/// Time of last status change
 	pub ctime: SystemTime,
+	/// Time of creation (only set when the inode is created)
+	pub crtime: SystemTime,
 	/// User + Group permissions (see [`Permissions`] for more info)
 	pub perm: Permissions,
 }
@@ -153,6 +200,70 @@ impl Into<FileSize> for database_objects::FileSize {
 }
 
 
+/// How `rename` treats an existing (or missing) destination
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenameMode {
+	/// Replace the destination if it exists
+	Replace,
+	/// Fail if the destination exists (`RENAME_NOREPLACE`)
+	NoReplace,
+	/// Swap the source and the destination, which has to exist (`RENAME_EXCHANGE`)
+	Exchange,
+}
+
+
+/// How `setxattr` treats an existing (or missing) attribute
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SetXattrMode {
+	/// Create the attribute or overwrite its value
+	Set,
+	/// Fail if the attribute already exists (`XATTR_CREATE`)
+	Create,
+	/// Fail if the attribute does not exist (`XATTR_REPLACE`)
+	Replace,
+}
+
+
+/// Reads within this interval of the last access time do not update it under [`AtimePolicy::Strict`]
+pub const STRICT_ATIME_INTERVAL: Duration = Duration::from_secs(1);
+
+/// When reading an inode updates its access time (the `strictatime`, `relatime` and `noatime`
+/// mount options)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AtimePolicy {
+	/// Update the access time on every read (at most once per [`STRICT_ATIME_INTERVAL`])
+	Strict,
+	/// Update the access time if it is older than the modification or change time, or than a day
+	Relative,
+	/// Never update the access time
+	Never,
+}
+impl AtimePolicy {
+	/// Whether reading the inode with the attributes `attr` at `time` updates its access time
+	pub fn update(&self, attr: &FileAttr, time: SystemTime) -> bool {
+		match self {
+			// An access time in the future is updated too
+			Self::Strict => time.duration_since(attr.atime).map_or(true, |age| age >= STRICT_ATIME_INTERVAL),
+			Self::Relative => attr.atime <= attr.mtime || attr.atime <= attr.ctime
+				|| time.duration_since(attr.atime).is_ok_and(|age| age >= Duration::from_secs(24 * 60 * 60)),
+			Self::Never => false,
+		}
+	}
+}
+impl std::str::FromStr for AtimePolicy {
+	type Err = super::Error;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		match value {
+			"strictatime" => Ok(Self::Strict),
+			"relatime" => Ok(Self::Relative),
+			"noatime" => Ok(Self::Never),
+			_ => Err(super::Error::ClientError("unknown atime policy (strictatime, relatime or noatime)")),
+		}
+	}
+}
+
+
 /// Filesystem statistics structure
 #[derive(Debug, PartialEq)]
 pub struct FilesystemStat {
@@ -160,3 +271,70 @@ pub struct FilesystemStat {
 	pub used_inodes: u64
 }
 
+
+/// Statistics of the block read cache (see [`super::TranslationLayer::set_read_cache`])
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct CacheStats {
+	/// Reads served from the cache
+	pub hits: u64,
+	/// Reads which had to fetch blocks from the database
+	pub misses: u64,
+	/// Blocks dropped to make room for others
+	pub evictions: u64,
+	/// Bytes of block data in the cache
+	pub bytes: u64,
+}
+impl CacheStats {
+	/// Share of the reads served from the cache (0 without reads)
+	pub fn hit_rate(&self) -> f64 {
+		match self.hits + self.misses {
+			0 => 0.0,
+			reads => self.hits as f64 / reads as f64,
+		}
+	}
+}
+
+
+
+/// Volume-wide settings (stored with the volume in the `volume` table)
+#[derive(Debug, PartialEq, Clone)]
+pub struct Superblock {
+	/// Size of every block except the last one of a file (in bytes)
+	pub block_size: u32,
+	/// Volume UUID (hyphenated, lowercase)
+	pub uuid: String,
+	/// Volume label (may be empty)
+	pub label: String,
+	/// Time the volume was formatted
+	pub created_at: SystemTime,
+	/// Features used by the volume (see [`super::SUPPORTED_FEATURES`])
+	pub features: u64,
+}
+
+
+/// A filesystem stored in the database next to other volumes
+#[derive(Debug, PartialEq, Clone)]
+pub struct Volume {
+	pub id: u64,
+	/// Unique name of the volume (`--volume` on the command line)
+	pub name: String,
+	/// Inode id of the root directory (`None` until the volume is formatted)
+	pub root_inode: Option<u64>,
+	pub superblock: Superblock,
+}
+impl From<database_objects::Volume> for Volume {
+	fn from(value: database_objects::Volume) -> Self {
+		Self {
+			id: value.id.into(),
+			name: value.name,
+			root_inode: value.root_inode_id.map(|val| val.into()),
+			superblock: Superblock {
+				block_size: value.block_size,
+				uuid: value.uuid,
+				label: value.label,
+				created_at: value.created_at.into(),
+				features: value.features,
+			},
+		}
+	}
+}
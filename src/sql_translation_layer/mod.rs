@@ -1,32 +1,70 @@
+mod acl;
+mod block_cache;
 mod database_objects;
 mod database_enums;
 pub mod driver_objects;
+pub mod backend;
 mod commands;
+mod commands_postgres;
+mod commands_sqlite;
+mod commands_tsql;
 mod passwd_table;
+mod retry;
+#[cfg(test)]
+pub mod test_util;
 
 
-use crate::{db_connector::chrono, settings};
+use crate::config::ConnectionConfig;
+use crate::settings;
 
 
 const CONN_LOCK_FAILED: &'static str = "could not lock onto the database connection (this could be a synchronization error)";
 const PASSWD_LOCK_FAILED: &'static str = "could not lock onto the local passwd table object";
 const DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE: &'static str = "could not convert database's i64 to u32 for the driver";
-const DRU64_TO_DBU32_CONVERSION_ERROR_MESSAGE: &'static str = "could not convert driver's u64 to u32 for the database";
-const OOB_WRITE: &'static str = "write is possibly out of bounds";
 
 
 /// Maximum allowed file name length. Taken from the `dbfs.sql` init script.
 pub const MAX_NAME_LEN: u32 = 255;
 
+/// Maximum length of an extended attribute name (in bytes)
+pub const MAX_XATTR_NAME_LEN: usize = 255;
+
+/// Maximum size of an extended attribute value (in bytes)
+pub const MAX_XATTR_SIZE: usize = 65536;
+
+/// Smallest block size a volume can be formatted with
+pub const MIN_BLOCK_SIZE: u32 = 512;
+
+/// Largest block size a volume can be formatted with
+pub const MAX_BLOCK_SIZE: u32 = 1 << 20;
+
+/// Superblock feature flags understood by this version (volumes using any other are refused)
+pub const SUPPORTED_FEATURES: u64 = 0;
+
+/// Inode id of the root directory as seen through the driver (whatever its id in the database)
+pub const ROOT_INODE: u64 = 1;
+
 
-use database_objects::{DirectoryChildrenDirectory, FileHardlinks, FileSize, FileSizeAndHead, Inode};
-use std::sync::Mutex;
-use crate::db_connector::{DbConnector, DbConnectorError};
+use std::sync::{Arc, Mutex};
+use crate::db_connector::DbConnectorError;
 
+use self::backend::Backend;
+use self::block_cache::BlockCache;
 use self::passwd_table::PasswdTable;
+use self::retry::Retry;
 
 
-pub struct TranslationLayer (Mutex<DbConnector>, Mutex<PasswdTable>);
+/// Filesystem operations on one volume
+///
+/// The layer is shared by the FUSE worker threads, so the operations only take `&self` (the
+/// backends draw their connections from a pool). Every operation which modifies the volume runs in
+/// one database transaction, so it is either applied completely or not at all.
+///
+/// Operations failing because the database is unreachable or because of a conflict with another
+/// transaction (deadlock, ...) are run again (see [`TranslationLayer::set_retry`]).
+///
+/// Blocks read from the database can be kept in a cache (see [`TranslationLayer::set_read_cache`]).
+pub struct TranslationLayer (Arc<dyn Backend>, Arc<Mutex<PasswdTable>>, Arc<driver_objects::Volume>, Option<Arc<Retry>>, Option<Arc<BlockCache>>);
 
 
 #[derive(Debug)]
@@ -36,8 +74,30 @@ pub enum Error {
 	RuntimeError(&'static str),
 	NotFoundError(&'static str),
 	ClientError(&'static str),
+	AlreadyExistsError(&'static str),
+	NotADirectoryError(&'static str),
+	IsADirectoryError(&'static str),
+	NotEmptyError(&'static str),
+	AccessError(&'static str),
+	NameTooLongError,
+	NoAttributeError(&'static str),
+	RangeError(&'static str),
+	TooBigError(&'static str),
+	SchemaVersionError(u32),
 	Unimplemented,
 }
+impl Error {
+	/// Whether the operation failed because of a lost connection or a conflict with another
+	/// transaction (see [`DbConnectorError::is_transient`])
+	pub fn is_transient(&self) -> bool {
+		matches!(self, Self::DbConnectorError(err) if err.is_transient())
+	}
+
+	/// Whether the database could not be reached
+	pub fn is_connection_error(&self) -> bool {
+		matches!(self, Self::DbConnectorError(DbConnectorError::ConnectionError(_)))
+	}
+}
 impl From<DbConnectorError> for Error {
 	fn from(value: DbConnectorError) -> Self {
 		Self::DbConnectorError(value)
@@ -51,12 +111,90 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 			Self::RuntimeError(val) => val.to_string(),
 			Self::NotFoundError(val) => val.to_string(),
 			Self::ClientError(val) => val.to_string(),
+			Self::AlreadyExistsError(val) => val.to_string(),
+			Self::NotADirectoryError(val) => val.to_string(),
+			Self::IsADirectoryError(val) => val.to_string(),
+			Self::NotEmptyError(val) => val.to_string(),
+			Self::AccessError(val) => val.to_string(),
+			Self::NameTooLongError => format!("file names can be at most {} bytes long", MAX_NAME_LEN),
+			Self::NoAttributeError(val) => val.to_string(),
+			Self::RangeError(val) => val.to_string(),
+			Self::TooBigError(val) => val.to_string(),
+			Self::SchemaVersionError(val) if *val < backend::SCHEMA_VERSION => format!("the volume has schema version {}, but version {} is required (run `dbfs migrate` to upgrade it)", val, backend::SCHEMA_VERSION),
+			Self::SchemaVersionError(val) => format!("the volume has schema version {}, which is newer than the supported version {} (upgrade dbfs)", val, backend::SCHEMA_VERSION),
 			Self::Unimplemented => "method isn't implemented yet".to_string(),
 		})
 	}
 }
 
 
+/// Block sizes must be powers of two between [`MIN_BLOCK_SIZE`] and [`MAX_BLOCK_SIZE`]
+fn valid_block_size(block_size: u32) -> bool {
+	block_size.is_power_of_two() && (MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&block_size)
+}
+
+
+/// Convert a file name for the database, refusing names longer than [`MAX_NAME_LEN`] bytes
+fn file_name(name: &std::ffi::OsStr) -> Result<&str, Error> {
+	let name = name.to_str().ok_or(Error::RuntimeError("could not parse path"))?;
+	if name.len() > MAX_NAME_LEN as usize {
+		return Err(Error::NameTooLongError);
+	}
+	Ok(name)
+}
+
+
+/// Convert an extended attribute name for the database, refusing names longer than
+/// [`MAX_XATTR_NAME_LEN`] bytes
+fn xattr_name(name: &std::ffi::OsStr) -> Result<&str, Error> {
+	let name = name.to_str().ok_or(Error::RuntimeError("could not parse the attribute name"))?;
+	if name.is_empty() || name.len() > MAX_XATTR_NAME_LEN {
+		return Err(Error::RangeError("attribute names must be between 1 and 255 bytes long"));
+	}
+	Ok(name)
+}
+
+
+/// Read a volume and refuse volumes this version can not work with
+fn load_volume(backend: &dyn Backend, name: &str) -> Result<driver_objects::Volume, Error> {
+	let volume = backend.get_volume(name)?.ok_or(Error::NotFoundError("no volume with this name exists (see dbfs volumes)"))?;
+	if volume.root_inode.is_none() {
+		return Err(Error::NotFoundError("the volume has no root directory (run dbfs format)"));
+	}
+	if !valid_block_size(volume.superblock.block_size) {
+		return Err(Error::RuntimeError("the block size of the volume is not supported"));
+	}
+	if volume.superblock.features & !SUPPORTED_FEATURES != 0 {
+		return Err(Error::RuntimeError("the volume uses features which are not supported by this version of dbfs"));
+	}
+	Ok(volume)
+}
+
+
+/// Create the superblock of a new volume (with a new UUID)
+///
+/// # Inputs
+/// `block_size: u32` is the block size of the new volume (a power of two between
+/// [`MIN_BLOCK_SIZE`] and [`MAX_BLOCK_SIZE`])
+/// `label: &str` is the label of the new volume
+pub fn new_superblock(block_size: u32, label: &str) -> Result<driver_objects::Superblock, Error> {
+	if !valid_block_size(block_size) {
+		return Err(Error::ClientError("the block size must be a power of two between 512 and 1048576"));
+	}
+	if label.chars().count() > MAX_NAME_LEN as usize {
+		return Err(Error::ClientError("the label is too long"));
+	}
+
+	Ok(driver_objects::Superblock {
+		block_size,
+		uuid: uuid::Uuid::new_v4().to_string(),
+		label: String::from(label),
+		created_at: std::time::SystemTime::now(),
+		features: 0,
+	})
+}
+
+
 /// Will try to clone a part of one slice into a part of other slice
 /// If anything is outside of range - it will cancel the operation
 /// If the two slices have different sizes - it will panic.
@@ -68,16 +206,180 @@ macro_rules! try_slice_from_slice {
 
 
 impl TranslationLayer {
-	/// Create a [`TranslationLayer`] object and use the defaults from [`crate::settings`] to
-	/// login to the database
-	pub fn new() -> Result<Self, Error> {
-		let dbconn = Mutex::new(DbConnector::default()?);
-		let passwdtab = Mutex::new(PasswdTable::new(&dbconn)?);
+	/// Create a [`TranslationLayer`] object and use the given [`ConnectionConfig`] to login to the
+	/// database (the backend is chosen by the scheme of the connection URL)
+	///
+	/// # Inputs
+	/// `volume: &str` is the name of the volume to open
+	pub fn new(config: &ConnectionConfig, volume: &str) -> Result<Self, Error> {
+		let backend = backend::open(config)?;
+		backend::check_schema(backend.as_ref())?;
+		Self::with_backend(backend, volume)
+	}
+
+
+	/// Create a [`TranslationLayer`] object like [`TranslationLayer::new`], but create the database
+	/// schema and the volume first if they do not exist yet (used before formatting a new volume)
+	pub fn new_with_schema(config: &ConnectionConfig, volume: &str) -> Result<Self, Error> {
+		let backend = backend::open(config)?;
+		backend.create_schema()?;
+		backend::check_schema(backend.as_ref())?;
+		if backend.get_volume(volume)?.is_none() {
+			backend::create_volume(backend.as_ref(), volume, &new_superblock(settings::FILE_BLOCK_SIZE_32, "")?)?;
+		}
+		Self::with_backend(backend, volume)
+	}
+
+
+	/// Create a [`TranslationLayer`] object on top of an already created [`Backend`]
+	pub fn with_backend(backend: Box<dyn Backend>, volume: &str) -> Result<Self, Error> {
+		let volume = load_volume(backend.as_ref(), volume)?;
+		let passwdtab = Arc::new(Mutex::new(PasswdTable::new(backend.as_ref())?));
 		Ok(Self (
-			dbconn,
+			Arc::from(backend),
 			passwdtab,
+			Arc::new(volume),
+			Some(Arc::new(Retry::new(Some(std::time::Duration::from_secs(settings::DB_RETRY_TIMEOUT_SECS))))),
+			None,
 		))
 	}
+
+
+	/// Set how long operations failing with transient database errors are retried
+	///
+	/// # Inputs
+	/// `timeout: Option<Duration>` is the time after which the last error is returned (a soft
+	/// mount), `None` retries until the database is reachable again (a hard mount)
+	pub fn set_retry(&mut self, timeout: Option<std::time::Duration>) {
+		self.3 = Some(Arc::new(Retry::new(timeout)));
+	}
+
+
+	/// A copy of the layer which fails right away when the database is unreachable, instead of
+	/// retrying the operations
+	pub fn once(&self) -> Self {
+		Self(self.0.clone(), self.1.clone(), self.2.clone(), None, self.4.clone())
+	}
+
+
+	/// Keep up to `size` bytes of the blocks read from the database in memory, the least recently
+	/// used blocks are dropped first (0 disables the cache)
+	///
+	/// Blocks are dropped when this layer writes, resizes or reclaims their inode, changes made
+	/// by other clients of the database are not seen until the blocks are evicted.
+	pub fn set_read_cache(&mut self, size: usize) {
+		self.4 = match size {
+			0 => None,
+			_ => Some(Arc::new(BlockCache::new(size))),
+		};
+	}
+
+
+	/// Hits, misses and evictions of the read cache, `None` if it is disabled
+	pub fn read_cache_stats(&self) -> Option<driver_objects::CacheStats> {
+		self.4.as_ref().map(|cache| cache.stats())
+	}
+
+
+	/// Drop the cached blocks of an inode after its data changed
+	fn invalidate(&self, inode: u64) {
+		if let Some(cache) = &self.4 {
+			cache.invalidate(self.map_root(inode));
+		}
+	}
+
+
+	/// The volume (name, root directory, ...) read when it was opened
+	pub fn volume(&self) -> &driver_objects::Volume {
+		&self.2
+	}
+
+
+	/// Volume-wide settings (block size, UUID, label, ...) read when the volume was opened
+	pub fn superblock(&self) -> &driver_objects::Superblock {
+		&self.2.superblock
+	}
+
+
+	fn block_size(&self) -> u64 {
+		self.2.superblock.block_size.into()
+	}
+
+
+	/// Translate between the inode ids of the driver and the database
+	///
+	/// The root directory of the volume is presented as [`ROOT_INODE`], and the database inode with
+	/// that id (which belongs to a different volume) takes the place of the root directory's id. The
+	/// mapping is its own inverse, so it translates in both directions.
+	fn map_root(&self, inode: u64) -> u64 {
+		let root = self.2.root_inode.unwrap_or(ROOT_INODE);
+		match inode {
+			ROOT_INODE => root,
+			_ if inode == root => ROOT_INODE,
+			_ => inode,
+		}
+	}
+
+
+	/// Refuse to create names in an inode which is not a directory
+	fn check_directory(&self, inode: u64) -> Result<(), Error> {
+		match self.is_directory(self.map_root(inode))? {
+			true => Ok(()),
+			false => Err(Error::NotADirectoryError("the parent is not a directory")),
+		}
+	}
+
+
+	/// Whether the database inode `inode` is a directory
+	fn is_directory(&self, inode: u64) -> Result<bool, Error> {
+		let inode = self.0.get_inode(inode)?.ok_or(Error::NotFoundError("no inode found with given id"))?;
+		Ok(matches!(database_enums::FileType::from(&inode.file_type), database_enums::FileType::Directory))
+	}
+
+
+	/// Refuse to move the directory `inode` into `parent_inode` if that is the directory itself or
+	/// one of its subdirectories (both are database inodes)
+	fn check_subtree(&self, inode: u64, parent_inode: u64) -> Result<(), Error> {
+		let mut current = parent_inode;
+		loop {
+			if current == inode {
+				return Err(Error::ClientError("a directory can not be moved into itself"));
+			}
+			// The root directory is its own parent
+			match self.0.get_parent(current)? {
+				Some(parent) if parent != current => current = parent,
+				_ => return Ok(()),
+			}
+		}
+	}
+
+
+	/// Run `operation` until it does not fail with a transient error
+	///
+	/// The operation gets a copy of the layer which does not retry, so only the outermost
+	/// operation is run again (a transaction is aborted as a whole by a lost connection or a
+	/// deadlock).
+	fn retry<T>(&self, mut operation: impl FnMut(&Self) -> Result<T, Error>) -> Result<T, Error> {
+		let tl = Self(self.0.clone(), self.1.clone(), self.2.clone(), None, None);
+		match &self.3 {
+			Some(retry) => retry.run(|| operation(&tl)),
+			None => operation(&tl),
+		}
+	}
+
+
+	/// Run `operation` in one database transaction
+	///
+	/// The operation gets a copy of the layer bound to the transaction, which is committed if the
+	/// operation succeeds and rolled back otherwise. Operations started by the operation join its
+	/// transaction. A transaction failing with a transient error is run again as a whole.
+	fn transaction<T>(&self, mut operation: impl FnMut(&Self) -> Result<T, Error>) -> Result<T, Error> {
+		self.retry(|tl| {
+			let tl = Self(Arc::from(tl.0.begin()?), tl.1.clone(), tl.2.clone(), None, None);
+			let result = operation(&tl);
+			backend::finish(tl.0.as_ref(), result)
+		})
+	}
 	
 
 	/// Computes the size of a file
@@ -87,17 +389,15 @@ impl TranslationLayer {
 	///
 	/// # Warnings
 	/// This function DOES NOT check whether the inode actually is a regular file or symlink.
-	pub fn filesize(&mut self, inode: u64) -> Result<driver_objects::FileSize, Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let Ok(size) = conn.query(commands::SQL_GET_FILE_SIZE, Some(&vec![inode.into()])) else {
-			return Ok(FileSize { bytes: 0, blocks: 0 }.into())
-		};
-		let size = match size.get(0) {
-			Some(val) => *val,
-			None => FileSize { bytes: 0, blocks: 0 }
-		};
-
-		Ok(size.into())
+	pub fn filesize(&self, inode: u64) -> Result<driver_objects::FileSize, Error> {
+		self.retry(|tl| {
+			// A file without blocks is empty
+			let Some(size) = tl.0.file_size(tl.map_root(inode), tl.block_size())? else {
+				return Ok(driver_objects::FileSize { bytes: 0, blocks: 0 })
+			};
+
+			Ok(size.into())
+		})
 	}
 
 
@@ -108,11 +408,11 @@ impl TranslationLayer {
 	///
 	/// # Warnings
 	/// This does not check whether the inode is a regular file or a symlink.
-	pub fn count_hardlinks(&mut self, inode: u64) -> Result<u32, Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let hardlinks: Vec<FileHardlinks> = conn.query(commands::SQL_COUNT_HARDLINKS, Some(&vec![inode.into()]))?;
-		let hardlinks = hardlinks.get(0).ok_or(Error::RuntimeError("could not count hardlinks"))?.hardlinks;
-		Ok(hardlinks.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?)
+	pub fn count_hardlinks(&self, inode: u64) -> Result<u32, Error> {
+		self.retry(|tl| {
+			let hardlinks = tl.0.count_hardlinks(tl.map_root(inode))?;
+			Ok(hardlinks.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?)
+		})
 	}
 
 
@@ -123,11 +423,11 @@ impl TranslationLayer {
 	///
 	/// # Warnings
 	/// This does not check whether the inode is a directory.
-	pub fn count_subdirs(&mut self, inode: u64) -> Result<u32, Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let subdirs: Vec<DirectoryChildrenDirectory> = conn.query(commands::SQL_COUNT_CHILDREN_OF_TYPE_DIRECTORY, Some(&vec![inode.into()]))?;
-		let subdirs = subdirs.get(0).ok_or(Error::RuntimeError("could not count subdirectories"))?.children_dirs;
-		Ok((subdirs + 2).try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?)
+	pub fn count_subdirs(&self, inode: u64) -> Result<u32, Error> {
+		self.retry(|tl| {
+			let subdirs = tl.0.count_subdirectories(tl.map_root(inode))?;
+			Ok((subdirs + 2).try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?)
+		})
 	}
 
 
@@ -138,54 +438,58 @@ impl TranslationLayer {
 	///
 	/// # Warnings
 	/// This is a relatively expensive operation, so use as sparingly as possible.
-	pub fn getattr(&mut self, _inode: u64) -> Result<driver_objects::FileAttr, Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let inode: Vec<Inode> = conn.query(commands::SQL_GET_INODE, Some(&vec![_inode.into()]))?;
-		drop(conn);
-
-		let Some(inode) = inode.get(0) else {
-			return Err(Error::NotFoundError("no inode found with given id"));
-		};
-
-		let file_type: database_enums::FileType = (&inode.file_type).into();
-
-		let hardlinks: u32 = match file_type {
-			database_enums::FileType::RegularFile
-				| database_enums::FileType::SymbolicLink
-				| database_enums::FileType::Socket
-				| database_enums::FileType::NamedPipe => self.count_hardlinks(_inode)?,
-			database_enums::FileType::Directory => self.count_subdirs(_inode)?,
-			database_enums::FileType::Unknown => 0,
-		};
-
-		let file_type: driver_objects::FileType = driver_objects::FileType::try_from(file_type)?;
-
-		let file_size = match file_type {
-			driver_objects::FileType::File | driver_objects::FileType::Symlink => {
-				self.filesize(_inode)?
-			},
-			driver_objects::FileType::Socket
-				| driver_objects::FileType::NamedPipe
-				| driver_objects::FileType::Directory => driver_objects::FileSize { bytes: 0, blocks: 0 },
-		};
-
-		Ok(driver_objects::FileAttr {
-			ino: inode.id,
-			uid: inode.owner,
-			gid: inode.group,
-			hardlinks: hardlinks.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?,
-			bytes: file_size.bytes.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?,
-			blocks: file_size.blocks.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?,
-			atime: inode.accessed_at.into(),
-			mtime: inode.modified_at.into(),
-			ctime: inode.created_at.into(),
-			kind: file_type,
-			perm: driver_objects::Permissions {
-				special: inode.special_bits,
-				owner: inode.user_perm,
-				group: inode.group_perm,
-				other: inode.other_perm
-			}
+	pub fn getattr(&self, _inode: u64) -> Result<driver_objects::FileAttr, Error> {
+		self.retry(|tl| {
+			let Some(inode) = tl.0.get_inode(tl.map_root(_inode))? else {
+				return Err(Error::NotFoundError("no inode found with given id"));
+			};
+
+			let file_type: database_enums::FileType = (&inode.file_type).into();
+
+			let hardlinks: u32 = match file_type {
+				database_enums::FileType::RegularFile
+					| database_enums::FileType::SymbolicLink
+					| database_enums::FileType::Socket
+					| database_enums::FileType::NamedPipe
+					| database_enums::FileType::BlockDevice
+					| database_enums::FileType::CharDevice => tl.count_hardlinks(_inode)?,
+				database_enums::FileType::Directory => tl.count_subdirs(_inode)?,
+				database_enums::FileType::Unknown => 0,
+			};
+
+			let file_type: driver_objects::FileType = driver_objects::FileType::try_from(file_type)?;
+
+			let file_size = match file_type {
+				driver_objects::FileType::File | driver_objects::FileType::Symlink => {
+					tl.filesize(_inode)?
+				},
+				driver_objects::FileType::Socket
+					| driver_objects::FileType::NamedPipe
+					| driver_objects::FileType::BlockDevice
+					| driver_objects::FileType::CharDevice
+					| driver_objects::FileType::Directory => driver_objects::FileSize { bytes: 0, blocks: 0 },
+			};
+
+			Ok(driver_objects::FileAttr {
+				ino: tl.map_root(inode.id.into()).try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?,
+				uid: inode.owner,
+				gid: inode.group,
+				hardlinks: hardlinks.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?,
+				bytes: file_size.bytes.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?,
+				blocks: file_size.blocks.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?,
+				atime: driver_objects::Timestamp { sec: inode.atime, nsec: inode.atime_nsec }.into(),
+				mtime: driver_objects::Timestamp { sec: inode.mtime, nsec: inode.mtime_nsec }.into(),
+				ctime: driver_objects::Timestamp { sec: inode.ctime, nsec: inode.ctime_nsec }.into(),
+				crtime: driver_objects::Timestamp { sec: inode.crtime, nsec: inode.crtime_nsec }.into(),
+				kind: file_type,
+				perm: driver_objects::Permissions {
+					special: inode.special_bits,
+					owner: inode.user_perm,
+					group: inode.group_perm,
+					other: inode.other_perm
+				},
+				rdev: inode.rdev,
+			})
 		})
 	}
 
@@ -195,14 +499,13 @@ impl TranslationLayer {
 	/// # Inputs
 	/// `name: &OsStr` is the name of the file
 	/// `parent_inode: u64` is the inode ID of the file's parent
-	pub fn lookup_id(&mut self, name: &std::ffi::OsStr, parent_inode: u64) -> Result<u64, Error> {
-		let path = name.to_str().ok_or(Error::RuntimeError("could not parse path"))?.to_string();
-
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let inode: Vec<database_objects::InodeLookup> = conn.query(commands::SQL_LOOKUP_INODE_ID, Some(&vec![path.into(), parent_inode.into()]))?;
-		let inode: &database_objects::InodeLookup = inode.get(0).ok_or(Error::NotFoundError("could not read inode ID"))?;
+	pub fn lookup_id(&self, name: &std::ffi::OsStr, parent_inode: u64) -> Result<u64, Error> {
+		self.retry(|tl| {
+			let path = file_name(name)?;
 
-		Ok(inode.inode_id.into())
+			let inode = tl.0.lookup(tl.map_root(parent_inode), path)?.ok_or(Error::NotFoundError("could not read inode ID"))?;
+			Ok(tl.map_root(inode))
+		})
 	}
 
 
@@ -215,9 +518,11 @@ impl TranslationLayer {
 	/// # Warnings
 	/// As this function internally calls getattr, it is also
 	/// a relatively expensive operation, so use as sparingly as possible.
-	pub fn lookup(&mut self, name: &std::ffi::OsStr, parent_inode: u64) -> Result<driver_objects::FileAttr, Error> {
-		let inode = self.lookup_id(name, parent_inode)?;
-		self.getattr(inode)
+	pub fn lookup(&self, name: &std::ffi::OsStr, parent_inode: u64) -> Result<driver_objects::FileAttr, Error> {
+		self.retry(|tl| {
+			let inode = tl.lookup_id(name, parent_inode)?;
+			tl.getattr(inode)
+		})
 	}
 
 
@@ -230,27 +535,30 @@ impl TranslationLayer {
 	///
 	/// This function DOES NOT check if the given `inode` id belongs to a directory (or a
 	/// different filetype). Nor does it check whether the parent is a directory.
-	pub fn readdir(&mut self, inode: u64) -> Result<Vec<driver_objects::DirectoryEntry>, Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-
-		let listing: Vec<database_objects::DirectoryEntry> = conn.query(commands::SQL_LIST_DIRECTORY, Some(&vec![inode.into()]))?;
-		let parent: u32 = DbConnector::query::<database_objects::DirectoryParent>(&mut conn, commands::SQL_GET_DIRECTORY_PARENT, Some(&vec![inode.into()]))?.get(0).ok_or(Error::RuntimeError("could not find the parent file on readdir"))?.parent_inode_id;
-
-		let mut entries = vec![
-			driver_objects::DirectoryEntry {
-				inode,
-				ftype: driver_objects::FileType::Directory,
-				name: ".".into()
-			},
-			driver_objects::DirectoryEntry {
-				inode: parent.into(),
-				ftype: driver_objects::FileType::Directory,
-				name: "..".into()
-			}
-		];
-		entries.append(&mut listing.iter().map(|val| Ok(driver_objects::DirectoryEntry::try_from(val)?)).collect::<Result<Vec<driver_objects::DirectoryEntry>, Error>>()?);
-
-		Ok(entries)
+	pub fn readdir(&self, inode: u64) -> Result<Vec<driver_objects::DirectoryEntry>, Error> {
+		self.retry(|tl| {
+			let listing = tl.0.list_directory(tl.map_root(inode))?;
+			let parent = tl.0.get_parent(tl.map_root(inode))?.ok_or(Error::RuntimeError("could not find the parent file on readdir"))?;
+
+			let mut entries = vec![
+				driver_objects::DirectoryEntry {
+					inode,
+					ftype: driver_objects::FileType::Directory,
+					name: ".".into()
+				},
+				driver_objects::DirectoryEntry {
+					inode: tl.map_root(parent),
+					ftype: driver_objects::FileType::Directory,
+					name: "..".into()
+				}
+			];
+			entries.append(&mut listing.iter().map(|val| {
+				let entry = driver_objects::DirectoryEntry::try_from(val)?;
+				Ok(driver_objects::DirectoryEntry { inode: tl.map_root(entry.inode), ..entry })
+			}).collect::<Result<Vec<driver_objects::DirectoryEntry>, Error>>()?);
+
+			Ok(entries)
+		})
 	}
 
 
@@ -263,13 +571,10 @@ impl TranslationLayer {
 	///
 	/// This function DOES NOT check if the given `inode` id belongs to a directory (or a
 	/// different filetype).
-	pub fn count_children(&mut self, inode: u64) -> Result<u64, Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-
-		let count: Vec<database_objects::ChildrenCount> = conn.query(commands::SQL_COUNT_DIRECTORY_CHILDREN, Some(&vec![inode.into()]))?;
-		let count: &database_objects::ChildrenCount = count.get(0).ok_or(Error::RuntimeError("could not determine children count"))?;
-
-		Ok((count.children + 2) as u64)
+	pub fn count_children(&self, inode: u64) -> Result<u64, Error> {
+		self.retry(|tl| {
+			Ok(tl.0.count_children(tl.map_root(inode))? + 2)
+		})
 	}
 
 
@@ -287,26 +592,51 @@ impl TranslationLayer {
 	///
 	/// Besides regular errors this function can return [`Error::ClientError`]`("pointer out of
 	/// range")`
-	pub fn read(&mut self, inode: u64, offset: u64, buffer: &mut [u8]) -> Result<usize, Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-
-		let max_bytes = buffer.len();
-		let max_blocks = (max_bytes.div_ceil(settings::FILE_BLOCK_SIZE_USIZE) + 1_usize) as u64;
-		let offset_blocks = offset / settings::FILE_BLOCK_SIZE;
-		let offset = offset as usize;
-
-		let blocks: Vec<database_objects::BlockData> = conn.query(commands::SQL_READ_FILE, Some(&vec![inode.into(), max_blocks.into(), offset_blocks.into()]))?;
-		if blocks.len() == 0 {
-			match max_bytes {
-				0 => { return Ok(0); }
-				_ => { return Err(Error::ClientError("read failed (pointer or size invalid)")); },
+	pub fn read(&self, inode: u64, offset: u64, buffer: &mut [u8]) -> Result<usize, Error> {
+		self.retry(|tl| {
+			let inode = tl.map_root(inode);
+			let block_size = tl.block_size();
+			let max_bytes = buffer.len();
+			let max_blocks = (max_bytes as u64).div_ceil(block_size) + 1;
+			let offset_blocks = offset / block_size;
+			let offset = offset as usize;
+
+			// The blocks holding the requested bytes, the database is asked for one more
+			let needed_blocks = match max_bytes {
+				0 => 0,
+				_ => (offset as u64 % block_size + max_bytes as u64 - 1) / block_size + 1,
+			};
+			let blocks = self.read_blocks(tl, inode, offset_blocks, max_blocks, needed_blocks)?;
+			if blocks.len() == 0 {
+				match max_bytes {
+					0 => { return Ok(0); }
+					_ => { return Err(Error::ClientError("read failed (pointer or size invalid)")); },
+				}
 			}
+			let bytes: Vec<u8> = blocks.iter().flat_map(|inner| inner.iter()).skip(offset - (offset_blocks * block_size) as usize).take(max_bytes).map(|val| val.clone()).collect();
+			let read = bytes.len();
+			buffer[..bytes.len()].copy_from_slice(&bytes);
+
+			Ok(read)
+		})
+	}
+
+
+	/// Read the blocks of a database inode like [`Backend::read_blocks`], from the read cache if it
+	/// holds the first `needed_blocks` of them
+	fn read_blocks(&self, tl: &Self, inode: u64, offset_blocks: u64, max_blocks: u64, needed_blocks: u64) -> Result<Vec<Arc<Vec<u8>>>, Error> {
+		let Some(cache) = self.4.as_ref().filter(|_| needed_blocks != 0) else {
+			return Ok(tl.0.read_blocks(inode, offset_blocks, max_blocks)?.into_iter().map(Arc::new).collect());
+		};
+
+		if let Some(blocks) = cache.get(inode, offset_blocks, needed_blocks, tl.block_size() as usize) {
+			return Ok(blocks);
 		}
-		let bytes: Vec<u8> = blocks.iter().flat_map(|inner| inner.data.iter()).skip(offset - (offset_blocks * settings::FILE_BLOCK_SIZE) as usize).take(max_bytes).map(|val| val.clone()).collect();
-		let read = bytes.len();
-		buffer[..bytes.len()].copy_from_slice(&bytes);
 
-		Ok(read)
+		let generation = cache.generation();
+		let blocks: Vec<Arc<Vec<u8>>> = tl.0.read_blocks(inode, offset_blocks, max_blocks)?.into_iter().map(Arc::new).collect();
+		cache.insert(inode, offset_blocks, &blocks, generation);
+		Ok(blocks)
 	}
 
 
@@ -316,14 +646,9 @@ impl TranslationLayer {
 	/// As the backend is an SQL database, the contents of the `free_blocks` and `free_inodes`
 	/// fields may be completely made up, as the driver assumes that the SQL backend provides
 	/// unlimited resources.
-	pub fn statfs(&mut self) -> Result<driver_objects::FilesystemStat, Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let stat: Vec<database_objects::FilesystemStat> = conn.query(commands::SQL_GET_FS_STAT, None)?;
-		let stat: &database_objects::FilesystemStat = stat.get(0).ok_or(Error::RuntimeError("could not determine fs stat"))?;
-		
-		Ok(driver_objects::FilesystemStat {
-			used_blocks: stat.used_blocks as u64,
-			used_inodes: stat.used_inodes as u64
+	pub fn statfs(&self) -> Result<driver_objects::FilesystemStat, Error> {
+		self.retry(|tl| {
+			tl.0.stat(tl.2.id)
 		})
 	}
 
@@ -339,102 +664,98 @@ impl TranslationLayer {
 	/// # Warning
 	/// This function makes multiple presumptions about the file contents:
 	/// - The `block_id`s are consistent (from 1 to n incrementally)
-	/// - Each block (except the last one) has size of exactly [`settings::FILE_BLOCK_SIZE`] octets
+	/// - Each block (except the last one) has size of exactly [`driver_objects::Superblock::block_size`] octets
 	///
 	/// # Inputs
 	/// `inode: u64` is the id of the inode which will be written to
 	/// `offset: u64` is the offset in the inode's data
 	/// `buffer: &[u8]` is the source buffer
-	pub fn write(&mut self, inode: u64, offset: u64, buffer: &[u8]) -> Result<(), Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
+	pub fn write(&self, inode: u64, offset: u64, buffer: &[u8]) -> Result<(), Error> {
+		self.write_with_mtime(inode, offset, buffer, std::time::SystemTime::now())
+	}
 
-		let buffer_len = buffer.len() as u64;
-		if buffer.len() == 0 { return Ok(()); }
 
-		// Some pointer calculations
-		let start_block = offset / settings::FILE_BLOCK_SIZE;
-		let end_block = (offset + buffer_len - 1) / settings::FILE_BLOCK_SIZE;
-		let start_idx = offset - start_block * settings::FILE_BLOCK_SIZE;
-		let end_idx = offset + buffer_len - end_block * settings::FILE_BLOCK_SIZE - 1;
-
-
-		// Fetch stuff from the DB (like the current block count)
-		// and init the buffer with paddings
-		let mut to_write: Vec<u8>;
-		let blocks = if start_idx == 0 && end_idx == settings::FILE_BLOCK_SIZE - 1 {
-			let result = conn.query(commands::SQL_GET_SIZE_ONLY, Some(&vec![inode.into()]))?;
-			let result_item: &database_objects::FileWriteInfoSizeOnly = result.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
-
-			to_write = vec![0; buffer_len as usize];
-			result_item.blocks
-		} else if start_idx == 0 {
-			let result = conn.query(commands::SQL_GET_SIZE_AND_SINGLE_BLOCK_DATA, Some(&vec![inode.into(), end_block.into()]))?;
-			let result_item: &database_objects::FileWriteInfoSingleBlock = result.get(0).ok_or(Error::NotFoundError("could not get filesize and block"))?;
-
-			let padding_end = result_item.block_data.len() as u64;
-			let padding_end = if end_idx >= padding_end { 0 } else { padding_end - end_idx - 1 };
-			to_write = vec![0; (buffer_len + padding_end) as usize];
-			try_slice_from_slice!(&result_item.block_data, end_idx as usize + 1.., to_write, buffer_len as usize..);
-			result_item.blocks
-		} else if end_idx == settings::FILE_BLOCK_SIZE - 1 {
-			let result = conn.query(commands::SQL_GET_SIZE_AND_SINGLE_BLOCK_DATA, Some(&vec![inode.into(), start_block.into()]))?;
-			let result_item: &database_objects::FileWriteInfoSingleBlock = result.get(0).ok_or(Error::NotFoundError("could not get filesize and block"))?;
+	/// Write inode contents like [`TranslationLayer::write`], the modification and change time of
+	/// the inode are set to `time` (the time the data was written to a cache, for example)
+	pub fn write_with_mtime(&self, inode: u64, offset: u64, buffer: &[u8], time: std::time::SystemTime) -> Result<(), Error> {
+		if buffer.len() == 0 { return Ok(()); }
+		let result = self.transaction(|tl| {
+			let buffer_len = buffer.len() as u64;
+			let inode = tl.map_root(inode);
+			let block_size = tl.block_size();
+
+			// Some pointer calculations
+			let start_block = offset / block_size;
+			let end_block = (offset + buffer_len - 1) / block_size;
+			let start_idx = offset - start_block * block_size;
+			let end_idx = offset + buffer_len - end_block * block_size - 1;
+
+
+			// Fetch stuff from the DB (like the current block count)
+			// and init the buffer with paddings
+			let mut to_write: Vec<u8>;
+			let blocks = if start_idx == 0 && end_idx == block_size - 1 {
+				let result_item = tl.0.write_info(inode, None, None)?;
+
+				to_write = vec![0; buffer_len as usize];
+				result_item.blocks
+			} else if start_idx == 0 {
+				let result_item = tl.0.write_info(inode, None, Some(end_block))?;
+
+				let padding_end = result_item.end_block_data.len() as u64;
+				let padding_end = if end_idx >= padding_end { 0 } else { padding_end - end_idx - 1 };
+				to_write = vec![0; (buffer_len + padding_end) as usize];
+				try_slice_from_slice!(&result_item.end_block_data, end_idx as usize + 1.., to_write, buffer_len as usize..);
+				result_item.blocks
+			} else if end_idx == block_size - 1 {
+				let result_item = tl.0.write_info(inode, Some(start_block), None)?;
 			
-			let padding_start = std::cmp::min(result_item.block_data.len() as u64, start_idx);
-			to_write = vec![0; (start_idx + buffer_len) as usize];
-			try_slice_from_slice!(&result_item.block_data, 0..padding_start as usize, to_write, 0..padding_start as usize);
-			result_item.blocks
-		} else if start_block == end_block {
-			let result = conn.query(commands::SQL_GET_SIZE_AND_SINGLE_BLOCK_DATA, Some(&vec![inode.into(), start_block.into()]))?;
-			let result_item: &database_objects::FileWriteInfoSingleBlock = result.get(0).ok_or(Error::NotFoundError("could not get filesize and block"))?;
-
-			let padding_start = std::cmp::min(result_item.block_data.len() as u64, start_idx);
-			let padding_end = result_item.block_data.len() as u64;
-			let padding_end = if end_idx >= padding_end { 0 } else { padding_end - end_idx - 1 };
-			to_write = vec![0; (start_idx + buffer_len + padding_end) as usize];
-			try_slice_from_slice!(&result_item.block_data, 0..padding_start as usize, to_write, 0..padding_start as usize);
-			try_slice_from_slice!(&result_item.block_data, end_idx as usize + 1.., to_write, padding_start as usize + buffer_len as usize..);
-			result_item.blocks
-		} else {
-			let result = conn.query(commands::SQL_GET_SIZE_AND_BLOCK_DATA, Some(&vec![inode.into(), start_block.into(), end_block.into()]))?;
-			let result_item: &database_objects::FileWriteInfo = result.get(0).ok_or(Error::NotFoundError("could not get filesize and block"))?;
-
-			let padding_start = std::cmp::min(result_item.start_block_data.len() as u64, start_idx);
-			let padding_end = result_item.end_block_data.len() as u64;
-			let padding_end = if end_idx >= padding_end { 0 } else { padding_end - end_idx - 1 };
-			to_write = vec![0; (start_idx + buffer_len + padding_end) as usize];
-			try_slice_from_slice!(&result_item.start_block_data, 0..padding_start as usize, to_write, 0..padding_start as usize);
-			try_slice_from_slice!(&result_item.end_block_data, end_idx as usize + 1.., to_write, padding_start as usize + buffer_len as usize..);
-			result_item.blocks
-		};
-		let blocks: u64 = blocks.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?;
-
-		if blocks < start_block + 1 {
-			conn.command(commands::SQL_PAD_LAST_BLOCK, Some(&vec![settings::FILE_BLOCK_SIZE.into(), inode.into()]))?;
-		}
-		if blocks < start_block { 
-			// Oh no... we need to pad the file up to the insertion point
-			let command = commands::dynamic_queries::sql_pad_until(inode, blocks + 1, start_block + 1);
-			conn.command(command.as_str(), None)?;
-		}
-
-		// Copy buffer
-		to_write[start_idx as usize..=((end_block - start_block) as usize * settings::FILE_BLOCK_SIZE_USIZE + end_idx as usize)].copy_from_slice(buffer);
+				let padding_start = std::cmp::min(result_item.start_block_data.len() as u64, start_idx);
+				to_write = vec![0; (start_idx + buffer_len) as usize];
+				try_slice_from_slice!(&result_item.start_block_data, 0..padding_start as usize, to_write, 0..padding_start as usize);
+				result_item.blocks
+			} else if start_block == end_block {
+				let result_item = tl.0.write_info(inode, Some(start_block), None)?;
+
+				let padding_start = std::cmp::min(result_item.start_block_data.len() as u64, start_idx);
+				let padding_end = result_item.start_block_data.len() as u64;
+				let padding_end = if end_idx >= padding_end { 0 } else { padding_end - end_idx - 1 };
+				to_write = vec![0; (start_idx + buffer_len + padding_end) as usize];
+				try_slice_from_slice!(&result_item.start_block_data, 0..padding_start as usize, to_write, 0..padding_start as usize);
+				try_slice_from_slice!(&result_item.start_block_data, end_idx as usize + 1.., to_write, padding_start as usize + buffer_len as usize..);
+				result_item.blocks
+			} else {
+				let result_item = tl.0.write_info(inode, Some(start_block), Some(end_block))?;
+
+				let padding_start = std::cmp::min(result_item.start_block_data.len() as u64, start_idx);
+				let padding_end = result_item.end_block_data.len() as u64;
+				let padding_end = if end_idx >= padding_end { 0 } else { padding_end - end_idx - 1 };
+				to_write = vec![0; (start_idx + buffer_len + padding_end) as usize];
+				try_slice_from_slice!(&result_item.start_block_data, 0..padding_start as usize, to_write, 0..padding_start as usize);
+				try_slice_from_slice!(&result_item.end_block_data, end_idx as usize + 1.., to_write, padding_start as usize + buffer_len as usize..);
+				result_item.blocks
+			};
+
+			if blocks < start_block + 1 {
+				tl.0.resize_last_block(inode, block_size)?;
+			}
+			if blocks < start_block { 
+				// Oh no... we need to pad the file up to the insertion point
+				tl.0.pad_blocks(inode, blocks + 1, start_block + 1, block_size)?;
+			}
 
-		// Convert data to a useful format
-		let mut data: Vec<_> = Vec::new();
-		let mut ptr = 0;
-		while ptr < to_write.len() {
-			data.push(Vec::from(&to_write[ptr..std::cmp::min(ptr + settings::FILE_BLOCK_SIZE_USIZE, to_write.len())]).into());
-			ptr += settings::FILE_BLOCK_SIZE_USIZE;
-		}
+			// Copy buffer
+			to_write[start_idx as usize..=((end_block - start_block) * block_size + end_idx) as usize].copy_from_slice(buffer);
 
-		// Generate the insert query
-		let command = commands::dynamic_queries::sql_write(inode, start_block + 1, end_block + 1);
-		// Now let's INSERT ... good luck
-		conn.command(command.as_str(), Some(&data))?;
+			// Convert data to a useful format
+			let data: Vec<Vec<u8>> = to_write.chunks(block_size as usize).map(Vec::from).collect();
 
-		Ok(())
+			// Now let's INSERT ... good luck
+			tl.0.write_blocks(inode, start_block + 1, data)?;
+			tl.0.touch_inode(inode, time)
+		});
+		self.invalidate(inode);
+		result
 	}
 
 
@@ -445,22 +766,47 @@ impl TranslationLayer {
 	/// `name: &OsStr` is the name of the file to be created
 	/// `kind: FileType` sets the inode type
 	/// `attr: FileSetAttr` sets the remaining inode attributes
-	pub fn mknod(&mut self, parent_inode: u64, name: &std::ffi::OsStr, kind: driver_objects::FileType, attr: driver_objects::FileSetAttr) -> Result<driver_objects::FileAttr, Error> {
-		self.1.lock().map_err(|_| Error::RuntimeError(PASSWD_LOCK_FAILED))?.check(&self.0, attr.uid, attr.gid)?;
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let inode = conn.command(commands::SQL_CREATE_INODE, Some(&vec![
-			attr.uid.into(),
-			attr.gid.into(),
-			Into::<String>::into(kind).into(),
-			attr.perm.special.into(),
-			attr.perm.owner.into(),
-			attr.perm.group.into(),
-			attr.perm.other.into()
-		]))?.last_insert_id;
-		drop(conn);
+	pub fn mknod(&self, parent_inode: u64, name: &std::ffi::OsStr, kind: driver_objects::FileType, attr: driver_objects::FileSetAttr) -> Result<driver_objects::FileAttr, Error> {
+		self.mknod_with_umask(parent_inode, name, kind, attr, 0, 0)
+	}
+
 
-		self.link(parent_inode, name, inode)?;
-		self.getattr(inode)
+	/// Creates a new inode like [`TranslationLayer::mknod`], its permissions are restricted by the
+	/// default ACL of the parent directory (which the inode inherits) or, without one, by `umask`
+	///
+	/// `rdev` is the device number of block and character devices (0 for other files).
+	pub fn mknod_with_umask(&self, parent_inode: u64, name: &std::ffi::OsStr, kind: driver_objects::FileType, attr: driver_objects::FileSetAttr, rdev: u32, umask: u16) -> Result<driver_objects::FileAttr, Error> {
+		// Outside of the transaction, the passwd table remembers the users it inserted
+		self.retry(|tl| tl.1.lock().map_err(|_| Error::RuntimeError(PASSWD_LOCK_FAILED))?.check(tl.0.as_ref(), attr.uid, attr.gid))?;
+		self.transaction(|tl| {
+			// Symlinks have no ACLs
+			let default_acl = match (&kind, tl.0.get_xattr(tl.map_root(parent_inode), acl::DEFAULT_ACL)?) {
+				(driver_objects::FileType::Symlink, _) | (_, None) => None,
+				(_, Some(value)) => acl::Acl::parse(&value)?,
+			};
+			let access_acl = default_acl.as_ref().map(|default_acl| default_acl.inherit(&attr.perm));
+			let perm = match &access_acl {
+				Some(access_acl) => access_acl.permissions(attr.perm.special),
+				None => driver_objects::Permissions {
+					special: attr.perm.special,
+					owner: attr.perm.owner & !(umask >> 6) as u8 & 7,
+					group: attr.perm.group & !(umask >> 3) as u8 & 7,
+					other: attr.perm.other & !umask as u8 & 7,
+				},
+			};
+			let node_attr = driver_objects::FileSetAttr { uid: attr.uid, gid: attr.gid, atime: attr.atime, mtime: attr.mtime, ctime: attr.ctime, crtime: attr.crtime, perm };
+			let inode = tl.map_root(tl.0.create_inode(tl.2.id, kind.clone(), &node_attr, rdev)?);
+
+			tl.link(parent_inode, name, inode)?;
+
+			if let Some(access_acl) = access_acl.filter(|access_acl| !access_acl.is_minimal()) {
+				tl.0.create_xattr(inode, acl::ACCESS_ACL, &access_acl.to_bytes())?;
+			}
+			if let (Some(default_acl), driver_objects::FileType::Directory) = (&default_acl, &kind) {
+				tl.0.create_xattr(inode, acl::DEFAULT_ACL, &default_acl.to_bytes())?;
+			}
+			tl.getattr(inode)
+		})
 	}
 
 
@@ -470,63 +816,69 @@ impl TranslationLayer {
 	/// `parent_inode: u64` specifies the parent inode where the file should be created
 	/// `name: &OsStr` is the name of the file to be created
 	/// `dest_inode: u64` sets the inode to which the new file will be poiting to
-	pub fn link(&mut self, parent_inode: u64, name: &std::ffi::OsStr, dest_inode: u64) -> Result<(), Error> {
-		let path = name.to_str().ok_or(Error::RuntimeError("could not parse path"))?.to_string();
-
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let status = conn.command(commands::SQL_CREATE_FILE, Some(&vec![
-			parent_inode.into(),
-			path.into(),
-			dest_inode.into()
-		]))?;
-		if status.rows_affected != 1 {
-			return Err(Error::RuntimeError("no changes made"));
-		}
+	pub fn link(&self, parent_inode: u64, name: &std::ffi::OsStr, dest_inode: u64) -> Result<(), Error> {
+		self.transaction(|tl| {
+			let path = file_name(name)?;
+			tl.check_directory(parent_inode)?;
 
-		let _ = conn.command(commands::SQL_UPDATE_INODE_CTIME_MTIME, Some(&vec![parent_inode.into()]))?;
-		Ok(())
+			if tl.0.create_file(tl.map_root(parent_inode), path, tl.map_root(dest_inode))? != 1 {
+				return Err(Error::RuntimeError("no changes made"));
+			}
+
+			let time = std::time::SystemTime::now();
+			tl.0.touch_inode(tl.map_root(parent_inode), time)?;
+			tl.0.change_inode(tl.map_root(dest_inode), time)
+		})
 	}
 
 
-	/// Truncates or expands an inode by deleting or adding blocks
+	/// Truncates or expands an inode by deleting or adding blocks, which sets its modification and
+	/// change time
 	///
 	/// # Inputs
 	/// `inode: u64` specifies the inode
 	/// `new_size: u64` specifies the new size the file should have
-	pub fn resize(&mut self, inode: u64, new_size: u64) -> Result<(), Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		if new_size == 0 {
-			conn.command(commands::SQL_DROP_BLOCKS, Some(&vec![inode.into()]))?;
-			return Ok(());
-		}
+	pub fn resize(&self, inode: u64, new_size: u64) -> Result<(), Error> {
+		let time = std::time::SystemTime::now();
+		let result = self.transaction(|tl| {
+			let inode = tl.map_root(inode);
+			tl.0.touch_inode(inode, time)?;
+			if new_size == 0 {
+				return tl.0.drop_blocks(inode);
+			}
 
-		// Get the current file head
-		let file_head: Vec<database_objects::FileHead> = conn.query(commands::SQL_GET_FILE_HEAD, Some(&vec![inode.into()]))?;
-		let file_head = file_head.get(0).ok_or(Error::NotFoundError("could not get filesize"))?;
-
-		// Pad with null blocks if necessary
-		let block_count: u64 = file_head.bc.try_into().map_err(|_| Error::RuntimeError(DBI64_TO_DRU32_CONVERSION_ERROR_MESSAGE))?;
-		let new_block_count = new_size.div_ceil(settings::FILE_BLOCK_SIZE);
-		let strip_blocks_count = if block_count < new_block_count {
-			conn.command(commands::SQL_RESIZE_LAST_BLOCK, Some(&vec![settings::FILE_BLOCK_SIZE.into(), settings::FILE_BLOCK_SIZE.into(), inode.into()]))?;
-			conn.command(commands::dynamic_queries::sql_pad_file(
-				inode.try_into().map_err(|_| Error::RuntimeError(DRU64_TO_DBU32_CONVERSION_ERROR_MESSAGE))?,
-				file_head.last_block_id,
-				(new_block_count - block_count).try_into().map_err(|_| Error::RuntimeError(DRU64_TO_DBU32_CONVERSION_ERROR_MESSAGE))?
-			).as_str(), None)?;
-			0
-		} else {
-			block_count - new_block_count
-		};
+			// Get the current file head
+			let file_head = tl.0.block_head(inode)?;
+
+			// Pad with null blocks if necessary
+			let block_size = tl.block_size();
+			let block_count = file_head.blocks;
+			let new_block_count = new_size.div_ceil(block_size);
+			let strip_blocks_count = if block_count < new_block_count {
+				tl.0.resize_last_block(inode, block_size)?;
+				tl.0.pad_blocks(inode, file_head.last_block_id + 1, file_head.last_block_id + 1 + new_block_count - block_count, block_size)?;
+				0
+			} else {
+				block_count - new_block_count
+			};
+
+			// Trim the file to the desired byte size
+			let new_last_block_size = new_size - (new_block_count - 1) * block_size;
+			if strip_blocks_count != 0 {
+				tl.0.trim_blocks(inode, strip_blocks_count)?;
+			}
+			tl.0.resize_last_block(inode, new_last_block_size)
+		});
+		self.invalidate(inode);
+		result
+	}
 
-		// Trim the file to the desired byte size
-		let new_last_block_size = new_size - (new_block_count - 1) * settings::FILE_BLOCK_SIZE;
-		if strip_blocks_count != 0 {
-			conn.command(commands::SQL_TRIM_BLOCKS, Some(&vec![inode.into(), strip_blocks_count.into()]))?;
-		}
-		conn.command(commands::SQL_RESIZE_LAST_BLOCK, Some(&vec![new_last_block_size.into(), new_last_block_size.into(), inode.into()]))?;
 
-		Ok(())
+	/// Set the access time of an inode which was read
+	///
+	/// Whether the read updates it is decided by the caller (see [`driver_objects::AtimePolicy`]).
+	pub fn access(&self, inode: u64, time: std::time::SystemTime) -> Result<(), Error> {
+		self.retry(|tl| tl.0.access_inode(tl.map_root(inode), time))
 	}
 
 
@@ -535,102 +887,319 @@ impl TranslationLayer {
 	/// # Inputs
 	/// `inode: u64` specifies the inode
 	/// `attr: FileSetAttr` sets the inode attributes
-	pub fn setattr(&mut self, inode: u64, attr: driver_objects::FileSetAttr) -> Result<driver_objects::FileAttr, Error> {
-		self.1.lock().map_err(|_| Error::RuntimeError(PASSWD_LOCK_FAILED))?.check(&self.0, attr.uid, attr.gid)?;
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let status = conn.command(commands::SQL_UPDATE_INODE, Some(&vec![
-			attr.uid.into(),
-			attr.gid.into(),
-			Into::<chrono::DateTime<chrono::Utc>>::into(attr.atime).into(),
-			Into::<chrono::DateTime<chrono::Utc>>::into(attr.mtime).into(),
-			Into::<chrono::DateTime<chrono::Utc>>::into(attr.ctime).into(),
-			attr.perm.special.into(),
-			attr.perm.owner.into(),
-			attr.perm.group.into(),
-			attr.perm.other.into(),
-			inode.into()
-		]))?;
-		drop(conn);
-		if status.rows_affected != 1 {
-			return Err(Error::NotFoundError("no changes made"));
-		}
-		
-		self.getattr(inode)
+	pub fn setattr(&self, inode: u64, attr: driver_objects::FileSetAttr) -> Result<driver_objects::FileAttr, Error> {
+		self.retry(|tl| tl.1.lock().map_err(|_| Error::RuntimeError(PASSWD_LOCK_FAILED))?.check(tl.0.as_ref(), attr.uid, attr.gid))?;
+		self.transaction(|tl| {
+			if tl.0.update_inode(tl.map_root(inode), &attr)? != 1 {
+				return Err(Error::NotFoundError("no changes made"));
+			}
+
+			// The permission bits are part of the access ACL
+			if let Some(value) = tl.0.get_xattr(tl.map_root(inode), acl::ACCESS_ACL)? {
+				if let Some(mut access_acl) = acl::Acl::parse(&value)? {
+					access_acl.chmod(&attr.perm);
+					tl.0.update_xattr(tl.map_root(inode), acl::ACCESS_ACL, &access_acl.to_bytes())?;
+				}
+			}
+
+			tl.getattr(inode)
+		})
 	}
 
 
 	/// Removes a reference to an inode
 	///
-	/// If the inode has zero references or it is a directory, it will also be deleted.
+	/// If the inode has zero references, it becomes an orphan which keeps its data until it is
+	/// deleted by [`TranslationLayer::reclaim`] (once it is no longer open). Directories can not
+	/// be unlinked (see [`TranslationLayer::rmdir`]).
 	///
 	/// # Inputs
 	/// `parent_inode: u64` specifies the file's parent inode
 	/// `name: &OsStr` is the name of the file to be deleted
-	pub fn unlink(&mut self, parent_inode: u64, name: &std::ffi::OsStr) -> Result<(), Error> {
-		let inode = self.lookup_id(name, parent_inode)?;
-		let path = name.to_str().ok_or(Error::RuntimeError("could not parse path"))?.to_string();
-
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let status = conn.command(commands::SQL_DELETE_FILE, Some(&vec![path.into(), parent_inode.into()]))?;
-		if status.rows_affected != 1 {
-			return Err(Error::NotFoundError("no changes made"));
-		}
+	pub fn unlink(&self, parent_inode: u64, name: &std::ffi::OsStr) -> Result<(), Error> {
+		self.remove(parent_inode, name, false)
+	}
 
-		let _ = conn.command(commands::SQL_UPDATE_INODE_CTIME_MTIME, Some(&vec![parent_inode.into()]))?;
-		drop(conn);
 
-		let attr = self.getattr(inode)?;
-		let delete_inode = match (attr.hardlinks, attr.kind) {
-			(0, driver_objects::FileType::File | driver_objects::FileType::Symlink) => true,
-			(_, driver_objects::FileType::Directory) => true,
-			_ => false
-		};
-		if !delete_inode {
-			return Ok(());
-		}
+	/// Deletes an orphaned inode (see [`TranslationLayer::unlink`]), inodes which still have a
+	/// name are left untouched
+	pub fn reclaim(&self, inode: u64) -> Result<(), Error> {
+		let result = self.retry(|tl| tl.0.delete_orphan(tl.map_root(inode)).map(|_| ()));
+		self.invalidate(inode);
+		result
+	}
+
 
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let status = conn.command(commands::SQL_DELETE_INODE, Some(&vec![inode.into()]))?;
-		match status.rows_affected {
-			1 => Ok(()),
-			_ => Err(Error::RuntimeError("could not delete inode"))
+	/// Deletes all orphaned inodes of the volume and returns their number
+	///
+	/// Orphans which were still open when the filesystem went away (e.g. after a crash) are left
+	/// behind, this has to be called before the volume is mounted.
+	pub fn reclaim_orphans(&self) -> Result<u64, Error> {
+		let result = self.transaction(|tl| {
+			let mut reclaimed = 0;
+			for inode in tl.0.list_orphans(tl.2.id)? {
+				reclaimed += tl.0.delete_orphan(inode)?;
+			}
+			Ok(reclaimed)
+		});
+		if let Some(cache) = &self.4 {
+			cache.clear();
 		}
+		result
+	}
+
+
+	/// Removes an empty directory
+	///
+	/// # Inputs
+	/// `parent_inode: u64` specifies the directory's parent inode
+	/// `name: &OsStr` is the name of the directory to be deleted
+	pub fn rmdir(&self, parent_inode: u64, name: &std::ffi::OsStr) -> Result<(), Error> {
+		self.remove(parent_inode, name, true)
+	}
+
+
+	/// Removes a name, `directory` tells whether it must name a directory (rmdir) or must not
+	/// (unlink)
+	fn remove(&self, parent_inode: u64, name: &std::ffi::OsStr, directory: bool) -> Result<(), Error> {
+		self.transaction(|tl| {
+			let inode = tl.lookup_id(name, parent_inode)?;
+			let path = file_name(name)?;
+
+			match (tl.getattr(inode)?.kind, directory) {
+				(driver_objects::FileType::Directory, false) => return Err(Error::IsADirectoryError("directories can not be unlinked")),
+				(driver_objects::FileType::Directory, true) => if tl.count_children(inode)? > 2 {
+					return Err(Error::NotEmptyError("the directory is not empty"));
+				},
+				(_, true) => return Err(Error::NotADirectoryError("the file is not a directory")),
+				(_, false) => {},
+			}
+
+			if tl.0.delete_file(tl.map_root(parent_inode), path)? != 1 {
+				return Err(Error::NotFoundError("no changes made"));
+			}
+
+			let time = std::time::SystemTime::now();
+			tl.0.touch_inode(tl.map_root(parent_inode), time)?;
+
+			let attr = tl.getattr(inode)?;
+			if attr.kind != driver_objects::FileType::Directory {
+				// The last name of a file is gone, but it may still be open: it is kept as an
+				// orphan until it is reclaimed
+				if attr.hardlinks == 0 {
+					tl.0.create_orphan(tl.map_root(inode))?;
+				}
+				return tl.0.change_inode(tl.map_root(inode), time);
+			}
+
+			match tl.0.delete_inode(tl.map_root(inode))? {
+				1 => Ok(()),
+				_ => Err(Error::RuntimeError("could not delete inode"))
+			}
+		})
 	}
 
 
 	/// Renames/moves a file or directory
 	///
+	/// The rename is a single transaction (including the removal of a replaced destination), so
+	/// the destination name refers either to the old or to the new file at any time.
+	///
 	/// # Inputs
 	/// `src_parent_inode: u64` specifies the file's former parent inode
 	/// `src_name: &OsStr` is the name of the file to be moved
 	/// `dest_parent_inode: u64` specifies the file's new parent inode
 	/// `dest_name: &OsStr` is the file's new name
-	pub fn rename(&mut self, src_parent_inode: u64, src_name: &std::ffi::OsStr, dest_parent_inode: u64, dest_name: &std::ffi::OsStr) -> Result<(), Error> {
-		let src_path = src_name.to_str().ok_or(Error::RuntimeError("could not parse path"))?.to_string();
-		let dest_path = dest_name.to_str().ok_or(Error::RuntimeError("could not parse path"))?.to_string();
-
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
-		let status = conn.command(commands::SQL_RENAME_FILE, Some(&vec![dest_parent_inode.into(), dest_path.into(), src_parent_inode.into(), src_path.into()]))?;
-		if status.rows_affected != 1 {
-			return Err(Error::NotFoundError("no changes made"));
-		}
+	/// `mode: RenameMode` decides what happens to an existing destination
+	pub fn rename(&self, src_parent_inode: u64, src_name: &std::ffi::OsStr, dest_parent_inode: u64, dest_name: &std::ffi::OsStr, mode: driver_objects::RenameMode) -> Result<(), Error> {
+		self.transaction(|tl| {
+			let src_path = file_name(src_name)?;
+			let dest_path = file_name(dest_name)?;
+
+			tl.check_directory(dest_parent_inode)?;
+
+			let src_parent = tl.map_root(src_parent_inode);
+			let dest_parent = tl.map_root(dest_parent_inode);
+			let src_inode = tl.0.lookup(src_parent, src_path)?.ok_or(Error::NotFoundError("no changes made"))?;
+			let dest_inode = tl.0.lookup(dest_parent, dest_path)?;
+
+			let src_directory = tl.is_directory(src_inode)?;
+			if src_directory {
+				tl.check_subtree(src_inode, dest_parent)?;
+			}
+
+			let time = std::time::SystemTime::now();
+			match (mode, dest_inode) {
+				(driver_objects::RenameMode::NoReplace, Some(_)) => return Err(Error::AlreadyExistsError("the destination exists")),
+				(driver_objects::RenameMode::Exchange, None) => return Err(Error::NotFoundError("the destination does not exist")),
+				// Both names already refer to the same file
+				(_, Some(dest_inode)) if dest_inode == src_inode => return Ok(()),
+				(driver_objects::RenameMode::Exchange, Some(dest_inode)) => {
+					if tl.is_directory(dest_inode)? {
+						tl.check_subtree(dest_inode, src_parent)?;
+					}
+
+					// The names are unique, so both are removed before they are created again
+					if tl.0.delete_file(src_parent, src_path)? != 1 || tl.0.delete_file(dest_parent, dest_path)? != 1 {
+						return Err(Error::NotFoundError("no changes made"));
+					}
+					if tl.0.create_file(src_parent, src_path, dest_inode)? != 1 || tl.0.create_file(dest_parent, dest_path, src_inode)? != 1 {
+						return Err(Error::RuntimeError("no changes made"));
+					}
+
+					tl.0.touch_inode(src_parent, time)?;
+					if dest_parent != src_parent {
+						tl.0.touch_inode(dest_parent, time)?;
+					}
+					tl.0.change_inode(src_inode, time)?;
+					return tl.0.change_inode(dest_inode, time);
+				},
+				(driver_objects::RenameMode::Replace, Some(dest_inode)) => {
+					match (src_directory, tl.is_directory(dest_inode)?) {
+						(true, false) => return Err(Error::NotADirectoryError("the destination is not a directory")),
+						(false, true) => return Err(Error::IsADirectoryError("the destination is a directory")),
+						// Non-empty directories are refused by `remove`
+						_ => tl.remove(dest_parent_inode, dest_name, src_directory)?,
+					}
+				},
+				(_, None) => {},
+			}
+
+			if tl.0.rename_file(src_parent, src_path, dest_parent, dest_path)? != 1 {
+				return Err(Error::NotFoundError("no changes made"));
+			}
+
+			tl.0.touch_inode(src_parent, time)?;
+			if dest_parent != src_parent {
+				tl.0.touch_inode(dest_parent, time)?;
+			}
+			tl.0.change_inode(src_inode, time)
+		})
+	}
+
+
+	/// Reads the value of an extended attribute
+	///
+	/// # Inputs
+	/// `inode: u64` specifies the inode
+	/// `name: &OsStr` is the name of the attribute
+	pub fn getxattr(&self, inode: u64, name: &std::ffi::OsStr) -> Result<Vec<u8>, Error> {
+		let key = xattr_name(name)?;
+		self.retry(|tl| {
+			tl.0.get_xattr(tl.map_root(inode), key)?.ok_or(Error::NoAttributeError("the attribute does not exist"))
+		})
+	}
+
+
+	/// Lists the names of all extended attributes of an inode
+	///
+	/// # Inputs
+	/// `inode: u64` specifies the inode
+	pub fn listxattr(&self, inode: u64) -> Result<Vec<String>, Error> {
+		self.retry(|tl| tl.0.list_xattrs(tl.map_root(inode)))
+	}
 
-		let _ = conn.command(commands::SQL_UPDATE_INODE_CTIME_MTIME, Some(&vec![src_parent_inode.into()]))?;
-		if dest_parent_inode != src_parent_inode {
-			let _ = conn.command(commands::SQL_UPDATE_INODE_CTIME_MTIME, Some(&vec![dest_parent_inode.into()]))?;
+
+	/// Creates or overwrites an extended attribute
+	///
+	/// # Inputs
+	/// `inode: u64` specifies the inode
+	/// `name: &OsStr` is the name of the attribute
+	/// `value: &[u8]` is the new value (at most [`MAX_XATTR_SIZE`] bytes)
+	/// `mode: SetXattrMode` tells whether the attribute must (not) exist already
+	pub fn setxattr(&self, inode: u64, name: &std::ffi::OsStr, value: &[u8], mode: driver_objects::SetXattrMode) -> Result<(), Error> {
+		let key = xattr_name(name)?;
+		if value.len() > MAX_XATTR_SIZE {
+			return Err(Error::TooBigError("attribute values can be at most 65536 bytes long"));
 		}
+		self.transaction(|tl| {
+			let mapped_inode = tl.map_root(inode);
+			if tl.0.get_inode(mapped_inode)?.is_none() {
+				return Err(Error::NotFoundError("no inode found with given id"));
+			}
 
-		Ok(())
+			let exists = tl.0.get_xattr(mapped_inode, key)?.is_some();
+			match (mode, exists) {
+				(driver_objects::SetXattrMode::Create, true) => return Err(Error::AlreadyExistsError("the attribute already exists")),
+				(driver_objects::SetXattrMode::Replace, false) => return Err(Error::NoAttributeError("the attribute does not exist")),
+				_ => {},
+			}
+
+			let value = match key {
+				acl::ACCESS_ACL | acl::DEFAULT_ACL => match tl.set_acl(inode, key, value)? {
+					Some(value) => value,
+					None => return tl.0.delete_xattr(mapped_inode, key).map(|_| ()),
+				},
+				_ => value.to_vec(),
+			};
+			match exists {
+				true => tl.0.update_xattr(mapped_inode, key, &value).map(|_| ()),
+				false => tl.0.create_xattr(mapped_inode, key, &value).map(|_| ()),
+			}
+		})
 	}
 
-	/// Erases the entire filesystem, creates the root user & group and creates the root directory.
-	pub fn format(&mut self) -> Result<(), Error> {
-		let mut conn = self.0.lock().map_err(|_| Error::DbLockError)?;
 
-		for cmd in commands::SQL_FORMAT_DELETE_SEQUENCE {
-			conn.command(cmd, None)?;
+	/// Checks an ACL set as extended attribute and applies an access ACL to the permission bits,
+	/// returns the value to store (`None` if the attribute is to be removed)
+	fn set_acl(&self, inode: u64, key: &str, value: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+		let Some(new_acl) = acl::Acl::parse(value)? else {
+			return Ok(None);
+		};
+		let attr = self.getattr(inode)?;
+
+		if key == acl::DEFAULT_ACL {
+			if attr.kind != driver_objects::FileType::Directory {
+				return Err(Error::AccessError("only directories can have a default ACL"));
+			}
+			return Ok(Some(new_acl.to_bytes()));
 		}
 
+		let perm = new_acl.permissions(attr.perm.special);
+		let attr = driver_objects::FileSetAttr { uid: attr.uid, gid: attr.gid, atime: attr.atime, mtime: attr.mtime, ctime: std::time::SystemTime::now(), crtime: attr.crtime, perm };
+		self.0.update_inode(self.map_root(inode), &attr)?;
+
+		// The permission bits say all a minimal ACL does
+		Ok(match new_acl.is_minimal() {
+			true => None,
+			false => Some(new_acl.to_bytes()),
+		})
+	}
+
+
+	/// Removes an extended attribute
+	///
+	/// # Inputs
+	/// `inode: u64` specifies the inode
+	/// `name: &OsStr` is the name of the attribute
+	pub fn removexattr(&self, inode: u64, name: &std::ffi::OsStr) -> Result<(), Error> {
+		let key = xattr_name(name)?;
+		self.transaction(|tl| {
+			match tl.0.delete_xattr(tl.map_root(inode), key)? {
+				0 => Err(Error::NoAttributeError("the attribute does not exist")),
+				_ => Ok(()),
+			}
+		})
+	}
+
+
+	/// Erases all files of the volume, writes a new superblock (with a new UUID) and creates the
+	/// root directory. Other volumes in the database are left untouched.
+	///
+	/// # Inputs
+	/// `block_size: u32` is the block size of the new volume (a power of two between
+	/// [`MIN_BLOCK_SIZE`] and [`MAX_BLOCK_SIZE`])
+	/// `label: &str` is the label of the new volume
+	pub fn format(&mut self, block_size: u32, label: &str) -> Result<(), Error> {
+		let superblock = new_superblock(block_size, label)?;
+		let volume = self.transaction(|tl| {
+			backend::format(tl.0.as_ref(), tl.2.id, &superblock)?;
+			load_volume(tl.0.as_ref(), &tl.2.name)
+		})?;
+		if let Some(cache) = &self.4 {
+			cache.clear();
+		}
+		self.2 = Arc::new(volume);
 		Ok(())
 	}
 }
@@ -638,6 +1207,684 @@ impl TranslationLayer {
 
 
 
+/// Tests of the layer on the in-memory backend (no database needed)
+#[cfg(test)]
+mod unit_test {
+	use std::ffi::OsStr;
+	use std::time::{Duration, SystemTime};
+	use super::*;
+	use super::backend::{memory::MemoryBackend, DEFAULT_VOLUME};
+	use super::test_util::{create, file_attr, pattern, read_all, volume};
+
+
+	const BLOCK: usize = settings::FILE_BLOCK_SIZE_32 as usize;
+
+
+	#[test]
+	fn formatted_volume() {
+		let tl = volume();
+		let attr = tl.getattr(1).unwrap();
+		assert_eq!(attr.kind, driver_objects::FileType::Directory);
+		assert_eq!(attr.hardlinks, 2);
+		assert_eq!(tl.readdir(1).unwrap().len(), 2);
+		assert_eq!(tl.statfs().unwrap().used_inodes, 1);
+	}
+
+	#[test]
+	fn format_with_block_size_and_label() {
+		let mut tl = volume();
+		let uuid = tl.superblock().uuid.clone();
+		tl.format(512, "backup").unwrap();
+		assert_eq!(tl.superblock().block_size, 512);
+		assert_eq!(tl.superblock().label, "backup");
+		assert_ne!(tl.superblock().uuid, uuid);
+
+		let ino = create(&tl, 1, "test.bin", driver_objects::FileType::File);
+		let data = pattern(512 * 2 + 100, 0);
+		tl.write(ino, 0, &data).unwrap();
+		assert_eq!(tl.filesize(ino).unwrap(), driver_objects::FileSize { bytes: data.len() as u64, blocks: 3 });
+		assert_eq!(read_all(&tl, ino), data);
+
+		tl.resize(ino, 512 * 4).unwrap();
+		assert_eq!(tl.filesize(ino).unwrap().blocks, 4);
+	}
+
+	#[test]
+	fn format_rejects_invalid_block_size() {
+		let mut tl = volume();
+		for block_size in [0, 256, 1000, 4097, 2 << 20] {
+			assert!(matches!(tl.format(block_size, ""), Err(Error::ClientError(_))));
+		}
+		assert!(matches!(tl.format(4096, &"x".repeat(300)), Err(Error::ClientError(_))));
+		assert_eq!(tl.superblock().block_size, settings::FILE_BLOCK_SIZE_32);
+	}
+
+	#[test]
+	fn unknown_features_are_refused() {
+		let backend = MemoryBackend::new();
+		let volume = backend.get_volume(DEFAULT_VOLUME).unwrap().unwrap();
+		backend.update_volume(volume.id, 1, &driver_objects::Superblock { features: 1 << 40, ..volume.superblock }).unwrap();
+		assert!(matches!(TranslationLayer::with_backend(Box::new(backend), DEFAULT_VOLUME), Err(Error::RuntimeError(_))));
+	}
+
+	#[test]
+	fn read_empty_file() {
+		let tl = volume();
+		let ino = create(&tl, 1, "empty.bin", driver_objects::FileType::File);
+		assert_eq!(tl.getattr(ino).unwrap().bytes, 0);
+		assert_eq!(tl.read(ino, 0, &mut []).unwrap(), 0);
+		assert!(matches!(tl.read(ino, 0, &mut [0; 10]), Err(Error::ClientError(_))));
+	}
+
+	#[test]
+	fn read_is_clamped_to_file_end() {
+		let tl = volume();
+		let ino = create(&tl, 1, "test.txt", driver_objects::FileType::File);
+		tl.write(ino, 0, b"Hello, world!\n").unwrap();
+		let mut buffer = [0; 100];
+		assert_eq!(tl.read(ino, 7, &mut buffer).unwrap(), 7);
+		assert_eq!(&buffer[..7], b"world!\n");
+	}
+
+	#[test]
+	fn lost_connection_is_not_an_empty_file() {
+		let backend = MemoryBackend::new();
+		let mut tl = TranslationLayer::with_backend(Box::new(backend.clone()), DEFAULT_VOLUME).unwrap();
+		let ino = create(&tl, 1, "test.txt", driver_objects::FileType::File);
+		tl.write(ino, 0, b"data").unwrap();
+		let calls = backend.calls();
+		tl.getattr(ino).unwrap();
+		let getattr_calls = backend.calls() - calls;
+
+		// A soft mount which gives up right away fails whichever call loses the connection
+		tl.set_retry(Some(Duration::ZERO));
+		backend.fail(0, 1);
+		assert!(matches!(tl.filesize(ino), Err(Error::DbConnectorError(_))));
+		for call in 0..getattr_calls {
+			backend.fail(call, 1);
+			assert!(matches!(tl.getattr(ino), Err(Error::DbConnectorError(_))), "call {} failed", call);
+		}
+
+		// Otherwise the operation is retried
+		tl.set_retry(None);
+		for call in 0..getattr_calls {
+			backend.fail(call, 1);
+			assert_eq!(tl.getattr(ino).unwrap().bytes, 4);
+		}
+	}
+
+	#[test]
+	fn write_full_blocks() {
+		let tl = volume();
+		let ino = create(&tl, 1, "test.bin", driver_objects::FileType::File);
+		let data = pattern(BLOCK * 3, 0);
+		tl.write(ino, 0, &data).unwrap();
+		assert_eq!(tl.filesize(ino).unwrap(), driver_objects::FileSize { bytes: BLOCK as u64 * 3, blocks: 3 });
+		assert_eq!(read_all(&tl, ino), data);
+	}
+
+	#[test]
+	fn overwrite_inside_block() {
+		let tl = volume();
+		let ino = create(&tl, 1, "test.txt", driver_objects::FileType::File);
+		tl.write(ino, 0, b"aaaaaaaaaa").unwrap();
+		tl.write(ino, 3, b"bbb").unwrap();
+		assert_eq!(read_all(&tl, ino), b"aaabbbaaaa");
+	}
+
+	#[test]
+	fn overwrite_across_blocks() {
+		let tl = volume();
+		let ino = create(&tl, 1, "test.bin", driver_objects::FileType::File);
+		let mut expected = pattern(BLOCK * 3, 0);
+		tl.write(ino, 0, &expected).unwrap();
+
+		let data = pattern(BLOCK + 100, 7);
+		tl.write(ino, BLOCK as u64 - 50, &data).unwrap();
+		expected[BLOCK - 50..BLOCK * 2 + 50].copy_from_slice(&data);
+		assert_eq!(read_all(&tl, ino), expected);
+	}
+
+	#[test]
+	fn append_unaligned() {
+		let tl = volume();
+		let ino = create(&tl, 1, "test.bin", driver_objects::FileType::File);
+		let mut expected = Vec::new();
+		for chunk in 0..5 {
+			let data = pattern(BLOCK / 3 + 17, chunk);
+			tl.write(ino, expected.len() as u64, &data).unwrap();
+			expected.extend(data);
+		}
+		assert_eq!(tl.filesize(ino).unwrap().bytes, expected.len() as u64);
+		assert_eq!(read_all(&tl, ino), expected);
+	}
+
+	#[test]
+	fn disjoint_write_pads_with_zeros() {
+		let tl = volume();
+		let ino = create(&tl, 1, "sparse.bin", driver_objects::FileType::File);
+		tl.write(ino, 0, b"head").unwrap();
+		tl.write(ino, BLOCK as u64 * 2 + 10, b"tail").unwrap();
+
+		let mut expected = vec![0; BLOCK * 2 + 14];
+		expected[..4].copy_from_slice(b"head");
+		expected[BLOCK * 2 + 10..].copy_from_slice(b"tail");
+		assert_eq!(tl.filesize(ino).unwrap(), driver_objects::FileSize { bytes: expected.len() as u64, blocks: 3 });
+		assert_eq!(read_all(&tl, ino), expected);
+	}
+
+	#[test]
+	fn disjoint_write_into_empty_file() {
+		let tl = volume();
+		let ino = create(&tl, 1, "sparse.bin", driver_objects::FileType::File);
+		tl.write(ino, 5, b"data").unwrap();
+		assert_eq!(read_all(&tl, ino), b"\0\0\0\0\0data");
+	}
+
+	#[test]
+	fn resize_grow_and_shrink() {
+		let tl = volume();
+		let ino = create(&tl, 1, "test.bin", driver_objects::FileType::File);
+		tl.write(ino, 0, b"hello").unwrap();
+
+		tl.resize(ino, BLOCK as u64 * 2 + 1).unwrap();
+		let mut expected = vec![0; BLOCK * 2 + 1];
+		expected[..5].copy_from_slice(b"hello");
+		assert_eq!(read_all(&tl, ino), expected);
+
+		tl.resize(ino, BLOCK as u64).unwrap();
+		assert_eq!(tl.filesize(ino).unwrap(), driver_objects::FileSize { bytes: BLOCK as u64, blocks: 1 });
+
+		tl.resize(ino, 2).unwrap();
+		assert_eq!(read_all(&tl, ino), b"he");
+
+		tl.resize(ino, 0).unwrap();
+		assert_eq!(tl.getattr(ino).unwrap().bytes, 0);
+		assert_eq!(tl.statfs().unwrap().used_blocks, 0);
+	}
+
+	#[test]
+	fn setattr_keeps_nanoseconds() {
+		let tl = volume();
+		let ino = create(&tl, 1, "test.txt", driver_objects::FileType::File);
+		for mtime in [
+			SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789),
+			SystemTime::UNIX_EPOCH - Duration::new(86_400, 500_000_000), // Before 1970
+			SystemTime::UNIX_EPOCH + Duration::from_secs(1u64 << 33) // After 2038
+		] {
+			let result = tl.setattr(ino, driver_objects::FileSetAttr { mtime, ..file_attr() }).unwrap();
+			assert_eq!(result.mtime, mtime);
+			assert_eq!(tl.getattr(ino).unwrap().mtime, mtime);
+		}
+	}
+
+	#[test]
+	fn ctime_is_separate_from_crtime() {
+		let tl = volume();
+		let crtime = SystemTime::UNIX_EPOCH + Duration::new(1_600_000_000, 1);
+		let ino = tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, driver_objects::FileSetAttr { ctime: crtime, crtime, ..file_attr() }).unwrap().ino as u64;
+		assert_eq!(tl.getattr(ino).unwrap().crtime, crtime);
+
+		tl.link(1, OsStr::new("link.txt"), ino).unwrap();
+		let attr = tl.getattr(ino).unwrap();
+		assert!(attr.ctime > crtime);
+		assert_eq!(attr.crtime, crtime);
+
+		let ctime = SystemTime::UNIX_EPOCH + Duration::new(1_650_000_000, 2);
+		let attr = tl.setattr(ino, driver_objects::FileSetAttr { ctime, crtime: SystemTime::now(), ..file_attr() }).unwrap();
+		assert_eq!(attr.ctime, ctime);
+		assert_eq!(attr.crtime, crtime);
+	}
+
+	#[test]
+	fn data_changes_set_mtime_and_ctime() {
+		let tl = volume();
+		let past = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+		let ino = tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, driver_objects::FileSetAttr { atime: past, mtime: past, ctime: past, crtime: past, ..file_attr() }).unwrap().ino as u64;
+
+		tl.write(ino, 0, b"data").unwrap();
+		let attr = tl.getattr(ino).unwrap();
+		assert!(attr.mtime > past);
+		assert_eq!(attr.ctime, attr.mtime);
+		assert_eq!((attr.atime, attr.crtime), (past, past));
+
+		tl.setattr(ino, driver_objects::FileSetAttr { mtime: past, ctime: past, crtime: past, ..file_attr() }).unwrap();
+		tl.resize(ino, 2).unwrap();
+		let attr = tl.getattr(ino).unwrap();
+		assert!(attr.mtime > past);
+		assert_eq!(attr.ctime, attr.mtime);
+
+		let time = SystemTime::UNIX_EPOCH + Duration::new(1_500_000_000, 5);
+		tl.write_with_mtime(ino, 0, b"x", time).unwrap();
+		let attr = tl.getattr(ino).unwrap();
+		assert_eq!((attr.mtime, attr.ctime), (time, time));
+	}
+
+	#[test]
+	fn atime_policies() {
+		let tl = volume();
+		let ino = create(&tl, 1, "test.txt", driver_objects::FileType::File);
+		let set_times = |atime: SystemTime, mtime: SystemTime| {
+			tl.setattr(ino, driver_objects::FileSetAttr { atime, mtime, ctime: mtime, ..file_attr() }).unwrap();
+		};
+		let read = |policy: driver_objects::AtimePolicy, time: SystemTime| {
+			if policy.update(&tl.getattr(ino).unwrap(), time) {
+				tl.access(ino, time).unwrap();
+			}
+		};
+		let hour = Duration::from_secs(60 * 60);
+		let now = SystemTime::now();
+
+		set_times(now - 3 * hour, now - 2 * hour);
+		read(driver_objects::AtimePolicy::Never, now);
+		assert_eq!(tl.getattr(ino).unwrap().atime, now - 3 * hour);
+
+		// Older than the modification
+		read(driver_objects::AtimePolicy::Relative, now);
+		assert_eq!(tl.getattr(ino).unwrap().atime, now);
+
+		// Newer than the modification, but less than a day old
+		set_times(now - hour, now - 2 * hour);
+		read(driver_objects::AtimePolicy::Relative, now);
+		assert_eq!(tl.getattr(ino).unwrap().atime, now - hour);
+		read(driver_objects::AtimePolicy::Strict, now);
+		assert_eq!(tl.getattr(ino).unwrap().atime, now);
+
+		// Strict updates are throttled
+		read(driver_objects::AtimePolicy::Strict, now + driver_objects::STRICT_ATIME_INTERVAL / 2);
+		assert_eq!(tl.getattr(ino).unwrap().atime, now);
+		read(driver_objects::AtimePolicy::Strict, now + driver_objects::STRICT_ATIME_INTERVAL);
+		assert_eq!(tl.getattr(ino).unwrap().atime, now + driver_objects::STRICT_ATIME_INTERVAL);
+
+		// More than a day old
+		set_times(now - 25 * hour, now - 26 * hour);
+		read(driver_objects::AtimePolicy::Relative, now);
+		assert_eq!(tl.getattr(ino).unwrap().atime, now);
+
+		assert_eq!("noatime".parse::<driver_objects::AtimePolicy>().unwrap(), driver_objects::AtimePolicy::Never);
+		assert!(matches!("atime".parse::<driver_objects::AtimePolicy>(), Err(Error::ClientError(_))));
+	}
+
+	#[test]
+	fn hardlinks_and_unlink() {
+		let tl = volume();
+		let ino = create(&tl, 1, "test.txt", driver_objects::FileType::File);
+		tl.write(ino, 0, b"data").unwrap();
+		tl.link(1, OsStr::new("link.txt"), ino).unwrap();
+		assert_eq!(tl.getattr(ino).unwrap().hardlinks, 2);
+
+		tl.unlink(1, OsStr::new("test.txt")).unwrap();
+		assert_eq!(tl.getattr(ino).unwrap().hardlinks, 1);
+		assert_eq!(read_all(&tl, ino), b"data");
+
+		// The file may still be open, it keeps its data until it is reclaimed
+		tl.unlink(1, OsStr::new("link.txt")).unwrap();
+		assert_eq!(tl.getattr(ino).unwrap().hardlinks, 0);
+		assert_eq!(read_all(&tl, ino), b"data");
+		tl.reclaim(ino).unwrap();
+		assert!(tl.getattr(ino).is_err());
+		assert_eq!(tl.statfs().unwrap().used_blocks, 0);
+		assert!(matches!(tl.unlink(1, OsStr::new("link.txt")), Err(Error::NotFoundError(_))));
+	}
+
+	#[test]
+	fn duplicate_name_is_rejected() {
+		let tl = volume();
+		create(&tl, 1, "test.txt", driver_objects::FileType::File);
+		assert!(tl.mknod(1, OsStr::new("test.txt"), driver_objects::FileType::File, file_attr()).is_err());
+		// The inode created before the name was refused is rolled back
+		assert_eq!(tl.statfs().unwrap().used_inodes, 2);
+	}
+
+	#[test]
+	fn directories() {
+		let tl = volume();
+		let dir = create(&tl, 1, "dir", driver_objects::FileType::Directory);
+		let sub = create(&tl, dir, "sub", driver_objects::FileType::Directory);
+		let file = create(&tl, dir, "file", driver_objects::FileType::File);
+
+		assert_eq!(tl.getattr(1).unwrap().hardlinks, 3);
+		assert_eq!(tl.getattr(dir).unwrap().hardlinks, 3);
+		assert_eq!(tl.count_children(dir).unwrap(), 4);
+		assert_eq!(tl.readdir(dir).unwrap(), vec![
+			driver_objects::DirectoryEntry { inode: dir, ftype: driver_objects::FileType::Directory, name: ".".into() },
+			driver_objects::DirectoryEntry { inode: 1, ftype: driver_objects::FileType::Directory, name: "..".into() },
+			driver_objects::DirectoryEntry { inode: sub, ftype: driver_objects::FileType::Directory, name: "sub".into() },
+			driver_objects::DirectoryEntry { inode: file, ftype: driver_objects::FileType::File, name: "file".into() },
+		]);
+
+		// A non-empty directory can not be deleted, and directories are not unlinked
+		assert!(matches!(tl.rmdir(1, OsStr::new("dir")), Err(Error::NotEmptyError(_))));
+		assert_eq!(tl.lookup_id(OsStr::new("dir"), 1).unwrap(), dir);
+		assert!(matches!(tl.unlink(dir, OsStr::new("sub")), Err(Error::IsADirectoryError(_))));
+		tl.rmdir(dir, OsStr::new("sub")).unwrap();
+		tl.unlink(dir, OsStr::new("file")).unwrap();
+		tl.rmdir(1, OsStr::new("dir")).unwrap();
+		assert_eq!(tl.readdir(1).unwrap().len(), 2);
+	}
+
+	#[test]
+	fn xattrs() {
+		let tl = volume();
+		let ino = create(&tl, 1, "a.txt", driver_objects::FileType::File);
+
+		assert!(matches!(tl.getxattr(ino, OsStr::new("user.a")), Err(Error::NoAttributeError(_))));
+		assert!(matches!(tl.setxattr(ino, OsStr::new("user.a"), b"1", driver_objects::SetXattrMode::Replace), Err(Error::NoAttributeError(_))));
+		tl.setxattr(ino, OsStr::new("user.a"), b"1", driver_objects::SetXattrMode::Create).unwrap();
+		assert!(matches!(tl.setxattr(ino, OsStr::new("user.a"), b"2", driver_objects::SetXattrMode::Create), Err(Error::AlreadyExistsError(_))));
+		tl.setxattr(ino, OsStr::new("user.a"), b"2", driver_objects::SetXattrMode::Replace).unwrap();
+		tl.setxattr(ino, OsStr::new("user.b"), b"3", driver_objects::SetXattrMode::Set).unwrap();
+		tl.setxattr(ino, OsStr::new("user.b"), b"4", driver_objects::SetXattrMode::Set).unwrap();
+		assert_eq!(tl.getxattr(ino, OsStr::new("user.a")).unwrap(), b"2");
+		assert_eq!(tl.getxattr(ino, OsStr::new("user.b")).unwrap(), b"4");
+		assert_eq!(tl.listxattr(ino).unwrap(), vec!["user.a", "user.b"]);
+
+		tl.removexattr(ino, OsStr::new("user.a")).unwrap();
+		assert!(matches!(tl.removexattr(ino, OsStr::new("user.a")), Err(Error::NoAttributeError(_))));
+		assert_eq!(tl.listxattr(ino).unwrap(), vec!["user.b"]);
+
+		// Limits
+		let long_name = format!("user.{}", "x".repeat(MAX_XATTR_NAME_LEN));
+		assert!(matches!(tl.setxattr(ino, OsStr::new(&long_name), b"", driver_objects::SetXattrMode::Set), Err(Error::RangeError(_))));
+		assert!(matches!(tl.setxattr(ino, OsStr::new("user.c"), &vec![0; MAX_XATTR_SIZE + 1], driver_objects::SetXattrMode::Set), Err(Error::TooBigError(_))));
+		tl.setxattr(ino, OsStr::new("user.c"), &vec![0; MAX_XATTR_SIZE], driver_objects::SetXattrMode::Set).unwrap();
+		assert!(matches!(tl.setxattr(12345, OsStr::new("user.a"), b"", driver_objects::SetXattrMode::Set), Err(Error::NotFoundError(_))));
+
+		// The root of the volume and attributes removed with the inode
+		tl.setxattr(ROOT_INODE, OsStr::new("user.root"), b"", driver_objects::SetXattrMode::Set).unwrap();
+		assert_eq!(tl.listxattr(ROOT_INODE).unwrap(), vec!["user.root"]);
+		tl.unlink(1, OsStr::new("a.txt")).unwrap();
+		tl.reclaim(ino).unwrap();
+		assert!(tl.0.list_xattrs(ino).unwrap().is_empty());
+	}
+
+	/// ACL in the xattr format, `(tag, perm, id)` entries
+	fn acl(entries: &[(u16, u16, u32)]) -> Vec<u8> {
+		let mut value = 2u32.to_le_bytes().to_vec();
+		for (tag, perm, id) in entries {
+			value.extend(tag.to_le_bytes());
+			value.extend(perm.to_le_bytes());
+			value.extend(id.to_le_bytes());
+		}
+		value
+	}
+
+	#[test]
+	fn acls() {
+		let tl = volume();
+		let dir = create(&tl, 1, "dir", driver_objects::FileType::Directory);
+		let file = create(&tl, 1, "file", driver_objects::FileType::File);
+		let perm = |ino: u64| tl.getattr(ino).unwrap().perm;
+		let access = OsStr::new("system.posix_acl_access");
+		let default = OsStr::new("system.posix_acl_default");
+
+		// An access ACL sets the permission bits, the group bits are the mask
+		let extended = acl(&[(0x01, 7, u32::MAX), (0x02, 6, 1000), (0x04, 4, u32::MAX), (0x10, 6, u32::MAX), (0x20, 0, u32::MAX)]);
+		tl.setxattr(file, access, &extended, driver_objects::SetXattrMode::Set).unwrap();
+		assert_eq!(perm(file), driver_objects::Permissions { special: 0, owner: 7, group: 6, other: 0 });
+		assert_eq!(tl.getxattr(file, access).unwrap(), extended);
+
+		// chmod changes the mask, not the owning group
+		let mut attr = file_attr();
+		attr.perm = driver_objects::Permissions { special: 0, owner: 6, group: 4, other: 4 };
+		tl.setattr(file, attr).unwrap();
+		assert_eq!(tl.getxattr(file, access).unwrap(), acl(&[(0x01, 6, u32::MAX), (0x02, 6, 1000), (0x04, 4, u32::MAX), (0x10, 4, u32::MAX), (0x20, 4, u32::MAX)]));
+
+		// A minimal ACL is only kept as permission bits
+		tl.setxattr(file, access, &acl(&[(0x01, 6, u32::MAX), (0x04, 0, u32::MAX), (0x20, 0, u32::MAX)]), driver_objects::SetXattrMode::Set).unwrap();
+		assert_eq!(perm(file), driver_objects::Permissions { special: 0, owner: 6, group: 0, other: 0 });
+		assert!(matches!(tl.getxattr(file, access), Err(Error::NoAttributeError(_))));
+
+		assert!(matches!(tl.setxattr(file, access, b"invalid", driver_objects::SetXattrMode::Set), Err(Error::ClientError(_))));
+		assert!(matches!(tl.setxattr(file, default, &extended, driver_objects::SetXattrMode::Set), Err(Error::AccessError(_))));
+
+		// New children inherit the default ACL instead of applying the umask
+		tl.setxattr(dir, default, &extended, driver_objects::SetXattrMode::Set).unwrap();
+		let child = tl.mknod_with_umask(dir, OsStr::new("child"), driver_objects::FileType::File, file_attr(), 0, 0o022).unwrap().ino as u64;
+		assert_eq!(perm(child), driver_objects::Permissions { special: 0, owner: 6, group: 4, other: 0 });
+		assert_eq!(tl.getxattr(child, access).unwrap(), acl(&[(0x01, 6, u32::MAX), (0x02, 6, 1000), (0x04, 4, u32::MAX), (0x10, 4, u32::MAX), (0x20, 0, u32::MAX)]));
+		assert!(matches!(tl.getxattr(child, default), Err(Error::NoAttributeError(_))));
+		let subdir = tl.mknod_with_umask(dir, OsStr::new("subdir"), driver_objects::FileType::Directory, file_attr(), 0, 0o022).unwrap().ino as u64;
+		assert_eq!(tl.getxattr(subdir, default).unwrap(), extended);
+
+		let masked = tl.mknod_with_umask(1, OsStr::new("masked"), driver_objects::FileType::File, file_attr(), 0, 0o022).unwrap().ino as u64;
+		assert_eq!(perm(masked), driver_objects::Permissions { special: 0, owner: 6, group: 4, other: 4 });
+		let mut attr = file_attr();
+		attr.perm = driver_objects::Permissions { special: 0, owner: 7, group: 7, other: 7 };
+		let masked = tl.mknod_with_umask(1, OsStr::new("masked2"), driver_objects::FileType::File, attr, 0, 0o027).unwrap().ino as u64;
+		assert_eq!(perm(masked), driver_objects::Permissions { special: 0, owner: 7, group: 5, other: 0 });
+
+		// Removing the default ACL
+		tl.setxattr(dir, default, &[], driver_objects::SetXattrMode::Set).unwrap();
+		assert!(tl.listxattr(dir).unwrap().is_empty());
+	}
+
+	#[test]
+	fn rename() {
+		let tl = volume();
+		let dir = create(&tl, 1, "dir", driver_objects::FileType::Directory);
+		let ino = create(&tl, 1, "a.txt", driver_objects::FileType::File);
+		create(&tl, dir, "taken.txt", driver_objects::FileType::File);
+
+		tl.rename(1, OsStr::new("a.txt"), 1, OsStr::new("b.txt"), driver_objects::RenameMode::Replace).unwrap();
+		assert_eq!(tl.lookup_id(OsStr::new("b.txt"), 1).unwrap(), ino);
+		assert!(tl.lookup_id(OsStr::new("a.txt"), 1).is_err());
+
+		tl.rename(1, OsStr::new("b.txt"), dir, OsStr::new("c.txt"), driver_objects::RenameMode::Replace).unwrap();
+		assert_eq!(tl.lookup_id(OsStr::new("c.txt"), dir).unwrap(), ino);
+
+		assert!(matches!(tl.rename(dir, OsStr::new("c.txt"), dir, OsStr::new("taken.txt"), driver_objects::RenameMode::NoReplace), Err(Error::AlreadyExistsError(_))));
+		assert!(matches!(tl.rename(1, OsStr::new("missing"), 1, OsStr::new("x"), driver_objects::RenameMode::Replace), Err(Error::NotFoundError(_))));
+	}
+
+	#[test]
+	fn rename_replaces_destination() {
+		let tl = volume();
+		let src = create(&tl, 1, "new.txt", driver_objects::FileType::File);
+		let dest = create(&tl, 1, "old.txt", driver_objects::FileType::File);
+		tl.write(src, 0, b"new").unwrap();
+		tl.write(dest, 0, b"old").unwrap();
+
+		tl.rename(1, OsStr::new("new.txt"), 1, OsStr::new("old.txt"), driver_objects::RenameMode::Replace).unwrap();
+		assert_eq!(tl.lookup_id(OsStr::new("old.txt"), 1).unwrap(), src);
+		assert_eq!(read_all(&tl, src), b"new");
+		assert_eq!(read_all(&tl, dest), b"old");
+		tl.reclaim(dest).unwrap();
+		assert!(tl.getattr(dest).is_err());
+		assert_eq!(tl.readdir(1).unwrap().len(), 3);
+
+		// A hardlink of the source stays (renaming one name of a file onto another changes nothing)
+		tl.link(1, OsStr::new("link.txt"), src).unwrap();
+		tl.rename(1, OsStr::new("link.txt"), 1, OsStr::new("old.txt"), driver_objects::RenameMode::Replace).unwrap();
+		assert_eq!(tl.getattr(src).unwrap().hardlinks, 2);
+
+		// Empty directories are replaced by directories
+		let dir = create(&tl, 1, "dir", driver_objects::FileType::Directory);
+		let empty = create(&tl, 1, "empty", driver_objects::FileType::Directory);
+		tl.rename(1, OsStr::new("dir"), 1, OsStr::new("empty"), driver_objects::RenameMode::Replace).unwrap();
+		assert_eq!(tl.lookup_id(OsStr::new("empty"), 1).unwrap(), dir);
+		assert!(tl.getattr(empty).is_err());
+	}
+
+	#[test]
+	fn rename_exchange() {
+		let tl = volume();
+		let first = create(&tl, 1, "first", driver_objects::FileType::Directory);
+		let file = create(&tl, first, "file", driver_objects::FileType::File);
+		let second = create(&tl, 1, "second", driver_objects::FileType::Directory);
+		let dir = create(&tl, second, "dir", driver_objects::FileType::Directory);
+
+		tl.rename(first, OsStr::new("file"), second, OsStr::new("dir"), driver_objects::RenameMode::Exchange).unwrap();
+		assert_eq!(tl.lookup_id(OsStr::new("file"), first).unwrap(), dir);
+		assert_eq!(tl.lookup_id(OsStr::new("dir"), second).unwrap(), file);
+		assert_eq!(tl.readdir(dir).unwrap()[1].inode, first);
+		assert_eq!((tl.getattr(first).unwrap().hardlinks, tl.getattr(second).unwrap().hardlinks), (3, 2));
+
+		// A directory can not be swapped with one of its own entries
+		assert!(matches!(tl.rename(1, OsStr::new("second"), second, OsStr::new("dir"), driver_objects::RenameMode::Exchange), Err(Error::ClientError(_))));
+		assert_eq!(tl.lookup_id(OsStr::new("second"), 1).unwrap(), second);
+	}
+
+	#[test]
+	fn rename_into_own_subtree() {
+		let tl = volume();
+		let dir = create(&tl, 1, "dir", driver_objects::FileType::Directory);
+		let sub = create(&tl, dir, "sub", driver_objects::FileType::Directory);
+		let subsub = create(&tl, sub, "subsub", driver_objects::FileType::Directory);
+
+		for parent in [dir, sub, subsub] {
+			assert!(matches!(tl.rename(1, OsStr::new("dir"), parent, OsStr::new("moved"), driver_objects::RenameMode::Replace), Err(Error::ClientError(_))));
+		}
+		tl.rename(sub, OsStr::new("subsub"), 1, OsStr::new("moved"), driver_objects::RenameMode::Replace).unwrap();
+		assert_eq!(tl.readdir(subsub).unwrap()[1].inode, 1);
+	}
+
+	#[test]
+	fn reclaim_orphans() {
+		let tl = volume();
+		let kept = create(&tl, 1, "kept.txt", driver_objects::FileType::File);
+		let first = create(&tl, 1, "first.txt", driver_objects::FileType::File);
+		let second = create(&tl, 1, "second.txt", driver_objects::FileType::File);
+		tl.write(first, 0, b"data").unwrap();
+		tl.unlink(1, OsStr::new("first.txt")).unwrap();
+		tl.unlink(1, OsStr::new("second.txt")).unwrap();
+
+		// Reclaiming a file which still has a name does nothing
+		tl.reclaim(kept).unwrap();
+		assert_eq!(tl.getattr(kept).unwrap().hardlinks, 1);
+
+		// The driver went away while both files were open
+		assert_eq!(tl.0.list_orphans(tl.2.id).unwrap(), vec![first, second]);
+		assert_eq!(tl.reclaim_orphans().unwrap(), 2);
+		assert!(tl.getattr(first).is_err());
+		assert!(tl.getattr(second).is_err());
+		assert_eq!(tl.statfs().unwrap().used_blocks, 0);
+		assert_eq!(tl.reclaim_orphans().unwrap(), 0);
+	}
+
+	#[test]
+	fn read_cache() {
+		let mut tl = volume();
+		assert_eq!(tl.read_cache_stats(), None);
+		tl.set_read_cache(1 << 20);
+		let block_size = tl.superblock().block_size as usize;
+		let ino = create(&tl, 1, "test.bin", driver_objects::FileType::File);
+		let mut data = pattern(block_size * 3 + 100, 0);
+		tl.write(ino, 0, &data).unwrap();
+
+		// Reads within and across blocks, the second one of each is served from the cache (and the
+		// first read across the first two blocks, the database returns the block after a read too)
+		for (offset, len) in [(0, 100), (block_size - 10, 20), (block_size * 3, 200)] {
+			for _ in 0..2 {
+				let mut buffer = vec![0; len];
+				let read = tl.read(ino, offset as u64, &mut buffer).unwrap();
+				assert_eq!(&buffer[..read], &data[offset..(offset + len).min(data.len())]);
+			}
+		}
+		let stats = tl.read_cache_stats().unwrap();
+		assert_eq!((stats.hits, stats.misses), (4, 2));
+
+		// Writes and resizes drop the cached blocks
+		tl.write(ino, 10, b"changed").unwrap();
+		data[10..17].copy_from_slice(b"changed");
+		let mut buffer = [0; 100];
+		assert_eq!(tl.read(ino, 0, &mut buffer).unwrap(), 100);
+		assert_eq!(&buffer[..], &data[..100]);
+		tl.resize(ino, 50).unwrap();
+		assert_eq!(tl.read(ino, 0, &mut buffer).unwrap(), 50);
+		assert_eq!(&buffer[..50], &data[..50]);
+		assert_eq!(tl.read_cache_stats().unwrap().misses, 4);
+
+		// So does deleting the file
+		tl.unlink(1, OsStr::new("test.bin")).unwrap();
+		assert_eq!(tl.read(ino, 0, &mut buffer).unwrap(), 50);
+		tl.reclaim(ino).unwrap();
+		assert!(tl.read(ino, 0, &mut buffer).is_err());
+		assert_eq!(tl.read_cache_stats().unwrap().bytes, 0);
+
+		tl.set_read_cache(0);
+		assert_eq!(tl.read_cache_stats(), None);
+	}
+
+	#[test]
+	fn format_keeps_inode_ids_unique() {
+		let mut tl = volume();
+		let ino = create(&tl, 1, "test.txt", driver_objects::FileType::File);
+		tl.format(settings::FILE_BLOCK_SIZE_32, "").unwrap();
+		assert!(tl.getattr(ino).is_err());
+		assert!(create(&tl, 1, "test.txt", driver_objects::FileType::File) > ino);
+	}
+
+	#[test]
+	fn device_nodes() {
+		let tl = volume();
+		let attr = tl.mknod_with_umask(1, OsStr::new("null"), driver_objects::FileType::CharDevice, file_attr(), 0x103, 0).unwrap();
+		assert_eq!((attr.kind, attr.rdev, attr.bytes, attr.hardlinks), (driver_objects::FileType::CharDevice, 0x103, 0, 1));
+		let attr = tl.lookup(OsStr::new("null"), 1).unwrap();
+		assert_eq!((attr.kind, attr.rdev), (driver_objects::FileType::CharDevice, 0x103));
+		let disk = tl.mknod_with_umask(1, OsStr::new("sda"), driver_objects::FileType::BlockDevice, file_attr(), 0x800, 0).unwrap().ino as u64;
+		assert_eq!(tl.getattr(disk).unwrap().rdev, 0x800);
+		assert_eq!(tl.getattr(create(&tl, 1, "file", driver_objects::FileType::File)).unwrap().rdev, 0);
+
+		tl.unlink(1, OsStr::new("null")).unwrap();
+		tl.reclaim(attr.ino as u64).unwrap();
+		assert!(tl.getattr(attr.ino as u64).is_err());
+		assert_eq!(tl.readdir(1).unwrap().len(), 4);
+	}
+
+	#[test]
+	fn volumes_are_isolated() {
+		let backend = MemoryBackend::new();
+		backend::create_volume(&backend, "second", &new_superblock(512, "second").unwrap()).unwrap();
+		let mut first = TranslationLayer::with_backend(Box::new(backend.clone()), DEFAULT_VOLUME).unwrap();
+		let mut second = TranslationLayer::with_backend(Box::new(backend.clone()), "second").unwrap();
+		assert_eq!(second.superblock().block_size, 512);
+		assert_eq!(second.superblock().label, "second");
+
+		let ino = create(&mut first, 1, "test.txt", driver_objects::FileType::File);
+		first.write(ino, 0, b"first").unwrap();
+		assert!(matches!(second.lookup_id(OsStr::new("test.txt"), 1), Err(Error::NotFoundError(_))));
+		assert_eq!(second.readdir(1).unwrap().len(), 2);
+
+		let other = create(&mut second, 1, "test.txt", driver_objects::FileType::File);
+		second.write(other, 0, b"second").unwrap();
+		assert_ne!(ino, other);
+		assert_eq!(read_all(&mut first, ino), b"first");
+		assert_eq!(read_all(&mut second, other), b"second");
+		assert_eq!(first.statfs().unwrap(), driver_objects::FilesystemStat { used_blocks: 1, used_inodes: 2 });
+		assert_eq!(second.statfs().unwrap(), driver_objects::FilesystemStat { used_blocks: 1, used_inodes: 2 });
+
+		second.format(4096, "").unwrap();
+		assert!(second.lookup_id(OsStr::new("test.txt"), 1).is_err());
+		assert_eq!(first.lookup_id(OsStr::new("test.txt"), 1).unwrap(), ino);
+		assert_eq!(read_all(&mut first, ino), b"first");
+	}
+
+	#[test]
+	fn root_of_other_volume_is_mapped() {
+		let backend = MemoryBackend::new();
+		backend::create_volume(&backend, "second", &new_superblock(4096, "").unwrap()).unwrap();
+		let tl = TranslationLayer::with_backend(Box::new(backend), "second").unwrap();
+		assert_ne!(tl.volume().root_inode, Some(ROOT_INODE));
+
+		let attr = tl.getattr(ROOT_INODE).unwrap();
+		assert_eq!((attr.ino, attr.kind, attr.hardlinks), (ROOT_INODE as u32, driver_objects::FileType::Directory, 2));
+		let dir = create(&tl, ROOT_INODE, "dir", driver_objects::FileType::Directory);
+		assert_eq!(tl.readdir(ROOT_INODE).unwrap(), vec![
+			driver_objects::DirectoryEntry { inode: ROOT_INODE, ftype: driver_objects::FileType::Directory, name: ".".into() },
+			driver_objects::DirectoryEntry { inode: ROOT_INODE, ftype: driver_objects::FileType::Directory, name: "..".into() },
+			driver_objects::DirectoryEntry { inode: dir, ftype: driver_objects::FileType::Directory, name: "dir".into() },
+		]);
+		assert_eq!(tl.readdir(dir).unwrap()[1].inode, ROOT_INODE);
+		assert_eq!(tl.lookup(OsStr::new("dir"), ROOT_INODE).unwrap().ino as u64, dir);
+		tl.rename(ROOT_INODE, OsStr::new("dir"), ROOT_INODE, OsStr::new("moved"), driver_objects::RenameMode::Replace).unwrap();
+		tl.rmdir(ROOT_INODE, OsStr::new("moved")).unwrap();
+		assert_eq!(tl.readdir(ROOT_INODE).unwrap().len(), 2);
+	}
+}
+
+
+
 #[cfg(feature = "integration_testing")]
 #[cfg(test)]
 mod test {
@@ -647,9 +1894,28 @@ mod test {
 	use super::*;
 
 
+	/// Connect to the integration testing database (`$DBFS_DB_URL` selects a different backend)
+	fn connect() -> TranslationLayer {
+		let config = ConnectionConfig { db_url: std::env::var("DBFS_DB_URL").ok(), ..ConnectionConfig::default() };
+		TranslationLayer::new(&config, backend::DEFAULT_VOLUME).unwrap()
+	}
+
+	/// Writing sets the modification and change time, give the test files their original times
+	/// back (like their original contents)
+	fn restore_times(sql: &TranslationLayer, inode: u64) {
+		let (atime, mtime, ctime) = match inode {
+			2 => (1729792440, 1729792440, 1729792440),
+			_ => (1729792594, 1729792634, 1729792594),
+		};
+		let time = |sec: u64| std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(sec);
+		let attr = sql.getattr(inode).unwrap();
+		sql.setattr(inode, driver_objects::FileSetAttr { uid: attr.uid, gid: attr.gid, atime: time(atime), mtime: time(mtime), ctime: time(ctime), crtime: attr.crtime, perm: attr.perm }).unwrap();
+	}
+
+
 	#[test]
 	fn getattr_dir_01() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let attr = sql.getattr(1).unwrap();
 		assert_eq!(attr, driver_objects::FileAttr {
 			ino: 1,
@@ -661,14 +1927,16 @@ mod test {
 			atime: "2024-10-24 17:52:52+0000".parse::<DateTime<Local>>().unwrap().into(),
 			mtime: "2024-10-24 17:53:10+0000".parse::<DateTime<Local>>().unwrap().into(),
 			ctime: "2024-10-24 17:52:52+0000".parse::<DateTime<Local>>().unwrap().into(),
+			crtime: "2024-10-24 17:52:52+0000".parse::<DateTime<Local>>().unwrap().into(),
 			kind: driver_objects::FileType::Directory,
 			perm: driver_objects::Permissions { special: 0, owner: 7, group: 5, other: 5 },
+			rdev: 0,
 		});
 	}
 
 	#[test]
 	fn getattr_dir_02() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let attr = sql.getattr(4).unwrap();
 		assert_eq!(attr, driver_objects::FileAttr {
 			ino: 4,
@@ -680,14 +1948,16 @@ mod test {
 			atime: "2024-10-26 16:59:30+0000".parse::<DateTime<Local>>().unwrap().into(),
 			mtime: "2024-10-26 16:59:30+0000".parse::<DateTime<Local>>().unwrap().into(),
 			ctime: "2024-10-26 16:59:30+0000".parse::<DateTime<Local>>().unwrap().into(),
+			crtime: "2024-10-26 16:59:30+0000".parse::<DateTime<Local>>().unwrap().into(),
 			kind: driver_objects::FileType::Directory,
 			perm: driver_objects::Permissions { special: 0, owner: 7, group: 5, other: 5 },
+			rdev: 0,
 		});
 	}
 	
 	#[test]
 	fn getattr_smaller_file() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let attr = sql.getattr(2).unwrap();
 		assert_eq!(attr, driver_objects::FileAttr {
 			ino: 2,
@@ -699,15 +1969,17 @@ mod test {
 			atime: "2024-10-24 17:54:00+0000".parse::<DateTime<Local>>().unwrap().into(),
 			mtime: "2024-10-24 17:54:00+0000".parse::<DateTime<Local>>().unwrap().into(),
 			ctime: "2024-10-24 17:54:00+0000".parse::<DateTime<Local>>().unwrap().into(),
+			crtime: "2024-10-24 17:54:00+0000".parse::<DateTime<Local>>().unwrap().into(),
 			kind: driver_objects::FileType::File,
 			perm: driver_objects::Permissions { special: 0, owner: 6, group: 4, other: 4 },
+			rdev: 0,
 		});
 	}
 
 	#[test]
 	#[serial]
 	fn getattr_larger_file() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let attr = sql.getattr(3).unwrap();
 		assert_eq!(attr, driver_objects::FileAttr {
 			ino: 3,
@@ -719,14 +1991,16 @@ mod test {
 			atime: "2024-10-24 17:56:34+0000".parse::<DateTime<Local>>().unwrap().into(),
 			mtime: "2024-10-24 17:57:14+0000".parse::<DateTime<Local>>().unwrap().into(),
 			ctime: "2024-10-24 17:56:34+0000".parse::<DateTime<Local>>().unwrap().into(),
+			crtime: "2024-10-24 17:56:34+0000".parse::<DateTime<Local>>().unwrap().into(),
 			kind: driver_objects::FileType::File,
 			perm: driver_objects::Permissions { special: 0, owner: 6, group: 4, other: 4 },
+			rdev: 0,
 		});
 	}
 
 	#[test]
 	fn listdir_root() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let listing = sql.readdir(1).unwrap();
 		assert_eq!(listing, vec![
 			driver_objects::DirectoryEntry { inode: 1, ftype: driver_objects::FileType::Directory, name: ".".into() },
@@ -742,7 +2016,7 @@ mod test {
 	#[test]
 	#[serial]
 	fn lookup_01() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let entry = sql.lookup(&OsString::from("test.txt"), 1).unwrap();
 		assert_eq!(entry, driver_objects::FileAttr  {
 			ino: 2,
@@ -754,15 +2028,17 @@ mod test {
 			atime: "2024-10-24 17:54:00+0000".parse::<DateTime<Local>>().unwrap().into(),
 			mtime: "2024-10-24 17:54:00+0000".parse::<DateTime<Local>>().unwrap().into(),
 			ctime: "2024-10-24 17:54:00+0000".parse::<DateTime<Local>>().unwrap().into(),
+			crtime: "2024-10-24 17:54:00+0000".parse::<DateTime<Local>>().unwrap().into(),
 			kind: driver_objects::FileType::File,
 			perm: driver_objects::Permissions { special: 0, owner: 6, group: 4, other: 4 },
+			rdev: 0,
 		});
 	}
 
 	#[test]
 	#[serial]
 	fn lookup_02() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let entry = sql.lookup(&OsString::from("test.bin"), 1).unwrap();
 		assert_eq!(entry, driver_objects::FileAttr  {
 			ino: 3,
@@ -774,15 +2050,17 @@ mod test {
 			atime: "2024-10-24 17:56:34+0000".parse::<DateTime<Local>>().unwrap().into(),
 			mtime: "2024-10-24 17:57:14+0000".parse::<DateTime<Local>>().unwrap().into(),
 			ctime: "2024-10-24 17:56:34+0000".parse::<DateTime<Local>>().unwrap().into(),
+			crtime: "2024-10-24 17:56:34+0000".parse::<DateTime<Local>>().unwrap().into(),
 			kind: driver_objects::FileType::File,
 			perm: driver_objects::Permissions { special: 0, owner: 6, group: 4, other: 4 },
+			rdev: 0,
 		});
 	}
 
 	#[test]
 	#[serial]
 	fn hardlink_01() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let entry_1 = sql.lookup(&OsString::from("test.bin"), 1).unwrap();
 		let entry_2 = sql.lookup(&OsString::from("hardlink_to_test.bin"), 1).unwrap();
 		assert_eq!(entry_1, entry_2);
@@ -790,7 +2068,7 @@ mod test {
 
 	#[test]
 	fn read_file_01() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let buffer: &mut [u8] = &mut [0; 14];
 		let read = sql.read(2, 0, buffer).unwrap();
 		assert_eq!(buffer, "Hello, world!\n".as_bytes());
@@ -799,7 +2077,7 @@ mod test {
 
 	#[test]
 	fn read_file_02() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let buffer: &mut [u8] = &mut [0; 4];
 		let read = sql.read(2, 0, buffer).unwrap();
 		assert_eq!(buffer, "Hell".as_bytes());
@@ -808,7 +2086,7 @@ mod test {
 
 	#[test]
 	fn read_file_03() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let buffer: &mut [u8] = &mut [0; 14];
 		let read = sql.read(2, 4096, buffer);
 		println!("{:?}", read);
@@ -817,7 +2095,7 @@ mod test {
 
 	#[test]
 	fn read_file_04() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let buffer: &mut [u8] = &mut [0; 4097];
 		let read = sql.read(3, 4096 * 2, buffer).unwrap();
 		let target: &mut [u8] = &mut [0; 4097];
@@ -829,13 +2107,14 @@ mod test {
 	#[test]
 	#[serial]
 	fn write_to_file_01() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let original: &[u8] = &"Hello, world!\n".as_bytes();
 		let buffer: &[u8] = &"Wasup".as_bytes();
 		let _write = sql.write(2, 0, buffer).unwrap();
 		let changed_value: &mut [u8] = &mut [0; 14];
 		let read = sql.read(2, 0, changed_value).unwrap();
 		let _write = sql.write(2, 0, original).unwrap();
+		restore_times(&sql, 2);
 		assert_eq!(read, 14);
 		assert_eq!(changed_value, "Wasup, world!\n".as_bytes());
 	}
@@ -843,7 +2122,7 @@ mod test {
 	#[test]
 	#[serial]
 	fn write_to_file_02() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		let original: &mut[u8] = &mut[0; 4096 * 3 + 4];
 		original[4096*3..].copy_from_slice("aaaa".as_bytes());
 		let buffer: &[u8] = &"bbbb".as_bytes();
@@ -851,6 +2130,7 @@ mod test {
 		let changed_value: &mut [u8] = &mut [0; 7];
 		let read = sql.read(3, 4096 * 3 - 1, changed_value).unwrap();
 		let _write = sql.write(3, 0, original).unwrap();
+		restore_times(&sql, 3);
 		assert_eq!(changed_value, "bbbba\n\0".as_bytes());
 		assert_eq!(read, 6);
 	}
@@ -858,7 +2138,7 @@ mod test {
 	#[test]
 	#[serial]
 	fn resize_01() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		sql.resize(3, 4096 * 3 + 3).unwrap();
 		let original: &mut[u8] = &mut[0; 4096 * 3 + 5];
 		let read_bytes = sql.read(3, 0, original).unwrap();
@@ -867,6 +2147,7 @@ mod test {
 
 		sql.resize(3, 4096 * 3 + 5).unwrap();
 		sql.write(3, 4096 * 3 + 3, &['a' as u8, '\n' as u8]).unwrap();
+		restore_times(&sql, 3);
 		let read_bytes = sql.read(3, 0, original).unwrap();
 		assert_eq!(read_bytes, 4096 * 3 + 5);
 	}
@@ -874,7 +2155,7 @@ mod test {
 	#[test]
 	#[serial]
 	fn resize_02() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		sql.resize(3, 4096 + 1028).unwrap();
 		let original: &mut[u8] = &mut[0; 4096 * 3 + 5];
 		let read_bytes = sql.read(3, 0, original).unwrap();
@@ -883,6 +2164,7 @@ mod test {
 
 		sql.resize(3, 4096 * 3 + 5).unwrap();
 		sql.write(3, 4096 * 3, &['a' as u8, 'a' as u8, 'a' as u8,'a' as u8, '\n' as u8]).unwrap();
+		restore_times(&sql, 3);
 		let read_bytes = sql.read(3, 0, original).unwrap();
 		assert_eq!(read_bytes, 4096 * 3 + 5);
 	}
@@ -891,11 +2173,12 @@ mod test {
 	#[serial]
 	fn write_01() {
 		// END PAD
-		let mut sql = TranslationLayer::new().unwrap();
-		sql.unsafe_write(3, 4096, &[1_u8; 1024]).unwrap();
+		let sql = connect();
+		sql.write(3, 4096, &[1_u8; 1024]).unwrap();
 		let read = &mut [0_u8; 4096];
 		let read_bytes = sql.read(3, 4096, read).unwrap();
-		sql.unsafe_write(3, 4096, &[0_u8; 1024]).unwrap();
+		sql.write(3, 4096, &[0_u8; 1024]).unwrap();
+		restore_times(&sql, 3);
 		let mut target = Vec::from([1_u8; 1024]);
 		target.extend_from_slice(&[0_u8; 4096 - 1024]);
 		assert_eq!(read, target.as_slice());
@@ -906,11 +2189,12 @@ mod test {
 	#[serial]
 	fn write_02() {
 		// START PAD
-		let mut sql = TranslationLayer::new().unwrap();
-		sql.unsafe_write(3, 4096*2-1024, &[1_u8; 1024]).unwrap();
+		let sql = connect();
+		sql.write(3, 4096*2-1024, &[1_u8; 1024]).unwrap();
 		let read = &mut [0_u8; 4096];
 		let read_bytes = sql.read(3, 4096, read).unwrap();
-		sql.unsafe_write(3, 4096*2-1024, &[0_u8; 1024]).unwrap();
+		sql.write(3, 4096*2-1024, &[0_u8; 1024]).unwrap();
+		restore_times(&sql, 3);
 		let mut target = Vec::from([0_u8; 4096-1024]);
 		target.extend_from_slice(&[1_u8; 1024]);
 		assert_eq!(read, target.as_slice());
@@ -921,11 +2205,12 @@ mod test {
 	#[serial]
 	fn write_03() {
 		// ALIGNED
-		let mut sql = TranslationLayer::new().unwrap();
-		sql.unsafe_write(3, 0, &[1_u8; 2*4096]).unwrap();
+		let sql = connect();
+		sql.write(3, 0, &[1_u8; 2*4096]).unwrap();
 		let read = &mut [0_u8; 4096*2];
 		let read_bytes = sql.read(3, 0, read).unwrap();
-		sql.unsafe_write(3, 0, &[0_u8; 2*4096]).unwrap();
+		sql.write(3, 0, &[0_u8; 2*4096]).unwrap();
+		restore_times(&sql, 3);
 		let target = Vec::from([1_u8; 4096*2]);
 		assert_eq!(read, target.as_slice());
 		assert_eq!(read_bytes, 4096*2);
@@ -935,11 +2220,12 @@ mod test {
 	#[serial]
 	fn write_04() {
 		// END PAD
-		let mut sql = TranslationLayer::new().unwrap();
-		sql.unsafe_write(3, 0, &[1_u8; 2*4096-1]).unwrap();
+		let sql = connect();
+		sql.write(3, 0, &[1_u8; 2*4096-1]).unwrap();
 		let read = &mut [0_u8; 4096*2];
 		let read_bytes = sql.read(3, 0, read).unwrap();
-		sql.unsafe_write(3, 0, &[0_u8; 2*4096-1]).unwrap();
+		sql.write(3, 0, &[0_u8; 2*4096-1]).unwrap();
+		restore_times(&sql, 3);
 		let mut target = Vec::from([1_u8; 4096*2-1]);
 		target.extend_from_slice(&[0_u8; 1]);
 		assert_eq!(read, target.as_slice());
@@ -949,11 +2235,12 @@ mod test {
 	#[test]
 	#[serial]
 	fn disjoint_write_01() {
-		let mut sql = TranslationLayer::new().unwrap();
-		sql.unsafe_write(3, 4096 * 4, &[2_u8; 4096]).unwrap();
+		let sql = connect();
+		sql.write(3, 4096 * 4, &[2_u8; 4096]).unwrap();
 		let read = &mut [0_u8; 4096*2];
 		let read_bytes = sql.read(3, 4096 * 3, read).unwrap();
 		sql.resize(3, 4096 * 3 + 5).unwrap();
+		restore_times(&sql, 3);
 		let mut target: Vec<u8> = Vec::from(['a' as u8, 'a' as u8, 'a' as u8, 'a' as u8, '\n' as u8]);
 		target.extend_from_slice(&[0_u8; 4096 - 5]);
 		target.extend_from_slice(&[2_u8; 4096]);
@@ -964,11 +2251,12 @@ mod test {
 	#[test]
 	#[serial]
 	fn disjoint_write_02() {
-		let mut sql = TranslationLayer::new().unwrap();
-		sql.unsafe_write(3, 4096 * 5, &[2_u8; 4096]).unwrap();
+		let sql = connect();
+		sql.write(3, 4096 * 5, &[2_u8; 4096]).unwrap();
 		let read = &mut [0_u8; 4096*3];
 		let read_bytes = sql.read(3, 4096 * 3, read).unwrap();
 		sql.resize(3, 4096 * 3 + 5).unwrap();
+		restore_times(&sql, 3);
 		let mut target: Vec<u8> = Vec::from(['a' as u8, 'a' as u8, 'a' as u8, 'a' as u8, '\n' as u8]);
 		target.extend_from_slice(&[0_u8; 4096 - 5 + 4096]);
 		target.extend_from_slice(&[2_u8; 4096]);
@@ -979,11 +2267,12 @@ mod test {
 	#[test]
 	#[serial]
 	fn disjoint_write_03() {
-		let mut sql = TranslationLayer::new().unwrap();
-		sql.unsafe_write(3, 4096 * 9, &[2_u8; 4096]).unwrap();
+		let sql = connect();
+		sql.write(3, 4096 * 9, &[2_u8; 4096]).unwrap();
 		let read = &mut [0_u8; 4096*8];
 		let read_bytes = sql.read(3, 4096 * 3, read).unwrap();
 		sql.resize(3, 4096 * 3 + 5).unwrap();
+		restore_times(&sql, 3);
 		let mut target: Vec<u8> = Vec::from(['a' as u8, 'a' as u8, 'a' as u8, 'a' as u8, '\n' as u8]);
 		target.extend_from_slice(&[0_u8; 4096 - 5 + 4096 * 5]);
 		target.extend_from_slice(&[2_u8; 4096]);
@@ -995,13 +2284,14 @@ mod test {
 	#[test]
 	#[serial]
 	fn disjoint_write_04() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		sql.resize(3, 0).unwrap();
-		sql.unsafe_write(3, 4436, &[2_u8; 8]).unwrap();
+		sql.write(3, 4436, &[2_u8; 8]).unwrap();
 		let read = &mut [0_u8; 4436 + 8];
 		let read_bytes = sql.read(3, 0, read).unwrap();
-		sql.unsafe_write(3, 0, &[0_u8; 4096 * 3]).unwrap();
-		sql.unsafe_write(3, 4096 * 3, &['a' as u8, 'a' as u8, 'a' as u8, 'a' as u8, '\n' as u8]).unwrap();
+		sql.write(3, 0, &[0_u8; 4096 * 3]).unwrap();
+		sql.write(3, 4096 * 3, &['a' as u8, 'a' as u8, 'a' as u8, 'a' as u8, '\n' as u8]).unwrap();
+		restore_times(&sql, 3);
 		let mut target: Vec<u8> = Vec::from(&[0_u8; 4436]);
 		target.extend_from_slice(&[2_u8; 8]);
 		assert_eq!(read, target.as_slice());
@@ -1011,13 +2301,14 @@ mod test {
 	#[test]
 	#[serial]
 	fn unsafe_write_bug_01_01() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		sql.resize(3, 4096*2).unwrap();
-		sql.unsafe_write(3, 4436, &[2_u8; 8]).unwrap();
+		sql.write(3, 4436, &[2_u8; 8]).unwrap();
 		let read = &mut [0_u8; 4436 + 8];
 		let read_bytes = sql.read(3, 0, read).unwrap();
-		sql.unsafe_write(3, 0, &[0_u8; 4096 * 3]).unwrap();
-		sql.unsafe_write(3, 4096 * 3, &['a' as u8, 'a' as u8, 'a' as u8, 'a' as u8, '\n' as u8]).unwrap();
+		sql.write(3, 0, &[0_u8; 4096 * 3]).unwrap();
+		sql.write(3, 4096 * 3, &['a' as u8, 'a' as u8, 'a' as u8, 'a' as u8, '\n' as u8]).unwrap();
+		restore_times(&sql, 3);
 		let mut target: Vec<u8> = Vec::from(&[0_u8; 4436]);
 		target.extend_from_slice(&[2_u8; 8]);
 		assert_eq!(read, target.as_slice());
@@ -1027,13 +2318,14 @@ mod test {
 	#[test]
 	#[serial]
 	fn unsafe_write_bug_01_02() {
-		let mut sql = TranslationLayer::new().unwrap();
+		let sql = connect();
 		sql.resize(3, 4096*2).unwrap();
-		sql.unsafe_write(3, 4095, &[2_u8; 8]).unwrap();
+		sql.write(3, 4095, &[2_u8; 8]).unwrap();
 		let read = &mut [0_u8; 4095 + 8];
 		let read_bytes = sql.read(3, 0, read).unwrap();
-		sql.unsafe_write(3, 0, &[0_u8; 4096 * 3]).unwrap();
-		sql.unsafe_write(3, 4096 * 3, &['a' as u8, 'a' as u8, 'a' as u8, 'a' as u8, '\n' as u8]).unwrap();
+		sql.write(3, 0, &[0_u8; 4096 * 3]).unwrap();
+		sql.write(3, 4096 * 3, &['a' as u8, 'a' as u8, 'a' as u8, 'a' as u8, '\n' as u8]).unwrap();
+		restore_times(&sql, 3);
 		let mut target: Vec<u8> = Vec::from(&[0_u8; 4095]);
 		target.extend_from_slice(&[2_u8; 8]);
 		assert_eq!(read, target.as_slice());
@@ -1,6 +1,6 @@
-use std::{collections::HashMap, sync::Mutex};
+use std::collections::HashMap;
 use users::{get_user_by_uid, get_group_by_gid};
-use super::{commands, database_objects};
+use super::backend::Backend;
 use crate::debug;
 
 
@@ -29,10 +29,9 @@ impl PasswdTable {
     /// Try to create a new locally stored [`PasswdTable`] and insert data fetched from a database.
     /// 
     /// This will return [`super::Error`] if some command (or the adapter connection itself) fails.
-    pub fn new(adapter: &Mutex<super::DbConnector>) -> Result<Self, super::Error> {
-    let mut conn = adapter.lock().map_err(|_| super::Error::RuntimeError(super::CONN_LOCK_FAILED))?;
-    let users: Vec<database_objects::User> = conn.query(commands::SQL_GET_USERS, None)?;
-    let groups: Vec<database_objects::Group> = conn.query(commands::SQL_GET_GROUPS, None)?;
+    pub fn new(backend: &dyn Backend) -> Result<Self, super::Error> {
+    let users = backend.get_users()?;
+    let groups = backend.get_groups()?;
 
     let mut this: Self = Self { users: HashMap::new(), groups: HashMap::new() };
 
@@ -56,24 +55,23 @@ impl PasswdTable {
     /// If the user _does not_ exist in the locally stored [`PasswdTable`], then this function will
     /// try to fetch the name of the user and group and insert it into database and the locally
     /// stored [`PasswdTable`].
-    pub fn check(&mut self, adapter: &Mutex<super::DbConnector>, user: u32, group: u32) -> Result<(), super::Error> {
+    pub fn check(&mut self, backend: &dyn Backend, user: u32, group: u32) -> Result<(), super::Error> {
     let exists: (bool, bool) = (self.users.contains_key(&user), self.groups.contains_key(&group));
     if exists.0 && exists.1 { return Ok(()); }
 
     // If user or group does not exist in the table already - let's insert
-    let mut conn = adapter.lock().map_err(|_| super::Error::RuntimeError(super::CONN_LOCK_FAILED))?;
 
     if !exists.0 {
 		let name = self.get_user_by_uid(user)?;
         debug!("ownermgr: useradd: Adding user \"{}\" with uid {}", &name, user);
-        conn.command(commands::SQL_INSERT_USER, Some(&vec![user.into(), name.as_str().into()]))?;
+        backend.insert_user(user, &name)?;
         self.users.insert(user, name);
     }
 
     if !exists.1 {
 		let name = self.get_group_by_gid(group)?;
         debug!("ownermgr: groupadd: Adding group \"{}\" with gid {}", &name, group);
-        conn.command(commands::SQL_INSERT_GROUP, Some(&vec![group.into(), name.as_str().into()]))?;
+        backend.insert_group(group, &name)?;
         self.groups.insert(group, name);
     }
 
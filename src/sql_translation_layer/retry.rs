@@ -0,0 +1,114 @@
+//! Retries operations failing with transient database errors
+//!
+//! A lost connection or a transaction aborted by a deadlock does not fail the filesystem operation
+//! right away - it is run again with an exponential backoff until it succeeds, fails for another
+//! reason or the timeout runs out (the last error is returned then).
+
+use super::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+
+/// Delay before the first retry, doubled on every other one
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Longest delay between two retries
+const MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+
+#[derive(Debug)]
+pub struct Retry {
+	/// `None` retries forever (hard mount)
+	timeout: Option<Duration>,
+	/// Shared by all operations, so losing and regaining the connection is logged only once
+	disconnected: AtomicBool,
+}
+
+impl Retry {
+	pub fn new(timeout: Option<Duration>) -> Self {
+		Self {
+			timeout,
+			disconnected: AtomicBool::new(false),
+		}
+	}
+
+
+	/// Run `operation` until it does not fail with a transient error (or the timeout runs out)
+	pub fn run<T>(&self, mut operation: impl FnMut() -> Result<T, Error>) -> Result<T, Error> {
+		let started = Instant::now();
+		let mut backoff = INITIAL_BACKOFF;
+		loop {
+			let err = match operation() {
+				Err(err) if err.is_transient() => err,
+				result => {
+					if result.is_ok() && self.disconnected.swap(false, Ordering::Relaxed) {
+						eprintln!("dbfs: reconnected to the database");
+					}
+					return result;
+				},
+			};
+
+			if err.is_connection_error() && !self.disconnected.swap(true, Ordering::Relaxed) {
+				eprintln!("dbfs: lost the connection to the database, retrying ({})", err);
+			}
+			if self.timeout.is_some_and(|timeout| started.elapsed() + backoff > timeout) {
+				return Err(err);
+			}
+			std::thread::sleep(backoff);
+			backoff = Duration::min(backoff * 2, MAX_BACKOFF);
+		}
+	}
+}
+
+
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::db_connector::DbConnectorError;
+	use std::cell::Cell;
+
+
+	fn connection_error() -> Error {
+		Error::DbConnectorError(DbConnectorError::ConnectionError(String::from("connection refused")))
+	}
+
+
+	#[test]
+	fn transient_errors_are_retried() {
+		let attempts = Cell::new(0);
+		let result = Retry::new(Some(Duration::from_secs(10))).run(|| {
+			attempts.set(attempts.get() + 1);
+			match attempts.get() {
+				1 => Err(connection_error()),
+				2 => Err(Error::DbConnectorError(DbConnectorError::ConflictError(String::from("deadlock")))),
+				_ => Ok(attempts.get()),
+			}
+		});
+		assert_eq!(result.unwrap(), 3);
+	}
+
+	#[test]
+	fn other_errors_are_not_retried() {
+		let attempts = Cell::new(0);
+		let result: Result<(), Error> = Retry::new(None).run(|| {
+			attempts.set(attempts.get() + 1);
+			Err(Error::NotFoundError("no inode found with given id"))
+		});
+		assert!(matches!(result, Err(Error::NotFoundError(_))));
+		assert_eq!(attempts.get(), 1);
+	}
+
+	#[test]
+	fn soft_timeout_gives_up() {
+		let attempts = Cell::new(0);
+		let started = Instant::now();
+		let result: Result<(), Error> = Retry::new(Some(Duration::from_millis(100))).run(|| {
+			attempts.set(attempts.get() + 1);
+			Err(connection_error())
+		});
+		assert!(matches!(result, Err(Error::DbConnectorError(DbConnectorError::ConnectionError(_)))));
+		assert!(attempts.get() > 1);
+		assert!(started.elapsed() < Duration::from_secs(1));
+	}
+}
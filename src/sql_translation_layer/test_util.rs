@@ -0,0 +1,44 @@
+//! Fixtures shared by the unit tests of the translation layer and the FUSE driver
+
+
+use std::ffi::OsStr;
+use std::time::SystemTime;
+use super::backend::{memory::MemoryBackend, DEFAULT_VOLUME};
+use super::{driver_objects, TranslationLayer};
+
+
+/// A freshly formatted volume in an in-memory database
+pub fn volume() -> TranslationLayer {
+	TranslationLayer::with_backend(Box::new(MemoryBackend::new()), DEFAULT_VOLUME).unwrap()
+}
+
+/// Attributes of a new inode owned by root (`rw-r--r--`)
+pub fn file_attr() -> driver_objects::FileSetAttr {
+	driver_objects::FileSetAttr {
+		uid: 0,
+		gid: 0,
+		atime: SystemTime::now(),
+		mtime: SystemTime::now(),
+		ctime: SystemTime::now(),
+		crtime: SystemTime::now(),
+		perm: driver_objects::Permissions { special: 0, owner: 6, group: 4, other: 4 },
+	}
+}
+
+/// Create an inode named `name` in `parent` and return its id
+pub fn create(tl: &TranslationLayer, parent: u64, name: &str, kind: driver_objects::FileType) -> u64 {
+	tl.mknod(parent, OsStr::new(name), kind, file_attr()).unwrap().ino as u64
+}
+
+/// The whole contents of a file
+pub fn read_all(tl: &TranslationLayer, inode: u64) -> Vec<u8> {
+	let mut buffer = vec![0xff; tl.filesize(inode).unwrap().bytes as usize];
+	let read = tl.read(inode, 0, &mut buffer).unwrap();
+	assert_eq!(read, buffer.len());
+	buffer
+}
+
+/// `len` bytes of test data, different `seed`s give different data
+pub fn pattern(len: usize, seed: usize) -> Vec<u8> {
+	(0..len).map(|val| ((val + seed) % 251) as u8).collect()
+}